@@ -0,0 +1,271 @@
+use crate::devices::WavWriter;
+use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
+use crate::emulator::utils::{pack_u32, unpack_u32};
+use crate::file_system::{CharDevice, OpenFileError, OpenFileFlags};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use unicorn_engine::Unicorn;
+
+// function codes of `OSAL_s32IOControl()` of the ACOUSTICOUT driver
+pub const ACOUSTICOUT_IOCTRL_VERSION: u32 = 0x01;
+pub const ACOUSTICOUT_IOCTRL_EXTWRITE: u32 = 0x02;
+pub const ACOUSTICOUT_IOCTRL_SETTIME: u32 = 0x03;
+pub const ACOUSTICOUT_IOCTRL_GETTIME: u32 = 0x04;
+pub const ACOUSTICOUT_IOCTRL_SETSAMPLERATE: u32 = 0x05;
+pub const ACOUSTICOUT_IOCTRL_GETSAMPLERATE: u32 = 0x06;
+pub const ACOUSTICOUT_IOCTRL_GETSUPP_SAMPLERATE: u32 = 0x07;
+pub const ACOUSTICOUT_IOCTRL_SETCHANNELS: u32 = 0x08;
+pub const ACOUSTICOUT_IOCTRL_GETCHANNELS: u32 = 0x09;
+pub const ACOUSTICOUT_IOCTRL_GETSUPP_CHANNELS: u32 = 0x0a;
+pub const ACOUSTICOUT_IOCTRL_SETSAMPLEFORMAT: u32 = 0x0b;
+pub const ACOUSTICOUT_IOCTRL_GETSAMPLEFORMAT: u32 = 0x0c;
+pub const ACOUSTICOUT_IOCTRL_GETSUPP_SAMPLEFORMAT: u32 = 0x0d;
+pub const ACOUSTICOUT_IOCTRL_SETBUFFERSIZE: u32 = 0x0e;
+pub const ACOUSTICOUT_IOCTRL_GETBUFFERSIZE: u32 = 0x0f;
+pub const ACOUSTICOUT_IOCTRL_GETSUPP_BUFFERSIZE: u32 = 0x10;
+pub const ACOUSTICOUT_IOCTRL_START: u32 = 0x11;
+pub const ACOUSTICOUT_IOCTRL_STOP: u32 = 0x12;
+pub const ACOUSTICOUT_IOCTRL_PAUSE: u32 = 0x13;
+pub const ACOUSTICOUT_IOCTRL_ABORT: u32 = 0x14;
+pub const ACOUSTICOUT_IOCTRL_WAITEVENT: u32 = 0x15;
+
+// `OSAL_tenAcousticSampleFormat`
+pub const SAMPLE_FORMAT_S8: u32 = 1;
+pub const SAMPLE_FORMAT_S16LE: u32 = 2;
+pub const SAMPLE_FORMAT_S32LE: u32 = 3;
+pub const SAMPLE_FORMAT_F32LE: u32 = 4;
+
+pub const SUPPORTED_SAMPLE_RATES: [u32; 7] = [8000, 11025, 16000, 22050, 24000, 44100, 48000];
+
+/// Returns bits per sample and whether the samples are floats.
+pub fn sample_format_info(sample_format: u32) -> Option<(u16, bool)> {
+    match sample_format {
+        SAMPLE_FORMAT_S8 => Some((8, false)),
+        SAMPLE_FORMAT_S16LE => Some((16, false)),
+        SAMPLE_FORMAT_S32LE => Some((32, false)),
+        SAMPLE_FORMAT_F32LE => Some((32, true)),
+        _ => None,
+    }
+}
+
+struct AcousticOutStream {
+    sample_rate: u32,
+    channels: u32,
+    sample_format: u32,
+    buffer_size: u32,
+    // written samples (in frames) since the start
+    position: u64,
+    wav: Option<WavWriter>,
+}
+
+impl AcousticOutStream {
+    fn stop(&mut self) {
+        if let Some(wav) = self.wav.take() {
+            if let Err(err) = wav.finish() {
+                log::warn!("unable to finish acoustic output: {}", err);
+            }
+        }
+    }
+}
+
+///
+/// `/dev/acousticout` - audio output of the OSAL ACOUSTICOUT driver.
+///
+/// Every started stream (`ACOUSTICOUT_IOCTRL_START`) is written to a new WAV
+/// file named by the time of the start.
+///
+pub struct AcousticOutDevice {
+    output_dir: PathBuf,
+    streams: HashMap<i32, AcousticOutStream>,
+    next_stream_id: u32,
+}
+
+impl AcousticOutDevice {
+    pub fn new(output_dir: PathBuf) -> Self {
+        Self {
+            output_dir,
+            streams: HashMap::new(),
+            next_stream_id: 0,
+        }
+    }
+
+    fn start(&mut self, fd: i32) -> Result<(), ()> {
+        let stream = self.streams.get_mut(&fd).ok_or(())?;
+        if stream.wav.is_some() {
+            return Ok(());
+        }
+
+        let (bits_per_sample, is_float) = sample_format_info(stream.sample_format).ok_or(())?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+        let path = self.output_dir.join(format!(
+            "acousticout_{}_{}.wav",
+            timestamp, self.next_stream_id
+        ));
+        self.next_stream_id += 1;
+
+        std::fs::create_dir_all(&self.output_dir).map_err(|_| ())?;
+        match WavWriter::create(
+            &path,
+            stream.sample_rate,
+            stream.channels as u16,
+            bits_per_sample,
+            is_float,
+        ) {
+            Ok(wav) => {
+                log::info!(
+                    "acoustic output {} Hz, {} channels, {} bits to {}",
+                    stream.sample_rate,
+                    stream.channels,
+                    bits_per_sample,
+                    path.display()
+                );
+                stream.wav = Some(wav);
+                stream.position = 0;
+                Ok(())
+            }
+            Err(err) => {
+                log::warn!("unable to create {}: {}", path.display(), err);
+                Err(())
+            }
+        }
+    }
+}
+
+impl CharDevice for AcousticOutDevice {
+    fn open(&mut self, fd: i32, _flags: OpenFileFlags) -> Result<(), OpenFileError> {
+        // default configuration of the navigation voice output
+        self.streams.insert(
+            fd,
+            AcousticOutStream {
+                sample_rate: 22050,
+                channels: 1,
+                sample_format: SAMPLE_FORMAT_S16LE,
+                buffer_size: 4096,
+                position: 0,
+                wav: None,
+            },
+        );
+        Ok(())
+    }
+
+    fn close(&mut self, fd: i32) {
+        if let Some(mut stream) = self.streams.remove(&fd) {
+            stream.stop();
+        }
+    }
+
+    fn read(&mut self, _fd: i32, _content: &mut [u8]) -> Result<u64, ()> {
+        Err(())
+    }
+
+    fn write(&mut self, fd: i32, content: &[u8]) -> Result<u64, ()> {
+        // the stream is started implicitly by the first write
+        self.start(fd)?;
+        let stream = self.streams.get_mut(&fd).ok_or(())?;
+
+        let (bits_per_sample, _) = sample_format_info(stream.sample_format).ok_or(())?;
+        let frame_size = stream.channels as usize * bits_per_sample as usize / 8;
+        let wav = stream.wav.as_mut().unwrap();
+        let res = if bits_per_sample == 8 {
+            // 8-bit WAV samples are unsigned
+            let samples: Vec<u8> = content.iter().map(|s| s ^ 0x80).collect();
+            wav.write_samples(&samples)
+        } else {
+            wav.write_samples(content)
+        };
+        res.map_err(|_| ())?;
+
+        stream.position += (content.len() / frame_size.max(1)) as u64;
+        Ok(content.len() as u64)
+    }
+
+    fn ioctl(&mut self, unicorn: &mut Unicorn<Context>, fd: i32, request: u32, addr: u32) -> i32 {
+        let read_u32 = |unicorn: &Unicorn<Context>, offset: u32| {
+            let mut buf = [0u8; 4];
+            unicorn.mem_read((addr + offset) as u64, &mut buf).unwrap();
+            unpack_u32(&buf)
+        };
+        let write_u32 = |unicorn: &mut Unicorn<Context>, offset: u32, value: u32| {
+            unicorn
+                .mem_write_guest((addr + offset) as u64, &pack_u32(value))
+                .unwrap();
+        };
+
+        if request == ACOUSTICOUT_IOCTRL_START {
+            return match self.start(fd) {
+                Ok(_) => 0i32,
+                Err(_) => -22i32, // -EINVAL
+            };
+        }
+
+        let stream = match self.streams.get_mut(&fd) {
+            Some(stream) => stream,
+            None => return -9i32, // -EBADF
+        };
+
+        match request {
+            ACOUSTICOUT_IOCTRL_VERSION => write_u32(unicorn, 0, 0x0100),
+            // arguments are `{ codec, value }` structures, the codec is always PCM
+            ACOUSTICOUT_IOCTRL_SETSAMPLERATE => {
+                let sample_rate = read_u32(unicorn, 4);
+                if !SUPPORTED_SAMPLE_RATES.contains(&sample_rate) {
+                    return -22i32; // -EINVAL
+                }
+                stream.sample_rate = sample_rate;
+            }
+            ACOUSTICOUT_IOCTRL_GETSAMPLERATE => write_u32(unicorn, 4, stream.sample_rate),
+            ACOUSTICOUT_IOCTRL_SETSAMPLEFORMAT => {
+                let sample_format = read_u32(unicorn, 4);
+                if sample_format_info(sample_format).is_none() {
+                    return -22i32; // -EINVAL
+                }
+                stream.sample_format = sample_format;
+            }
+            ACOUSTICOUT_IOCTRL_GETSAMPLEFORMAT => write_u32(unicorn, 4, stream.sample_format),
+            ACOUSTICOUT_IOCTRL_SETBUFFERSIZE => stream.buffer_size = read_u32(unicorn, 4),
+            ACOUSTICOUT_IOCTRL_GETBUFFERSIZE => write_u32(unicorn, 4, stream.buffer_size),
+            // the argument is the value itself
+            ACOUSTICOUT_IOCTRL_SETCHANNELS => {
+                if !(1..=2).contains(&addr) {
+                    return -22i32; // -EINVAL
+                }
+                stream.channels = addr;
+            }
+            ACOUSTICOUT_IOCTRL_GETCHANNELS => write_u32(unicorn, 0, stream.channels),
+            ACOUSTICOUT_IOCTRL_GETTIME => {
+                // played time in milliseconds
+                let time = stream.position * 1000 / stream.sample_rate as u64;
+                write_u32(unicorn, 0, time as u32);
+            }
+            ACOUSTICOUT_IOCTRL_STOP | ACOUSTICOUT_IOCTRL_ABORT => stream.stop(),
+            ACOUSTICOUT_IOCTRL_EXTWRITE => {
+                // `{ buffer, size }`
+                let buffer = read_u32(unicorn, 0);
+                let size = read_u32(unicorn, 4);
+                let mut content = vec![0u8; size as usize];
+                unicorn.mem_read(buffer as u64, &mut content).unwrap();
+                if self.write(fd, &content).is_err() {
+                    return -22i32; // -EINVAL
+                }
+            }
+            // the output is not played, so it is never paused or waiting
+            ACOUSTICOUT_IOCTRL_PAUSE
+            | ACOUSTICOUT_IOCTRL_WAITEVENT
+            | ACOUSTICOUT_IOCTRL_SETTIME
+            | ACOUSTICOUT_IOCTRL_GETSUPP_SAMPLERATE
+            | ACOUSTICOUT_IOCTRL_GETSUPP_CHANNELS
+            | ACOUSTICOUT_IOCTRL_GETSUPP_SAMPLEFORMAT
+            | ACOUSTICOUT_IOCTRL_GETSUPP_BUFFERSIZE => {}
+            _ => {
+                log::warn!("unsupported ACOUSTICOUT control {:#x}", request);
+                return -25i32; // -ENOTTY
+            }
+        }
+        0i32
+    }
+}
@@ -0,0 +1,286 @@
+use crate::devices::{
+    read_wav, sample_format_info, WavData, SAMPLE_FORMAT_S16LE, SUPPORTED_SAMPLE_RATES,
+};
+use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
+use crate::emulator::utils::{pack_u32, unpack_u32};
+use crate::file_system::{CharDevice, OpenFileError, OpenFileFlags, PollEvents};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::time::Instant;
+use unicorn_engine::Unicorn;
+
+// function codes of `OSAL_s32IOControl()` of the ACOUSTICSRC driver
+pub const ACOUSTICSRC_IOCTRL_VERSION: u32 = 0x01;
+pub const ACOUSTICSRC_IOCTRL_SETSAMPLERATE: u32 = 0x05;
+pub const ACOUSTICSRC_IOCTRL_GETSAMPLERATE: u32 = 0x06;
+pub const ACOUSTICSRC_IOCTRL_SETCHANNELS: u32 = 0x08;
+pub const ACOUSTICSRC_IOCTRL_GETCHANNELS: u32 = 0x09;
+pub const ACOUSTICSRC_IOCTRL_SETSAMPLEFORMAT: u32 = 0x0b;
+pub const ACOUSTICSRC_IOCTRL_GETSAMPLEFORMAT: u32 = 0x0c;
+pub const ACOUSTICSRC_IOCTRL_SETBUFFERSIZE: u32 = 0x0e;
+pub const ACOUSTICSRC_IOCTRL_GETBUFFERSIZE: u32 = 0x0f;
+pub const ACOUSTICSRC_IOCTRL_START: u32 = 0x11;
+pub const ACOUSTICSRC_IOCTRL_STOP: u32 = 0x12;
+pub const ACOUSTICSRC_IOCTRL_ABORT: u32 = 0x14;
+pub const ACOUSTICSRC_IOCTRL_WAITEVENT: u32 = 0x15;
+/// Reads one period: `{ buffer, size, read_size }` (served by the libosal hook,
+/// it waits for the data).
+pub const ACOUSTICSRC_IOCTRL_READ: u32 = 0x16;
+
+struct AcousticSrcStream {
+    sample_rate: u32,
+    channels: u32,
+    sample_format: u32,
+    // size of one period (the data is returned by whole periods)
+    buffer_size: u32,
+    // time of the start, the data is available at the rate of the real microphone
+    started: Option<Instant>,
+    delivered_frames: u64,
+    // samples of the recording converted to the format of the stream
+    pending: VecDeque<u8>,
+}
+
+impl AcousticSrcStream {
+    fn frame_size(&self) -> usize {
+        let (bits_per_sample, _) = sample_format_info(self.sample_format).unwrap();
+        self.channels as usize * bits_per_sample as usize / 8
+    }
+
+    fn period_frames(&self) -> u64 {
+        (self.buffer_size as usize / self.frame_size()).max(1) as u64
+    }
+
+    fn available_frames(&self) -> u64 {
+        match self.started {
+            Some(started) => {
+                let frames = started.elapsed().as_micros() * self.sample_rate as u128 / 1000000;
+                (frames as u64).saturating_sub(self.delivered_frames)
+            }
+            None => 0,
+        }
+    }
+
+    /// Converts the recording to the format of the stream (with linear resampling).
+    fn queue_recording(&mut self, wav: &WavData) {
+        let in_channels = wav.channels as usize;
+        let in_frames = wav.samples.len() / in_channels;
+        let out_frames = in_frames as u64 * self.sample_rate as u64 / wav.sample_rate as u64;
+        let (bits_per_sample, is_float) = sample_format_info(self.sample_format).unwrap();
+
+        let sample_at = |frame: usize, channel: usize| {
+            let frame = frame.min(in_frames.saturating_sub(1));
+            wav.samples[frame * in_channels + channel.min(in_channels - 1)]
+        };
+
+        for out_frame in 0..out_frames {
+            let position = out_frame as f64 * wav.sample_rate as f64 / self.sample_rate as f64;
+            let frame = position as usize;
+            let fraction = (position - frame as f64) as f32;
+            for channel in 0..self.channels as usize {
+                // mono output is mixed from all input channels
+                let sample = if self.channels == 1 && in_channels > 1 {
+                    (0..in_channels)
+                        .map(|c| {
+                            sample_at(frame, c) * (1.0 - fraction)
+                                + sample_at(frame + 1, c) * fraction
+                        })
+                        .sum::<f32>()
+                        / in_channels as f32
+                } else {
+                    sample_at(frame, channel) * (1.0 - fraction)
+                        + sample_at(frame + 1, channel) * fraction
+                };
+                let sample = sample.clamp(-1.0, 1.0);
+                match (bits_per_sample, is_float) {
+                    (8, _) => self.pending.push_back((sample * 127.0) as i8 as u8),
+                    (16, _) => self
+                        .pending
+                        .extend(((sample * 32767.0) as i16).to_le_bytes()),
+                    (_, false) => self
+                        .pending
+                        .extend(((sample as f64 * 2147483647.0) as i32).to_le_bytes()),
+                    (_, true) => self.pending.extend(sample.to_le_bytes()),
+                }
+            }
+        }
+    }
+}
+
+///
+/// `/dev/acousticsrc` - microphone of the OSAL ACOUSTICSRC driver.
+///
+/// Every started capture (`ACOUSTICSRC_IOCTRL_START` or the first read) plays the next of the
+/// configured WAV recordings followed by silence (just silence when there are no more recordings).
+/// The data is produced in real time and read by whole periods.
+///
+pub struct AcousticSrcDevice {
+    recordings: VecDeque<PathBuf>,
+    streams: HashMap<i32, AcousticSrcStream>,
+}
+
+impl AcousticSrcDevice {
+    pub fn new(recordings: Vec<PathBuf>) -> Self {
+        Self {
+            recordings: recordings.into(),
+            streams: HashMap::new(),
+        }
+    }
+
+    fn start(&mut self, fd: i32) -> Result<(), ()> {
+        let stream = self.streams.get_mut(&fd).ok_or(())?;
+        if stream.started.is_some() {
+            return Ok(());
+        }
+
+        stream.pending.clear();
+        if let Some(path) = self.recordings.pop_front() {
+            match read_wav(&path) {
+                Ok(wav) => {
+                    log::info!("acoustic source plays {}", path.display());
+                    stream.queue_recording(&wav);
+                }
+                Err(err) => log::warn!("unable to read {}: {}", path.display(), err),
+            }
+        }
+        stream.started = Some(Instant::now());
+        stream.delivered_frames = 0;
+        Ok(())
+    }
+}
+
+impl CharDevice for AcousticSrcDevice {
+    fn open(&mut self, fd: i32, _flags: OpenFileFlags) -> Result<(), OpenFileError> {
+        // default configuration of the speech recognition input
+        self.streams.insert(
+            fd,
+            AcousticSrcStream {
+                sample_rate: 16000,
+                channels: 1,
+                sample_format: SAMPLE_FORMAT_S16LE,
+                buffer_size: 1024,
+                started: None,
+                delivered_frames: 0,
+                pending: VecDeque::new(),
+            },
+        );
+        Ok(())
+    }
+
+    fn close(&mut self, fd: i32) {
+        self.streams.remove(&fd);
+    }
+
+    fn read(&mut self, fd: i32, content: &mut [u8]) -> Result<u64, ()> {
+        let stream = self.streams.get_mut(&fd).ok_or(())?;
+
+        let frame_size = stream.frame_size();
+        let frames = (content.len() / frame_size)
+            .min(stream.period_frames() as usize)
+            .min(stream.available_frames() as usize);
+        if frames == 0 {
+            return Err(());
+        }
+
+        let len = frames * frame_size;
+        for byte in content[0..len].iter_mut() {
+            *byte = stream.pending.pop_front().unwrap_or(0);
+        }
+        stream.delivered_frames += frames as u64;
+        Ok(len as u64)
+    }
+
+    fn write(&mut self, _fd: i32, _content: &[u8]) -> Result<u64, ()> {
+        Err(())
+    }
+
+    fn ioctl(&mut self, unicorn: &mut Unicorn<Context>, fd: i32, request: u32, addr: u32) -> i32 {
+        let read_u32 = |unicorn: &Unicorn<Context>, offset: u32| {
+            let mut buf = [0u8; 4];
+            unicorn.mem_read((addr + offset) as u64, &mut buf).unwrap();
+            unpack_u32(&buf)
+        };
+        let write_u32 = |unicorn: &mut Unicorn<Context>, offset: u32, value: u32| {
+            unicorn
+                .mem_write_guest((addr + offset) as u64, &pack_u32(value))
+                .unwrap();
+        };
+
+        if request == ACOUSTICSRC_IOCTRL_START {
+            return match self.start(fd) {
+                Ok(_) => 0i32,
+                Err(_) => -22i32, // -EINVAL
+            };
+        }
+
+        let stream = match self.streams.get_mut(&fd) {
+            Some(stream) => stream,
+            None => return -9i32, // -EBADF
+        };
+        // the format can be changed only while the capture is stopped
+        let is_configuration = matches!(
+            request,
+            ACOUSTICSRC_IOCTRL_SETSAMPLERATE
+                | ACOUSTICSRC_IOCTRL_SETCHANNELS
+                | ACOUSTICSRC_IOCTRL_SETSAMPLEFORMAT
+                | ACOUSTICSRC_IOCTRL_SETBUFFERSIZE
+        );
+        if is_configuration && stream.started.is_some() {
+            return -16i32; // -EBUSY
+        }
+
+        match request {
+            ACOUSTICSRC_IOCTRL_VERSION => write_u32(unicorn, 0, 0x0100),
+            // arguments are `{ codec, value }` structures, the codec is always PCM
+            ACOUSTICSRC_IOCTRL_SETSAMPLERATE => {
+                let sample_rate = read_u32(unicorn, 4);
+                if !SUPPORTED_SAMPLE_RATES.contains(&sample_rate) {
+                    return -22i32; // -EINVAL
+                }
+                stream.sample_rate = sample_rate;
+            }
+            ACOUSTICSRC_IOCTRL_GETSAMPLERATE => write_u32(unicorn, 4, stream.sample_rate),
+            ACOUSTICSRC_IOCTRL_SETSAMPLEFORMAT => {
+                let sample_format = read_u32(unicorn, 4);
+                if sample_format_info(sample_format).is_none() {
+                    return -22i32; // -EINVAL
+                }
+                stream.sample_format = sample_format;
+            }
+            ACOUSTICSRC_IOCTRL_GETSAMPLEFORMAT => write_u32(unicorn, 4, stream.sample_format),
+            ACOUSTICSRC_IOCTRL_SETBUFFERSIZE => {
+                let buffer_size = read_u32(unicorn, 4);
+                if (buffer_size as usize) < stream.frame_size() {
+                    return -22i32; // -EINVAL
+                }
+                stream.buffer_size = buffer_size;
+            }
+            ACOUSTICSRC_IOCTRL_GETBUFFERSIZE => write_u32(unicorn, 4, stream.buffer_size),
+            // the argument is the value itself
+            ACOUSTICSRC_IOCTRL_SETCHANNELS => {
+                if !(1..=2).contains(&addr) {
+                    return -22i32; // -EINVAL
+                }
+                stream.channels = addr;
+            }
+            ACOUSTICSRC_IOCTRL_GETCHANNELS => write_u32(unicorn, 0, stream.channels),
+            ACOUSTICSRC_IOCTRL_STOP | ACOUSTICSRC_IOCTRL_ABORT => {
+                stream.started = None;
+                stream.pending.clear();
+            }
+            ACOUSTICSRC_IOCTRL_WAITEVENT => {}
+            _ => {
+                log::warn!("unsupported ACOUSTICSRC control {:#x}", request);
+                return -25i32; // -ENOTTY
+            }
+        }
+        0i32
+    }
+
+    fn poll(&mut self, fd: i32) -> PollEvents {
+        match self.streams.get(&fd) {
+            Some(stream) if stream.available_frames() >= stream.period_frames() => PollEvents::IN,
+            _ => PollEvents::empty(),
+        }
+    }
+}
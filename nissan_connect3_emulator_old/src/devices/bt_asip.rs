@@ -0,0 +1,277 @@
+use crate::file_system::{CharDevice, OpenFileError, OpenFileFlags, PollEvents};
+use std::collections::VecDeque;
+use std::path::Path;
+
+// header of the ASIP packet:
+// `{ u8 id, u8 control, u16 payload length, u16 payload CRC, u8 remote id, u8 checksum }`
+const ASIP_HEADER_SIZE: usize = 8;
+const ASIP_HEADER_ID: u8 = 0xa5;
+// bits of the control byte (`asipSetHeaderAck()`, `asipSetHeaderSeqReset()`, `asipSetHeaderSeqNo()`)
+const ASIP_CONTROL_ACK: u8 = 0x80;
+const ASIP_CONTROL_SEQ_RESET: u8 = 0x40;
+const ASIP_SEQ_NO_MASK: u8 = 0x0f;
+// id of the BT module in the packets sent to the host
+const ASIP_REMOTE_ID: u8 = 0x01;
+// maximum length of the payload (longer packets are treated as a broken stream)
+const ASIP_MAX_PAYLOAD_LENGTH: usize = 0x1000;
+
+/// Answers of the BT module to the payloads starting with `request`.
+struct AsipResponse {
+    request: Vec<u8>,
+    // the request is matched by the prefix, otherwise the whole payload must match
+    is_prefix: bool,
+    responses: Vec<Vec<u8>>,
+    is_used: bool,
+}
+
+///
+/// `/dev/btasip` - BT module (UGZZC) behind the ASIP link of the OSAL BT driver.
+///
+/// Written data are ASIP packets. Every valid packet with payload is acknowledged and answered
+/// by the responses of the script (one item per line, `#` starts a comment):
+///
+/// - `< <payload>` - sent by the module, the ones before the first request are sent on open
+/// - `> <payload>` - request, the following `<` lines are its responses
+///
+/// Payloads are hex bytes, a request ending with `..` matches by the prefix.
+/// Recorded conversations are replayed in order, the last matching responses are repeated
+/// when the recording runs out.
+///
+pub struct BtAsipDevice {
+    startup: Vec<Vec<u8>>,
+    responses: Vec<AsipResponse>,
+
+    // incomplete packet written by the host
+    received: Vec<u8>,
+    // packets sent to the host
+    pending: VecDeque<u8>,
+    // sequence number of the next packet sent to the host (`None` until the sequence is reset)
+    tx_seq_no: Option<u8>,
+}
+
+impl BtAsipDevice {
+    pub fn new() -> Self {
+        Self {
+            startup: Vec::new(),
+            responses: Vec::new(),
+            received: Vec::new(),
+            pending: VecDeque::new(),
+            tx_seq_no: None,
+        }
+    }
+
+    /// Loads the script with the responses (recorded or written by hand).
+    pub fn load_script(&mut self, script_path: &Path) -> std::io::Result<()> {
+        let script = std::fs::read_to_string(script_path)?;
+        for (index, line) in script.lines().enumerate() {
+            if let Err(err) = self.add_script_line(line) {
+                log::warn!("{}:{}: {}", script_path.display(), index + 1, err);
+            }
+        }
+        Ok(())
+    }
+
+    fn add_script_line(&mut self, line: &str) -> Result<(), String> {
+        let line = line.split('#').next().unwrap().trim();
+        let mut args: Vec<&str> = line.split_whitespace().collect();
+        match args.first().copied() {
+            None => {}
+            Some(">") => {
+                let is_prefix = args.last() == Some(&"..");
+                if is_prefix {
+                    args.pop();
+                }
+                self.responses.push(AsipResponse {
+                    request: parse_payload(&args[1..])?,
+                    is_prefix,
+                    responses: Vec::new(),
+                    is_used: false,
+                });
+            }
+            Some("<") => {
+                let payload = parse_payload(&args[1..])?;
+                match self.responses.last_mut() {
+                    Some(response) => response.responses.push(payload),
+                    None => self.startup.push(payload),
+                }
+            }
+            Some(direction) => return Err(format!("unknown direction `{}`", direction)),
+        }
+        Ok(())
+    }
+
+    /// Parses the complete packets written by the host.
+    fn process_received(&mut self) {
+        loop {
+            // synchronization to the next header
+            match self
+                .received
+                .iter()
+                .position(|byte| *byte == ASIP_HEADER_ID)
+            {
+                Some(start) => {
+                    self.received.drain(0..start);
+                }
+                None => {
+                    self.received.clear();
+                    return;
+                }
+            }
+            if self.received.len() < ASIP_HEADER_SIZE {
+                return;
+            }
+
+            let header = &self.received[0..ASIP_HEADER_SIZE];
+            let payload_length = u16::from_le_bytes([header[2], header[3]]) as usize;
+            if header_checksum(&header[0..ASIP_HEADER_SIZE - 1]) != header[ASIP_HEADER_SIZE - 1]
+                || payload_length > ASIP_MAX_PAYLOAD_LENGTH
+            {
+                log::debug!("invalid ASIP header {:02x?}", header);
+                self.received.remove(0);
+                continue;
+            }
+            if self.received.len() < ASIP_HEADER_SIZE + payload_length {
+                return;
+            }
+
+            let packet: Vec<u8> = self
+                .received
+                .drain(0..ASIP_HEADER_SIZE + payload_length)
+                .collect();
+            let control = packet[1];
+            let crc = u16::from_le_bytes([packet[4], packet[5]]);
+            let payload = &packet[ASIP_HEADER_SIZE..];
+            if crc16(payload) != crc {
+                log::warn!("ASIP packet with invalid CRC: {:02x?}", payload);
+                continue;
+            }
+            // acknowledgements of the sent packets are not checked
+            if control & ASIP_CONTROL_ACK != 0 || payload.is_empty() {
+                continue;
+            }
+
+            self.send_packet(ASIP_CONTROL_ACK | (control & ASIP_SEQ_NO_MASK), &[]);
+            let responses = self.find_responses(payload);
+            for response in responses {
+                self.send_data(&response);
+            }
+        }
+    }
+
+    fn find_responses(&mut self, payload: &[u8]) -> Vec<Vec<u8>> {
+        let matches = |response: &AsipResponse| {
+            if response.is_prefix {
+                payload.starts_with(&response.request)
+            } else {
+                payload == response.request
+            }
+        };
+        let index = self
+            .responses
+            .iter()
+            .position(|response| !response.is_used && matches(response))
+            .or_else(|| self.responses.iter().rposition(matches));
+        match index {
+            Some(index) => {
+                let response = &mut self.responses[index];
+                response.is_used = true;
+                response.responses.clone()
+            }
+            None => {
+                log::debug!("unanswered ASIP payload: {:02x?}", payload);
+                Vec::new()
+            }
+        }
+    }
+
+    fn send_data(&mut self, payload: &[u8]) {
+        // the first packet resets the sequence of the host
+        let (seq_no, seq_reset) = match self.tx_seq_no {
+            Some(seq_no) => (seq_no, 0),
+            None => (0, ASIP_CONTROL_SEQ_RESET),
+        };
+        self.tx_seq_no = Some((seq_no + 1) & ASIP_SEQ_NO_MASK);
+        self.send_packet(seq_reset | seq_no, payload);
+    }
+
+    fn send_packet(&mut self, control: u8, payload: &[u8]) {
+        let mut header = vec![ASIP_HEADER_ID, control];
+        header.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+        header.extend_from_slice(&crc16(payload).to_le_bytes());
+        header.push(ASIP_REMOTE_ID);
+        header.push(header_checksum(&header));
+        self.pending.extend(header);
+        self.pending.extend(payload);
+    }
+}
+
+impl CharDevice for BtAsipDevice {
+    fn open(&mut self, _fd: i32, _flags: OpenFileFlags) -> Result<(), OpenFileError> {
+        // the module starts again with every connection
+        self.received.clear();
+        self.pending.clear();
+        self.tx_seq_no = None;
+        for response in &mut self.responses {
+            response.is_used = false;
+        }
+        for payload in self.startup.clone() {
+            self.send_data(&payload);
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, _fd: i32, content: &mut [u8]) -> Result<u64, ()> {
+        let len = self.pending.len().min(content.len());
+        for (byte, pending) in content.iter_mut().zip(self.pending.drain(0..len)) {
+            *byte = pending;
+        }
+        Ok(len as u64)
+    }
+
+    fn write(&mut self, _fd: i32, content: &[u8]) -> Result<u64, ()> {
+        self.received.extend_from_slice(content);
+        self.process_received();
+        Ok(content.len() as u64)
+    }
+
+    fn poll(&mut self, _fd: i32) -> PollEvents {
+        if self.pending.is_empty() {
+            PollEvents::OUT
+        } else {
+            PollEvents::IN | PollEvents::OUT
+        }
+    }
+}
+
+fn parse_payload(args: &[&str]) -> Result<Vec<u8>, String> {
+    args.iter()
+        .map(|arg| {
+            u8::from_str_radix(arg.trim_start_matches("0x"), 16)
+                .map_err(|_| format!("invalid byte `{}`", arg))
+        })
+        .collect()
+}
+
+/// Checksum of the header (`drvBtAsipChksum()`), the sum of all header bytes is zero.
+fn header_checksum(header: &[u8]) -> u8 {
+    header
+        .iter()
+        .fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+        .wrapping_neg()
+}
+
+/// CRC of the payload (`drvBtAsipCrc()`, CRC-16/CCITT).
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0xffffu16;
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
@@ -0,0 +1,59 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+
+///
+/// Emulated hardware controlled by text commands (one command per line, `#` starts a comment),
+/// either from a script file or from TCP connections on localhost.
+///
+pub trait CommandControl: Clone + Send + 'static {
+    /// What is controlled, used in the logs (like `input`).
+    const NAME: &'static str;
+
+    /// Executes the command (waits are done in the calling thread).
+    fn execute(&self, line: &str) -> Result<(), String>;
+
+    /// Executes commands of the script file in a new thread.
+    fn run_script(&self, script_path: PathBuf) -> std::io::Result<()> {
+        let script = std::fs::read_to_string(&script_path)?;
+        let control = self.clone();
+        std::thread::spawn(move || {
+            for (index, line) in script.lines().enumerate() {
+                if let Err(err) = control.execute(line) {
+                    log::warn!("{}:{}: {}", script_path.display(), index + 1, err);
+                }
+            }
+            log::info!("{} script {} finished", Self::NAME, script_path.display());
+        });
+        Ok(())
+    }
+
+    /// Executes commands received from TCP connections on localhost (like `nc localhost <port>`),
+    /// every command is answered with `ok` or `error: <reason>`.
+    fn start_server(&self, port: u16) -> std::io::Result<()> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        let control = self.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let control = control.clone();
+                std::thread::spawn(move || {
+                    let mut writer = match stream.try_clone() {
+                        Ok(writer) => writer,
+                        Err(_) => return,
+                    };
+                    for line in BufReader::new(stream).lines() {
+                        let Ok(line) = line else { break };
+                        let reply = match control.execute(&line) {
+                            Ok(_) => "ok\n".to_string(),
+                            Err(err) => format!("error: {}\n", err),
+                        };
+                        if writer.write_all(reply.as_bytes()).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        Ok(())
+    }
+}
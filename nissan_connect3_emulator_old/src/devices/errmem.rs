@@ -0,0 +1,378 @@
+use crate::emulator::context::Context;
+use crate::emulator::utils::{
+    civil_from_days, days_from_civil, pack_i32, pack_u16, pack_u32, read_string, unpack_u32,
+};
+use crate::file_system::{CharDevice, OpenFileError, OpenFileFlags};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use unicorn_engine::Unicorn;
+
+/// Size of `trErrmemEntry`.
+pub const ERRMEM_ENTRY_SIZE: usize = 280;
+/// Maximum size of the data of `trErrmemEntry`.
+pub const ERRMEM_MAX_ENTRY_LENGTH: usize = 232;
+// offsets of `eEntryType`, `u16EntryLength` and `au8EntryData` in `trErrmemEntry`
+const ERRMEM_ENTRY_TYPE_OFFSET: usize = 40;
+const ERRMEM_ENTRY_LENGTH_OFFSET: usize = 44;
+const ERRMEM_ENTRY_DATA_OFFSET: usize = 46;
+
+// `tenErrmemEntryType`
+pub const ERRMEM_ENTRY_INFO: u32 = 0;
+pub const ERRMEM_ENTRY_NORMAL: u32 = 1;
+pub const ERRMEM_ENTRY_FATAL: u32 = 2;
+
+// requests of the emulator, the process and thread of the entry are the calling ones
+/// Writes `trErrmemEntry` at the address.
+pub const ERRMEM_IOCTL_WRITE_ENTRY: u32 = 0x45520001;
+/// Writes the zero-terminated text at the address as a normal entry.
+pub const ERRMEM_IOCTL_WRITE_TEXT: u32 = 0x45520002;
+
+pub enum ErrMemData {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl ErrMemData {
+    /// Text if the data is printable (trailing zeros are removed), binary otherwise.
+    fn from_bytes(data: &[u8]) -> Self {
+        let len = data
+            .iter()
+            .rposition(|byte| *byte != 0)
+            .map_or(0, |pos| pos + 1);
+        match std::str::from_utf8(&data[0..len]) {
+            Ok(text)
+                if !text
+                    .chars()
+                    .any(|c| c.is_control() && c != '\n' && c != '\t') =>
+            {
+                ErrMemData::Text(text.to_string())
+            }
+            _ => ErrMemData::Binary(data.to_vec()),
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            ErrMemData::Text(text) => text.as_bytes().to_vec(),
+            ErrMemData::Binary(data) => data.clone(),
+        }
+    }
+}
+
+///
+/// Entry of the error memory.
+///
+pub struct ErrMemEntry {
+    /// Time since the Unix epoch.
+    pub time: Duration,
+    pub pid: u32,
+    pub process: String,
+    pub thread_id: u32,
+    pub thread: String,
+    pub entry_type: u32,
+    pub data: ErrMemData,
+}
+
+impl ErrMemEntry {
+    /// Returns the line of the error memory file (tab separated fields).
+    fn to_line(&self) -> String {
+        let (kind, data) = match &self.data {
+            ErrMemData::Text(text) => ("text", escape(text)),
+            ErrMemData::Binary(data) => (
+                "binary",
+                data.iter().map(|byte| format!("{:02x}", byte)).collect(),
+            ),
+        };
+        format!(
+            "{}.{:03}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.time.as_secs(),
+            self.time.subsec_millis(),
+            self.pid,
+            escape(&self.process),
+            self.thread_id,
+            escape(&self.thread),
+            self.entry_type,
+            kind,
+            data
+        )
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 8 {
+            return None;
+        }
+        let (seconds, millis) = fields[0].split_once('.')?;
+        let data = match fields[6] {
+            "text" => ErrMemData::Text(unescape(fields[7])),
+            "binary" => ErrMemData::Binary(
+                (0..fields[7].len())
+                    .step_by(2)
+                    .map(|index| u8::from_str_radix(fields[7].get(index..index + 2)?, 16).ok())
+                    .collect::<Option<Vec<u8>>>()?,
+            ),
+            _ => return None,
+        };
+        Some(Self {
+            time: Duration::from_secs(seconds.parse().ok()?)
+                + Duration::from_millis(millis.parse().ok()?),
+            pid: fields[1].parse().ok()?,
+            process: unescape(fields[2]),
+            thread_id: fields[3].parse().ok()?,
+            thread: unescape(fields[4]),
+            entry_type: fields[5].parse().ok()?,
+            data,
+        })
+    }
+
+    /// Returns `trErrmemEntry` of the entry (the data is truncated to `ERRMEM_MAX_ENTRY_LENGTH`).
+    pub fn pack(&self, number: u16) -> Vec<u8> {
+        let seconds = self.time.as_secs() as i64;
+        let days = seconds.div_euclid(86400);
+        let (year, month, day) = civil_from_days(days);
+        let time_of_day = seconds.rem_euclid(86400) as i32;
+
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&pack_u16(number)); // u16Entry
+        buf.extend_from_slice(&[0u8; 2]);
+        // rEntryTime (`OSAL_trTimeDate`, the year is counted from 1900 like in `struct tm`)
+        buf.extend_from_slice(&pack_i32(time_of_day % 60)); // s32Second
+        buf.extend_from_slice(&pack_i32(time_of_day / 60 % 60)); // s32Minute
+        buf.extend_from_slice(&pack_i32(time_of_day / 3600)); // s32Hour
+        buf.extend_from_slice(&pack_i32(day as i32)); // s32Day
+        buf.extend_from_slice(&pack_i32(month as i32)); // s32Month
+        buf.extend_from_slice(&pack_i32(year as i32 - 1900)); // s32Year
+        buf.extend_from_slice(&pack_i32((days + 4).rem_euclid(7) as i32)); // s32Weekday
+        buf.extend_from_slice(&pack_i32((days - days_from_civil(year, 1, 1)) as i32)); // s32Yearday
+        buf.extend_from_slice(&pack_i32(0)); // s32Daylightsaving
+        buf.extend_from_slice(&pack_u32(self.entry_type)); // eEntryType
+
+        let mut data = self.data.to_bytes();
+        data.truncate(ERRMEM_MAX_ENTRY_LENGTH);
+        buf.extend_from_slice(&pack_u16(data.len() as u16)); // u16EntryLength
+        buf.extend_from_slice(&data); // au8EntryData
+        buf.resize(ERRMEM_ENTRY_SIZE, 0u8);
+        buf
+    }
+}
+
+impl fmt::Display for ErrMemEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.time.as_secs() as i64;
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+        let time_of_day = seconds.rem_euclid(86400);
+        let entry_type = match self.entry_type {
+            ERRMEM_ENTRY_INFO => "INFO",
+            ERRMEM_ENTRY_NORMAL => "NORMAL",
+            ERRMEM_ENTRY_FATAL => "FATAL",
+            _ => "UNKNOWN",
+        };
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:03} [{}] {}:{} ({}:{}) ",
+            year,
+            month,
+            day,
+            time_of_day / 3600,
+            time_of_day / 60 % 60,
+            time_of_day % 60,
+            self.time.subsec_millis(),
+            entry_type,
+            self.process,
+            self.pid,
+            self.thread,
+            self.thread_id
+        )?;
+        match &self.data {
+            ErrMemData::Text(text) => write!(f, "{}", text),
+            ErrMemData::Binary(data) => {
+                for byte in data {
+                    write!(f, "{:02x} ", byte)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+///
+/// `/dev/errmem` - error memory of the OSAL ERRMEM driver.
+///
+/// Entries are appended to the file (one line per entry) and survive across emulator runs.
+/// Every read returns the next entry as `trErrmemEntry` (zero at the end).
+///
+pub struct ErrMemDevice {
+    path: PathBuf,
+    entries: Vec<ErrMemEntry>,
+    // index of the next entry read by the file descriptor
+    read_positions: HashMap<i32, usize>,
+}
+
+impl ErrMemDevice {
+    pub fn new(path: PathBuf) -> Self {
+        let entries = if path.is_file() {
+            read_errmem(&path).unwrap_or_else(|err| {
+                log::warn!("unable to read {}: {}", path.display(), err);
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+        Self {
+            path,
+            entries,
+            read_positions: HashMap::new(),
+        }
+    }
+
+    fn add_entry(&mut self, entry: ErrMemEntry) {
+        log::warn!("errmem: {}", entry);
+        let res = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "{}", entry.to_line()));
+        if let Err(err) = res {
+            log::warn!("unable to write {}: {}", self.path.display(), err);
+        }
+        self.entries.push(entry);
+    }
+
+    /// Adds the entry written by the calling thread.
+    fn add_thread_entry(&mut self, unicorn: &Unicorn<Context>, entry_type: u32, data: ErrMemData) {
+        let pid = unicorn.get_data().inner.pid;
+        let thread_id = unicorn.get_data().inner.thread_id;
+        let (process, thread) = match unicorn
+            .get_data()
+            .inner
+            .process_table
+            .lock()
+            .unwrap()
+            .get(pid)
+        {
+            Some(process) => (
+                process.get_thread_name(pid),
+                process.get_thread_name(thread_id),
+            ),
+            None => (String::new(), String::new()),
+        };
+        self.add_entry(ErrMemEntry {
+            time: SystemTime::now().duration_since(UNIX_EPOCH).unwrap(),
+            pid,
+            process,
+            thread_id,
+            thread,
+            entry_type,
+            data,
+        });
+    }
+}
+
+impl CharDevice for ErrMemDevice {
+    fn open(&mut self, fd: i32, _flags: OpenFileFlags) -> Result<(), OpenFileError> {
+        self.read_positions.insert(fd, 0);
+        Ok(())
+    }
+
+    fn close(&mut self, fd: i32) {
+        self.read_positions.remove(&fd);
+    }
+
+    fn read(&mut self, fd: i32, content: &mut [u8]) -> Result<u64, ()> {
+        let position = self.read_positions.get_mut(&fd).ok_or(())?;
+        let Some(entry) = self.entries.get(*position) else {
+            return Ok(0);
+        };
+        if content.len() < ERRMEM_ENTRY_SIZE {
+            return Err(());
+        }
+
+        content[0..ERRMEM_ENTRY_SIZE].copy_from_slice(&entry.pack((*position + 1) as u16));
+        *position += 1;
+        Ok(ERRMEM_ENTRY_SIZE as u64)
+    }
+
+    fn write(&mut self, _fd: i32, content: &[u8]) -> Result<u64, ()> {
+        // the writer is not known
+        self.add_entry(ErrMemEntry {
+            time: SystemTime::now().duration_since(UNIX_EPOCH).unwrap(),
+            pid: 0,
+            process: String::new(),
+            thread_id: 0,
+            thread: String::new(),
+            entry_type: ERRMEM_ENTRY_NORMAL,
+            data: ErrMemData::from_bytes(content),
+        });
+        Ok(content.len() as u64)
+    }
+
+    fn ioctl(&mut self, unicorn: &mut Unicorn<Context>, _fd: i32, request: u32, addr: u32) -> i32 {
+        match request {
+            ERRMEM_IOCTL_WRITE_ENTRY => {
+                let mut buf = vec![0u8; ERRMEM_ENTRY_SIZE];
+                unicorn.mem_read(addr as u64, &mut buf).unwrap();
+                let entry_type =
+                    unpack_u32(&buf[ERRMEM_ENTRY_TYPE_OFFSET..ERRMEM_ENTRY_TYPE_OFFSET + 4]);
+                let length = u16::from_le_bytes([
+                    buf[ERRMEM_ENTRY_LENGTH_OFFSET],
+                    buf[ERRMEM_ENTRY_LENGTH_OFFSET + 1],
+                ]) as usize;
+                let data = &buf[ERRMEM_ENTRY_DATA_OFFSET
+                    ..ERRMEM_ENTRY_DATA_OFFSET + length.min(ERRMEM_MAX_ENTRY_LENGTH)];
+                self.add_thread_entry(unicorn, entry_type, ErrMemData::from_bytes(data));
+            }
+            ERRMEM_IOCTL_WRITE_TEXT => {
+                let text = read_string(unicorn, addr);
+                self.add_thread_entry(unicorn, ERRMEM_ENTRY_NORMAL, ErrMemData::Text(text));
+            }
+            _ => {
+                log::warn!("unsupported ERRMEM control {:#x}", request);
+                return -25i32; // -ENOTTY
+            }
+        }
+        0i32
+    }
+}
+
+/// Reads entries of the error memory file.
+pub fn read_errmem(path: &Path) -> std::io::Result<Vec<ErrMemEntry>> {
+    let content = std::fs::read_to_string(path)?;
+    let mut entries = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        match ErrMemEntry::from_line(line) {
+            Some(entry) => entries.push(entry),
+            None => log::warn!("{}:{}: invalid entry", path.display(), index + 1),
+        }
+    }
+    Ok(entries)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn unescape(text: &str) -> String {
+    let mut res = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            res.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => res.push('\t'),
+            Some('n') => res.push('\n'),
+            Some('r') => res.push('\r'),
+            Some(c) => res.push(c),
+            None => {}
+        }
+    }
+    res
+}
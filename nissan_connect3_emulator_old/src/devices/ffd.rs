@@ -0,0 +1,232 @@
+use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
+use crate::emulator::utils::{pack_u32, unpack_u32};
+use crate::file_system::CharDevice;
+use flate2::Crc;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use unicorn_engine::Unicorn;
+
+// function codes of `OSAL_s32IOControl()` of the FFD driver
+pub const FFD_IOCTRL_SAVENOW: u32 = 0x01;
+pub const FFD_IOCTRL_RELOAD: u32 = 0x02;
+/// Returns size of the data set: `{ entry_id, size }`.
+pub const FFD_IOCTRL_GET_ENTRY_SIZE: u32 = 0x03;
+/// Reads the data set: `OSAL_trFFDDeviceInfo` (used by `DEV_FFD_s32IORead()`).
+pub const FFD_IOCTRL_READ_ENTRY: u32 = 0x10;
+/// Writes the data set: `OSAL_trFFDDeviceInfo` (used by `DEV_FFD_s32IOWrite()`).
+pub const FFD_IOCTRL_WRITE_ENTRY: u32 = 0x11;
+
+// header of the host file (magic and version of the format)
+const FFD_FILE_MAGIC: &[u8; 4] = b"FFD\0";
+const FFD_FILE_VERSION: u32 = 1;
+// header of the data set in the host file (id, length and CRC-32 of the data)
+const FFD_ENTRY_HEADER_SIZE: usize = 12;
+
+///
+/// `/dev/ffd` - flash data storage (calibration and configuration) of the OSAL FFD driver.
+///
+/// Data sets are kept in the host file and saved after every change. If the file does not exist,
+/// the data sets are imported from the seed directory (data sets dumped from a real unit,
+/// named by their id in hex like `0a.bin`).
+///
+pub struct FfdDevice {
+    path: PathBuf,
+    data_sets: BTreeMap<u8, Vec<u8>>,
+}
+
+impl FfdDevice {
+    pub fn new(path: PathBuf, seed_dir: Option<PathBuf>) -> Self {
+        let mut device = Self {
+            path,
+            data_sets: BTreeMap::new(),
+        };
+        if device.path.is_file() {
+            device.reload();
+        } else if let Some(seed_dir) = seed_dir.filter(|dir| dir.is_dir()) {
+            match read_seed(&seed_dir) {
+                Ok(data_sets) => {
+                    log::info!(
+                        "FFD seeded with {} data sets from {}",
+                        data_sets.len(),
+                        seed_dir.display()
+                    );
+                    device.data_sets = data_sets;
+                    device.save();
+                }
+                Err(err) => log::warn!("unable to read {}: {}", seed_dir.display(), err),
+            }
+        }
+        device
+    }
+
+    /// Loads the data sets from the host file (invalid data sets are dropped).
+    fn reload(&mut self) {
+        self.data_sets.clear();
+        let content = match std::fs::read(&self.path) {
+            Ok(content) => content,
+            Err(err) => {
+                log::warn!("unable to read {}: {}", self.path.display(), err);
+                return;
+            }
+        };
+        if content.len() < 8 || &content[0..4] != FFD_FILE_MAGIC {
+            log::warn!("{} is not FFD file", self.path.display());
+            return;
+        }
+        let version = unpack_u32(&content[4..8]);
+        if version != FFD_FILE_VERSION {
+            log::warn!(
+                "{} has unsupported version {}",
+                self.path.display(),
+                version
+            );
+            return;
+        }
+
+        let mut pos = 8;
+        while pos + FFD_ENTRY_HEADER_SIZE <= content.len() {
+            let id = content[pos];
+            let length = unpack_u32(&content[pos + 4..pos + 8]) as usize;
+            let checksum = unpack_u32(&content[pos + 8..pos + 12]);
+            pos += FFD_ENTRY_HEADER_SIZE;
+            let Some(data) = content.get(pos..pos + length) else {
+                log::warn!("FFD data set {:#x} is truncated", id);
+                break;
+            };
+            pos += length;
+
+            if crc32(data) == checksum {
+                self.data_sets.insert(id, data.to_vec());
+            } else {
+                log::warn!("FFD data set {:#x} has invalid checksum", id);
+            }
+        }
+    }
+
+    fn save(&self) -> bool {
+        let mut content = FFD_FILE_MAGIC.to_vec();
+        content.extend_from_slice(&pack_u32(FFD_FILE_VERSION));
+        for (id, data) in &self.data_sets {
+            content.extend_from_slice(&[*id, 0, 0, 0]);
+            content.extend_from_slice(&pack_u32(data.len() as u32));
+            content.extend_from_slice(&pack_u32(crc32(data)));
+            content.extend_from_slice(data);
+        }
+
+        // the old data stays valid if the write fails
+        let tmp_path = self.path.with_extension("tmp");
+        match std::fs::write(&tmp_path, content)
+            .and_then(|_| std::fs::rename(&tmp_path, &self.path))
+        {
+            Ok(_) => true,
+            Err(err) => {
+                log::warn!("unable to write {}: {}", self.path.display(), err);
+                false
+            }
+        }
+    }
+}
+
+impl CharDevice for FfdDevice {
+    fn read(&mut self, _fd: i32, _content: &mut [u8]) -> Result<u64, ()> {
+        // data sets are read by `FFD_IOCTRL_READ_ENTRY`
+        Err(())
+    }
+
+    fn write(&mut self, _fd: i32, _content: &[u8]) -> Result<u64, ()> {
+        Err(())
+    }
+
+    fn ioctl(&mut self, unicorn: &mut Unicorn<Context>, _fd: i32, request: u32, addr: u32) -> i32 {
+        let read_u32 = |unicorn: &Unicorn<Context>, offset: u32| {
+            let mut buf = [0u8; 4];
+            unicorn.mem_read((addr + offset) as u64, &mut buf).unwrap();
+            unpack_u32(&buf)
+        };
+
+        match request {
+            FFD_IOCTRL_SAVENOW => {
+                if !self.save() {
+                    return -5i32; // -EIO
+                }
+            }
+            FFD_IOCTRL_RELOAD => self.reload(),
+            FFD_IOCTRL_GET_ENTRY_SIZE => {
+                let id = read_u32(unicorn, 0) as u8;
+                let Some(data) = self.data_sets.get(&id) else {
+                    return -2i32; // -ENOENT
+                };
+                unicorn
+                    .mem_write_guest(addr as u64 + 4, &pack_u32(data.len() as u32))
+                    .unwrap();
+            }
+            // `{ u8EntryID, u32EntryOffset, u32EntryLength, pvEntryData }`, returns the length
+            FFD_IOCTRL_READ_ENTRY => {
+                let id = read_u32(unicorn, 0) as u8;
+                let offset = read_u32(unicorn, 4) as usize;
+                let length = read_u32(unicorn, 8) as usize;
+                let buffer = read_u32(unicorn, 12);
+                let Some(data) = self.data_sets.get(&id) else {
+                    return -2i32; // -ENOENT
+                };
+                let data = data.get(offset..).unwrap_or_default();
+                let length = length.min(data.len());
+                unicorn
+                    .mem_write_guest(buffer as u64, &data[0..length])
+                    .unwrap();
+                return length as i32;
+            }
+            FFD_IOCTRL_WRITE_ENTRY => {
+                let id = read_u32(unicorn, 0) as u8;
+                let offset = read_u32(unicorn, 4) as usize;
+                let length = read_u32(unicorn, 8) as usize;
+                let buffer = read_u32(unicorn, 12);
+                let mut content = vec![0u8; length];
+                unicorn.mem_read(buffer as u64, &mut content).unwrap();
+
+                let data = self.data_sets.entry(id).or_default();
+                if data.len() < offset + length {
+                    data.resize(offset + length, 0u8);
+                }
+                data[offset..offset + length].copy_from_slice(&content);
+                if !self.save() {
+                    return -5i32; // -EIO
+                }
+                return length as i32;
+            }
+            _ => {
+                log::warn!("unsupported FFD control {:#x}", request);
+                return -25i32; // -ENOTTY
+            }
+        }
+        0i32
+    }
+}
+
+/// Reads data sets dumped to the files named by their id (`<id in hex>.bin`).
+fn read_seed(seed_dir: &Path) -> io::Result<BTreeMap<u8, Vec<u8>>> {
+    let mut data_sets = BTreeMap::new();
+    for entry in std::fs::read_dir(seed_dir)?.flatten() {
+        let path = entry.path();
+        let id = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(".bin"))
+            .and_then(|id| u8::from_str_radix(id, 16).ok());
+        match id {
+            Some(id) => {
+                data_sets.insert(id, std::fs::read(&path)?);
+            }
+            None => log::warn!("{} is not FFD data set", path.display()),
+        }
+    }
+    Ok(data_sets)
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = Crc::new();
+    crc.update(data);
+    crc.sum()
+}
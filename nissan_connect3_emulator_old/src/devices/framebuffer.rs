@@ -0,0 +1,311 @@
+use crate::devices::write_png;
+use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
+use crate::emulator::utils::{pack_u16, pack_u32, unpack_u32};
+use crate::file_system::{CharDevice, OpenFileError, OpenFileFlags, SharedMemory};
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use unicorn_engine::Unicorn;
+
+const FBIOGET_VSCREENINFO: u32 = 0x4600;
+const FBIOPUT_VSCREENINFO: u32 = 0x4601;
+const FBIOGET_FSCREENINFO: u32 = 0x4602;
+const FBIOPAN_DISPLAY: u32 = 0x4606;
+const FBIOBLANK: u32 = 0x4611;
+const FBIO_WAITFORVSYNC: u32 = 0x40044620;
+
+// size of `struct fb_var_screeninfo`
+const VAR_SCREEN_INFO_SIZE: usize = 160;
+
+// number of screens in the virtual resolution (for double buffering)
+const VIRTUAL_SCREENS: u32 = 2;
+
+// physical address reported in `smem_start` (the memory is mapped by `mmap()` only)
+const FRAMEBUFFER_PHYSICAL_ADDRESS: u32 = 0x80000000;
+
+// set by SIGUSR1 signal of the emulator to capture the current frame
+static CAPTURE_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+///
+/// `/dev/fb0` - Linux framebuffer (true color, 16 or 32 bits per pixel).
+///
+/// The pixel memory is allocated for the largest format and mapped directly into the guest.
+///
+pub struct FramebufferDevice {
+    width: u32,
+    height: u32,
+    bits_per_pixel: u32,
+    x_offset: u32,
+    y_offset: u32,
+    memory: Arc<SharedMemory>,
+    // position of `read()` / `write()` of every opened file
+    positions: HashMap<i32, usize>,
+}
+
+impl FramebufferDevice {
+    pub fn new(width: u32, height: u32, bits_per_pixel: u32) -> Self {
+        let memory_size = width * height * VIRTUAL_SCREENS * 4;
+        Self {
+            width,
+            height,
+            bits_per_pixel,
+            x_offset: 0,
+            y_offset: 0,
+            memory: Arc::new(SharedMemory::new(memory_size as usize)),
+            positions: HashMap::new(),
+        }
+    }
+
+    fn line_length(&self) -> u32 {
+        self.width * self.bits_per_pixel / 8
+    }
+
+    /// Returns visible part of the framebuffer as 8-bit RGB pixels.
+    pub fn frame_rgb(&self) -> Vec<u8> {
+        let bytes_per_pixel = (self.bits_per_pixel / 8) as usize;
+        let mut line = vec![0u8; self.width as usize * bytes_per_pixel];
+        let mut rgb = Vec::with_capacity((self.width * self.height * 3) as usize);
+
+        for y in 0..self.height {
+            let offset =
+                (self.y_offset + y) * self.line_length() + self.x_offset * bytes_per_pixel as u32;
+            self.memory.read(offset as usize, &mut line);
+            for pixel in line.chunks(bytes_per_pixel) {
+                if bytes_per_pixel == 2 {
+                    // RGB565
+                    let value = u16::from_le_bytes([pixel[0], pixel[1]]);
+                    let r = ((value >> 11) & 0x1f) as u8;
+                    let g = ((value >> 5) & 0x3f) as u8;
+                    let b = (value & 0x1f) as u8;
+                    rgb.extend_from_slice(&[r << 3 | r >> 2, g << 2 | g >> 4, b << 3 | b >> 2]);
+                } else {
+                    // ARGB8888 (stored as BGRA)
+                    rgb.extend_from_slice(&[pixel[2], pixel[1], pixel[0]]);
+                }
+            }
+        }
+        rgb
+    }
+
+    /// Returns `struct fb_var_screeninfo`.
+    fn var_screen_info(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&pack_u32(self.width)); // xres
+        buf.extend_from_slice(&pack_u32(self.height)); // yres
+        buf.extend_from_slice(&pack_u32(self.width)); // xres_virtual
+        buf.extend_from_slice(&pack_u32(self.height * VIRTUAL_SCREENS)); // yres_virtual
+        buf.extend_from_slice(&pack_u32(self.x_offset)); // xoffset
+        buf.extend_from_slice(&pack_u32(self.y_offset)); // yoffset
+        buf.extend_from_slice(&pack_u32(self.bits_per_pixel)); // bits_per_pixel
+        buf.extend_from_slice(&pack_u32(0)); // grayscale
+
+        // red, green, blue and transp (offset, length, msb_right)
+        let bitfields: [(u32, u32); 4] = if self.bits_per_pixel == 16 {
+            [(11, 5), (5, 6), (0, 5), (0, 0)]
+        } else {
+            [(16, 8), (8, 8), (0, 8), (24, 8)]
+        };
+        for (offset, length) in bitfields {
+            buf.extend_from_slice(&pack_u32(offset));
+            buf.extend_from_slice(&pack_u32(length));
+            buf.extend_from_slice(&pack_u32(0));
+        }
+
+        buf.extend_from_slice(&pack_u32(0)); // nonstd
+        buf.extend_from_slice(&pack_u32(0)); // activate
+        buf.extend_from_slice(&pack_u32(0xffffffff)); // height (mm)
+        buf.extend_from_slice(&pack_u32(0xffffffff)); // width (mm)
+        buf.resize(VAR_SCREEN_INFO_SIZE, 0u8);
+        buf
+    }
+
+    /// Returns `struct fb_fix_screeninfo`.
+    fn fix_screen_info(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        let mut id = b"emulator-fb".to_vec();
+        id.resize(16, 0u8);
+        buf.extend_from_slice(&id); // id
+        buf.extend_from_slice(&pack_u32(FRAMEBUFFER_PHYSICAL_ADDRESS)); // smem_start
+        buf.extend_from_slice(&pack_u32(self.memory.len() as u32)); // smem_len
+        buf.extend_from_slice(&pack_u32(0)); // type = FB_TYPE_PACKED_PIXELS
+        buf.extend_from_slice(&pack_u32(0)); // type_aux
+        buf.extend_from_slice(&pack_u32(2)); // visual = FB_VISUAL_TRUECOLOR
+        buf.extend_from_slice(&pack_u16(0)); // xpanstep
+        buf.extend_from_slice(&pack_u16(1)); // ypanstep
+        buf.extend_from_slice(&pack_u16(0)); // ywrapstep
+        buf.extend_from_slice(&pack_u16(0)); // padding
+        buf.extend_from_slice(&pack_u32(self.line_length())); // line_length
+        buf.extend_from_slice(&pack_u32(0)); // mmio_start
+        buf.extend_from_slice(&pack_u32(0)); // mmio_len
+        buf.extend_from_slice(&pack_u32(0)); // accel
+        buf.extend_from_slice(&pack_u16(0)); // capabilities
+        buf.extend_from_slice(&pack_u16(0)); // reserved
+        buf.extend_from_slice(&pack_u16(0)); // reserved
+        buf.extend_from_slice(&pack_u16(0)); // padding
+        buf
+    }
+
+    /// Applies `struct fb_var_screeninfo` (only the panning and the pixel format can be changed).
+    fn set_var_screen_info(&mut self, buf: &[u8], pan_only: bool) -> Result<(), ()> {
+        let field = |index: usize| unpack_u32(&buf[index * 4..index * 4 + 4]);
+        let (x_offset, y_offset, bits_per_pixel) = (field(4), field(5), field(6));
+
+        if !pan_only {
+            if field(0) != self.width
+                || field(1) != self.height
+                || ![16, 32].contains(&bits_per_pixel)
+            {
+                return Err(());
+            }
+            self.bits_per_pixel = bits_per_pixel;
+        }
+        if x_offset != 0 || y_offset > self.height * (VIRTUAL_SCREENS - 1) {
+            return Err(());
+        }
+        self.x_offset = x_offset;
+        self.y_offset = y_offset;
+        Ok(())
+    }
+}
+
+impl CharDevice for FramebufferDevice {
+    fn open(&mut self, fd: i32, _flags: OpenFileFlags) -> Result<(), OpenFileError> {
+        self.positions.insert(fd, 0);
+        Ok(())
+    }
+
+    fn close(&mut self, fd: i32) {
+        self.positions.remove(&fd);
+    }
+
+    fn read(&mut self, fd: i32, content: &mut [u8]) -> Result<u64, ()> {
+        let position = self.positions.get_mut(&fd).ok_or(())?;
+        let len = self.memory.read(*position, content);
+        *position += len;
+        Ok(len as u64)
+    }
+
+    fn write(&mut self, fd: i32, content: &[u8]) -> Result<u64, ()> {
+        let position = self.positions.get_mut(&fd).ok_or(())?;
+        let len = self.memory.write(*position, content);
+        *position += len;
+        if len == 0 && !content.is_empty() {
+            // ENOSPC
+            return Err(());
+        }
+        Ok(len as u64)
+    }
+
+    fn seek(&mut self, fd: i32, pos: SeekFrom) -> Result<u64, ()> {
+        let position = self.positions.get_mut(&fd).ok_or(())?;
+        let new_position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => (*position as u64).checked_add_signed(offset),
+            SeekFrom::End(offset) => (self.memory.len() as u64).checked_add_signed(offset),
+        }
+        // EINVAL
+        .ok_or(())?;
+        *position = new_position as usize;
+        Ok(new_position)
+    }
+
+    fn ioctl(&mut self, unicorn: &mut Unicorn<Context>, _fd: i32, request: u32, addr: u32) -> i32 {
+        match request {
+            FBIOGET_VSCREENINFO => {
+                unicorn
+                    .mem_write_guest(addr as u64, &self.var_screen_info())
+                    .unwrap();
+                0i32
+            }
+            FBIOGET_FSCREENINFO => {
+                unicorn
+                    .mem_write_guest(addr as u64, &self.fix_screen_info())
+                    .unwrap();
+                0i32
+            }
+            FBIOPUT_VSCREENINFO | FBIOPAN_DISPLAY => {
+                let mut buf = vec![0u8; VAR_SCREEN_INFO_SIZE];
+                unicorn.mem_read(addr as u64, &mut buf).unwrap();
+                if self
+                    .set_var_screen_info(&buf, request == FBIOPAN_DISPLAY)
+                    .is_err()
+                {
+                    return -22i32; // -EINVAL
+                }
+                // the applied values are returned back
+                unicorn
+                    .mem_write_guest(addr as u64, &self.var_screen_info())
+                    .unwrap();
+                0i32
+            }
+            FBIOBLANK | FBIO_WAITFORVSYNC => 0i32,
+            _ => -25i32, // -ENOTTY
+        }
+    }
+
+    fn mmap(&mut self, _fd: i32, size: usize) -> Option<Arc<SharedMemory>> {
+        if size <= self.memory.len() {
+            Some(self.memory.clone())
+        } else {
+            None
+        }
+    }
+}
+
+/// Saves frames of the framebuffer into `output_dir` as `frame_NNNNN.png` images.
+///
+/// Frames are saved every `interval` (only when the screen changes) and whenever
+/// the emulator receives SIGUSR1 signal (`kill -USR1 <pid>`).
+pub fn start_frame_capture(
+    framebuffer: Arc<Mutex<FramebufferDevice>>,
+    output_dir: PathBuf,
+    interval: Option<Duration>,
+) -> std::io::Result<()> {
+    extern "C" fn request_capture(_signal: libc::c_int) {
+        CAPTURE_REQUESTED.store(true, Ordering::SeqCst);
+    }
+
+    std::fs::create_dir_all(&output_dir)?;
+    unsafe {
+        libc::signal(
+            libc::SIGUSR1,
+            request_capture as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        );
+    }
+
+    std::thread::spawn(move || {
+        let mut frame_number = 0;
+        let mut last_frame = Vec::new();
+        let mut last_capture = Instant::now();
+        loop {
+            std::thread::sleep(Duration::from_millis(100));
+
+            let is_requested = CAPTURE_REQUESTED.swap(false, Ordering::SeqCst);
+            let is_periodic = interval.is_some_and(|interval| last_capture.elapsed() >= interval);
+            if !is_requested && !is_periodic {
+                continue;
+            }
+            last_capture = Instant::now();
+
+            let framebuffer = framebuffer.lock().unwrap();
+            let frame = framebuffer.frame_rgb();
+            if !is_requested && frame == last_frame {
+                continue;
+            }
+
+            let path = output_dir.join(format!("frame_{:05}.png", frame_number));
+            match write_png(&path, framebuffer.width, framebuffer.height, &frame) {
+                Ok(_) => log::info!("framebuffer captured to {}", path.display()),
+                Err(err) => log::warn!("unable to capture framebuffer: {}", err),
+            }
+            frame_number += 1;
+            last_frame = frame;
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,373 @@
+use crate::emulator::clock::{clock_now, CLOCK_MONOTONIC, CLOCK_REALTIME};
+use crate::emulator::utils::{civil_from_days, days_from_civil};
+use crate::file_system::{CharDevice, OpenFileError, OpenFileFlags, PollEvents};
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+
+// mean radius of the Earth in meters
+const EARTH_RADIUS: f64 = 6371000.0;
+const KNOTS_PER_METER_PER_SECOND: f64 = 1.943844;
+
+#[derive(Clone, Copy)]
+pub struct TrackPoint {
+    /// Seconds from the start of the track.
+    pub time: f64,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+}
+
+///
+/// Route of the emulated vehicle.
+///
+pub struct GnssTrack {
+    /// Unix time of the first point (`None` if it is not known).
+    start_time: Option<f64>,
+    points: Vec<TrackPoint>,
+}
+
+impl GnssTrack {
+    /// Vehicle standing at one place.
+    pub fn fixed(latitude: f64, longitude: f64, altitude: f64) -> Self {
+        Self {
+            start_time: None,
+            points: vec![TrackPoint {
+                time: 0.0,
+                latitude,
+                longitude,
+                altitude,
+            }],
+        }
+    }
+
+    /// Reads GPX file (`.gpx`) or NMEA log (other files).
+    pub fn from_file(path: &Path) -> std::io::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let track = if path.extension().is_some_and(|ext| ext == "gpx") {
+            Self::from_gpx(&content)
+        } else {
+            Self::from_nmea(&content)
+        };
+        if track.points.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "no track points",
+            ));
+        }
+        Ok(track)
+    }
+
+    /// Points without the time are one second apart.
+    fn from_points(points: Vec<(Option<f64>, f64, f64, f64)>) -> Self {
+        let start_time = points.first().and_then(|point| point.0);
+        let mut track_points: Vec<TrackPoint> = Vec::new();
+        for (index, (time, latitude, longitude, altitude)) in points.into_iter().enumerate() {
+            let time = match (time, start_time) {
+                (Some(time), Some(start_time)) => time - start_time,
+                _ => index as f64,
+            };
+            // the time must increase (duplicate records are skipped)
+            if track_points.last().is_some_and(|last| last.time >= time) {
+                continue;
+            }
+            track_points.push(TrackPoint {
+                time,
+                latitude,
+                longitude,
+                altitude,
+            });
+        }
+        Self {
+            start_time,
+            points: track_points,
+        }
+    }
+
+    fn from_gpx(content: &str) -> Self {
+        let attribute = |tag: &str, name: &str| -> Option<f64> {
+            let start = tag.find(&format!("{}=\"", name))? + name.len() + 2;
+            let len = tag[start..].find('"')?;
+            tag[start..start + len].parse().ok()
+        };
+        let element = |body: &str, name: &str| -> Option<String> {
+            let start = body.find(&format!("<{}>", name))? + name.len() + 2;
+            let len = body[start..].find(&format!("</{}>", name))?;
+            Some(body[start..start + len].trim().to_string())
+        };
+
+        let mut points = Vec::new();
+        for part in content
+            .split("<trkpt")
+            .skip(1)
+            .chain(content.split("<rtept").skip(1))
+        {
+            let tag_end = part.find('>').unwrap_or(part.len());
+            let body = part.split("</trkpt>").next().unwrap();
+            let body = body.split("</rtept>").next().unwrap();
+            if let (Some(latitude), Some(longitude)) = (
+                attribute(&part[..tag_end], "lat"),
+                attribute(&part[..tag_end], "lon"),
+            ) {
+                let altitude = element(body, "ele").and_then(|e| e.parse().ok());
+                let time = element(body, "time").and_then(|t| parse_iso_time(&t));
+                points.push((time, latitude, longitude, altitude.unwrap_or(0.0)));
+            }
+        }
+        Self::from_points(points)
+    }
+
+    fn from_nmea(content: &str) -> Self {
+        let mut points: Vec<(Option<f64>, f64, f64, f64)> = Vec::new();
+        for line in content.lines() {
+            let sentence = line.trim().split('*').next().unwrap();
+            let fields: Vec<&str> = sentence.split(',').collect();
+            let kind = fields[0].get(3..).unwrap_or("");
+            match kind {
+                "RMC" if fields.len() >= 10 && fields[2] == "A" => {
+                    let (Some(latitude), Some(longitude)) = (
+                        parse_nmea_coordinate(fields[3], fields[4]),
+                        parse_nmea_coordinate(fields[5], fields[6]),
+                    ) else {
+                        continue;
+                    };
+                    let time = parse_nmea_time(fields[9], fields[1]);
+                    points.push((time, latitude, longitude, 0.0));
+                }
+                "GGA" if fields.len() >= 10 && fields[6] != "0" => {
+                    let altitude = fields[9].parse::<f64>().unwrap_or(0.0);
+                    // the altitude belongs to the position of the last RMC sentence
+                    if let Some(last) = points.last_mut() {
+                        last.3 = altitude;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Self::from_points(points)
+    }
+
+    /// Returns the position at `time` (seconds from the start) with speed (m/s)
+    /// and course (degrees). The vehicle stops at the end of the track.
+    fn position_at(&self, time: f64) -> (TrackPoint, f64, f64) {
+        let index = self.points.partition_point(|point| point.time <= time);
+        if index == 0 || index == self.points.len() {
+            let point = self.points[index.saturating_sub(1)];
+            return (point, 0.0, 0.0);
+        }
+
+        let (from, to) = (self.points[index - 1], self.points[index]);
+        let fraction = (time - from.time) / (to.time - from.time);
+        let point = TrackPoint {
+            time,
+            latitude: from.latitude + (to.latitude - from.latitude) * fraction,
+            longitude: from.longitude + (to.longitude - from.longitude) * fraction,
+            altitude: from.altitude + (to.altitude - from.altitude) * fraction,
+        };
+
+        let (lat1, lat2) = (from.latitude.to_radians(), to.latitude.to_radians());
+        let delta_lon = (to.longitude - from.longitude).to_radians();
+        let delta_lat = lat2 - lat1;
+        let a = (delta_lat / 2.0).sin().powi(2)
+            + lat1.cos() * lat2.cos() * (delta_lon / 2.0).sin().powi(2);
+        let distance = 2.0 * EARTH_RADIUS * a.sqrt().asin();
+        let course = (delta_lon.sin() * lat2.cos())
+            .atan2(lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * delta_lon.cos())
+            .to_degrees()
+            .rem_euclid(360.0);
+        (point, distance / (to.time - from.time), course)
+    }
+}
+
+///
+/// `/dev/gnss` - GNSS receiver sending NMEA sentences (`RMC`, `GGA`) every second.
+///
+/// The track is replayed on the guest monotonic clock, `speed` makes the vehicle
+/// drive faster (or slower) than recorded.
+///
+pub struct GnssDevice {
+    track: GnssTrack,
+    speed: f64,
+    // Unix time of the start of the replay (in the reported sentences)
+    start_time: f64,
+    readers: HashMap<i32, GnssReader>,
+}
+
+struct GnssReader {
+    // number of the next epoch (second of the replay)
+    next_epoch: u64,
+    pending: VecDeque<u8>,
+}
+
+impl GnssDevice {
+    pub fn new(track: GnssTrack, speed: f64) -> Self {
+        let start_time = track.start_time.unwrap_or_else(|| {
+            (clock_now(CLOCK_REALTIME).as_secs_f64() - clock_now(CLOCK_MONOTONIC).as_secs_f64())
+                .floor()
+        });
+        Self {
+            track,
+            speed,
+            start_time,
+            readers: HashMap::new(),
+        }
+    }
+
+    fn current_epoch(&self) -> u64 {
+        (clock_now(CLOCK_MONOTONIC).as_secs_f64() * self.speed) as u64
+    }
+
+    /// Returns sentences of the epoch (one second of the track).
+    fn sentences(&self, epoch: u64) -> String {
+        let (point, speed, course) = self.track.position_at(epoch as f64);
+        let (date, time) = format_nmea_time(self.start_time + epoch as f64);
+        let latitude = format_nmea_coordinate(point.latitude, 2, 'N', 'S');
+        let longitude = format_nmea_coordinate(point.longitude, 3, 'E', 'W');
+
+        let rmc = format!(
+            "GPRMC,{},A,{},{},{:.1},{:.1},{},,,A",
+            time,
+            latitude,
+            longitude,
+            speed * KNOTS_PER_METER_PER_SECOND,
+            course,
+            date
+        );
+        let gga = format!(
+            "GPGGA,{},{},{},1,08,0.9,{:.1},M,0.0,M,,",
+            time, latitude, longitude, point.altitude
+        );
+        [rmc, gga]
+            .iter()
+            .map(|sentence| {
+                let checksum = sentence.bytes().fold(0u8, |checksum, b| checksum ^ b);
+                format!("${}*{:02X}\r\n", sentence, checksum)
+            })
+            .collect()
+    }
+
+    /// Queues sentences of the epochs that already elapsed.
+    fn update_reader(&mut self, fd: i32) {
+        let current_epoch = self.current_epoch();
+        let Some(reader) = self.readers.get(&fd) else {
+            return;
+        };
+        let epochs = reader.next_epoch..=current_epoch;
+        let sentences: String = epochs.map(|epoch| self.sentences(epoch)).collect();
+
+        let reader = self.readers.get_mut(&fd).unwrap();
+        reader.pending.extend(sentences.as_bytes());
+        reader.next_epoch = current_epoch + 1;
+    }
+}
+
+impl CharDevice for GnssDevice {
+    fn open(&mut self, fd: i32, _flags: OpenFileFlags) -> Result<(), OpenFileError> {
+        // the reader gets the current position first
+        self.readers.insert(
+            fd,
+            GnssReader {
+                next_epoch: self.current_epoch(),
+                pending: VecDeque::new(),
+            },
+        );
+        Ok(())
+    }
+
+    fn close(&mut self, fd: i32) {
+        self.readers.remove(&fd);
+    }
+
+    fn read(&mut self, fd: i32, content: &mut [u8]) -> Result<u64, ()> {
+        self.update_reader(fd);
+        let reader = self.readers.get_mut(&fd).ok_or(())?;
+        if reader.pending.is_empty() {
+            return Err(());
+        }
+
+        let len = content.len().min(reader.pending.len());
+        for (byte, value) in content.iter_mut().zip(reader.pending.drain(..len)) {
+            *byte = value;
+        }
+        Ok(len as u64)
+    }
+
+    fn write(&mut self, _fd: i32, content: &[u8]) -> Result<u64, ()> {
+        // configuration commands of the receiver are ignored
+        Ok(content.len() as u64)
+    }
+
+    fn poll(&mut self, fd: i32) -> PollEvents {
+        self.update_reader(fd);
+        match self.readers.get(&fd) {
+            Some(reader) if !reader.pending.is_empty() => PollEvents::IN | PollEvents::OUT,
+            _ => PollEvents::OUT,
+        }
+    }
+}
+
+/// Returns `ddmm.mmmm,N` (or `dddmm.mmmm,E` for the longitude).
+fn format_nmea_coordinate(
+    value: f64,
+    degree_digits: usize,
+    positive: char,
+    negative: char,
+) -> String {
+    let hemisphere = if value < 0.0 { negative } else { positive };
+    let value = value.abs();
+    let degrees = value.trunc();
+    let minutes = (value - degrees) * 60.0;
+    format!(
+        "{:0width$}{:07.4},{}",
+        degrees as u32,
+        minutes,
+        hemisphere,
+        width = degree_digits
+    )
+}
+
+fn parse_nmea_coordinate(value: &str, hemisphere: &str) -> Option<f64> {
+    let point = value.find('.').unwrap_or(value.len());
+    let degrees: f64 = value.get(..point.checked_sub(2)?)?.parse().ok()?;
+    let minutes: f64 = value.get(point - 2..)?.parse().ok()?;
+    let value = degrees + minutes / 60.0;
+    match hemisphere {
+        "S" | "W" => Some(-value),
+        _ => Some(value),
+    }
+}
+
+/// Returns Unix time of the NMEA date (`ddmmyy`) and time (`hhmmss.ss`).
+fn parse_nmea_time(date: &str, time: &str) -> Option<f64> {
+    let field = |text: &str, index: usize| text.get(index..index + 2)?.parse::<i64>().ok();
+    let days = days_from_civil(2000 + field(date, 4)?, field(date, 2)?, field(date, 0)?);
+    let seconds: f64 = time.get(4..)?.parse().ok()?;
+    Some((days * 86400 + field(time, 0)? * 3600 + field(time, 2)? * 60) as f64 + seconds)
+}
+
+/// Returns NMEA date (`ddmmyy`) and time (`hhmmss.ss`) of the Unix time.
+fn format_nmea_time(unix_time: f64) -> (String, String) {
+    let days = (unix_time / 86400.0).floor() as i64;
+    let seconds = unix_time - (days * 86400) as f64;
+    let (year, month, day) = civil_from_days(days);
+    let date = format!("{:02}{:02}{:02}", day, month, year % 100);
+    let time = format!(
+        "{:02}{:02}{:05.2}",
+        (seconds / 3600.0) as u32,
+        (seconds % 3600.0 / 60.0) as u32,
+        seconds % 60.0
+    );
+    (date, time)
+}
+
+/// Returns Unix time of ISO 8601 time in UTC (`2023-05-01T12:30:00Z`).
+fn parse_iso_time(text: &str) -> Option<f64> {
+    let text = text.trim_end_matches('Z');
+    let (date, time) = text.split_once('T')?;
+    let mut date = date.split('-').map(|field| field.parse::<i64>().ok());
+    let days = days_from_civil(date.next()??, date.next()??, date.next()??);
+    let mut time = time.split(':');
+    let hours: i64 = time.next()?.parse().ok()?;
+    let minutes: i64 = time.next()?.parse().ok()?;
+    let seconds: f64 = time.next()?.parse().ok()?;
+    Some((days * 86400 + hours * 3600 + minutes * 60) as f64 + seconds)
+}
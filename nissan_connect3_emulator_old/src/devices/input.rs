@@ -0,0 +1,324 @@
+use crate::emulator::clock::{clock_now, CLOCK_REALTIME};
+use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
+use crate::emulator::utils::{pack_i32, pack_u16, pack_u32};
+use crate::file_system::{CharDevice, OpenFileError, OpenFileFlags, PollEvents};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use unicorn_engine::Unicorn;
+
+pub const EV_SYN: u16 = 0x00;
+pub const EV_KEY: u16 = 0x01;
+pub const EV_REL: u16 = 0x02;
+pub const EV_ABS: u16 = 0x03;
+
+pub const SYN_REPORT: u16 = 0x00;
+
+pub const REL_DIAL: u16 = 0x07;
+
+pub const ABS_X: u16 = 0x00;
+pub const ABS_Y: u16 = 0x01;
+
+pub const BTN_TOUCH: u16 = 0x14a;
+
+const KEY_MAX: usize = 0x2ff;
+const REL_MAX: usize = 0x0f;
+const ABS_MAX: usize = 0x3f;
+const EV_MAX: usize = 0x1f;
+
+const INPUT_PROP_DIRECT: usize = 0x01;
+
+// `EV_VERSION` of the evdev protocol
+const EVDEV_VERSION: u32 = 0x010001;
+const BUS_VIRTUAL: u16 = 0x06;
+
+// size of `struct input_event` (32-bit `struct timeval`)
+const INPUT_EVENT_SIZE: usize = 16;
+// events kept for every reader (older ones are dropped like in the kernel buffer)
+const MAX_QUEUED_EVENTS: usize = 4096;
+
+// ioctl numbers of evdev are `_IOC(dir, 'E', nr, size)`
+const EVDEV_IOCTL_TYPE: u32 = 0x45;
+const EVIOCGVERSION: u32 = 0x01;
+const EVIOCGID: u32 = 0x02;
+const EVIOCGREP: u32 = 0x03;
+const EVIOCGNAME: u32 = 0x06;
+const EVIOCGPHYS: u32 = 0x07;
+const EVIOCGUNIQ: u32 = 0x08;
+const EVIOCGPROP: u32 = 0x09;
+const EVIOCGKEY: u32 = 0x18;
+const EVIOCGLED: u32 = 0x19;
+const EVIOCGSND: u32 = 0x1a;
+const EVIOCGSW: u32 = 0x1b;
+const EVIOCGBIT: u32 = 0x20;
+const EVIOCGABS: u32 = 0x40;
+const EVIOCGRAB: u32 = 0x90;
+const EVIOCSCLOCKID: u32 = 0xa0;
+
+/// `struct input_absinfo`
+#[derive(Clone, Copy, Default)]
+pub struct AbsInfo {
+    pub value: i32,
+    pub minimum: i32,
+    pub maximum: i32,
+    pub fuzz: i32,
+    pub flat: i32,
+    pub resolution: i32,
+}
+
+impl AbsInfo {
+    pub fn new(minimum: i32, maximum: i32) -> Self {
+        Self {
+            minimum,
+            maximum,
+            ..Default::default()
+        }
+    }
+}
+
+///
+/// `/dev/input/eventN` - evdev input device (touchscreen, keys, rotary encoder, ...).
+///
+/// Events are generated by the emulator (see `InputControl`) and every opened
+/// file gets its own copy of them.
+///
+pub struct InputDevice {
+    name: String,
+    product: u16,
+    keys: BTreeSet<u16>,
+    relative_axes: BTreeSet<u16>,
+    absolute_axes: BTreeMap<u16, AbsInfo>,
+    properties: BTreeSet<usize>,
+    pressed_keys: BTreeSet<u16>,
+    // events waiting to be read by every opened file
+    readers: HashMap<i32, VecDeque<Vec<u8>>>,
+}
+
+impl InputDevice {
+    fn new(name: &str, product: u16) -> Self {
+        Self {
+            name: name.to_string(),
+            product,
+            keys: BTreeSet::new(),
+            relative_axes: BTreeSet::new(),
+            absolute_axes: BTreeMap::new(),
+            properties: BTreeSet::new(),
+            pressed_keys: BTreeSet::new(),
+            readers: HashMap::new(),
+        }
+    }
+
+    /// Single touch screen reporting the position in pixels.
+    pub fn touchscreen(name: &str, width: u32, height: u32) -> Self {
+        let mut device = Self::new(name, 1);
+        device.keys.insert(BTN_TOUCH);
+        device
+            .absolute_axes
+            .insert(ABS_X, AbsInfo::new(0, width as i32 - 1));
+        device
+            .absolute_axes
+            .insert(ABS_Y, AbsInfo::new(0, height as i32 - 1));
+        device.properties.insert(INPUT_PROP_DIRECT);
+        device
+    }
+
+    /// Buttons reporting the key codes (`KEY_*`).
+    pub fn keys(name: &str, keys: &[u16]) -> Self {
+        let mut device = Self::new(name, 2);
+        device.keys.extend(keys);
+        device
+    }
+
+    /// Rotary encoder reporting the steps as `REL_DIAL`.
+    pub fn rotary(name: &str) -> Self {
+        let mut device = Self::new(name, 3);
+        device.relative_axes.insert(REL_DIAL);
+        device
+    }
+
+    pub fn has_key(&self, code: u16) -> bool {
+        self.keys.contains(&code)
+    }
+
+    /// Sends the events followed by `SYN_REPORT` to all readers.
+    pub fn send_events(&mut self, events: &[(u16, u16, i32)]) {
+        for &(event_type, code, value) in events {
+            match event_type {
+                EV_KEY if value != 0 => {
+                    self.pressed_keys.insert(code);
+                }
+                EV_KEY => {
+                    self.pressed_keys.remove(&code);
+                }
+                EV_ABS => {
+                    if let Some(abs_info) = self.absolute_axes.get_mut(&code) {
+                        abs_info.value = value.clamp(abs_info.minimum, abs_info.maximum);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let time = clock_now(CLOCK_REALTIME);
+        for &(event_type, code, value) in events.iter().chain(&[(EV_SYN, SYN_REPORT, 0)]) {
+            let mut buf = Vec::new();
+            buf.extend_from_slice(&pack_u32(time.as_secs() as u32)); // tv_sec
+            buf.extend_from_slice(&pack_u32(time.subsec_micros())); // tv_usec
+            buf.extend_from_slice(&pack_u16(event_type));
+            buf.extend_from_slice(&pack_u16(code));
+            buf.extend_from_slice(&pack_i32(value));
+            for queue in self.readers.values_mut() {
+                if queue.len() >= MAX_QUEUED_EVENTS {
+                    queue.pop_front();
+                }
+                queue.push_back(buf.clone());
+            }
+        }
+    }
+
+    /// Returns bitmap of `bits` (`max` is the highest possible bit).
+    fn bitmap(bits: impl IntoIterator<Item = usize>, max: usize) -> Vec<u8> {
+        let mut buf = vec![0u8; max / 8 + 1];
+        for bit in bits {
+            buf[bit / 8] |= 1 << (bit % 8);
+        }
+        buf
+    }
+
+    fn event_types(&self) -> Vec<usize> {
+        let mut event_types = vec![EV_SYN as usize];
+        if !self.keys.is_empty() {
+            event_types.push(EV_KEY as usize);
+        }
+        if !self.relative_axes.is_empty() {
+            event_types.push(EV_REL as usize);
+        }
+        if !self.absolute_axes.is_empty() {
+            event_types.push(EV_ABS as usize);
+        }
+        event_types
+    }
+
+    /// Returns data of the read request or `None` if the request is not supported.
+    fn ioctl_read(&self, nr: u32) -> Option<Vec<u8>> {
+        let to_usize = |set: &BTreeSet<u16>| set.iter().map(|&c| c as usize).collect::<Vec<_>>();
+        let buf = match nr {
+            EVIOCGVERSION => pack_u32(EVDEV_VERSION),
+            EVIOCGID => {
+                let mut buf = Vec::new();
+                buf.extend_from_slice(&pack_u16(BUS_VIRTUAL)); // bustype
+                buf.extend_from_slice(&pack_u16(0)); // vendor
+                buf.extend_from_slice(&pack_u16(self.product)); // product
+                buf.extend_from_slice(&pack_u16(1)); // version
+                buf
+            }
+            EVIOCGNAME => [self.name.as_bytes(), &[0]].concat(),
+            EVIOCGPHYS => b"emulator/input0\0".to_vec(),
+            EVIOCGUNIQ => vec![0u8],
+            EVIOCGPROP => Self::bitmap(self.properties.iter().copied(), 0x1f),
+            EVIOCGKEY => Self::bitmap(to_usize(&self.pressed_keys), KEY_MAX),
+            EVIOCGLED | EVIOCGSND | EVIOCGSW => vec![0u8; 8],
+            nr if nr == EVIOCGBIT => Self::bitmap(self.event_types(), EV_MAX),
+            nr if nr == EVIOCGBIT + EV_KEY as u32 => Self::bitmap(to_usize(&self.keys), KEY_MAX),
+            nr if nr == EVIOCGBIT + EV_REL as u32 => {
+                Self::bitmap(to_usize(&self.relative_axes), REL_MAX)
+            }
+            nr if nr == EVIOCGBIT + EV_ABS as u32 => {
+                Self::bitmap(self.absolute_axes.keys().map(|&c| c as usize), ABS_MAX)
+            }
+            nr if (EVIOCGBIT..EVIOCGBIT + EV_MAX as u32).contains(&nr) => vec![0u8; 8],
+            nr if (EVIOCGABS..EVIOCGABS + ABS_MAX as u32).contains(&nr) => {
+                let abs_info = self.absolute_axes.get(&((nr - EVIOCGABS) as u16))?;
+                let mut buf = Vec::new();
+                buf.extend_from_slice(&pack_i32(abs_info.value));
+                buf.extend_from_slice(&pack_i32(abs_info.minimum));
+                buf.extend_from_slice(&pack_i32(abs_info.maximum));
+                buf.extend_from_slice(&pack_i32(abs_info.fuzz));
+                buf.extend_from_slice(&pack_i32(abs_info.flat));
+                buf.extend_from_slice(&pack_i32(abs_info.resolution));
+                buf
+            }
+            _ => return None,
+        };
+        Some(buf)
+    }
+}
+
+impl CharDevice for InputDevice {
+    fn open(&mut self, fd: i32, _flags: OpenFileFlags) -> Result<(), OpenFileError> {
+        self.readers.insert(fd, VecDeque::new());
+        Ok(())
+    }
+
+    fn close(&mut self, fd: i32) {
+        self.readers.remove(&fd);
+    }
+
+    fn read(&mut self, fd: i32, content: &mut [u8]) -> Result<u64, ()> {
+        let queue = self.readers.get_mut(&fd).ok_or(())?;
+
+        let mut len = 0;
+        while len + INPUT_EVENT_SIZE <= content.len() {
+            match queue.pop_front() {
+                Some(event) => {
+                    content[len..len + INPUT_EVENT_SIZE].copy_from_slice(&event);
+                    len += INPUT_EVENT_SIZE;
+                }
+                None => break,
+            }
+        }
+
+        // no events or the buffer is too small for one event
+        if len == 0 {
+            return Err(());
+        }
+        Ok(len as u64)
+    }
+
+    fn write(&mut self, _fd: i32, content: &[u8]) -> Result<u64, ()> {
+        // events written by the guest (LEDs, sounds) are ignored
+        Ok(content.len() as u64)
+    }
+
+    fn ioctl(&mut self, unicorn: &mut Unicorn<Context>, _fd: i32, request: u32, addr: u32) -> i32 {
+        let direction = request >> 30;
+        let size = ((request >> 16) & 0x3fff) as usize;
+        let nr = request & 0xff;
+        if (request >> 8) & 0xff != EVDEV_IOCTL_TYPE {
+            return -25i32; // -ENOTTY
+        }
+
+        match (direction, nr) {
+            // _IOC_READ
+            (2, EVIOCGREP) => {
+                // delay and period of the key repeat (not generated)
+                let mut buf = pack_u32(250);
+                buf.extend_from_slice(&pack_u32(33));
+                unicorn.mem_write_guest(addr as u64, &buf).unwrap();
+                0i32
+            }
+            (2, nr) => match self.ioctl_read(nr) {
+                Some(buf) => {
+                    let len = buf.len().min(size);
+                    unicorn.mem_write_guest(addr as u64, &buf[0..len]).unwrap();
+                    // variable length requests return the length
+                    if matches!(nr, EVIOCGVERSION | EVIOCGID) || nr >= EVIOCGABS {
+                        0i32
+                    } else {
+                        len as i32
+                    }
+                }
+                None => -22i32, // -EINVAL
+            },
+            // _IOC_WRITE (exclusive access and clock of the timestamps are accepted)
+            (1, EVIOCGRAB | EVIOCSCLOCKID) => 0i32,
+            _ => -22i32, // -EINVAL
+        }
+    }
+
+    fn poll(&mut self, fd: i32) -> PollEvents {
+        match self.readers.get(&fd) {
+            Some(queue) if !queue.is_empty() => PollEvents::IN | PollEvents::OUT,
+            _ => PollEvents::OUT,
+        }
+    }
+}
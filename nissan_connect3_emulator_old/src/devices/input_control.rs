@@ -0,0 +1,120 @@
+use crate::devices::{
+    CommandControl, InputDevice, ABS_X, ABS_Y, BTN_TOUCH, EV_ABS, EV_KEY, EV_REL, REL_DIAL,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// `KEY_*` codes of the hard keys of the head unit
+pub const HARD_KEYS: [(&str, u16); 16] = [
+    ("enter", 28),
+    ("up", 103),
+    ("left", 105),
+    ("right", 106),
+    ("down", 108),
+    ("mute", 113),
+    ("volumedown", 114),
+    ("volumeup", 115),
+    ("power", 116),
+    ("menu", 139),
+    ("back", 158),
+    ("nextsong", 163),
+    ("previoussong", 165),
+    ("phone", 169),
+    ("home", 172),
+    ("media", 226),
+];
+
+// time between the press and the release of `tap` and `key`
+const PRESS_DURATION: Duration = Duration::from_millis(100);
+
+///
+/// Generates input events of the emulated touchscreen, hard keys and rotary encoder
+/// from text commands (one command per line, `#` starts a comment):
+///
+/// - `wait <ms>`
+/// - `tap <x> <y>`, `touch <x> <y>` (press or move), `release`
+/// - `key <name or code> [down|up]` (press and release without the state)
+/// - `rotate <steps>` (negative steps turn counter-clockwise)
+///
+#[derive(Clone)]
+pub struct InputControl {
+    pub touchscreen: Arc<Mutex<InputDevice>>,
+    pub keys: Arc<Mutex<InputDevice>>,
+    pub rotary: Arc<Mutex<InputDevice>>,
+}
+
+impl CommandControl for InputControl {
+    const NAME: &'static str = "input";
+
+    fn execute(&self, line: &str) -> Result<(), String> {
+        let line = line.split('#').next().unwrap().trim();
+        let args: Vec<&str> = line.split_whitespace().collect();
+        let number = |index: usize| -> Result<i32, String> {
+            args.get(index)
+                .ok_or(format!("missing argument of `{}`", line))?
+                .parse::<i32>()
+                .map_err(|_| format!("invalid number in `{}`", line))
+        };
+
+        match args.first().copied() {
+            None => {}
+            Some("wait") => std::thread::sleep(Duration::from_millis(number(1)? as u64)),
+            Some("touch") => self.touch(number(1)?, number(2)?),
+            Some("release") => self.release(),
+            Some("tap") => {
+                self.touch(number(1)?, number(2)?);
+                std::thread::sleep(PRESS_DURATION);
+                self.release();
+            }
+            Some("key") => {
+                let name = args.get(1).ok_or(format!("missing key in `{}`", line))?;
+                let code = HARD_KEYS
+                    .iter()
+                    .find(|(key_name, _)| key_name == name)
+                    .map(|(_, code)| *code)
+                    .or_else(|| name.parse::<u16>().ok())
+                    .ok_or(format!("unknown key `{}`", name))?;
+                let mut keys = self.keys.lock().unwrap();
+                if !keys.has_key(code) {
+                    return Err(format!("key {} is not supported", code));
+                }
+                match args.get(2).copied() {
+                    Some("down") => keys.send_events(&[(EV_KEY, code, 1)]),
+                    Some("up") => keys.send_events(&[(EV_KEY, code, 0)]),
+                    None => {
+                        keys.send_events(&[(EV_KEY, code, 1)]);
+                        drop(keys);
+                        std::thread::sleep(PRESS_DURATION);
+                        self.keys.lock().unwrap().send_events(&[(EV_KEY, code, 0)]);
+                    }
+                    Some(state) => return Err(format!("invalid key state `{}`", state)),
+                }
+            }
+            Some("rotate") => {
+                self.rotary
+                    .lock()
+                    .unwrap()
+                    .send_events(&[(EV_REL, REL_DIAL, number(1)?)])
+            }
+            Some(command) => return Err(format!("unknown command `{}`", command)),
+        }
+        Ok(())
+    }
+}
+
+impl InputControl {
+    fn touch(&self, x: i32, y: i32) {
+        self.touchscreen.lock().unwrap().send_events(&[
+            (EV_ABS, ABS_X, x),
+            (EV_ABS, ABS_Y, y),
+            (EV_KEY, BTN_TOUCH, 1),
+        ]);
+    }
+
+    fn release(&self) {
+        self.touchscreen
+            .lock()
+            .unwrap()
+            .send_events(&[(EV_KEY, BTN_TOUCH, 0)]);
+    }
+}
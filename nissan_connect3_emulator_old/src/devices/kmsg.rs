@@ -0,0 +1,131 @@
+use crate::emulator::clock::{clock_now, CLOCK_MONOTONIC};
+use crate::file_system::{CharDevice, OpenFileError, OpenFileFlags, PollEvents};
+use std::collections::{HashMap, VecDeque};
+use std::io::SeekFrom;
+
+// number of records kept in the buffer (older ones are dropped)
+const MAX_RECORDS: usize = 1024;
+
+struct KmsgRecord {
+    sequence: u64,
+    priority: u32,
+    timestamp_us: u128,
+    message: String,
+}
+
+///
+/// `/dev/kmsg` - kernel log buffer.
+///
+/// Written messages are passed to the emulator log, readers get them as
+/// `priority,sequence,timestamp,-;message` records (one record per `read()`).
+///
+#[derive(Default)]
+pub struct KmsgDevice {
+    records: VecDeque<KmsgRecord>,
+    next_sequence: u64,
+    // sequence number of the next record returned to the reader
+    readers: HashMap<i32, u64>,
+}
+
+impl KmsgDevice {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the message (it can start with `<priority>` prefix).
+    fn add_message(&mut self, message: &str) {
+        let (priority, message) = match message
+            .strip_prefix('<')
+            .and_then(|rest| rest.split_once('>'))
+            .and_then(|(priority, rest)| Some((priority.parse::<u32>().ok()?, rest)))
+        {
+            Some((priority, message)) => (priority, message),
+            // default log level of the user space messages (LOG_USER | LOG_NOTICE)
+            None => (13, message),
+        };
+
+        match priority & 7 {
+            0..=3 => log::error!("[kmsg] {}", message),
+            4 => log::warn!("[kmsg] {}", message),
+            5 | 6 => log::info!("[kmsg] {}", message),
+            _ => log::debug!("[kmsg] {}", message),
+        }
+
+        if self.records.len() >= MAX_RECORDS {
+            self.records.pop_front();
+        }
+        self.records.push_back(KmsgRecord {
+            sequence: self.next_sequence,
+            priority,
+            timestamp_us: clock_now(CLOCK_MONOTONIC).as_micros(),
+            message: message.to_string(),
+        });
+        self.next_sequence += 1;
+    }
+
+    fn next_record(&self, fd: i32) -> Option<&KmsgRecord> {
+        let sequence = *self.readers.get(&fd)?;
+        self.records
+            .iter()
+            .find(|record| record.sequence >= sequence)
+    }
+}
+
+impl CharDevice for KmsgDevice {
+    fn open(&mut self, fd: i32, _flags: OpenFileFlags) -> Result<(), OpenFileError> {
+        // reading starts with the oldest record in the buffer
+        let first_sequence = self.records.front().map(|r| r.sequence).unwrap_or(0);
+        self.readers.insert(fd, first_sequence);
+        Ok(())
+    }
+
+    fn close(&mut self, fd: i32) {
+        self.readers.remove(&fd);
+    }
+
+    fn read(&mut self, fd: i32, content: &mut [u8]) -> Result<u64, ()> {
+        let record = self.next_record(fd).ok_or(())?;
+        let text = format!(
+            "{},{},{},-;{}\n",
+            record.priority,
+            record.sequence,
+            record.timestamp_us,
+            record.message.trim_end_matches('\n')
+        );
+        if text.len() > content.len() {
+            // EINVAL
+            return Err(());
+        }
+        content[0..text.len()].copy_from_slice(text.as_bytes());
+
+        let sequence = record.sequence + 1;
+        self.readers.insert(fd, sequence);
+        Ok(text.len() as u64)
+    }
+
+    fn write(&mut self, _fd: i32, content: &[u8]) -> Result<u64, ()> {
+        let message = String::from_utf8_lossy(content);
+        self.add_message(message.trim_end_matches('\n'));
+        Ok(content.len() as u64)
+    }
+
+    fn seek(&mut self, fd: i32, pos: SeekFrom) -> Result<u64, ()> {
+        // like `devkmsg_llseek()`, only the start and the end of the buffer can be selected
+        let sequence = match pos {
+            SeekFrom::Start(0) => self.records.front().map(|r| r.sequence).unwrap_or(0),
+            SeekFrom::End(0) => self.next_sequence,
+            // ESPIPE or EINVAL
+            _ => return Err(()),
+        };
+        self.readers.insert(fd, sequence);
+        Ok(0)
+    }
+
+    fn poll(&mut self, fd: i32) -> PollEvents {
+        if self.next_record(fd).is_some() {
+            PollEvents::IN | PollEvents::OUT
+        } else {
+            PollEvents::OUT
+        }
+    }
+}
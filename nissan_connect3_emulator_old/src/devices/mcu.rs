@@ -0,0 +1,285 @@
+use crate::devices::CommandControl;
+use crate::emulator::clock::{clock_now, CLOCK_MONOTONIC};
+use crate::file_system::McuModel;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// component status handshake that every INC service starts with (`C_`/`R_COMPONENT_STATUS`)
+const MSG_C_COMPONENT_STATUS: u8 = 0x20;
+const MSG_R_COMPONENT_STATUS: u8 = 0x21;
+
+// 48 ticks per revolution of the wheel with 1.95 m circumference
+const WHEEL_TICKS_PER_METER: f64 = 48.0 / 1.95;
+
+/// State of the vehicle reported by the MCU.
+pub struct VehicleState {
+    pub ignition: bool,
+    /// Speed in km/h.
+    pub speed: f64,
+    pub reverse: bool,
+    pub illumination: bool,
+    // wheel ticks counted until the last change of the speed
+    wheel_ticks: f64,
+    speed_changed: Duration,
+}
+
+impl VehicleState {
+    /// Returns wheel ticks counted until `now` (the counter wraps around).
+    pub fn wheel_ticks(&self, now: Duration) -> u32 {
+        let distance = self.speed / 3.6 * now.saturating_sub(self.speed_changed).as_secs_f64();
+        (self.wheel_ticks + distance * WHEEL_TICKS_PER_METER) as u64 as u32
+    }
+
+    fn set_speed(&mut self, speed: f64) {
+        let now = clock_now(CLOCK_MONOTONIC);
+        self.wheel_ticks = self.wheel_ticks(now) as f64;
+        self.speed_changed = now;
+        self.speed = speed;
+    }
+}
+
+/// Part of the message, values of the vehicle state are little-endian (as on the V850).
+#[derive(Clone, Copy)]
+enum MessageItem {
+    Byte(u8),
+    /// `{ignition}` (1 byte).
+    Ignition,
+    /// `{speed}` in 0.01 km/h (2 bytes).
+    Speed,
+    /// `{reverse}` (1 byte).
+    Reverse,
+    /// `{illumination}` (1 byte).
+    Illumination,
+    /// `{wheel_ticks}` (4 bytes).
+    WheelTicks,
+}
+
+struct McuResponse {
+    port: u16,
+    // prefix of the matching requests (empty matches all requests)
+    request: Vec<u8>,
+    messages: Vec<Vec<MessageItem>>,
+}
+
+struct McuReport {
+    port: u16,
+    interval: Duration,
+    message: Vec<MessageItem>,
+}
+
+struct McuState {
+    vehicle: VehicleState,
+    responses: Vec<McuResponse>,
+    reports: Vec<McuReport>,
+}
+
+impl McuState {
+    fn build_message(&self, items: &[MessageItem]) -> Vec<u8> {
+        let mut message = Vec::new();
+        for item in items {
+            match item {
+                MessageItem::Byte(byte) => message.push(*byte),
+                MessageItem::Ignition => message.push(self.vehicle.ignition as u8),
+                MessageItem::Speed => message.extend_from_slice(
+                    &((self.vehicle.speed * 100.0).round() as u16).to_le_bytes(),
+                ),
+                MessageItem::Reverse => message.push(self.vehicle.reverse as u8),
+                MessageItem::Illumination => message.push(self.vehicle.illumination as u8),
+                MessageItem::WheelTicks => message.extend_from_slice(
+                    &self
+                        .vehicle
+                        .wheel_ticks(clock_now(CLOCK_MONOTONIC))
+                        .to_le_bytes(),
+                ),
+            }
+        }
+        message
+    }
+}
+
+///
+/// MCU (V850) model with the vehicle state and scripted messages of its INC services.
+/// It is configured by text commands (one command per line, `#` starts a comment):
+///
+/// - `wait <ms>`
+/// - `ignition on|off`, `speed <km/h>`, `reverse on|off`, `illumination on|off`
+/// - `respond <port> <request prefix> => <message> [; <message> ...]`
+/// - `report <port> <interval ms> <message>`
+///
+/// Messages are hex bytes (`20 0a`) and values of the vehicle state (`{ignition}`, `{speed}`,
+/// `{reverse}`, `{illumination}`, `{wheel_ticks}`).
+/// `C_COMPONENT_STATUS` requests without a response are answered with the same status,
+/// so the handshake of every service succeeds.
+///
+#[derive(Clone)]
+pub struct ScriptedMcu {
+    state: Arc<Mutex<McuState>>,
+}
+
+impl ScriptedMcu {
+    /// Creates the MCU of the parked vehicle with ignition on.
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(McuState {
+                vehicle: VehicleState {
+                    ignition: true,
+                    speed: 0.0,
+                    reverse: false,
+                    illumination: false,
+                    wheel_ticks: 0.0,
+                    speed_changed: Duration::ZERO,
+                },
+                responses: Vec::new(),
+                reports: Vec::new(),
+            })),
+        }
+    }
+}
+
+impl CommandControl for ScriptedMcu {
+    const NAME: &'static str = "MCU";
+
+    fn execute(&self, line: &str) -> Result<(), String> {
+        let line = line.split('#').next().unwrap().trim();
+        let args: Vec<&str> = line.split_whitespace().collect();
+        let argument = |index: usize| -> Result<&str, String> {
+            args.get(index)
+                .copied()
+                .ok_or(format!("missing argument of `{}`", line))
+        };
+        let switch = |index: usize| -> Result<bool, String> {
+            match argument(index)? {
+                "on" => Ok(true),
+                "off" => Ok(false),
+                value => Err(format!("invalid state `{}`", value)),
+            }
+        };
+        let number = |index: usize| -> Result<f64, String> {
+            argument(index)?
+                .parse::<f64>()
+                .map_err(|_| format!("invalid number in `{}`", line))
+        };
+
+        let mut state = self.state.lock().unwrap();
+        match args.first().copied() {
+            None => {}
+            Some("wait") => {
+                drop(state);
+                std::thread::sleep(Duration::from_millis(number(1)? as u64));
+            }
+            Some("ignition") => state.vehicle.ignition = switch(1)?,
+            Some("speed") => state.vehicle.set_speed(number(1)?.max(0.0)),
+            Some("reverse") => state.vehicle.reverse = switch(1)?,
+            Some("illumination") => state.vehicle.illumination = switch(1)?,
+            Some("respond") => {
+                let port = parse_port(argument(1)?)?;
+                let separator = args
+                    .iter()
+                    .position(|arg| *arg == "=>")
+                    .ok_or(format!("missing `=>` in `{}`", line))?;
+                let request = parse_message(&args[2..separator])?
+                    .into_iter()
+                    .map(|item| match item {
+                        MessageItem::Byte(byte) => Ok(byte),
+                        _ => Err(format!("values are not allowed in the request `{}`", line)),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let messages = args[separator + 1..]
+                    .split(|arg| *arg == ";")
+                    .map(parse_message)
+                    .collect::<Result<Vec<_>, _>>()?;
+                state.responses.push(McuResponse {
+                    port,
+                    request,
+                    messages,
+                });
+            }
+            Some("report") => {
+                let port = parse_port(argument(1)?)?;
+                let interval = Duration::from_millis(number(2)? as u64);
+                if interval.is_zero() {
+                    return Err(format!("invalid interval in `{}`", line));
+                }
+                let message = parse_message(&args[3..])?;
+                state.reports.push(McuReport {
+                    port,
+                    interval,
+                    message,
+                });
+            }
+            Some(command) => return Err(format!("unknown command `{}`", command)),
+        }
+        Ok(())
+    }
+}
+
+impl McuModel for ScriptedMcu {
+    fn connect(&mut self, port: u16) -> Vec<Vec<u8>> {
+        log::debug!("INC connection to MCU port {}", port);
+        Vec::new()
+    }
+
+    fn handle_message(&mut self, port: u16, message: &[u8]) -> Vec<Vec<u8>> {
+        let state = self.state.lock().unwrap();
+        let response = state
+            .responses
+            .iter()
+            .find(|response| response.port == port && message.starts_with(&response.request));
+        match response {
+            Some(response) => response
+                .messages
+                .iter()
+                .map(|message| state.build_message(message))
+                .collect(),
+            None if message.first() == Some(&MSG_C_COMPONENT_STATUS) => {
+                let mut response = message.to_vec();
+                response[0] = MSG_R_COMPONENT_STATUS;
+                vec![response]
+            }
+            None => {
+                log::debug!(
+                    "unhandled INC message to MCU port {}: {:02x?}",
+                    port,
+                    message
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    fn periodic_messages(&mut self, port: u16, from: Duration, to: Duration) -> Vec<Vec<u8>> {
+        let state = self.state.lock().unwrap();
+        // only the last report of the period is sent (older values are out of date anyway)
+        state
+            .reports
+            .iter()
+            .filter(|report| {
+                report.port == port
+                    && to.as_nanos() / report.interval.as_nanos()
+                        > from.as_nanos() / report.interval.as_nanos()
+            })
+            .map(|report| state.build_message(&report.message))
+            .collect()
+    }
+}
+
+fn parse_port(value: &str) -> Result<u16, String> {
+    value
+        .parse::<u16>()
+        .map_err(|_| format!("invalid port `{}`", value))
+}
+
+fn parse_message(args: &[&str]) -> Result<Vec<MessageItem>, String> {
+    args.iter()
+        .map(|arg| match *arg {
+            "{ignition}" => Ok(MessageItem::Ignition),
+            "{speed}" => Ok(MessageItem::Speed),
+            "{reverse}" => Ok(MessageItem::Reverse),
+            "{illumination}" => Ok(MessageItem::Illumination),
+            "{wheel_ticks}" => Ok(MessageItem::WheelTicks),
+            _ => u8::from_str_radix(arg.trim_start_matches("0x"), 16)
+                .map(MessageItem::Byte)
+                .map_err(|_| format!("invalid byte `{}`", arg)),
+        })
+        .collect()
+}
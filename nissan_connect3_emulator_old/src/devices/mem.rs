@@ -0,0 +1,120 @@
+use crate::file_system::{CharDevice, SharedMemory};
+use std::io::SeekFrom;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+///
+/// `/dev/null` - discards written data, reading returns end of file.
+///
+pub struct NullDevice;
+
+impl CharDevice for NullDevice {
+    fn read(&mut self, _fd: i32, _content: &mut [u8]) -> Result<u64, ()> {
+        Ok(0)
+    }
+
+    fn write(&mut self, _fd: i32, content: &[u8]) -> Result<u64, ()> {
+        Ok(content.len() as u64)
+    }
+
+    fn seek(&mut self, _fd: i32, _pos: SeekFrom) -> Result<u64, ()> {
+        // the position stays at the beginning (like `null_lseek()`)
+        Ok(0)
+    }
+}
+
+///
+/// `/dev/zero` - discards written data, reading returns zeros.
+///
+pub struct ZeroDevice;
+
+impl CharDevice for ZeroDevice {
+    fn read(&mut self, _fd: i32, content: &mut [u8]) -> Result<u64, ()> {
+        content.fill(0);
+        Ok(content.len() as u64)
+    }
+
+    fn write(&mut self, _fd: i32, content: &[u8]) -> Result<u64, ()> {
+        Ok(content.len() as u64)
+    }
+
+    fn seek(&mut self, _fd: i32, _pos: SeekFrom) -> Result<u64, ()> {
+        Ok(0)
+    }
+
+    fn mmap(&mut self, _fd: i32, size: usize) -> Option<Arc<SharedMemory>> {
+        // shared mapping of /dev/zero is the anonymous shared memory
+        Some(Arc::new(SharedMemory::new(size)))
+    }
+}
+
+///
+/// `/dev/full` - reading returns zeros, writing fails (like the device is full).
+///
+pub struct FullDevice;
+
+impl CharDevice for FullDevice {
+    fn read(&mut self, _fd: i32, content: &mut [u8]) -> Result<u64, ()> {
+        content.fill(0);
+        Ok(content.len() as u64)
+    }
+
+    fn write(&mut self, _fd: i32, _content: &[u8]) -> Result<u64, ()> {
+        // ENOSPC
+        Err(())
+    }
+
+    fn seek(&mut self, _fd: i32, _pos: SeekFrom) -> Result<u64, ()> {
+        Ok(0)
+    }
+}
+
+///
+/// `/dev/random` and `/dev/urandom` - pseudo random numbers (never blocks).
+///
+/// Numbers are generated by splitmix64, so the same seed always gives the same sequence.
+///
+pub struct RandomDevice {
+    state: u64,
+}
+
+impl RandomDevice {
+    /// Creates the generator (seeded by the current time if `seed` is not set).
+    pub fn new(seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos() as u64
+        });
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl CharDevice for RandomDevice {
+    fn read(&mut self, _fd: i32, content: &mut [u8]) -> Result<u64, ()> {
+        for chunk in content.chunks_mut(8) {
+            let value = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&value[0..chunk.len()]);
+        }
+        Ok(content.len() as u64)
+    }
+
+    fn write(&mut self, _fd: i32, content: &[u8]) -> Result<u64, ()> {
+        // written data would be mixed into the entropy pool, ignored to keep the sequence
+        Ok(content.len() as u64)
+    }
+
+    fn seek(&mut self, _fd: i32, _pos: SeekFrom) -> Result<u64, ()> {
+        // there is no position in the stream of numbers
+        Ok(0)
+    }
+}
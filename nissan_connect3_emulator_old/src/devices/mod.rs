@@ -0,0 +1,56 @@
+mod acoustic_out;
+mod acoustic_src;
+mod bt_asip;
+mod command_control;
+mod errmem;
+mod ffd;
+mod framebuffer;
+mod gnss;
+mod input;
+mod input_control;
+mod kmsg;
+mod mcu;
+mod mem;
+mod png;
+mod registry;
+mod tty;
+mod wav;
+
+pub use acoustic_out::*;
+pub use acoustic_src::*;
+pub use bt_asip::*;
+pub use command_control::*;
+pub use errmem::*;
+pub use ffd::*;
+pub use framebuffer::*;
+pub use gnss::*;
+pub use input::*;
+pub use input_control::*;
+pub use kmsg::*;
+pub use mcu::*;
+pub use mem::*;
+pub use png::*;
+pub use registry::*;
+pub use tty::*;
+pub use wav::*;
+
+use crate::file_system::CharDeviceRegistry;
+
+/// Registers the standard Linux devices (`/dev/null`, `/dev/zero`, `/dev/tty`, ...).
+///
+/// `/dev/random` and `/dev/urandom` give the same numbers in every run if `random_seed` is set.
+pub fn register_standard_devices(registry: &mut CharDeviceRegistry, random_seed: Option<u64>) {
+    registry.register("/null", 1, 3, NullDevice);
+    registry.register("/zero", 1, 5, ZeroDevice);
+    registry.register("/full", 1, 7, FullDevice);
+    registry.register("/random", 1, 8, RandomDevice::new(random_seed));
+    registry.register(
+        "/urandom",
+        1,
+        9,
+        RandomDevice::new(random_seed.map(|seed| seed.wrapping_add(1))),
+    );
+    registry.register("/kmsg", 1, 11, KmsgDevice::new());
+    registry.register("/tty", 5, 0, TtyDevice);
+    registry.register("/console", 5, 1, TtyDevice);
+}
@@ -0,0 +1,90 @@
+use flate2::write::ZlibEncoder;
+use flate2::{Compression, Crc};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+/// Saves 8-bit RGB pixels (`width * height * 3` bytes) as PNG image.
+pub fn write_png(path: &Path, width: u32, height: u32, rgb: &[u8]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(&PNG_SIGNATURE)?;
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // bit depth 8, color type RGB, deflate compression, adaptive filtering, no interlace
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+    write_chunk(&mut writer, b"IHDR", &header)?;
+
+    // every scanline starts with the filter type (none)
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
+    for line in rgb.chunks(width as usize * 3) {
+        encoder.write_all(&[0])?;
+        encoder.write_all(line)?;
+    }
+    write_chunk(&mut writer, b"IDAT", &encoder.finish()?)?;
+
+    write_chunk(&mut writer, b"IEND", &[])?;
+    writer.flush()
+}
+
+fn write_chunk(writer: &mut impl Write, chunk_type: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    let mut crc = Crc::new();
+    crc.update(chunk_type);
+    crc.update(data);
+
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(chunk_type)?;
+    writer.write_all(data)?;
+    writer.write_all(&crc.sum().to_be_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::ZlibDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn test_write_png() {
+        let path = std::env::temp_dir().join(format!("png-test-{}.png", std::process::id()));
+        let rgb: Vec<u8> = (0..2 * 3 * 3).collect();
+        write_png(&path, 3, 2, &rgb).unwrap();
+        let content = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(content[0..8], PNG_SIGNATURE);
+
+        let mut chunks = Vec::new();
+        let mut offset = 8;
+        while offset < content.len() {
+            let len = u32::from_be_bytes(content[offset..offset + 4].try_into().unwrap()) as usize;
+            let chunk_type = &content[offset + 4..offset + 8];
+            let data = &content[offset + 8..offset + 8 + len];
+            let mut crc = Crc::new();
+            crc.update(chunk_type);
+            crc.update(data);
+            assert_eq!(
+                content[offset + 8 + len..offset + 12 + len],
+                crc.sum().to_be_bytes()
+            );
+            chunks.push((chunk_type.to_vec(), data.to_vec()));
+            offset += 12 + len;
+        }
+
+        let types: Vec<&[u8]> = chunks.iter().map(|(t, _)| t.as_slice()).collect();
+        assert_eq!(types, vec![&b"IHDR"[..], b"IDAT", b"IEND"]);
+        assert_eq!(chunks[0].1, [0, 0, 0, 3, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+
+        let mut pixels = Vec::new();
+        ZlibDecoder::new(chunks[1].1.as_slice())
+            .read_to_end(&mut pixels)
+            .unwrap();
+        let mut expected = vec![0];
+        expected.extend(&rgb[0..9]);
+        expected.push(0);
+        expected.extend(&rgb[9..18]);
+        assert_eq!(pixels, expected);
+    }
+}
@@ -0,0 +1,696 @@
+use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
+use crate::emulator::utils::{pack_i32, pack_u32, read_string, unpack_u32};
+use crate::file_system::{CharDevice, FileType, MountFileSystem, OpenFileError, OpenFileFlags};
+use std::collections::{BTreeMap, HashMap};
+use unicorn_engine::Unicorn;
+
+// function codes of `OSAL_s32IOControl()` of the registry
+/// Sets the value: `OSAL_trIOCtrlRegistry`.
+pub const REGISTRY_IOCTRL_SETVALUE: u32 = 0x01;
+/// Gets type, size and content of the value: `OSAL_trIOCtrlRegistry`
+/// (only the size is returned if the buffer is null).
+pub const REGISTRY_IOCTRL_QUERYVALUE: u32 = 0x02;
+/// Removes the value, the argument is its name.
+pub const REGISTRY_IOCTRL_REMOVEVALUE: u32 = 0x03;
+/// Gets the value by its index (`s32Key`) to `OSAL_trIOCtrlRegistry`
+/// (the name is written to `pcos8Name`).
+pub const REGISTRY_IOCTRL_ENUMVALUE: u32 = 0x04;
+/// Gets name of the sub-key by the cookie: `OSAL_trIOCtrlDir`.
+pub const REGISTRY_IOCTRL_READDIR: u32 = 0x05;
+/// Creates the sub-key, the argument is its name.
+pub const REGISTRY_IOCTRL_CREATEKEY: u32 = 0x06;
+/// Removes the sub-key with its sub-keys, the argument is its name.
+pub const REGISTRY_IOCTRL_REMOVEKEY: u32 = 0x07;
+
+// requests of the emulator (`OSAL_IOOpen()` and `OSAL_IOCreate()` of the registry path)
+/// Selects the key of the descriptor, the argument is its path (`/dev/registry/LOCAL_MACHINE/...`).
+pub const REGISTRY_IOCTL_OPEN_KEY: u32 = 0x52470001;
+/// Creates the key (with the missing parent keys) and selects it like `REGISTRY_IOCTL_OPEN_KEY`.
+pub const REGISTRY_IOCTL_CREATE_KEY: u32 = 0x52470002;
+
+// types of the values (`OSAL_C_S32_VALUE_*`)
+pub const REGISTRY_VALUE_S32: u32 = 1;
+pub const REGISTRY_VALUE_STRING: u32 = 2;
+/// Type of `hex:` values of the `.reg` files (read as raw bytes).
+pub const REGISTRY_VALUE_BINARY: u32 = 3;
+
+// size of names in `OSAL_trIOCtrlDir` and the names written by `REGISTRY_IOCTRL_ENUMVALUE`
+const REGISTRY_MAX_NAME_LENGTH: usize = 256;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RegistryValue {
+    Number(u32),
+    String(String),
+    Binary(Vec<u8>),
+}
+
+impl RegistryValue {
+    pub fn value_type(&self) -> u32 {
+        match self {
+            RegistryValue::Number(_) => REGISTRY_VALUE_S32,
+            RegistryValue::String(_) => REGISTRY_VALUE_STRING,
+            RegistryValue::Binary(_) => REGISTRY_VALUE_BINARY,
+        }
+    }
+
+    /// Returns content of the value as seen by the firmware (strings are zero-terminated).
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            RegistryValue::Number(value) => pack_u32(*value),
+            RegistryValue::String(value) => {
+                let mut content = value.as_bytes().to_vec();
+                content.push(0);
+                content
+            }
+            RegistryValue::Binary(value) => value.clone(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct RegistryKey {
+    // name as written in the `.reg` file (the path of the key is upper case)
+    name: String,
+    values: Vec<(String, RegistryValue)>,
+}
+
+impl RegistryKey {
+    fn value(&self, name: &str) -> Option<&RegistryValue> {
+        self.values
+            .iter()
+            .find(|(value_name, _)| value_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+
+    fn set_value(&mut self, name: &str, value: RegistryValue) {
+        match self
+            .values
+            .iter_mut()
+            .find(|(value_name, _)| value_name.eq_ignore_ascii_case(name))
+        {
+            Some((_, old_value)) => *old_value = value,
+            None => self.values.push((name.to_string(), value)),
+        }
+    }
+
+    fn remove_value(&mut self, name: &str) -> bool {
+        let len = self.values.len();
+        self.values
+            .retain(|(value_name, _)| !value_name.eq_ignore_ascii_case(name));
+        self.values.len() != len
+    }
+}
+
+///
+/// `/dev/registry` - configuration of the OSAL registry.
+///
+/// Keys are loaded from the `.reg` files of the firmware (`[HKEY_LOCAL_MACHINE\SOFTWARE\...]`
+/// with `"name"="string"`, `"name"=dword:...` and `"name"=hex:...` values) and the overrides
+/// of the emulator. Changes are kept in memory only, like the registry of the real unit.
+/// Names of the keys and values are case-insensitive.
+///
+pub struct RegistryDevice {
+    // keys by their upper case path (`LOCAL_MACHINE/SOFTWARE/...`, the root key is empty)
+    keys: BTreeMap<String, RegistryKey>,
+    // key selected by the file descriptor
+    open_keys: HashMap<i32, String>,
+}
+
+impl RegistryDevice {
+    pub fn new() -> Self {
+        let mut keys = BTreeMap::new();
+        keys.insert(String::new(), RegistryKey::default());
+        Self {
+            keys,
+            open_keys: HashMap::new(),
+        }
+    }
+
+    /// Loads the `.reg` files found in the directory of the emulated file system (and its
+    /// sub-directories) in the order of their paths.
+    pub fn load_firmware_files(&mut self, file_system: &mut MountFileSystem, dir_path: &str) {
+        let mut file_paths = Vec::new();
+        find_reg_files(file_system, dir_path, &mut file_paths);
+        file_paths.sort();
+
+        for file_path in file_paths {
+            let Ok(fd) = file_system.open(&file_path, OpenFileFlags::READ) else {
+                log::warn!("unable to open {}", file_path);
+                continue;
+            };
+            let mut content = vec![0u8; file_system.get_length(fd) as usize];
+            let res = file_system.read_all(fd, &mut content);
+            file_system.close(fd).unwrap();
+            match res {
+                Ok(_) => self.load(&String::from_utf8_lossy(&content), &file_path),
+                Err(_) => log::warn!("unable to read {}", file_path),
+            }
+        }
+    }
+
+    /// Loads keys and values of the `.reg` file (`source` is used in the warnings).
+    pub fn load(&mut self, content: &str, source: &str) {
+        let mut key_path = None;
+        let mut value_count = 0;
+        let mut lines = content.lines().enumerate();
+        while let Some((index, line)) = lines.next() {
+            // `hex:` values continue on the next lines
+            let mut line = line.trim().to_string();
+            while line.ends_with('\\') && !line.starts_with('[') {
+                line.pop();
+                match lines.next() {
+                    Some((_, next_line)) => line.push_str(next_line.trim()),
+                    None => break,
+                }
+            }
+
+            if line.is_empty()
+                || line.starts_with(';')
+                || line.starts_with('#')
+                || line == "REGEDIT4"
+                || line.starts_with("Windows Registry Editor")
+            {
+                continue;
+            }
+
+            if let Some(path) = line.strip_prefix('[') {
+                let Some(path) = path.strip_suffix(']') else {
+                    log::warn!("{}:{}: invalid key `{}`", source, index + 1, line);
+                    key_path = None;
+                    continue;
+                };
+                key_path = match path.strip_prefix('-') {
+                    Some(path) => {
+                        self.remove_key(&normalize_path(path));
+                        None
+                    }
+                    None => Some(self.create_key(path)),
+                };
+                continue;
+            }
+
+            let Some(key_path) = &key_path else {
+                log::warn!("{}:{}: value without key", source, index + 1);
+                continue;
+            };
+            match parse_value_line(&line) {
+                Ok((name, Some(value))) => {
+                    self.keys.get_mut(key_path).unwrap().set_value(&name, value);
+                    value_count += 1;
+                }
+                Ok((name, None)) => {
+                    self.keys.get_mut(key_path).unwrap().remove_value(&name);
+                }
+                Err(err) => log::warn!("{}:{}: {}", source, index + 1, err),
+            }
+        }
+        log::debug!("registry: {} values loaded from {}", value_count, source);
+    }
+
+    /// Creates the key with the missing parent keys, returns its normalized path.
+    fn create_key(&mut self, path: &str) -> String {
+        let mut key_path = String::new();
+        for name in path_names(path) {
+            if !key_path.is_empty() {
+                key_path.push('/');
+            }
+            key_path.push_str(&name.to_uppercase());
+            self.keys
+                .entry(key_path.clone())
+                .or_insert_with(|| RegistryKey {
+                    name: name.to_string(),
+                    values: Vec::new(),
+                });
+        }
+        key_path
+    }
+
+    /// Removes the key with its sub-keys (the root key can not be removed).
+    fn remove_key(&mut self, key_path: &str) -> bool {
+        if key_path.is_empty() || self.keys.remove(key_path).is_none() {
+            return false;
+        }
+        let prefix = format!("{}/", key_path);
+        self.keys.retain(|path, _| !path.starts_with(&prefix));
+        true
+    }
+
+    /// Returns names of the direct sub-keys.
+    fn sub_keys(&self, key_path: &str) -> Vec<&str> {
+        let prefix = if key_path.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", key_path)
+        };
+        self.keys
+            .range(prefix.clone()..)
+            .skip_while(|(path, _)| path.is_empty())
+            .take_while(|(path, _)| path.starts_with(&prefix))
+            .filter(|(path, _)| !path[prefix.len()..].contains('/'))
+            .map(|(_, key)| key.name.as_str())
+            .collect()
+    }
+
+    /// Returns the key selected by the file descriptor.
+    fn open_key(&mut self, fd: i32) -> Result<(&String, &mut RegistryKey), i32> {
+        let key_path = self.open_keys.get(&fd).ok_or(-9i32)?; // -EBADF
+        let key = self.keys.get_mut(key_path).ok_or(-2i32)?; // -ENOENT
+        Ok((key_path, key))
+    }
+}
+
+impl CharDevice for RegistryDevice {
+    fn open(&mut self, fd: i32, _flags: OpenFileFlags) -> Result<(), OpenFileError> {
+        self.open_keys.insert(fd, String::new());
+        Ok(())
+    }
+
+    fn close(&mut self, fd: i32) {
+        self.open_keys.remove(&fd);
+    }
+
+    fn read(&mut self, _fd: i32, _content: &mut [u8]) -> Result<u64, ()> {
+        // values are read by `REGISTRY_IOCTRL_QUERYVALUE`
+        Err(())
+    }
+
+    fn write(&mut self, _fd: i32, _content: &[u8]) -> Result<u64, ()> {
+        Err(())
+    }
+
+    fn ioctl(&mut self, unicorn: &mut Unicorn<Context>, fd: i32, request: u32, addr: u32) -> i32 {
+        let read_u32 = |unicorn: &Unicorn<Context>, offset: u32| {
+            let mut buf = [0u8; 4];
+            unicorn.mem_read((addr + offset) as u64, &mut buf).unwrap();
+            unpack_u32(&buf)
+        };
+
+        match request {
+            REGISTRY_IOCTL_OPEN_KEY | REGISTRY_IOCTL_CREATE_KEY => {
+                let path = read_string(unicorn, addr);
+                let key_path = if request == REGISTRY_IOCTL_CREATE_KEY {
+                    self.create_key(&path)
+                } else {
+                    normalize_path(&path)
+                };
+                if !self.keys.contains_key(&key_path) {
+                    log::debug!("registry key {} does not exist", path);
+                    return -2i32; // -ENOENT
+                }
+                self.open_keys.insert(fd, key_path);
+            }
+            // `{ s32Key, s32Type, pcos8Name, ps8Value, u32Size }`
+            REGISTRY_IOCTRL_SETVALUE => {
+                let value_type = read_u32(unicorn, 4);
+                let name = read_string(unicorn, read_u32(unicorn, 8));
+                let buffer = read_u32(unicorn, 12);
+                let size = read_u32(unicorn, 16) as usize;
+                let value = match value_type {
+                    REGISTRY_VALUE_S32 => {
+                        let mut content = [0u8; 4];
+                        unicorn.mem_read(buffer as u64, &mut content).unwrap();
+                        RegistryValue::Number(unpack_u32(&content))
+                    }
+                    REGISTRY_VALUE_STRING => RegistryValue::String(read_string(unicorn, buffer)),
+                    REGISTRY_VALUE_BINARY => {
+                        let mut content = vec![0u8; size];
+                        unicorn.mem_read(buffer as u64, &mut content).unwrap();
+                        RegistryValue::Binary(content)
+                    }
+                    _ => return -22i32, // -EINVAL
+                };
+                match self.open_key(fd) {
+                    Ok((_, key)) => key.set_value(&name, value),
+                    Err(err) => return err,
+                }
+            }
+            REGISTRY_IOCTRL_QUERYVALUE => {
+                let name = read_string(unicorn, read_u32(unicorn, 8));
+                let value = match self.open_key(fd) {
+                    Ok((_, key)) => key.value(&name).cloned(),
+                    Err(err) => return err,
+                };
+                let Some(value) = value else {
+                    log::debug!("registry value {} does not exist", name);
+                    return -2i32; // -ENOENT
+                };
+                if let Err(err) = write_value(unicorn, addr, &value) {
+                    return err;
+                }
+            }
+            REGISTRY_IOCTRL_REMOVEVALUE => {
+                let name = read_string(unicorn, addr);
+                match self.open_key(fd) {
+                    Ok((_, key)) => {
+                        if !key.remove_value(&name) {
+                            return -2i32; // -ENOENT
+                        }
+                    }
+                    Err(err) => return err,
+                }
+            }
+            REGISTRY_IOCTRL_ENUMVALUE => {
+                let index = read_u32(unicorn, 0) as usize;
+                let name_buffer = read_u32(unicorn, 8);
+                let value = match self.open_key(fd) {
+                    Ok((_, key)) => key.values.get(index).cloned(),
+                    Err(err) => return err,
+                };
+                let Some((name, value)) = value else {
+                    return -2i32; // -ENOENT
+                };
+                if name.len() >= REGISTRY_MAX_NAME_LENGTH {
+                    return -36i32; // -ENAMETOOLONG
+                }
+                unicorn
+                    .mem_write_guest(name_buffer as u64, format!("{}\0", name).as_bytes())
+                    .unwrap();
+                if let Err(err) = write_value(unicorn, addr, &value) {
+                    return err;
+                }
+            }
+            // `{ fd, s32Cookie, dirent { s8Name[OSAL_C_U32_MAX_PATHLENGTH] } }`
+            REGISTRY_IOCTRL_READDIR => {
+                let cookie = read_u32(unicorn, 4) as usize;
+                let key_path = match self.open_key(fd) {
+                    Ok((key_path, _)) => key_path.clone(),
+                    Err(err) => return err,
+                };
+                let Some(name) = self
+                    .sub_keys(&key_path)
+                    .get(cookie)
+                    .map(|name| name.to_string())
+                else {
+                    return -2i32; // -ENOENT
+                };
+                let mut dirent = vec![0u8; REGISTRY_MAX_NAME_LENGTH];
+                let len = name.len().min(REGISTRY_MAX_NAME_LENGTH - 1);
+                dirent[0..len].copy_from_slice(&name.as_bytes()[0..len]);
+                unicorn
+                    .mem_write_guest(addr as u64 + 4, &pack_i32(cookie as i32 + 1))
+                    .unwrap();
+                unicorn.mem_write_guest(addr as u64 + 8, &dirent).unwrap();
+            }
+            REGISTRY_IOCTRL_CREATEKEY | REGISTRY_IOCTRL_REMOVEKEY => {
+                let name = read_string(unicorn, addr);
+                let key_path = match self.open_key(fd) {
+                    Ok((key_path, _)) => key_path.clone(),
+                    Err(err) => return err,
+                };
+                let path = format!("{}/{}", key_path, name);
+                if request == REGISTRY_IOCTRL_CREATEKEY {
+                    self.create_key(&path);
+                } else if !self.remove_key(&normalize_path(&path)) {
+                    return -2i32; // -ENOENT
+                }
+            }
+            _ => {
+                log::warn!("unsupported registry control {:#x}", request);
+                return -25i32; // -ENOTTY
+            }
+        }
+        0i32
+    }
+}
+
+/// Writes type, size and content of the value to `OSAL_trIOCtrlRegistry` at the address.
+fn write_value(
+    unicorn: &mut Unicorn<Context>,
+    addr: u32,
+    value: &RegistryValue,
+) -> Result<(), i32> {
+    let mut buf = [0u8; 4];
+    unicorn.mem_read(addr as u64 + 12, &mut buf).unwrap();
+    let buffer = unpack_u32(&buf);
+    unicorn.mem_read(addr as u64 + 16, &mut buf).unwrap();
+    let size = unpack_u32(&buf) as usize;
+
+    let content = value.to_bytes();
+    unicorn
+        .mem_write_guest(addr as u64 + 4, &pack_u32(value.value_type()))
+        .unwrap();
+    unicorn
+        .mem_write_guest(addr as u64 + 16, &pack_u32(content.len() as u32))
+        .unwrap();
+    if buffer == 0 {
+        return Ok(());
+    }
+    if size < content.len() {
+        return Err(-22i32); // -EINVAL
+    }
+    unicorn.mem_write_guest(buffer as u64, &content).unwrap();
+    Ok(())
+}
+
+/// Returns names of the keys in the path (`HKEY_` prefix of the root key is removed).
+fn path_names(path: &str) -> Vec<&str> {
+    let path = path.strip_prefix("/dev/registry").unwrap_or(path);
+    path.split(['/', '\\'])
+        .filter(|name| !name.is_empty())
+        .enumerate()
+        .map(|(index, name)| match name.strip_prefix("HKEY_") {
+            Some(name) if index == 0 => name,
+            _ => name,
+        })
+        .collect()
+}
+
+/// Returns the path of the key as used in the registry (`LOCAL_MACHINE/SOFTWARE/...`).
+fn normalize_path(path: &str) -> String {
+    path_names(path).join("/").to_uppercase()
+}
+
+/// Parses `"name"=value` (`@` is the default value), `None` value removes the value (`=-`).
+fn parse_value_line(line: &str) -> Result<(String, Option<RegistryValue>), String> {
+    let (name, rest) = match line.strip_prefix('@') {
+        Some(rest) => (String::new(), rest),
+        None => parse_string(line)?,
+    };
+    let Some(value) = rest.trim_start().strip_prefix('=') else {
+        return Err(format!("missing `=` in `{}`", line));
+    };
+    let value = value.trim();
+
+    if value == "-" {
+        return Ok((name, None));
+    }
+    if value.starts_with('"') {
+        let (value, _) = parse_string(value)?;
+        return Ok((name, Some(RegistryValue::String(value))));
+    }
+    if let Some(number) = value.strip_prefix("dword:") {
+        let number =
+            u32::from_str_radix(number, 16).map_err(|_| format!("invalid dword in `{}`", line))?;
+        return Ok((name, Some(RegistryValue::Number(number))));
+    }
+    if let Some((kind, bytes)) = value.split_once(':') {
+        if kind == "hex" || kind.starts_with("hex(") {
+            let bytes = bytes
+                .split(',')
+                .map(str::trim)
+                .filter(|byte| !byte.is_empty())
+                .map(|byte| u8::from_str_radix(byte, 16))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| format!("invalid hex in `{}`", line))?;
+            return Ok((name, Some(RegistryValue::Binary(bytes))));
+        }
+    }
+    Err(format!("invalid value in `{}`", line))
+}
+
+/// Parses the quoted string (with `\\` and `\"` escapes), returns it and the rest of the text.
+fn parse_string(text: &str) -> Result<(String, &str), String> {
+    let Some(text) = text.strip_prefix('"') else {
+        return Err(format!("missing `\"` in `{}`", text));
+    };
+    let mut value = String::new();
+    let mut chars = text.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, &text[index + 1..])),
+            '\\' => match chars.next() {
+                Some((_, c)) => value.push(c),
+                None => break,
+            },
+            c => value.push(c),
+        }
+    }
+    Err(format!("unterminated string `\"{}`", text))
+}
+
+fn find_reg_files(file_system: &mut MountFileSystem, dir_path: &str, file_paths: &mut Vec<String>) {
+    let Ok(names) = file_system.read_dir(dir_path) else {
+        return;
+    };
+    for name in names {
+        if name == "." || name == ".." {
+            continue;
+        }
+        let path = format!("{}/{}", dir_path.trim_end_matches('/'), name);
+        match file_system.get_file_info_from_filepath(&path) {
+            Some(file_info) if file_info.file_details.file_type == FileType::Directory => {
+                find_reg_files(file_system, &path, file_paths)
+            }
+            Some(file_info)
+                if file_info.file_details.file_type == FileType::File
+                    && name.to_lowercase().ends_with(".reg") =>
+            {
+                file_paths.push(path)
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"Windows Registry Editor Version 5.00
+
+; navigation settings
+[HKEY_LOCAL_MACHINE\SOFTWARE\Blaupunkt\Navigation]
+"Version"="4.2 \"beta\""
+"Timeout"=dword:0000001e
+"Key"=hex:01,02,\
+  03,ff
+@="default"
+
+[HKEY_LOCAL_MACHINE\SOFTWARE\Blaupunkt\Navigation\Map]
+"Path"="C:\\maps"
+
+[HKEY_LOCAL_MACHINE\SOFTWARE\Blaupunkt\Radio
+"Broken"="key"
+"Timeout"
+"Timeout"=dword:xyz
+"Key"=hex:01,zz
+"Name"=unknown:1
+"Unterminated
+[HKEY_LOCAL_MACHINE\SOFTWARE\Blaupunkt\Radio]
+"Band"="FM"
+"#;
+
+    fn registry() -> RegistryDevice {
+        let mut registry = RegistryDevice::new();
+        registry.load(SAMPLE, "sample.reg");
+        registry
+    }
+
+    fn value<'a>(registry: &'a RegistryDevice, key: &str, name: &str) -> Option<&'a RegistryValue> {
+        registry.keys.get(&normalize_path(key))?.value(name)
+    }
+
+    #[test]
+    fn test_load() {
+        let registry = registry();
+        let key = "LOCAL_MACHINE/SOFTWARE/BLAUPUNKT/NAVIGATION";
+        assert_eq!(
+            value(&registry, key, "version"),
+            Some(&RegistryValue::String("4.2 \"beta\"".to_string()))
+        );
+        assert_eq!(
+            value(&registry, key, "TIMEOUT"),
+            Some(&RegistryValue::Number(30))
+        );
+        assert_eq!(
+            value(&registry, key, "Key"),
+            Some(&RegistryValue::Binary(vec![1, 2, 3, 0xff]))
+        );
+        assert_eq!(
+            value(&registry, key, ""),
+            Some(&RegistryValue::String("default".to_string()))
+        );
+        assert_eq!(
+            value(
+                &registry,
+                "/dev/registry/LOCAL_MACHINE/SOFTWARE/Blaupunkt/Navigation/Map",
+                "Path"
+            ),
+            Some(&RegistryValue::String("C:\\maps".to_string()))
+        );
+        assert_eq!(
+            value(&registry, "LOCAL_MACHINE/SOFTWARE/BLAUPUNKT/RADIO", "Band"),
+            Some(&RegistryValue::String("FM".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_malformed_lines() {
+        let registry = registry();
+        // values after the invalid key are skipped
+        let radio = &registry.keys["LOCAL_MACHINE/SOFTWARE/BLAUPUNKT/RADIO"];
+        assert_eq!(radio.values.len(), 1);
+        assert!(registry
+            .keys
+            .values()
+            .all(|key| key.value("Broken").is_none()));
+
+        let mut registry = RegistryDevice::new();
+        registry.load("\"Orphan\"=\"value\"\n", "orphan.reg");
+        assert_eq!(registry.keys.len(), 1);
+        assert!(registry.keys[""].values.is_empty());
+
+        assert!(parse_value_line("\"Timeout\"").is_err());
+        assert!(parse_value_line("\"Timeout\"=dword:xyz").is_err());
+        assert!(parse_value_line("\"Key\"=hex:01,zz").is_err());
+        assert!(parse_value_line("\"Name\"=unknown:1").is_err());
+        assert!(parse_value_line("\"Unterminated").is_err());
+        assert!(parse_value_line("Name=\"value\"").is_err());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut registry = registry();
+        registry.load(
+            "[HKEY_LOCAL_MACHINE\\SOFTWARE\\Blaupunkt\\Navigation]\n\"Timeout\"=-\n\
+             [-HKEY_LOCAL_MACHINE\\SOFTWARE\\Blaupunkt\\Radio]\n",
+            "remove.reg",
+        );
+        let key = "LOCAL_MACHINE/SOFTWARE/BLAUPUNKT/NAVIGATION";
+        assert_eq!(value(&registry, key, "Timeout"), None);
+        assert!(value(&registry, key, "Version").is_some());
+        assert!(!registry
+            .keys
+            .contains_key("LOCAL_MACHINE/SOFTWARE/BLAUPUNKT/RADIO"));
+
+        // sub-keys are removed with the key
+        assert!(registry.remove_key(key));
+        assert!(!registry.keys.contains_key(&format!("{}/MAP", key)));
+        assert!(!registry.remove_key(key));
+        assert!(!registry.remove_key(""));
+    }
+
+    #[test]
+    fn test_sub_keys() {
+        let registry = registry();
+        assert_eq!(registry.sub_keys(""), vec!["LOCAL_MACHINE"]);
+        assert_eq!(
+            registry.sub_keys("LOCAL_MACHINE/SOFTWARE/BLAUPUNKT"),
+            vec!["Navigation", "Radio"]
+        );
+        assert_eq!(
+            registry.sub_keys("LOCAL_MACHINE/SOFTWARE/BLAUPUNKT/NAVIGATION"),
+            vec!["Map"]
+        );
+        assert!(registry
+            .sub_keys("LOCAL_MACHINE/SOFTWARE/BLAUPUNKT/RADIO")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_value_bytes() {
+        assert_eq!(
+            RegistryValue::Number(0x1234).to_bytes(),
+            vec![0x34, 0x12, 0, 0]
+        );
+        assert_eq!(
+            RegistryValue::String("FM".to_string()).to_bytes(),
+            b"FM\0".to_vec()
+        );
+        assert_eq!(
+            RegistryValue::Binary(vec![1, 2]).value_type(),
+            REGISTRY_VALUE_BINARY
+        );
+    }
+}
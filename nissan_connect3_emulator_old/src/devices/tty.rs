@@ -0,0 +1,61 @@
+use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
+use crate::emulator::utils::{pack_u16, pack_u32};
+use crate::file_system::CharDevice;
+use std::io::{self, Read, Write};
+use unicorn_engine::Unicorn;
+
+const TCGETS: u32 = 0x5401;
+const TCSETS: u32 = 0x5402;
+const TCSETSW: u32 = 0x5403;
+const TCSETSF: u32 = 0x5404;
+const TIOCGWINSZ: u32 = 0x5413;
+
+///
+/// `/dev/tty` and `/dev/console` - terminal bound to stdin and stdout of the emulator.
+///
+pub struct TtyDevice;
+
+impl CharDevice for TtyDevice {
+    fn read(&mut self, _fd: i32, content: &mut [u8]) -> Result<u64, ()> {
+        io::stdin().read(content).map(|s| s as u64).map_err(|_| ())
+    }
+
+    fn write(&mut self, _fd: i32, content: &[u8]) -> Result<u64, ()> {
+        let mut stdout = io::stdout();
+        let res = stdout.write(content).map(|s| s as u64).map_err(|_| ());
+        stdout.flush().ok();
+        res
+    }
+
+    fn ioctl(&mut self, unicorn: &mut Unicorn<Context>, _fd: i32, request: u32, addr: u32) -> i32 {
+        match request {
+            TCGETS => {
+                // struct termios of the canonical terminal with echo (38400 baud, 8 bits)
+                let mut buf = Vec::new();
+                buf.extend_from_slice(&pack_u32(0x0500)); // c_iflag = ICRNL | IXON
+                buf.extend_from_slice(&pack_u32(0x0005)); // c_oflag = OPOST | ONLCR
+                buf.extend_from_slice(&pack_u32(0x00bf)); // c_cflag = B38400 | CS8 | CREAD
+                buf.extend_from_slice(&pack_u32(0x8a3b)); // c_lflag = ISIG | ICANON | ECHO...
+                buf.push(0); // c_line
+                buf.extend_from_slice(&[
+                    3, 28, 127, 21, 4, 0, 1, 0, 17, 19, 26, 0, 18, 15, 23, 22, 0, 0, 0,
+                ]); // c_cc
+                unicorn.mem_write_guest(addr as u64, &buf).unwrap();
+                0i32
+            }
+            // terminal settings are accepted, but the host terminal is not changed
+            TCSETS | TCSETSW | TCSETSF => 0i32,
+            TIOCGWINSZ => {
+                let mut buf = Vec::new();
+                buf.extend_from_slice(&pack_u16(24u16)); // rows in characters
+                buf.extend_from_slice(&pack_u16(80u16)); // columns, in characters
+                buf.extend_from_slice(&pack_u16(0u16)); // horizontal size, pixels
+                buf.extend_from_slice(&pack_u16(0u16)); // vertical size, pixels
+                unicorn.mem_write_guest(addr as u64, &buf).unwrap();
+                0i32
+            }
+            _ => -25i32, // -ENOTTY
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+
+// size of the RIFF header, `fmt ` chunk and `data` chunk header
+const WAV_HEADER_SIZE: u32 = 44;
+
+///
+/// Writes PCM samples to WAV file.
+///
+/// Lengths in the header are updated by `finish()`, so the file can be played
+/// also while it is being written (players read up to the end of the file).
+///
+pub struct WavWriter {
+    writer: BufWriter<File>,
+    data_len: u32,
+}
+
+impl WavWriter {
+    pub fn create(
+        path: &Path,
+        sample_rate: u32,
+        channels: u16,
+        bits_per_sample: u16,
+        is_float: bool,
+    ) -> io::Result<Self> {
+        let block_align = channels * bits_per_sample / 8;
+        let format = if is_float {
+            WAVE_FORMAT_IEEE_FLOAT
+        } else {
+            WAVE_FORMAT_PCM
+        };
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&(WAV_HEADER_SIZE - 8).to_le_bytes())?;
+        writer.write_all(b"WAVE")?;
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        writer.write_all(&format.to_le_bytes())?;
+        writer.write_all(&channels.to_le_bytes())?;
+        writer.write_all(&sample_rate.to_le_bytes())?;
+        writer.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        writer.write_all(&block_align.to_le_bytes())?;
+        writer.write_all(&bits_per_sample.to_le_bytes())?;
+        writer.write_all(b"data")?;
+        writer.write_all(&0u32.to_le_bytes())?;
+
+        Ok(Self {
+            writer,
+            data_len: 0,
+        })
+    }
+
+    /// Appends interleaved samples in the format of the header.
+    pub fn write_samples(&mut self, data: &[u8]) -> io::Result<()> {
+        self.writer.write_all(data)?;
+        self.data_len += data.len() as u32;
+        Ok(())
+    }
+
+    /// Updates lengths in the header and closes the file.
+    pub fn finish(mut self) -> io::Result<()> {
+        // the data chunk is padded to even length
+        if self.data_len % 2 == 1 {
+            self.writer.write_all(&[0u8])?;
+        }
+        let riff_len = WAV_HEADER_SIZE - 8 + self.data_len.next_multiple_of(2);
+
+        self.writer.seek(SeekFrom::Start(4))?;
+        self.writer.write_all(&riff_len.to_le_bytes())?;
+        self.writer
+            .seek(SeekFrom::Start(WAV_HEADER_SIZE as u64 - 4))?;
+        self.writer.write_all(&self.data_len.to_le_bytes())?;
+        self.writer.flush()
+    }
+}
+
+/// PCM samples of WAV file.
+pub struct WavData {
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Interleaved samples in range -1.0 to 1.0.
+    pub samples: Vec<f32>,
+}
+
+/// Reads WAV file with 8, 16, 24 or 32-bit PCM or 32-bit float samples.
+pub fn read_wav(path: &Path) -> io::Result<WavData> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let content = std::fs::read(path)?;
+    if content.len() < 12 || &content[0..4] != b"RIFF" || &content[8..12] != b"WAVE" {
+        return Err(invalid("not a WAV file"));
+    }
+
+    let mut format = None;
+    let mut data = None;
+    let mut offset = 12;
+    while offset + 8 <= content.len() {
+        let chunk_id = &content[offset..offset + 4];
+        let chunk_len = u32::from_le_bytes(content[offset + 4..offset + 8].try_into().unwrap());
+        let chunk_start = offset + 8;
+        let chunk_end = (chunk_start + chunk_len as usize).min(content.len());
+        let chunk = &content[chunk_start..chunk_end];
+        match chunk_id {
+            b"fmt " if chunk.len() >= 16 => {
+                let format_tag = u16::from_le_bytes([chunk[0], chunk[1]]);
+                let channels = u16::from_le_bytes([chunk[2], chunk[3]]);
+                let sample_rate = u32::from_le_bytes(chunk[4..8].try_into().unwrap());
+                let bits_per_sample = u16::from_le_bytes([chunk[14], chunk[15]]);
+                format = Some((format_tag, channels, sample_rate, bits_per_sample));
+            }
+            b"data" => data = Some(chunk),
+            _ => {}
+        }
+        // chunks are aligned to even offsets
+        offset = chunk_start + (chunk_len as usize).next_multiple_of(2);
+    }
+
+    let (format_tag, channels, sample_rate, bits_per_sample) =
+        format.ok_or_else(|| invalid("missing fmt chunk"))?;
+    let data = data.ok_or_else(|| invalid("missing data chunk"))?;
+    if channels == 0 {
+        return Err(invalid("no channels"));
+    }
+
+    let samples = match (format_tag, bits_per_sample) {
+        (WAVE_FORMAT_PCM, 8) => data.iter().map(|&s| (s as f32 - 128.0) / 128.0).collect(),
+        (WAVE_FORMAT_PCM, 16) => data
+            .chunks_exact(2)
+            .map(|s| i16::from_le_bytes([s[0], s[1]]) as f32 / 32768.0)
+            .collect(),
+        (WAVE_FORMAT_PCM, 24) => data
+            .chunks_exact(3)
+            .map(|s| i32::from_le_bytes([0, s[0], s[1], s[2]]) as f32 / 2147483648.0)
+            .collect(),
+        (WAVE_FORMAT_PCM, 32) => data
+            .chunks_exact(4)
+            .map(|s| i32::from_le_bytes(s.try_into().unwrap()) as f32 / 2147483648.0)
+            .collect(),
+        (WAVE_FORMAT_IEEE_FLOAT, 32) => data
+            .chunks_exact(4)
+            .map(|s| f32::from_le_bytes(s.try_into().unwrap()))
+            .collect(),
+        _ => return Err(invalid("unsupported sample format")),
+    };
+
+    Ok(WavData {
+        sample_rate,
+        channels,
+        samples,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("wav-test-{}-{}.wav", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_round_trip_pcm16() {
+        let path = temp_path("pcm16");
+        let samples: [i16; 6] = [0, 16384, -16384, i16::MAX, i16::MIN, 1];
+        let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+
+        let mut writer = WavWriter::create(&path, 48000, 2, 16, false).unwrap();
+        writer.write_samples(&data[..4]).unwrap();
+        writer.write_samples(&data[4..]).unwrap();
+        writer.finish().unwrap();
+
+        let content = std::fs::read(&path).unwrap();
+        assert_eq!(content.len(), WAV_HEADER_SIZE as usize + data.len());
+        assert_eq!(&content[0..4], b"RIFF");
+        assert_eq!(content[4..8], (content.len() as u32 - 8).to_le_bytes());
+        assert_eq!(&content[8..16], b"WAVEfmt ");
+        // byte rate and block align
+        assert_eq!(content[28..32], (48000u32 * 4).to_le_bytes());
+        assert_eq!(content[32..34], 4u16.to_le_bytes());
+        assert_eq!(content[40..44], (data.len() as u32).to_le_bytes());
+
+        let wav = read_wav(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((wav.sample_rate, wav.channels), (48000, 2));
+        let expected: Vec<f32> = samples.iter().map(|s| *s as f32 / 32768.0).collect();
+        assert_eq!(wav.samples, expected);
+    }
+
+    #[test]
+    fn test_round_trip_float_odd_length() {
+        let path = temp_path("float");
+        let mut writer = WavWriter::create(&path, 16000, 1, 32, true).unwrap();
+        writer.write_samples(&0.5f32.to_le_bytes()).unwrap();
+        writer.write_samples(&[0x00]).unwrap();
+        writer.finish().unwrap();
+
+        // the data chunk is padded, the trailing partial sample is ignored
+        let content = std::fs::read(&path).unwrap();
+        assert_eq!(content.len(), WAV_HEADER_SIZE as usize + 6);
+        assert_eq!(content[20..22], WAVE_FORMAT_IEEE_FLOAT.to_le_bytes());
+        assert_eq!(content[40..44], 5u32.to_le_bytes());
+
+        let wav = read_wav(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((wav.sample_rate, wav.channels), (16000, 1));
+        assert_eq!(wav.samples, vec![0.5]);
+    }
+
+    #[test]
+    fn test_read_invalid() {
+        let path = temp_path("invalid");
+        std::fs::write(&path, b"RIFF\x04\x00\x00\x00WAVE").unwrap();
+        assert!(read_wav(&path).is_err());
+        std::fs::write(&path, b"not a wav file").unwrap();
+        assert!(read_wav(&path).is_err());
+
+        // 12-bit samples are not supported
+        let mut writer = WavWriter::create(&path, 8000, 1, 12, false).unwrap();
+        writer.write_samples(&[0, 0]).unwrap();
+        writer.finish().unwrap();
+        assert!(read_wav(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
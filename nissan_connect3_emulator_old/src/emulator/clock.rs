@@ -0,0 +1,22 @@
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime};
+
+pub const CLOCK_REALTIME: u32 = 0;
+pub const CLOCK_MONOTONIC: u32 = 1;
+pub const CLOCK_REALTIME_COARSE: u32 = 5;
+pub const CLOCK_BOOTTIME: u32 = 7;
+
+static BOOT_TIME: OnceLock<Instant> = OnceLock::new();
+
+/// Returns current time of the guest clock.
+/// All clocks except the realtime ones start at zero when the emulator is started
+/// (like the uptime of the real head unit).
+pub fn clock_now(clock_id: u32) -> Duration {
+    let boot_time = BOOT_TIME.get_or_init(Instant::now);
+    match clock_id {
+        CLOCK_REALTIME | CLOCK_REALTIME_COARSE => SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap(),
+        _ => boot_time.elapsed(),
+    }
+}
@@ -1,6 +1,8 @@
 use crate::emulator::mmu::Mmu;
+use crate::emulator::process_table::ProcessTable;
+use crate::emulator::signals::PendingSignals;
 use crate::emulator::thread::Thread;
-use crate::file_system::MountFileSystem;
+use crate::file_system::ProcessFileSystem;
 use crate::os::SysCallsState;
 use std::collections::HashSet;
 use std::sync::atomic::{AtomicBool, AtomicU32};
@@ -13,11 +15,14 @@ pub struct Context {
 
 pub struct ContextInner {
     pub mmu: Arc<Mutex<Mmu>>,
-    pub file_system: Arc<Mutex<MountFileSystem>>,
+    pub file_system: ProcessFileSystem,
     pub sys_calls_state: Arc<Mutex<SysCallsState>>,
+    pub pending_signals: Arc<Mutex<PendingSignals>>,
     pub threads: Weak<Mutex<Vec<Thread>>>,
     pub next_thread_id: Arc<AtomicU32>,
+    pub process_table: Arc<Mutex<ProcessTable>>,
 
+    pub pid: u32,
     pub thread_id: u32,
     pub instruction_tracing: Arc<AtomicBool>,
 
@@ -1,6 +1,6 @@
 use crate::emulator::context::Context;
 use crate::emulator::memory_map::*;
-use crate::emulator::users::{EGID, EUID, GID, UID};
+use crate::emulator::users::{egid, euid, gid, uid};
 use crate::emulator::utils::{
     load_binary, mem_align_down, mem_align_up, pack_u32, push_text_on_stack, to_unicorn_permissions,
 };
@@ -14,7 +14,7 @@ use xmas_elf::program::Flags;
 /// Auxiliary vector placed on the stack by ELF loader.
 /// See: https://man7.org/linux/man-pages/man3/getauxval.3.html
 #[repr(u32)]
-enum AUX {
+enum Aux {
     AtNull = 0,
     //AtIgnore = 1,
     //AtExecFd = 2,
@@ -115,26 +115,33 @@ impl<'a> ElfLoader for ArmElfLoader<'a> {
     }
 }
 
-pub fn load_elf(
-    unicorn: &mut Unicorn<Context>,
-    elf_filepath: &str,
-    buf: &[u8],
-    program_args: &Vec<String>,
-    program_envs: &Vec<(String, String)>,
-) -> Result<(u32, u32, u32), &'static str> {
-    // parse elf file
-    let binary = ElfBinary::new(buf).expect("Got proper ELF file");
+/// Checks that the file is an ARM executable which can be loaded by `load_elf()`.
+///
+/// Returns path of its interpreter (`None` for static binaries).
+pub fn check_elf(buf: &[u8]) -> Result<Option<String>, &'static str> {
+    let binary = ElfBinary::new(buf).map_err(|_| "Not an ELF file!")?;
 
-    // verify architecture
     if binary.get_arch() != Machine::Arm {
         return Err("Wrong architecture!");
     }
 
-    let data = binary.file.header.pt1.data.as_data();
-    if data != Data::LittleEndian {
+    if binary.file.header.pt1.data.as_data() != Data::LittleEndian {
         return Err("Wrong endianness!");
     }
 
+    Ok(binary.interpreter().map(String::from))
+}
+
+pub fn load_elf(
+    unicorn: &mut Unicorn<Context>,
+    elf_filepath: &str,
+    buf: &[u8],
+    program_args: &Vec<String>,
+    program_envs: &Vec<(String, String)>,
+) -> Result<(u32, u32, u32), &'static str> {
+    check_elf(buf)?;
+    let binary = ElfBinary::new(buf).unwrap();
+
     // choose load address
     let load_address = if binary.file.header.pt2.type_().as_type() == header::Type::Executable {
         EXE_LOAD_ADDRESS
@@ -151,7 +158,9 @@ pub fn load_elf(
         mem_end: 0u32,
     };
 
-    binary.load(&mut loader).expect("Can't load the binary?");
+    binary
+        .load(&mut loader)
+        .map_err(|_| "Can't load the binary!")?;
 
     let mem_start = loader.mem_start;
     let mem_end = loader.mem_end;
@@ -167,8 +176,9 @@ pub fn load_elf(
     if let Some(interp_path) = binary.interpreter() {
         log::debug!("Load interpreter: {:?}", &interp_path);
 
-        let interp_bin = load_binary(unicorn, &interp_path);
-        let binary = ElfBinary::new(&interp_bin).expect("Got proper ELF file");
+        let interp_bin = load_binary(unicorn, interp_path);
+        check_elf(&interp_bin)?;
+        let binary = ElfBinary::new(&interp_bin).unwrap();
 
         let mut interp_loader = ArmElfLoader {
             unicorn,
@@ -179,7 +189,7 @@ pub fn load_elf(
         };
         binary
             .load(&mut interp_loader)
-            .expect("Can't load the binary?");
+            .map_err(|_| "Can't load the interpreter!")?;
 
         interp_entry_point = binary.file.header.pt2.entry_point() as u32;
 
@@ -203,6 +213,7 @@ pub fn load_elf(
     Ok((interp_entry_point, elf_entry, stack_ptr))
 }
 
+#[allow(clippy::too_many_arguments)]
 fn setup_stack(
     unicorn: &mut Unicorn<Context>,
     elf_filepath: &str,
@@ -233,13 +244,9 @@ fn setup_stack(
     let mut elf_table = Vec::new();
 
     // argc
-    elf_table.extend_from_slice(&pack_u32(program_args.len() as u32 + 1));
-
-    // argv[0]
-    stack_ptr = push_text_on_stack(unicorn, stack_ptr, elf_filepath);
-    elf_table.extend_from_slice(&pack_u32(stack_ptr));
+    elf_table.extend_from_slice(&pack_u32(program_args.len() as u32));
 
-    // argv[1..n]
+    // argv[0..n - 1]
     for argv in program_args {
         stack_ptr = push_text_on_stack(unicorn, stack_ptr, argv);
         elf_table.extend_from_slice(&pack_u32(stack_ptr));
@@ -297,36 +304,36 @@ fn get_auxv_data(
     platformaddr: u32,
 ) -> Vec<(u32, u32)> {
     vec![
-        (AUX::AtHwcap as u32, 0x1FB8D7), // for 32-bit
-        (AUX::AtPageSz as u32, 0x1000),
-        (AUX::AtClkTck as u32, 100),
+        (Aux::AtHwcap as u32, 0x1FB8D7), // for 32-bit
+        (Aux::AtPageSz as u32, 0x1000),
+        (Aux::AtClkTck as u32, 100),
         (
-            AUX::AtPhdr as u32,
+            Aux::AtPhdr as u32,
             load_address + binary.file.header.pt2.ph_offset() as u32 + mem_start,
         ),
         (
-            AUX::AtPhent as u32,
+            Aux::AtPhent as u32,
             binary.file.header.pt2.ph_entry_size() as u32,
         ),
         (
-            AUX::AtPhnum as u32,
+            Aux::AtPhnum as u32,
             binary.file.header.pt2.ph_count() as u32,
         ),
-        (AUX::AtBase as u32, interp_address),
-        (AUX::AtFlags as u32, 0),
+        (Aux::AtBase as u32, interp_address),
+        (Aux::AtFlags as u32, 0),
         (
-            AUX::AtEntry as u32,
+            Aux::AtEntry as u32,
             load_address + binary.file.header.pt2.entry_point() as u32,
         ),
-        (AUX::AtUid as u32, UID),
-        (AUX::AtEuid as u32, EUID),
-        (AUX::AtGid as u32, GID),
-        (AUX::AtEgid as u32, EGID),
-        (AUX::AtSecure as u32, 0),
-        (AUX::AtRandom as u32, randstraddr),
-        (AUX::AtHwcap2 as u32, 0),
-        (AUX::AtExecFn as u32, execfnaddr),
-        (AUX::AtPlatform as u32, platformaddr),
-        (AUX::AtNull as u32, 0),
+        (Aux::AtUid as u32, uid()),
+        (Aux::AtEuid as u32, euid()),
+        (Aux::AtGid as u32, gid()),
+        (Aux::AtEgid as u32, egid()),
+        (Aux::AtSecure as u32, 0),
+        (Aux::AtRandom as u32, randstraddr),
+        (Aux::AtHwcap2 as u32, 0),
+        (Aux::AtExecFn as u32, execfnaddr),
+        (Aux::AtPlatform as u32, platformaddr),
+        (Aux::AtNull as u32, 0),
     ]
 }
@@ -0,0 +1,47 @@
+use crate::emulator::process::Process;
+use crate::emulator::process_table::ProcessTable;
+use crate::file_system::MountFileSystem;
+use std::error::Error;
+use std::sync::atomic::AtomicU32;
+use std::sync::{Arc, Mutex};
+use unicorn_engine::unicorn_const::uc_error;
+
+pub struct Emulator {
+    file_system: Arc<Mutex<MountFileSystem>>,
+    process_table: Arc<Mutex<ProcessTable>>,
+
+    // thread ids (and process ids) are unique across all processes
+    next_thread_id: Arc<AtomicU32>,
+}
+
+impl Emulator {
+    pub fn new(
+        file_system: MountFileSystem,
+        process_table: Arc<Mutex<ProcessTable>>,
+    ) -> Result<Emulator, uc_error> {
+        Ok(Self {
+            file_system: Arc::new(Mutex::new(file_system)),
+            process_table,
+            next_thread_id: Arc::new(AtomicU32::new(1)),
+        })
+    }
+
+    /// File system shared by all processes (used to mount media at runtime).
+    pub fn file_system(&self) -> Arc<Mutex<MountFileSystem>> {
+        self.file_system.clone()
+    }
+
+    pub fn run_process(
+        &mut self,
+        elf_filepath: String,
+        program_args: Vec<String>,
+        program_envs: Vec<(String, String)>,
+    ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        let mut process = Process::new(
+            self.file_system.clone(),
+            self.process_table.clone(),
+            self.next_thread_id.clone(),
+        );
+        process.run(elf_filepath, program_args, program_envs)
+    }
+}
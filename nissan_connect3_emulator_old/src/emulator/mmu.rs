@@ -5,11 +5,12 @@ use std::sync::{Arc, Mutex};
 use crate::emulator::context::Context;
 use crate::emulator::thread::Thread;
 use crate::emulator::utils::mem_align_up;
+use crate::file_system::SharedMemory;
 use crate::os::add_library_hook;
-use unicorn_engine::unicorn_const::Permission;
+use unicorn_engine::unicorn_const::{uc_error, Permission};
 use unicorn_engine::Unicorn;
 
-#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone)]
+#[derive(Clone)]
 pub struct MmuRegion {
     pub memory_start: u32,
     pub memory_end: u32,
@@ -18,7 +19,82 @@ pub struct MmuRegion {
     pub filepath: String,
 
     // important! do not move the data after allocated
-    data: Vec<u8>,
+    data: MmuRegionData,
+}
+
+#[derive(Clone)]
+enum MmuRegionData {
+    /// Memory owned by the region.
+    Private(Vec<u8>),
+    /// Memory of `MAP_SHARED` file mapping (`offset` is the file offset of the region start).
+    /// The clean memory is mapped without write permission, the first write makes it dirty.
+    Shared {
+        memory: Arc<SharedMemory>,
+        offset: u32,
+        is_dirty: bool,
+    },
+    /// Memory shared with the forked process until one of them writes to it
+    /// (mapped without write permission).
+    CopyOnWrite(Arc<Vec<u8>>),
+}
+
+impl MmuRegionData {
+    fn as_mut_ptr(&mut self) -> *mut c_void {
+        match self {
+            MmuRegionData::Private(data) => data.as_mut_ptr() as *mut c_void,
+            MmuRegionData::Shared { memory, offset, .. } => unsafe {
+                memory.as_mut_ptr().add(*offset as usize) as *mut c_void
+            },
+            MmuRegionData::CopyOnWrite(data) => data.as_ptr() as *mut c_void,
+        }
+    }
+
+    /// Permissions of the memory mapped into unicorn.
+    fn unicorn_perms(&self, perms: Permission) -> Permission {
+        match self {
+            MmuRegionData::CopyOnWrite(_) => perms & !Permission::WRITE,
+            MmuRegionData::Shared {
+                is_dirty: false, ..
+            } => perms & !Permission::WRITE,
+            _ => perms,
+        }
+    }
+
+    /// Returns data of the sub-range (shared memory is not copied).
+    fn slice(&self, start: u32, end: u32) -> MmuRegionData {
+        match self {
+            MmuRegionData::Private(data) => {
+                MmuRegionData::Private(Vec::from(&data[start as usize..end as usize]))
+            }
+            MmuRegionData::Shared {
+                memory,
+                offset,
+                is_dirty,
+            } => MmuRegionData::Shared {
+                memory: memory.clone(),
+                offset: offset + start,
+                is_dirty: *is_dirty,
+            },
+            MmuRegionData::CopyOnWrite(data) => {
+                MmuRegionData::Private(Vec::from(&data[start as usize..end as usize]))
+            }
+        }
+    }
+}
+
+/// Part of the `MAP_SHARED` file mapping.
+pub struct SharedMapping {
+    pub file_path: String,
+    pub memory: Arc<SharedMemory>,
+    pub offset: u32,
+    pub length: u32,
+}
+
+impl MmuRegion {
+    /// Memory of `MAP_SHARED` mapping.
+    pub fn is_shared(&self) -> bool {
+        matches!(self.data, MmuRegionData::Shared { .. })
+    }
 }
 
 impl std::fmt::Display for MmuRegion {
@@ -78,12 +154,10 @@ impl Mmu {
     ) {
         let threads = unicorn.get_data().inner.threads.upgrade().unwrap();
 
-        Self::pause_all_threads(&threads);
-
         self.remove_internal(address, size, &threads);
 
         // allocate memory
-        let data = vec![0u8; size as usize];
+        let data = MmuRegionData::Private(vec![0u8; size as usize]);
 
         let desc = match description.len() {
             0 => String::from("[mapped]"),
@@ -101,15 +175,45 @@ impl Mmu {
             desc,
             filepath
         );
+    }
 
-        Self::resume_all_threads(&threads);
+    /// Maps the memory of `MAP_SHARED` file mapping starting at file `offset`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn map_shared(
+        &mut self,
+        unicorn: &mut Unicorn<Context>,
+        address: u32,
+        size: u32,
+        perms: Permission,
+        filepath: &str,
+        memory: Arc<SharedMemory>,
+        offset: u32,
+    ) {
+        let threads = unicorn.get_data().inner.threads.upgrade().unwrap();
+
+        self.remove_internal(address, size, &threads);
+
+        let data = MmuRegionData::Shared {
+            memory,
+            offset,
+            is_dirty: false,
+        };
+        self.map_internal(&threads, address, size, perms, "[shared]", filepath, data);
+
+        log::debug!(
+            "mmu_map_shared: {:#x} - {:#x} (size: {:#x}), {:?} {} (offset: {:#x})",
+            address,
+            address + size - 1,
+            size,
+            perms,
+            filepath,
+            offset
+        );
     }
 
     pub fn unmap(&mut self, unicorn: &mut Unicorn<Context>, address: u32, size: u32) {
         let threads = unicorn.get_data().inner.threads.upgrade().unwrap();
 
-        Self::pause_all_threads(&threads);
-
         self.remove_internal(address, size, &threads);
 
         log::debug!(
@@ -118,8 +222,6 @@ impl Mmu {
             address + size,
             size,
         );
-
-        Self::resume_all_threads(&threads);
     }
 
     pub fn mem_protect(
@@ -131,37 +233,330 @@ impl Mmu {
     ) {
         let threads = unicorn.get_data().inner.threads.upgrade().unwrap();
 
-        Self::pause_all_threads(&threads);
-
         // split regions at the beginning and end point of the range
         self.split_internal(address, &threads);
         self.split_internal(address + size, &threads);
 
         // change permissions
+        for item in &mut self.regions {
+            if item.memory_start >= address && item.memory_end < address + size {
+                item.memory_perms = perms;
+
+                for thread in threads.lock().unwrap().iter_mut() {
+                    thread
+                        .unicorn
+                        .mem_protect(
+                            item.memory_start as u64,
+                            (item.memory_end - item.memory_start + 1) as usize,
+                            item.data.unicorn_perms(perms),
+                        )
+                        .unwrap();
+                }
+            }
+        }
+    }
+
+    pub fn get_regions(&self) -> &Vec<MmuRegion> {
+        &self.regions
+    }
+
+    /// Creates memory map for the forked process.
+    ///
+    /// Private memory is shared with the new process and it is copied when any of the processes
+    /// writes to it. Both processes see the shared memory as read-only, the write protection hook
+    /// copies it on the writes of the program and [`GuestMemory::mem_write_guest`] on the writes
+    /// of the syscalls (unicorn does not check the protection in `mem_write`).
+    pub fn fork(&mut self, unicorn: &mut Unicorn<Context>) -> Mmu {
+        let threads = unicorn.get_data().inner.threads.upgrade().unwrap();
+        let mut regions = Vec::with_capacity(self.regions.len());
+        for region in &mut self.regions {
+            if let MmuRegionData::Private(data) = &mut region.data {
+                // the vector is moved without moving its buffer, so the memory
+                // stays mapped at the same place
+                region.data = MmuRegionData::CopyOnWrite(Arc::new(std::mem::take(data)));
+
+                if region.memory_perms.contains(Permission::WRITE) {
+                    // the parent must not write to the shared memory either
+                    for thread in threads.lock().unwrap().iter_mut() {
+                        thread
+                            .unicorn
+                            .mem_protect(
+                                region.memory_start as u64,
+                                (region.memory_end - region.memory_start + 1) as usize,
+                                region.data.unicorn_perms(region.memory_perms),
+                            )
+                            .unwrap();
+                    }
+                }
+            }
+            regions.push(region.clone());
+        }
+
+        Mmu {
+            regions,
+            brk_mem_end: self.brk_mem_end,
+            heap_mem_end: self.heap_mem_end,
+        }
+    }
+
+    /// Handles write to the copy-on-write memory (called from the write protection hook).
+    ///
+    /// Returns `false` if the `address` is not in the copy-on-write memory.
+    pub fn resolve_copy_on_write(&mut self, unicorn: &mut Unicorn<Context>, address: u32) -> bool {
+        let index = match self.regions.iter().position(|region| {
+            region.memory_start <= address
+                && region.memory_end >= address
+                && region.memory_perms.contains(Permission::WRITE)
+                && matches!(region.data, MmuRegionData::CopyOnWrite(_))
+        }) {
+            Some(index) => index,
+            None => return false,
+        };
+
+        self.copy_region(unicorn, index);
+        true
+    }
+
+    /// Handles the first write to the clean shared memory (called from the write protection hook).
+    ///
+    /// Returns `false` if the `address` is not in the clean shared memory.
+    pub fn mark_shared_dirty(&mut self, unicorn: &mut Unicorn<Context>, address: u32) -> bool {
+        let threads = unicorn.get_data().inner.threads.upgrade().unwrap();
+        let Some(region) = self.regions.iter_mut().find(|region| {
+            region.memory_start <= address
+                && region.memory_end >= address
+                && region.memory_perms.contains(Permission::WRITE)
+                && matches!(
+                    region.data,
+                    MmuRegionData::Shared {
+                        is_dirty: false,
+                        ..
+                    }
+                )
+        }) else {
+            return false;
+        };
+
+        Self::set_shared_dirty(&threads, region);
+        true
+    }
+
+    /// Prepares the memory in the range before it is written by a syscall (regardless of
+    /// the permissions of the guest): the copy-on-write memory is copied and the shared
+    /// memory is marked as dirty.
+    pub fn prepare_write(&mut self, unicorn: &mut Unicorn<Context>, address: u32, size: u32) {
+        let end = address.saturating_add(size.saturating_sub(1));
+        let is_in_range =
+            |region: &MmuRegion| region.memory_start <= end && region.memory_end >= address;
+
+        while let Some(index) = self.regions.iter().position(|region| {
+            is_in_range(region) && matches!(region.data, MmuRegionData::CopyOnWrite(_))
+        }) {
+            self.copy_region(unicorn, index);
+        }
+
+        let threads = unicorn.get_data().inner.threads.upgrade().unwrap();
+        for region in &mut self.regions {
+            if is_in_range(region)
+                && matches!(
+                    region.data,
+                    MmuRegionData::Shared {
+                        is_dirty: false,
+                        ..
+                    }
+                )
+            {
+                Self::set_shared_dirty(&threads, region);
+            }
+        }
+    }
+
+    /// Marks the shared memory as dirty, it is writable for the guest from now on.
+    fn set_shared_dirty(threads: &Arc<Mutex<Vec<Thread>>>, region: &mut MmuRegion) {
+        if let MmuRegionData::Shared { is_dirty, .. } = &mut region.data {
+            *is_dirty = true;
+        }
         for thread in threads.lock().unwrap().iter_mut() {
             thread
                 .unicorn
-                .mem_protect(address as u64, size as usize, perms)
+                .mem_protect(
+                    region.memory_start as u64,
+                    (region.memory_end - region.memory_start + 1) as usize,
+                    region.data.unicorn_perms(region.memory_perms),
+                )
                 .unwrap();
         }
-        for item in &mut self.regions {
-            if item.memory_start >= address && item.memory_end <= address + size - 1 {
-                item.memory_perms = perms;
+        log::trace!(
+            "mmu_shared_dirty: {:#x} - {:#x}",
+            region.memory_start,
+            region.memory_end
+        );
+    }
+
+    /// Replaces the copy-on-write region by its private copy.
+    fn copy_region(&mut self, unicorn: &mut Unicorn<Context>, index: usize) {
+        let threads = unicorn.get_data().inner.threads.upgrade().unwrap();
+        let region = self.regions.remove(index);
+        let size = region.memory_end - region.memory_start + 1;
+
+        let data = match region.data {
+            // the other process does not use the memory anymore
+            MmuRegionData::CopyOnWrite(data) => {
+                Arc::try_unwrap(data).unwrap_or_else(|data| data.as_ref().clone())
             }
+            _ => unreachable!(),
+        };
+
+        for thread in threads.lock().unwrap().iter_mut() {
+            thread
+                .unicorn
+                .mem_unmap(region.memory_start as u64, size as usize)
+                .unwrap();
         }
 
-        Self::resume_all_threads(&threads);
+        self.map_internal(
+            &threads,
+            region.memory_start,
+            size,
+            region.memory_perms,
+            &region.description,
+            &region.filepath,
+            MmuRegionData::Private(data),
+        );
+
+        log::trace!(
+            "mmu_copy_on_write: {:#x} - {:#x} (size: {:#x})",
+            region.memory_start,
+            region.memory_end,
+            size
+        );
     }
 
-    pub fn get_regions(&self) -> &Vec<MmuRegion> {
-        &self.regions
+    /// Writes to the memory of the forked process before it is mapped into unicorn
+    /// (the region is not shared with the parent anymore).
+    pub fn write_unmapped(&mut self, address: u32, bytes: &[u8]) {
+        let end = address + bytes.len() as u32 - 1;
+        if let Some(region) = self
+            .regions
+            .iter_mut()
+            .find(|region| region.memory_start <= address && region.memory_end >= end)
+        {
+            let offset = (address - region.memory_start) as usize;
+            if let MmuRegionData::CopyOnWrite(data) = &region.data {
+                region.data = MmuRegionData::Private(data.as_ref().clone());
+            }
+            match &mut region.data {
+                MmuRegionData::Private(data) => {
+                    data[offset..offset + bytes.len()].copy_from_slice(bytes);
+                }
+                MmuRegionData::Shared {
+                    memory,
+                    offset: file_offset,
+                    is_dirty,
+                } => {
+                    memory.write(*file_offset as usize + offset, bytes);
+                    *is_dirty = true;
+                }
+                MmuRegionData::CopyOnWrite(_) => unreachable!(),
+            }
+        }
+    }
+
+    /// Removes all memory regions (used by `execve()`).
+    pub fn unmap_all(&mut self, unicorn: &mut Unicorn<Context>) {
+        let threads = unicorn.get_data().inner.threads.upgrade().unwrap();
+
+        for thread in threads.lock().unwrap().iter_mut() {
+            for region in &self.regions {
+                thread
+                    .unicorn
+                    .mem_unmap(
+                        region.memory_start as u64,
+                        (region.memory_end - region.memory_start + 1) as usize,
+                    )
+                    .unwrap();
+            }
+        }
+
+        self.regions.clear();
+        self.brk_mem_end = 0;
+        self.heap_mem_end = 0;
+    }
+
+    /// Maps all memory regions into the unicorn instance.
+    pub fn map_regions(&mut self, unicorn: &mut Unicorn<Context>) {
+        for item in &mut self.regions {
+            unsafe {
+                unicorn
+                    .mem_map_ptr(
+                        item.memory_start as u64,
+                        (item.memory_end - item.memory_start + 1) as usize,
+                        item.data.unicorn_perms(item.memory_perms),
+                        item.data.as_mut_ptr(),
+                    )
+                    .unwrap();
+            }
+        }
+    }
+
+    /// Returns parts of `MAP_SHARED` file mappings within the range.
+    /// Returns the dirty parts of the writable `MAP_SHARED` mappings within the range
+    /// to be written back to the files. The mappings that are whole in the range are
+    /// clean afterwards (they are write-protected again to notice the next write).
+    pub fn take_dirty_shared_mappings(
+        &mut self,
+        unicorn: &mut Unicorn<Context>,
+        address: u32,
+        size: u32,
+    ) -> Vec<SharedMapping> {
+        let threads = unicorn.get_data().inner.threads.upgrade().unwrap();
+        let range_end = address as u64 + size as u64;
+        let mut shared_mappings = Vec::new();
+        for region in &mut self.regions {
+            let MmuRegionData::Shared {
+                memory,
+                offset,
+                is_dirty,
+            } = &mut region.data
+            else {
+                continue;
+            };
+            let start = region.memory_start.max(address);
+            let end = (region.memory_end as u64 + 1).min(range_end);
+            if !*is_dirty || !region.memory_perms.contains(Permission::WRITE) || start as u64 >= end
+            {
+                continue;
+            }
+
+            shared_mappings.push(SharedMapping {
+                file_path: region.filepath.clone(),
+                memory: memory.clone(),
+                offset: *offset + (start - region.memory_start),
+                length: (end - start as u64) as u32,
+            });
+
+            if region.memory_start >= address && (region.memory_end as u64) < range_end {
+                *is_dirty = false;
+                for thread in threads.lock().unwrap().iter_mut() {
+                    thread
+                        .unicorn
+                        .mem_protect(
+                            region.memory_start as u64,
+                            (region.memory_end - region.memory_start + 1) as usize,
+                            region.data.unicorn_perms(region.memory_perms),
+                        )
+                        .unwrap();
+                }
+            }
+        }
+        shared_mappings
     }
 
     pub fn get_libraries_and_base_addresses(&self) -> Vec<(String, u32)> {
         self.regions
             .iter()
             .filter(|region| {
-                region.memory_perms.contains(Permission::EXEC) && region.filepath.len() > 0
+                region.memory_perms.contains(Permission::EXEC) && !region.filepath.is_empty()
             })
             .map(|region| (region.filepath.clone(), region.memory_start))
             .collect()
@@ -171,13 +566,9 @@ impl Mmu {
     pub fn update_library_hooks_for_all_threads(&self, unicorn: &Unicorn<Context>) {
         let threads = unicorn.get_data().inner.threads.upgrade().unwrap();
 
-        Self::pause_all_threads(&threads);
-
         for thread in threads.lock().unwrap().iter_mut() {
             self.update_library_hooks(&mut thread.unicorn);
         }
-
-        Self::resume_all_threads(&threads);
     }
 
     /// Updates library hooks for a single unicorn instance
@@ -207,31 +598,10 @@ impl Mmu {
                 memory_perms: map_info.memory_perms,
                 description: map_info.description.clone(),
                 filepath: map_info.filepath.clone(),
-                data: vec![],
+                data: MmuRegionData::Private(vec![]),
             });
         }
-        v.sort_by(|x, y| x.memory_start.cmp(&y.memory_start));
-
-        let mut str = format!("{} regions:", v.len());
-        for map_info in v {
-            str.push_str(&format!("\n{}", map_info));
-        }
-        str
-    }
-
-    pub fn display_mapped_unicorn(unicorn: &Unicorn<Context>) -> String {
-        let mut v: Vec<_> = Vec::new();
-        for mem_region in unicorn.mem_regions().unwrap() {
-            v.push(MmuRegion {
-                memory_start: mem_region.begin as u32,
-                memory_end: mem_region.end as u32,
-                memory_perms: mem_region.perms,
-                description: "".to_string(),
-                filepath: "".to_string(),
-                data: vec![],
-            });
-        }
-        v.sort_by(|x, y| x.memory_start.cmp(&y.memory_start));
+        v.sort_by_key(|x| x.memory_start);
 
         let mut str = format!("{} regions:", v.len());
         for map_info in v {
@@ -247,17 +617,25 @@ impl Mmu {
         perms: Permission,
         filepath: &str,
     ) -> u32 {
-        let heap_addr = self.heap_mem_end;
-
         let size = mem_align_up(size, None);
+        let heap_addr = self.heap_reserve(size);
+
         self.map(unicorn, heap_addr, size, perms, "[heap]", filepath);
 
-        self.heap_mem_end = heap_addr + size;
+        heap_addr
+    }
+
+    /// Reserves address range on the heap without mapping it.
+    pub fn heap_reserve(&mut self, size: u32) -> u32 {
+        let heap_addr = self.heap_mem_end;
+
+        self.heap_mem_end = heap_addr + mem_align_up(size, None);
 
         heap_addr
     }
 
     /// map region to all threads without verifying overlaps
+    #[allow(clippy::too_many_arguments)]
     fn map_internal(
         &mut self,
         threads: &Arc<Mutex<Vec<Thread>>>,
@@ -266,7 +644,7 @@ impl Mmu {
         perms: Permission,
         description: &str,
         filepath: &str,
-        mut data: Vec<u8>,
+        mut data: MmuRegionData,
     ) {
         // map allocated memory to all threads
         for thread in threads.lock().unwrap().iter_mut() {
@@ -274,13 +652,13 @@ impl Mmu {
             // 1. data will not be moved (Vec resized etc.)
             // 2. memory will be unmapped before deallocating data
             unsafe {
-                let _ = thread
+                thread
                     .unicorn
                     .mem_map_ptr(
                         address as u64,
                         size as usize,
-                        perms,
-                        data.as_mut_ptr() as *mut c_void,
+                        data.unicorn_perms(perms),
+                        data.as_mut_ptr(),
                     )
                     .unwrap();
             }
@@ -303,11 +681,11 @@ impl Mmu {
         let regions_to_unmap: Vec<_> = self
             .regions
             .iter()
-            .filter(|item| item.memory_start >= address && item.memory_end <= address + size - 1)
+            .filter(|item| item.memory_start >= address && item.memory_end < address + size)
             .map(|item| (item.memory_start, item.memory_end))
             .collect();
 
-        if regions_to_unmap.len() == 0 {
+        if regions_to_unmap.is_empty() {
             return;
         }
 
@@ -338,7 +716,7 @@ impl Mmu {
             .regions
             .iter()
             .filter(|item| item.memory_start < address && item.memory_end >= address)
-            .map(|item| item.clone())
+            .cloned()
             .collect();
 
         for item in to_be_split {
@@ -356,7 +734,7 @@ impl Mmu {
                 item.memory_perms,
                 &item.description,
                 &item.filepath,
-                Vec::from(&item.data[0..(address - item.memory_start) as usize]),
+                item.data.slice(0, address - item.memory_start),
             );
 
             // right item
@@ -367,24 +745,31 @@ impl Mmu {
                 item.memory_perms,
                 &item.description,
                 &item.filepath,
-                Vec::from(
-                    &item.data[(address - item.memory_start) as usize
-                        ..(item.memory_end - item.memory_start + 1) as usize],
+                item.data.slice(
+                    address - item.memory_start,
+                    item.memory_end - item.memory_start + 1,
                 ),
             );
         }
     }
+}
 
-    fn pause_all_threads(threads: &Arc<Mutex<Vec<Thread>>>) {
-        for thread in threads.lock().unwrap().iter_mut() {
-            thread.pause().unwrap();
-        }
-    }
+/// Writes of the syscalls and devices to the memory of the emulated process.
+pub trait GuestMemory {
+    /// Writes to the guest memory, the memory shared with the forked process is copied first
+    /// and the written `MAP_SHARED` memory becomes dirty.
+    fn mem_write_guest(&mut self, address: u64, bytes: &[u8]) -> Result<(), uc_error>;
+}
 
-    fn resume_all_threads(threads: &Arc<Mutex<Vec<Thread>>>) {
-        for thread in threads.lock().unwrap().iter_mut() {
-            thread.resume();
+impl GuestMemory for Unicorn<Context> {
+    fn mem_write_guest(&mut self, address: u64, bytes: &[u8]) -> Result<(), uc_error> {
+        if !bytes.is_empty() {
+            let mmu = self.get_data().inner.mmu.clone();
+            mmu.lock()
+                .unwrap()
+                .prepare_write(self, address as u32, bytes.len() as u32);
         }
+        self.mem_write(address, bytes)
     }
 }
 
@@ -395,18 +780,7 @@ pub fn mmu_clone_map(
     let data = src_unicorn.get_data();
     let mmu = &mut data.inner.mmu.lock().unwrap();
 
-    for item in &mut mmu.regions {
-        unsafe {
-            dest_unicorn
-                .mem_map_ptr(
-                    item.memory_start as u64,
-                    (item.memory_end - item.memory_start + 1) as usize,
-                    item.memory_perms,
-                    item.data.as_mut_ptr() as *mut c_void,
-                )
-                .unwrap();
-        }
-    }
+    mmu.map_regions(dest_unicorn);
 
     /*let mut map: Vec<_> = mmu.map_infos.values().collect();
     map.sort_by(|map1, map2| map1.memory_start.cmp(&map2.memory_start));
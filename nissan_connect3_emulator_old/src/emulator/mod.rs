@@ -1,11 +1,13 @@
+pub mod clock;
 pub mod context;
-pub mod emulator;
+pub mod elf_loader;
+pub mod machine;
 pub mod memory_map;
 pub mod mmu;
 pub mod print;
 pub mod process;
+pub mod process_table;
+pub mod signals;
 pub mod thread;
 pub mod users;
 pub mod utils;
-
-mod elf_loader;
@@ -30,19 +30,6 @@ pub fn print_stack(unicorn: &Unicorn<Context>) {
     println!();
 }
 
-pub fn mem_dump(unicorn: &Unicorn<Context>, address: u32, len: u32) {
-    println!("------------------ MEM DUMP at {:#010x}:", address);
-    let mut mem = vec![0u8; len as usize];
-    unicorn.mem_read(address as u64, &mut mem).unwrap();
-    for i in 0..len / 4 {
-        print!(
-            "{:#010x} ",
-            unpack_u32(&mem[(4 * i) as usize..(4 * (i + 1)) as usize])
-        );
-    }
-    println!();
-}
-
 pub fn disasm(unicorn: &Unicorn<Context>, address: u32, len: u32) {
     let cs = Capstone::new()
         .arm()
@@ -1,54 +1,93 @@
 use crate::emulator::context::{Context, ContextInner};
+use crate::emulator::elf_loader::load_elf;
+use crate::emulator::memory_map::GET_TLS_ADDR;
 use crate::emulator::mmu::Mmu;
+use crate::emulator::process_table::{ExitReason, ProcessEntry, ProcessState, ProcessTable};
+use crate::emulator::signals::{PendingSignals, SIGKILL};
 use crate::emulator::thread::Thread;
-use crate::file_system::MountFileSystem;
+use crate::emulator::utils::pack_u32;
+use crate::file_system::{MountFileSystem, ProcessFileSystem};
 use crate::os::SysCallsState;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
+use unicorn_engine::{RegisterARM, Unicorn};
 
 pub struct Process {
     mmu: Arc<Mutex<Mmu>>,
     file_system: Arc<Mutex<MountFileSystem>>,
     sys_calls_state: Arc<Mutex<SysCallsState>>,
+    pending_signals: Arc<Mutex<PendingSignals>>,
     threads: Arc<Mutex<Vec<Thread>>>,
     next_thread_id: Arc<AtomicU32>,
+    process_table: Arc<Mutex<ProcessTable>>,
 }
 
 impl Process {
-    pub fn new(file_system: Arc<Mutex<MountFileSystem>>) -> Self {
+    pub fn new(
+        file_system: Arc<Mutex<MountFileSystem>>,
+        process_table: Arc<Mutex<ProcessTable>>,
+        next_thread_id: Arc<AtomicU32>,
+    ) -> Self {
         let mmu = Arc::new(Mutex::new(Mmu::new()));
         let sys_calls_state = Arc::new(Mutex::new(SysCallsState::new()));
         Self {
             mmu,
             file_system,
             sys_calls_state,
+            pending_signals: Arc::new(Mutex::new(PendingSignals::new())),
             threads: Arc::new(Mutex::new(Vec::new())),
-            next_thread_id: Arc::new(AtomicU32::new(1)),
+            next_thread_id,
+            process_table,
         }
     }
 
+    /// Starts the program, `program_args` are the arguments after `argv[0]`.
     pub fn run(
         &mut self,
         elf_filepath: String,
         program_args: Vec<String>,
         program_envs: Vec<(String, String)>,
     ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+        // argv[0] is the program path
+        let program_args: Vec<String> = std::iter::once(elf_filepath.clone())
+            .chain(program_args)
+            .collect();
+
+        // the main thread id is the process id
         let thread_id = self.next_thread_id.fetch_add(1, Ordering::Relaxed);
+        self.file_system.lock().unwrap().create_fd_table(thread_id);
         let context = Context {
             inner: Arc::new(ContextInner {
                 mmu: self.mmu.clone(),
-                file_system: self.file_system.clone(),
+                file_system: ProcessFileSystem::new(thread_id, self.file_system.clone()),
                 sys_calls_state: self.sys_calls_state.clone(),
+                pending_signals: self.pending_signals.clone(),
                 threads: Arc::downgrade(&self.threads),
                 next_thread_id: self.next_thread_id.clone(),
+                process_table: self.process_table.clone(),
+                pid: thread_id,
                 thread_id,
                 instruction_tracing: Arc::new(AtomicBool::new(false)),
                 hooked_libraries: Arc::new(Mutex::new(HashSet::new())),
             }),
         };
 
+        self.process_table.lock().unwrap().insert(ProcessEntry {
+            pid: thread_id,
+            parent_pid: 0,
+            state: ProcessState::Running,
+            elf_filepath: elf_filepath.clone(),
+            program_args: program_args.clone(),
+            program_envs: program_envs.clone(),
+            threads: self.threads.clone(),
+            pending_signals: self.pending_signals.clone(),
+            thread_names: HashMap::new(),
+            is_vfork_pending: false,
+            umask: 0o022,
+        });
+
         let (emu_main_thread, main_thread_handle) =
             Thread::start_elf_file(context, elf_filepath, program_args, program_envs)?;
 
@@ -59,3 +98,230 @@ impl Process {
         Ok(())
     }
 }
+
+/// Creates a copy of the calling process (`fork()`).
+///
+/// The pid of the child is stored at `child_tid_ptr` in the child memory (`CLONE_CHILD_SETTID`).
+///
+/// Returns pid of the new process.
+pub fn fork_process(
+    unicorn: &mut Unicorn<Context>,
+    is_vfork: bool,
+    child_tid_ptr: Option<u32>,
+) -> u32 {
+    let source_context = unicorn.get_data();
+
+    let child_pid = source_context
+        .inner
+        .next_thread_id
+        .fetch_add(1, Ordering::Relaxed);
+
+    let mut mmu = source_context.inner.mmu.lock().unwrap().fork(unicorn);
+    if let Some(child_tid_ptr) = child_tid_ptr {
+        mmu.write_unmapped(child_tid_ptr, &pack_u32(child_pid));
+    }
+
+    // all file descriptors are inherited (they share the file offset with the parent)
+    let shared_file_system = source_context.inner.file_system.shared();
+    shared_file_system
+        .lock()
+        .unwrap()
+        .fork_fds(source_context.inner.pid, child_pid);
+
+    let (elf_filepath, program_args, program_envs, thread_name, umask) = {
+        let process_table = source_context.inner.process_table.lock().unwrap();
+        let parent = process_table.get(source_context.inner.pid).unwrap();
+        (
+            parent.elf_filepath.clone(),
+            parent.program_args.clone(),
+            parent.program_envs.clone(),
+            parent.get_thread_name(source_context.inner.thread_id),
+            parent.umask,
+        )
+    };
+
+    let threads = Arc::new(Mutex::new(Vec::new()));
+    let pending_signals = Arc::new(Mutex::new(PendingSignals::new()));
+    let context = Context {
+        inner: Arc::new(ContextInner {
+            mmu: Arc::new(Mutex::new(mmu)),
+            file_system: ProcessFileSystem::new(child_pid, shared_file_system),
+            sys_calls_state: Arc::new(Mutex::new(SysCallsState::new())),
+            pending_signals: pending_signals.clone(),
+            threads: Arc::downgrade(&threads),
+            next_thread_id: source_context.inner.next_thread_id.clone(),
+            process_table: source_context.inner.process_table.clone(),
+            pid: child_pid,
+            thread_id: child_pid,
+            instruction_tracing: Arc::new(AtomicBool::new(false)),
+            hooked_libraries: Arc::new(Mutex::new(HashSet::new())),
+        }),
+    };
+
+    source_context
+        .inner
+        .process_table
+        .lock()
+        .unwrap()
+        .insert(ProcessEntry {
+            pid: child_pid,
+            parent_pid: source_context.inner.pid,
+            state: ProcessState::Running,
+            elf_filepath,
+            program_args,
+            program_envs,
+            threads: threads.clone(),
+            pending_signals,
+            // the child inherits name of the calling thread
+            thread_names: HashMap::from([(child_pid, thread_name)]),
+            is_vfork_pending: is_vfork,
+            umask,
+        });
+
+    // the thread list must contain the thread before it starts mapping memory
+    let mut threads_guard = threads.lock().unwrap();
+    let (child_thread, _) = Thread::fork(unicorn, context).unwrap();
+    threads_guard.push(child_thread);
+
+    log::info!(
+        "[{}] process {} forked to process {}",
+        source_context.inner.thread_id,
+        source_context.inner.pid,
+        child_pid
+    );
+
+    child_pid
+}
+
+/// Replaces the program of the calling process (`execve()`).
+///
+/// The ELF file in `buf` must be already checked, the old program is gone
+/// when an error is returned.
+pub fn exec_process(
+    unicorn: &mut Unicorn<Context>,
+    elf_filepath: String,
+    buf: &[u8],
+    program_args: Vec<String>,
+    program_envs: Vec<(String, String)>,
+) -> Result<(), &'static str> {
+    let context = unicorn.get_data().clone();
+
+    // only the calling thread survives
+    if let Some(threads) = context.inner.threads.upgrade() {
+        threads.lock().unwrap().retain_mut(|thread| {
+            if thread.unicorn.get_data().inner.thread_id == context.inner.thread_id {
+                true
+            } else {
+                thread.exit().unwrap();
+                false
+            }
+        });
+    }
+
+    // close files opened with `O_CLOEXEC` (or `FD_CLOEXEC` set by `fcntl()`)
+    context
+        .inner
+        .file_system
+        .lock()
+        .unwrap()
+        .close_on_exec(context.inner.pid);
+
+    context.inner.mmu.lock().unwrap().unmap_all(unicorn);
+    context.inner.hooked_libraries.lock().unwrap().clear();
+
+    let (interp_entry_point, elf_entry, stack_ptr) =
+        load_elf(unicorn, &elf_filepath, buf, &program_args, &program_envs)?;
+
+    unicorn
+        .reg_write(RegisterARM::SP as i32, stack_ptr as u64)
+        .unwrap();
+
+    // the new program sets its own tls
+    unicorn.reg_write(RegisterARM::C13_C0_3, 0).unwrap();
+    unicorn
+        .mem_write(GET_TLS_ADDR as u64 + 16, &pack_u32(0))
+        .unwrap();
+
+    // static binaries have no interpreter
+    let entry_point = if interp_entry_point != 0 {
+        interp_entry_point
+    } else {
+        elf_entry
+    };
+    unicorn.set_pc(entry_point as u64).unwrap();
+
+    log::info!(
+        "========== Exec program {} (interp_entry_point: {:#x}, elf_entry_point: {:#x}) ==========",
+        elf_filepath,
+        interp_entry_point,
+        elf_entry
+    );
+
+    if let Some(process) = context
+        .inner
+        .process_table
+        .lock()
+        .unwrap()
+        .get_mut(context.inner.pid)
+    {
+        process.elf_filepath = elf_filepath;
+        process.program_args = program_args;
+        process.program_envs = program_envs;
+        process.thread_names.clear();
+
+        // the parent waiting in `vfork()` can continue
+        process.is_vfork_pending = false;
+    }
+
+    context
+        .inner
+        .file_system
+        .lock()
+        .unwrap()
+        .notifier()
+        .notify();
+
+    Ok(())
+}
+
+/// Terminates the process `pid` (all its threads are already stopped).
+///
+/// The process stays as a zombie until the parent reaps it with `wait4()`.
+pub fn exit_process(unicorn: &Unicorn<Context>, pid: u32, reason: ExitReason) {
+    let inner = &unicorn.get_data().inner;
+
+    let is_exited = inner.process_table.lock().unwrap().exit(pid, reason);
+
+    let mut file_system = inner.file_system.lock().unwrap();
+    if is_exited {
+        file_system.remove_fd_table(pid);
+    }
+
+    // wake up the parent waiting in `wait4()` or `vfork()`
+    file_system.notifier().notify();
+}
+
+/// Stops all threads of the process `pid` (`SIGKILL`).
+///
+/// Returns `false` if there is no such process.
+pub fn kill_process(unicorn: &Unicorn<Context>, pid: u32) -> bool {
+    let threads = match unicorn
+        .get_data()
+        .inner
+        .process_table
+        .lock()
+        .unwrap()
+        .get(pid)
+    {
+        Some(process) => process.threads.clone(),
+        None => return false,
+    };
+
+    for thread in threads.lock().unwrap().iter_mut() {
+        thread.exit().unwrap();
+    }
+
+    exit_process(unicorn, pid, ExitReason::Killed(SIGKILL));
+
+    true
+}
@@ -0,0 +1,256 @@
+use crate::emulator::signals::{PendingSignals, SigInfo, SIGCHLD};
+use crate::emulator::thread::Thread;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+/// `si_code` of `SIGCHLD` when the child has exited.
+pub const CLD_EXITED: i32 = 1;
+/// `si_code` of `SIGCHLD` when the child was killed by a signal.
+pub const CLD_KILLED: i32 = 2;
+
+// pid of the init process, which adopts the orphaned processes
+const INIT_PID: u32 = 1;
+// parent pid of the processes without a parent (like the first one)
+const NO_PARENT_PID: u32 = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExitReason {
+    /// `exit()` with the exit code.
+    Exited(i32),
+    /// Terminated by the signal.
+    Killed(u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProcessState {
+    Running,
+    /// Process has exited and waits to be reaped by the parent (`wait4()` status).
+    Zombie(i32),
+}
+
+pub struct ProcessEntry {
+    pub pid: u32,
+    pub parent_pid: u32,
+    pub state: ProcessState,
+
+    pub elf_filepath: String,
+    pub program_args: Vec<String>,
+    pub program_envs: Vec<(String, String)>,
+
+    // the only strong reference to the threads (contexts keep weak ones)
+    pub threads: Arc<Mutex<Vec<Thread>>>,
+    pub pending_signals: Arc<Mutex<PendingSignals>>,
+
+    // names set by `prctl(PR_SET_NAME)` (by thread id)
+    pub thread_names: HashMap<u32, String>,
+
+    // parent created the process with `vfork()` and waits for `execve()` or exit
+    pub is_vfork_pending: bool,
+
+    // permission bits cleared from the created files (inherited by the child)
+    pub umask: u32,
+}
+
+///
+/// All processes of the emulator (shared by all of them).
+///
+pub struct ProcessTable {
+    processes: BTreeMap<u32, ProcessEntry>,
+}
+
+impl ProcessEntry {
+    /// Returns name of the thread (`comm`), the executable name by default.
+    pub fn get_thread_name(&self, thread_id: u32) -> String {
+        self.thread_names
+            .get(&thread_id)
+            .cloned()
+            .unwrap_or_else(|| {
+                let name = self.elf_filepath.rsplit('/').next().unwrap_or_default();
+                // TASK_COMM_LEN
+                name.chars().take(15).collect()
+            })
+    }
+}
+
+impl ProcessTable {
+    pub fn new() -> Self {
+        Self {
+            processes: BTreeMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, process: ProcessEntry) {
+        self.processes.insert(process.pid, process);
+    }
+
+    pub fn get(&self, pid: u32) -> Option<&ProcessEntry> {
+        self.processes.get(&pid)
+    }
+
+    pub fn get_mut(&mut self, pid: u32) -> Option<&mut ProcessEntry> {
+        self.processes.get_mut(&pid)
+    }
+
+    pub fn pids(&self) -> Vec<u32> {
+        self.processes.keys().cloned().collect()
+    }
+
+    pub fn has_children(&self, pid: u32) -> bool {
+        self.processes.values().any(|p| p.parent_pid == pid)
+    }
+
+    /// Marks the process as zombie and queues `SIGCHLD` for the parent.
+    ///
+    /// `SIGCHLD` is only pushed into `PendingSignals` of the parent, it is not delivered
+    /// (the handler of the parent is not called), so it is seen only by `signalfd()`
+    /// and `rt_sigtimedwait()`. The parent learns about the exit from `wait4()`.
+    ///
+    /// Returns `false` if the process is not running.
+    pub fn exit(&mut self, pid: u32, reason: ExitReason) -> bool {
+        let parent_pid = match self.processes.get_mut(&pid) {
+            Some(process) if process.state == ProcessState::Running => {
+                process.state = ProcessState::Zombie(match reason {
+                    // WIFEXITED
+                    ExitReason::Exited(exit_code) => (exit_code & 0xff) << 8,
+                    // WIFSIGNALED
+                    ExitReason::Killed(signo) => (signo & 0x7f) as i32,
+                });
+                process.is_vfork_pending = false;
+                process.parent_pid
+            }
+            _ => return false,
+        };
+
+        // orphaned children are adopted by the init process if it is emulated, otherwise
+        // nobody waits for them (exited ones are reaped now, running ones when they exit)
+        let reaper_pid = match self.processes.get(&INIT_PID) {
+            Some(init) if init.pid != pid && init.state == ProcessState::Running => INIT_PID,
+            _ => NO_PARENT_PID,
+        };
+        self.processes.retain(|_, process| {
+            if process.parent_pid != pid {
+                return true;
+            }
+            process.parent_pid = reaper_pid;
+            reaper_pid != NO_PARENT_PID || process.state == ProcessState::Running
+        });
+
+        let (code, status) = match reason {
+            ExitReason::Exited(exit_code) => (CLD_EXITED, exit_code),
+            ExitReason::Killed(signo) => (CLD_KILLED, signo as i32),
+        };
+
+        if let Some(parent) = self.processes.get(&parent_pid) {
+            parent.pending_signals.lock().unwrap().push(SigInfo {
+                signo: SIGCHLD,
+                code,
+                pid,
+                uid: 0,
+                tid: pid,
+                status,
+            });
+        } else {
+            // nobody is going to reap the process
+            self.processes.remove(&pid);
+        }
+
+        log::info!("process {} exited: {:?}", pid, reason);
+
+        true
+    }
+
+    /// Removes zombie child of the `parent_pid` (any child if `pid` is `None`).
+    ///
+    /// Returns pid of the child and its status.
+    pub fn reap(&mut self, parent_pid: u32, pid: Option<u32>) -> Option<(u32, i32)> {
+        let (child_pid, status) = self.processes.values().find_map(|p| match p.state {
+            ProcessState::Zombie(status)
+                if p.parent_pid == parent_pid && pid.is_none_or(|pid| pid == p.pid) =>
+            {
+                Some((p.pid, status))
+            }
+            _ => None,
+        })?;
+
+        self.processes.remove(&child_pid);
+
+        Some((child_pid, status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, parent_pid: u32) -> ProcessEntry {
+        ProcessEntry {
+            pid,
+            parent_pid,
+            state: ProcessState::Running,
+            elf_filepath: "/bin/test".to_string(),
+            program_args: Vec::new(),
+            program_envs: Vec::new(),
+            threads: Arc::new(Mutex::new(Vec::new())),
+            pending_signals: Arc::new(Mutex::new(PendingSignals::new())),
+            thread_names: HashMap::new(),
+            is_vfork_pending: false,
+            umask: 0o022,
+        }
+    }
+
+    #[test]
+    fn test_orphans_adopted_by_init() {
+        let mut process_table = ProcessTable::new();
+        process_table.insert(process(1, 0));
+        process_table.insert(process(2, 1));
+        process_table.insert(process(3, 2));
+        process_table.insert(process(4, 2));
+
+        assert!(process_table.exit(4, ExitReason::Exited(0)));
+        assert!(process_table.exit(2, ExitReason::Exited(0)));
+        assert_eq!(process_table.get(3).unwrap().parent_pid, 1);
+        assert_eq!(process_table.get(4).unwrap().parent_pid, 1);
+
+        // init reaps the exited process and the zombie orphan
+        assert_eq!(process_table.reap(1, Some(4)), Some((4, 0)));
+        assert_eq!(process_table.reap(1, None), Some((2, 0)));
+        assert_eq!(process_table.reap(1, None), None);
+        assert!(process_table.has_children(1));
+    }
+
+    #[test]
+    fn test_orphans_without_init() {
+        let mut process_table = ProcessTable::new();
+        process_table.insert(process(100, 0));
+        process_table.insert(process(101, 100));
+        process_table.insert(process(102, 100));
+
+        assert!(process_table.exit(102, ExitReason::Killed(9)));
+        assert!(process_table.exit(100, ExitReason::Exited(1)));
+
+        // the zombie orphan is reaped, the running one has no parent
+        assert!(process_table.get(100).is_none());
+        assert!(process_table.get(102).is_none());
+        assert_eq!(process_table.get(101).unwrap().parent_pid, 0);
+
+        // nobody waits for the orphan
+        assert!(process_table.exit(101, ExitReason::Exited(0)));
+        assert!(process_table.pids().is_empty());
+    }
+
+    #[test]
+    fn test_sigchld_queued() {
+        let mut process_table = ProcessTable::new();
+        process_table.insert(process(100, 0));
+        process_table.insert(process(101, 100));
+
+        assert!(process_table.exit(101, ExitReason::Exited(3)));
+        assert!(!process_table.exit(101, ExitReason::Exited(3)));
+        let pending_signals = process_table.get(100).unwrap().pending_signals.clone();
+        let sig_info = pending_signals.lock().unwrap().pop(u64::MAX).unwrap();
+        assert_eq!(sig_info.signo, SIGCHLD);
+        assert_eq!(sig_info.code, CLD_EXITED);
+        assert_eq!(sig_info.status, 3);
+        assert_eq!(process_table.reap(100, None), Some((101, 3 << 8)));
+    }
+}
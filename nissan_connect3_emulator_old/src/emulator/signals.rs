@@ -0,0 +1,60 @@
+use std::collections::VecDeque;
+
+pub const SIGKILL: u32 = 9;
+pub const SIGCHLD: u32 = 17;
+
+/// `si_code` values
+pub const SI_USER: i32 = 0;
+pub const SI_TKILL: i32 = -6;
+
+/// Information about a signal sent to the process.
+#[derive(Debug, Clone)]
+pub struct SigInfo {
+    pub signo: u32,
+    pub code: i32,
+    pub pid: u32,
+    pub uid: u32,
+    pub tid: u32,
+    pub status: i32,
+}
+
+/// Signals sent to the process that were not consumed yet.
+///
+/// Signal handlers are not invoked by the emulator, queued signals are consumed
+/// by `signalfd` file descriptors and `rt_sigtimedwait()`.
+pub struct PendingSignals {
+    queue: VecDeque<SigInfo>,
+}
+
+impl PendingSignals {
+    pub fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, sig_info: SigInfo) {
+        // standard signals are not queued more than once
+        if sig_info.signo < 32 && self.queue.iter().any(|s| s.signo == sig_info.signo) {
+            return;
+        }
+        self.queue.push_back(sig_info);
+    }
+
+    /// Removes the first signal that is a member of the `mask` (bit `signo - 1`).
+    pub fn pop(&mut self, mask: u64) -> Option<SigInfo> {
+        let index = self
+            .queue
+            .iter()
+            .position(|s| Self::is_in_mask(s.signo, mask))?;
+        self.queue.remove(index)
+    }
+
+    pub fn has_pending(&self, mask: u64) -> bool {
+        self.queue.iter().any(|s| Self::is_in_mask(s.signo, mask))
+    }
+
+    fn is_in_mask(signo: u32, mask: u64) -> bool {
+        (1..=64).contains(&signo) && mask & (1u64 << (signo - 1)) != 0
+    }
+}
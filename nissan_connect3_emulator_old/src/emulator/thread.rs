@@ -4,26 +4,21 @@ use crate::emulator::memory_map::{GET_TLS_ADDR, STACK_BASE, STACK_SIZE};
 use crate::emulator::mmu::mmu_clone_map;
 use crate::emulator::print::{disasm, print_mmu, print_stack};
 use crate::emulator::utils::{load_binary, pack_u32};
-use crate::os::libosal_add_code_hooks;
 use std::collections::HashSet;
 use std::error::Error;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::thread::JoinHandle;
 use unicorn_engine::unicorn_const::{uc_error, Arch, HookType, MemType, Mode, Permission};
 use unicorn_engine::{RegisterARM, Unicorn};
 
+/// Handle of the host thread running the emulation.
+pub type ThreadHandle = JoinHandle<Result<(), Box<dyn Error + Send + Sync + 'static>>>;
+
 pub struct Thread {
     pub unicorn: Unicorn<Context>,
-
-    // used to not start emulation at all when pause is requested very early
-    is_paused: Arc<AtomicBool>,
     is_exit: Arc<AtomicBool>,
-
-    // used to resume emulation
-    resume_tx: Sender<()>,
 }
 
 impl Thread {
@@ -33,39 +28,17 @@ impl Thread {
         elf_filepath: String,
         program_args: Vec<String>,
         program_envs: Vec<(String, String)>,
-    ) -> Result<
-        (
-            Self,
-            JoinHandle<Result<(), Box<dyn Error + Send + Sync + 'static>>>,
-        ),
-        Box<dyn Error + Send + Sync + 'static>,
-    > {
+    ) -> Result<(Self, ThreadHandle), Box<dyn Error + Send + Sync + 'static>> {
         let mut unicorn = Unicorn::new_with_data(Arch::ARM, Mode::LITTLE_ENDIAN, context)
             .map_err(|err| format!("Unicorn error: {:?}", err))
             .unwrap();
 
-        unicorn.add_intr_hook(crate::os::hook_syscall).unwrap();
-        unicorn
-            .add_mem_hook(HookType::MEM_FETCH_UNMAPPED, 1, 0, callback_mem_error)
-            .unwrap();
-        unicorn
-            .add_mem_hook(HookType::MEM_READ_UNMAPPED, 1, 0, callback_mem_rw)
-            .unwrap();
-        unicorn
-            .add_mem_hook(HookType::MEM_WRITE_UNMAPPED, 1, 0, callback_mem_rw)
-            .unwrap();
-        unicorn
-            .add_mem_hook(HookType::MEM_WRITE_PROT, 1, 0, callback_mem_rw)
-            .unwrap();
+        add_hooks(&mut unicorn);
 
-        let is_paused = Arc::new(AtomicBool::new(false));
         let is_exit = Arc::new(AtomicBool::new(false));
 
-        let (resume_tx, resume_rx) = channel();
-
         let handle = thread::spawn({
             let mut unicorn = unicorn.clone();
-            let is_paused = is_paused.clone();
             let is_exit = is_exit.clone();
             move || {
                 let buf = load_binary(&mut unicorn, &elf_filepath);
@@ -91,41 +64,30 @@ impl Thread {
                     elf_entry
                 );
 
-                emu_thread_loop(unicorn, interp_entry_point, is_paused, is_exit, resume_rx)
+                run_emulation(unicorn, interp_entry_point, is_exit)
             }
         });
 
-        Ok((
-            Self {
-                unicorn,
-                is_paused,
-                is_exit,
-                resume_tx,
-            },
-            handle,
-        ))
+        Ok((Self { unicorn, is_exit }, handle))
     }
 
     pub fn clone(
-        mut source_unicorn: &mut Unicorn<Context>,
+        source_unicorn: &mut Unicorn<Context>,
         child_thread_id: u32,
         child_tls: u32,
         mut child_stack: u32,
-    ) -> Result<
-        (
-            Self,
-            JoinHandle<Result<(), Box<dyn Error + Send + Sync + 'static>>>,
-        ),
-        Box<dyn Error + Send + Sync + 'static>,
-    > {
+    ) -> Result<(Self, ThreadHandle), Box<dyn Error + Send + Sync + 'static>> {
         let source_context = source_unicorn.get_data();
         let context = Context {
             inner: Arc::new(ContextInner {
                 mmu: source_context.inner.mmu.clone(),
                 file_system: source_context.inner.file_system.clone(),
                 sys_calls_state: source_context.inner.sys_calls_state.clone(),
+                pending_signals: source_context.inner.pending_signals.clone(),
                 threads: source_context.inner.threads.clone(),
                 next_thread_id: source_context.inner.next_thread_id.clone(),
+                process_table: source_context.inner.process_table.clone(),
+                pid: source_context.inner.pid,
                 thread_id: child_thread_id,
                 instruction_tracing: Arc::new(AtomicBool::new(false)),
                 hooked_libraries: Arc::new(Mutex::new(HashSet::new())),
@@ -144,19 +106,7 @@ impl Thread {
             .context_restore(&registers_context)
             .map_err(|err| format!("Unicorn context restore error: {:?}", err))?;
 
-        unicorn.add_intr_hook(crate::os::hook_syscall).unwrap();
-        unicorn
-            .add_mem_hook(HookType::MEM_FETCH_UNMAPPED, 1, 0, callback_mem_error)
-            .unwrap();
-        unicorn
-            .add_mem_hook(HookType::MEM_READ_UNMAPPED, 1, 0, callback_mem_rw)
-            .unwrap();
-        unicorn
-            .add_mem_hook(HookType::MEM_WRITE_UNMAPPED, 1, 0, callback_mem_rw)
-            .unwrap();
-        unicorn
-            .add_mem_hook(HookType::MEM_WRITE_PROT, 1, 0, callback_mem_rw)
-            .unwrap();
+        add_hooks(&mut unicorn);
 
         {
             let data = unicorn.get_data();
@@ -184,7 +134,7 @@ impl Thread {
             let data = source_unicorn.get_data();
             let mmu = &mut data.inner.mmu.lock().unwrap();
             let stack_ptr = mmu.heap_alloc(
-                &mut source_unicorn,
+                source_unicorn,
                 STACK_SIZE,
                 Permission::READ | Permission::WRITE,
                 "",
@@ -204,52 +154,84 @@ impl Thread {
             .unwrap();
 
         // copy memory map
-        mmu_clone_map(&source_unicorn, &mut unicorn)?;
+        mmu_clone_map(source_unicorn, &mut unicorn)?;
 
         // set 0 in R0 (result from syscall)
         unicorn.reg_write(RegisterARM::R0 as i32, 0).unwrap();
 
-        let is_paused = Arc::new(AtomicBool::new(false));
         let is_exit = Arc::new(AtomicBool::new(false));
 
-        let (resume_tx, resume_rx) = channel();
-
         let handle = thread::spawn({
             let unicorn = unicorn.clone();
-            let is_paused = is_paused.clone();
             let is_exit = is_exit.clone();
             move || {
                 let pc = unicorn.reg_read(RegisterARM::PC).unwrap() as u32;
 
                 log::info!("========== Clone thread at address: {:#x} ==========", pc);
 
-                emu_thread_loop(unicorn, pc, is_paused, is_exit, resume_rx)
+                run_emulation(unicorn, pc, is_exit)
             }
         });
 
-        Ok((
-            Self {
-                unicorn,
-                is_paused,
-                is_exit,
-                resume_tx,
-            },
-            handle,
-        ))
+        Ok((Self { unicorn, is_exit }, handle))
     }
 
-    pub fn pause(&mut self) -> Result<(), uc_error> {
-        /*self.is_paused.store(true, Ordering::Relaxed);
+    /// Starts the main thread of the forked process (continues after the syscall).
+    pub fn fork(
+        source_unicorn: &mut Unicorn<Context>,
+        context: Context,
+    ) -> Result<(Self, ThreadHandle), Box<dyn Error + Send + Sync + 'static>> {
+        let mut unicorn = Unicorn::new_with_data(Arch::ARM, Mode::LITTLE_ENDIAN, context)
+            .map_err(|err| format!("Unicorn error: {:?}", err))
+            .unwrap();
+
+        // copy registers
+        let registers_context = source_unicorn
+            .context_init()
+            .map_err(|err| format!("Unicorn context init error: {:?}", err))?;
+        unicorn
+            .context_restore(&registers_context)
+            .map_err(|err| format!("Unicorn context restore error: {:?}", err))?;
+
+        add_hooks(&mut unicorn);
 
-        self.unicorn.emu_stop()*/
-        Ok(())
-    }
+        // copy memory map
+        {
+            let data = unicorn.get_data();
+            let mut mmu = data.inner.mmu.lock().unwrap();
+            mmu.map_regions(&mut unicorn);
+            mmu.update_library_hooks(&mut unicorn);
+        }
+
+        set_kernel_traps(&mut unicorn);
+
+        // keep tls of the parent thread
+        let tls = source_unicorn.reg_read(RegisterARM::C13_C0_3).unwrap();
+        unicorn.reg_write(RegisterARM::C13_C0_3, tls).unwrap();
+        unicorn
+            .mem_write(GET_TLS_ADDR as u64 + 16, &pack_u32(tls as u32))
+            .unwrap();
 
-    pub fn resume(&mut self) {
-        /*if self.is_paused.load(Ordering::Relaxed) {
-            self.is_paused.store(false, Ordering::Relaxed);
-            self.resume_tx.send(()).unwrap();
-        }*/
+        enable_vfp(&mut unicorn);
+
+        // set 0 in R0 (result from syscall)
+        unicorn.reg_write(RegisterARM::R0 as i32, 0).unwrap();
+
+        let is_exit = Arc::new(AtomicBool::new(false));
+
+        let handle = thread::spawn({
+            let unicorn = unicorn.clone();
+            let is_exit = is_exit.clone();
+            move || {
+                let pc = unicorn.reg_read(RegisterARM::PC).unwrap() as u32;
+
+                log::info!("========== Fork process at address: {:#x} ==========", pc);
+
+                run_emulation(unicorn, pc, is_exit)
+            }
+        });
+
+        Ok((Self { unicorn, is_exit }, handle))
     }
 
     pub fn exit(&mut self) -> Result<(), uc_error> {
@@ -257,53 +239,42 @@ impl Thread {
 
         self.unicorn.emu_stop()
     }
+
+    pub fn is_exited(&self) -> bool {
+        self.is_exit.load(Ordering::Relaxed)
+    }
 }
 
-fn emu_thread_loop(
+fn run_emulation(
     mut unicorn: Unicorn<Context>,
-    mut start_address: u32,
-    is_paused: Arc<AtomicBool>,
+    start_address: u32,
     is_exit: Arc<AtomicBool>,
-    resume_rx: Receiver<()>,
 ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
-    loop {
-        if !is_paused.load(Ordering::Relaxed) {
-            log::trace!(
-                "{:#x}: [{}] thread start or resume",
-                start_address,
-                unicorn.get_data().inner.thread_id
-            );
+    log::trace!(
+        "{:#x}: [{}] thread start",
+        start_address,
+        unicorn.get_data().inner.thread_id
+    );
 
-            match unicorn.emu_start(start_address as u64, 0, 0, 0) {
-                Ok(()) => {
-                    if is_exit.load(Ordering::Relaxed) {
-                        // thread has ended
-                        break;
-                    } else {
-                        // we have stopped because the pause was requested
-
-                        start_address = unicorn.reg_read(RegisterARM::PC).unwrap() as u32;
-                        log::trace!(
-                            "{:#x}: [{}] thread paused",
-                            start_address,
-                            unicorn.get_data().inner.thread_id,
-                        );
-
-                        // wait for the signal to resume
-                        resume_rx.recv().unwrap();
-                    }
-                }
-                Err(error) => {
-                    log::error!(
-                        "{:#x}: [{}] Execution error: {:?}",
-                        unicorn.reg_read(RegisterARM::PC).unwrap(),
-                        unicorn.get_data().inner.thread_id,
-                        error
-                    );
-                    break;
-                }
+    match unicorn.emu_start(start_address as u64, 0, 0, 0) {
+        Ok(()) => {
+            // the emulation is stopped only by the exit (or by jumping to address 0)
+            if !is_exit.load(Ordering::Relaxed) {
+                log::error!(
+                    "{:#x}: [{}] Execution stopped without exit",
+                    unicorn.reg_read(RegisterARM::PC).unwrap(),
+                    unicorn.get_data().inner.thread_id,
+                );
             }
         }
+        Err(error) => {
+            log::error!(
+                "{:#x}: [{}] Execution error: {:?}",
+                unicorn.reg_read(RegisterARM::PC).unwrap(),
+                unicorn.get_data().inner.thread_id,
+                error
+            );
+        }
     }
 
     log::info!("========== Program done ==========");
@@ -311,6 +282,22 @@ fn emu_thread_loop(
     Ok(())
 }
 
+fn add_hooks(unicorn: &mut Unicorn<Context>) {
+    unicorn.add_intr_hook(crate::os::hook_syscall).unwrap();
+    unicorn
+        .add_mem_hook(HookType::MEM_FETCH_UNMAPPED, 1, 0, callback_mem_error)
+        .unwrap();
+    unicorn
+        .add_mem_hook(HookType::MEM_READ_UNMAPPED, 1, 0, callback_mem_rw)
+        .unwrap();
+    unicorn
+        .add_mem_hook(HookType::MEM_WRITE_UNMAPPED, 1, 0, callback_mem_rw)
+        .unwrap();
+    unicorn
+        .add_mem_hook(HookType::MEM_WRITE_PROT, 1, 0, callback_mem_write_prot)
+        .unwrap();
+}
+
 // If the compiler for the target does not provides some primitives for some
 // reasons (e.g. target limitations), the kernel is responsible to assist
 // with these operations.
@@ -413,6 +400,27 @@ pub fn callback_mem_error(
     false
 }
 
+pub fn callback_mem_write_prot(
+    unicorn: &mut Unicorn<Context>,
+    memtype: MemType,
+    address: u64,
+    size: usize,
+    value: i64,
+) -> bool {
+    // memory shared with the forked process is copied on the first write,
+    // shared file mapping becomes dirty on the first write
+    let mmu = unicorn.get_data().inner.mmu.clone();
+    let mut mmu = mmu.lock().unwrap();
+    if mmu.resolve_copy_on_write(unicorn, address as u32)
+        || mmu.mark_shared_dirty(unicorn, address as u32)
+    {
+        return true;
+    }
+    drop(mmu);
+
+    callback_mem_rw(unicorn, memtype, address, size, value)
+}
+
 pub fn callback_mem_rw(
     unicorn: &mut Unicorn<Context>,
     memtype: MemType,
@@ -454,7 +462,7 @@ fn dump_context(unicorn: &mut Unicorn<Context>) {
         unicorn.reg_read(RegisterARM::LR).unwrap() as u32 - 100,
         200,
     );
-    disasm(unicorn, 0x484e93ec as u32, 200);
+    disasm(unicorn, 0x484e93ec_u32, 200);
 
     print_stack(unicorn);
 }
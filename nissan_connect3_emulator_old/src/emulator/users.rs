@@ -1,4 +1,28 @@
-pub const UID: u32 = 1000;
-pub const EUID: u32 = 1000;
-pub const GID: u32 = 1001;
-pub const EGID: u32 = 1001;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+// credentials of the emulated processes, an ordinary user of the firmware by default
+// (the effective ids are the same as the real ones, setuid is not emulated)
+static UID: AtomicU32 = AtomicU32::new(1000);
+static GID: AtomicU32 = AtomicU32::new(1001);
+
+/// Changes the user and group of the emulated processes (before the first one is started).
+pub fn set_credentials(uid: u32, gid: u32) {
+    UID.store(uid, Ordering::Relaxed);
+    GID.store(gid, Ordering::Relaxed);
+}
+
+pub fn uid() -> u32 {
+    UID.load(Ordering::Relaxed)
+}
+
+pub fn euid() -> u32 {
+    uid()
+}
+
+pub fn gid() -> u32 {
+    GID.load(Ordering::Relaxed)
+}
+
+pub fn egid() -> u32 {
+    gid()
+}
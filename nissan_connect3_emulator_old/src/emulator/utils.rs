@@ -1,26 +1,29 @@
 use crate::emulator::context::Context;
-use crate::file_system::OpenFileFlags;
+use crate::file_system::{OpenFileError, OpenFileFlags};
 use byteorder::{ByteOrder, LittleEndian};
 use unicorn_engine::unicorn_const::Permission;
 use unicorn_engine::Unicorn;
 use xmas_elf::program;
 
 pub fn load_binary(unicorn: &mut Unicorn<Context>, filepath: &str) -> Vec<u8> {
+    read_binary(unicorn, filepath).unwrap_or_else(|_| panic!("Cannot load file: {}", filepath))
+}
+
+/// Reads the whole file from the emulated file system.
+pub fn read_binary(unicorn: &Unicorn<Context>, filepath: &str) -> Result<Vec<u8>, OpenFileError> {
     let data = unicorn.get_data().inner;
     let file_system = &mut data.file_system.lock().unwrap();
-    if let Ok(fd) = file_system.open(filepath, OpenFileFlags::READ) {
-        let size = file_system.get_length(fd);
-        let mut content = vec![0u8; size as usize];
-        file_system.read_all(fd, &mut content).unwrap();
-        file_system.close(fd).unwrap();
-        content
-    } else {
-        panic!("Cannot load file: {}", filepath);
-    }
+    let fd = file_system.open(filepath, OpenFileFlags::READ)?;
+    let size = file_system.get_length(fd);
+    let mut content = vec![0u8; size as usize];
+    let res = file_system.read_all(fd, &mut content);
+    file_system.close(fd).unwrap();
+    res.map_err(|_| OpenFileError::NoSuchFileOrDirectory)?;
+    Ok(content)
 }
 
-/// Converts NULL terminated string to rust string
-/*pub fn null_str(input: &str) -> String {
+/*/// Converts NULL terminated string to rust string
+pub fn null_str(input: &str) -> String {
     let res = input.trim_matches(char::from(0));
     String::from(res)
 }*/
@@ -38,24 +41,24 @@ pub fn mem_align_down(address: u32, alignment: Option<u32>) -> u32 {
 /// to page size.
 pub fn mem_align_up(address: u32, alignment: Option<u32>) -> u32 {
     let align = alignment.unwrap_or(0x1000);
-    ((address + align - 1) / align) * align
+    address.div_ceil(align) * align
 }
 
 pub fn to_unicorn_permissions(perms: program::Flags) -> Permission {
     let mut uc_perms: Permission = Permission::NONE;
 
     if perms.is_execute() {
-        uc_perms = uc_perms | Permission::EXEC;
+        uc_perms |= Permission::EXEC;
         // assumes read if execute
-        uc_perms = uc_perms | Permission::READ;
+        uc_perms |= Permission::READ;
     }
 
     if perms.is_write() {
-        uc_perms = uc_perms | Permission::WRITE;
+        uc_perms |= Permission::WRITE;
     }
 
     if perms.is_read() {
-        uc_perms = uc_perms | Permission::READ;
+        uc_perms |= Permission::READ;
     }
 
     uc_perms
@@ -68,7 +71,7 @@ pub fn push_text_on_stack(unicorn: &mut Unicorn<Context>, address: u32, text: &s
     let address = mem_align_down(address - data.len() as u32 - 1, Some(4));
     unicorn.mem_write(address as u64, data).unwrap();
     unicorn
-        .mem_write(address as u64 + data.len() as u64, &vec![0u8])
+        .mem_write(address as u64 + data.len() as u64, &[0u8])
         .unwrap();
     address
 }
@@ -110,3 +113,32 @@ pub fn pack_i64(value: i64) -> Vec<u8> {
 pub fn pack_u64(value: u64) -> Vec<u8> {
     value.to_le_bytes().to_vec()
 }
+
+/// Returns number of days since 1970-01-01 of the date in the Gregorian calendar.
+pub fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Returns date in the Gregorian calendar of number of days since 1970-01-01.
+pub fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
@@ -0,0 +1,530 @@
+use crate::emulator::clock::clock_now;
+use crate::emulator::context::Context;
+use crate::emulator::signals::PendingSignals;
+use crate::emulator::users::{egid, euid};
+use crate::emulator::utils::{pack_i32, pack_u32, pack_u64};
+use crate::file_system::file_info::FileDetails;
+use crate::file_system::{
+    CloseFileError, FdNotifier, FileSystem, FileSystemType, FileType, IncSocket, Inotify,
+    OpenFileError, OpenFileFlags, PipeEnd, PollEvents,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::io::SeekFrom;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use unicorn_engine::Unicorn;
+
+/// Size of `struct signalfd_siginfo`.
+pub const SIGNALFD_SIGINFO_SIZE: usize = 128;
+
+/// File without a path, created by `eventfd()`, `timerfd_create()`, `signalfd()`,
+/// `epoll_create()`, `inotify_init()`, `pipe()` or `socket()` of the INC family.
+pub enum AnonFile {
+    EventFd(EventFd),
+    TimerFd(TimerFd),
+    SignalFd(SignalFd),
+    Epoll(Epoll),
+    Inotify(Inotify),
+    IncSocket(IncSocket),
+    Pipe(PipeEnd),
+}
+
+pub struct EventFd {
+    pub counter: u64,
+    pub is_semaphore: bool,
+}
+
+pub struct TimerFd {
+    pub clock_id: u32,
+    // absolute time of the next expiration (measured with `clock_id` clock)
+    pub next_expiration: Option<Duration>,
+    pub interval: Duration,
+    pub expirations: u64,
+}
+
+pub struct SignalFd {
+    // bit `signo - 1` is set for every accepted signal
+    pub mask: u64,
+    pub pending_signals: Arc<Mutex<PendingSignals>>,
+}
+
+pub struct Epoll {
+    pub interests: BTreeMap<i32, EpollInterest>,
+}
+
+#[derive(Clone)]
+pub struct EpollInterest {
+    pub events: u32,
+    pub data: u64,
+    // open file of the watched file descriptor
+    pub file: i32,
+}
+
+impl AnonFile {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AnonFile::EventFd(_) => "eventfd",
+            AnonFile::TimerFd(_) => "timerfd",
+            AnonFile::SignalFd(_) => "signalfd",
+            AnonFile::Epoll(_) => "eventpoll",
+            AnonFile::Inotify(_) => "inotify",
+            AnonFile::IncSocket(_) => "inc_socket",
+            AnonFile::Pipe(_) => "pipe",
+        }
+    }
+}
+
+impl TimerFd {
+    pub fn new(clock_id: u32) -> Self {
+        Self {
+            clock_id,
+            next_expiration: None,
+            interval: Duration::ZERO,
+            expirations: 0,
+        }
+    }
+
+    /// Arms (or disarms if `value` is zero) the timer.
+    pub fn set_time(&mut self, value: Duration, interval: Duration, is_absolute: bool) {
+        self.expirations = 0;
+        self.interval = interval;
+        self.next_expiration = if value.is_zero() {
+            None
+        } else if is_absolute {
+            Some(value)
+        } else {
+            Some(clock_now(self.clock_id) + value)
+        };
+    }
+
+    /// Returns time left to the next expiration and the interval.
+    pub fn get_time(&self) -> (Duration, Duration) {
+        let now = clock_now(self.clock_id);
+        let remaining = match self.next_expiration {
+            None => Duration::ZERO,
+            Some(next) if now < next => next - now,
+            // expired periodic timer, the next expiration is not counted yet
+            Some(next) if !self.interval.is_zero() => {
+                let elapsed = (now - next).as_nanos() % self.interval.as_nanos();
+                self.interval - Duration::from_nanos(elapsed as u64)
+            }
+            Some(_) => Duration::ZERO,
+        };
+        (remaining, self.interval)
+    }
+
+    /// Counts expirations that happened until now.
+    pub fn update(&mut self) {
+        self.update_at(clock_now(self.clock_id));
+    }
+
+    /// Counts expirations that happened until `now` (time of the timer clock).
+    fn update_at(&mut self, now: Duration) {
+        if let Some(next_expiration) = self.next_expiration {
+            if now >= next_expiration {
+                if self.interval.is_zero() {
+                    self.expirations = self.expirations.saturating_add(1);
+                    self.next_expiration = None;
+                } else {
+                    // computed in nanoseconds, the count can be huge for short intervals
+                    let interval = self.interval.as_nanos();
+                    let count = (now - next_expiration).as_nanos() / interval + 1;
+                    self.expirations = self
+                        .expirations
+                        .saturating_add(count.try_into().unwrap_or(u64::MAX));
+                    let next = next_expiration.as_nanos() + count * interval;
+                    self.next_expiration = Some(Duration::new(
+                        (next / 1_000_000_000) as u64,
+                        (next % 1_000_000_000) as u32,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+///
+/// File system that keeps files without paths (anonymous inodes).
+/// It is mounted automatically by `MountFileSystem`.
+///
+#[derive(Clone)]
+pub struct AnonFileSystem {
+    files: Arc<Mutex<HashMap<i32, AnonFile>>>,
+    notifier: Arc<FdNotifier>,
+}
+
+impl AnonFileSystem {
+    pub fn new(notifier: Arc<FdNotifier>) -> Self {
+        Self {
+            files: Arc::new(Mutex::new(HashMap::new())),
+            notifier,
+        }
+    }
+
+    pub fn insert(&mut self, fd: i32, file: AnonFile) {
+        self.files.lock().unwrap().insert(fd, file);
+    }
+
+    /// Gives read-only access to the file (used to check its state).
+    pub fn with_file<R>(&self, fd: i32, f: impl FnOnce(&AnonFile) -> R) -> Option<R> {
+        self.files.lock().unwrap().get(&fd).map(f)
+    }
+
+    /// Gives access to the file to change its state and wakes up the threads waiting for it.
+    pub fn with_file_mut<R>(&mut self, fd: i32, f: impl FnOnce(&mut AnonFile) -> R) -> Option<R> {
+        let res = self.files.lock().unwrap().get_mut(&fd).map(f);
+        if res.is_some() {
+            self.notifier.notify();
+        }
+        res
+    }
+
+    /// Passes the event of the file at `path` to all inotify instances.
+    pub fn notify_path_event(&mut self, path: &str, mask: u32, cookie: u32) {
+        let mut is_queued = false;
+        for file in self.files.lock().unwrap().values_mut() {
+            if let AnonFile::Inotify(inotify) = file {
+                is_queued |= inotify.handle_event(path, mask, cookie);
+            }
+        }
+        if is_queued {
+            self.notifier.notify();
+        }
+    }
+
+    /// Updates paths watched by inotify instances after the file was moved.
+    pub fn notify_path_move(&mut self, old_path: &str, new_path: &str) {
+        for file in self.files.lock().unwrap().values_mut() {
+            if let AnonFile::Inotify(inotify) = file {
+                inotify.handle_move(old_path, new_path);
+            }
+        }
+    }
+
+    /// Removes closed file from all epoll interest lists.
+    pub fn forget_file(&mut self, file: i32) {
+        for anon_file in self.files.lock().unwrap().values_mut() {
+            if let AnonFile::Epoll(epoll) = anon_file {
+                epoll.interests.retain(|_, interest| interest.file != file);
+            }
+        }
+    }
+}
+
+impl FileSystem for AnonFileSystem {
+    fn support_file_paths(&self) -> bool {
+        false
+    }
+
+    fn file_system_type(&self) -> FileSystemType {
+        FileSystemType::Anon
+    }
+
+    fn exists(&mut self, _file_path: &str) -> bool {
+        false
+    }
+
+    fn mkdir(&mut self, _file_path: &str, _mode: u32) -> Result<(), OpenFileError> {
+        Err(OpenFileError::NoPermission)
+    }
+
+    fn read_dir(&mut self, _dir_path: &str) -> Result<Vec<String>, ()> {
+        Err(())
+    }
+
+    fn open(
+        &mut self,
+        _file_path: &str,
+        _flags: OpenFileFlags,
+        _fd: i32,
+    ) -> Result<(), OpenFileError> {
+        // anonymous files are created with `insert()`
+        Err(OpenFileError::NoSuchFileOrDirectory)
+    }
+
+    fn close(&mut self, fd: i32) -> Result<(), CloseFileError> {
+        let file = self.files.lock().unwrap().remove(&fd);
+        match file {
+            None => Err(CloseFileError::FileNotOpened),
+            Some(AnonFile::Pipe(pipe_end)) => {
+                // the other end of the pipe gets end of file (or error)
+                drop(pipe_end);
+                self.notifier.notify();
+                Ok(())
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
+    fn link(&mut self, _old_path: &str, _new_path: &str) -> Result<(), OpenFileError> {
+        Err(OpenFileError::NoPermission)
+    }
+
+    fn unlink(&mut self, _file_path: &str) -> Result<(), OpenFileError> {
+        Err(OpenFileError::NoPermission)
+    }
+
+    fn get_file_details(&mut self, fd: i32) -> Option<FileDetails> {
+        let file_type = match self.files.lock().unwrap().get(&fd)? {
+            AnonFile::Pipe(_) => FileType::NamedPipe,
+            _ => FileType::File,
+        };
+        Some(FileDetails {
+            file_type,
+            is_readonly: false,
+            length: 0,
+            mode: 0o600,
+            uid: euid(),
+            gid: egid(),
+            device: 0,
+            links_count: 1,
+            times: None,
+        })
+    }
+
+    fn is_open(&self, fd: i32) -> bool {
+        self.files.lock().unwrap().contains_key(&fd)
+    }
+
+    fn get_length(&mut self, _fd: i32) -> u64 {
+        0
+    }
+
+    fn stream_position(&mut self, _fd: i32) -> Result<u64, ()> {
+        Ok(0)
+    }
+
+    fn seek(&mut self, _fd: i32, _pos: SeekFrom) -> Result<u64, ()> {
+        // ESPIPE
+        Err(())
+    }
+
+    fn read(&mut self, fd: i32, content: &mut [u8]) -> Result<u64, ()> {
+        let mut files = self.files.lock().unwrap();
+        let res = match files.get_mut(&fd) {
+            Some(AnonFile::EventFd(event_fd)) => {
+                if content.len() < 8 || event_fd.counter == 0 {
+                    return Err(());
+                }
+                let value = if event_fd.is_semaphore {
+                    1
+                } else {
+                    event_fd.counter
+                };
+                event_fd.counter -= value;
+                content[0..8].copy_from_slice(&pack_u64(value));
+                8
+            }
+            Some(AnonFile::TimerFd(timer_fd)) => {
+                timer_fd.update();
+                if content.len() < 8 || timer_fd.expirations == 0 {
+                    return Err(());
+                }
+                content[0..8].copy_from_slice(&pack_u64(timer_fd.expirations));
+                timer_fd.expirations = 0;
+                8
+            }
+            Some(AnonFile::SignalFd(signal_fd)) => {
+                let mut pending_signals = signal_fd.pending_signals.lock().unwrap();
+                let mut len = 0;
+                while len + SIGNALFD_SIGINFO_SIZE <= content.len() {
+                    if let Some(sig_info) = pending_signals.pop(signal_fd.mask) {
+                        let mut buf = Vec::new();
+                        buf.extend_from_slice(&pack_u32(sig_info.signo)); // ssi_signo
+                        buf.extend_from_slice(&pack_i32(0)); // ssi_errno
+                        buf.extend_from_slice(&pack_i32(sig_info.code)); // ssi_code
+                        buf.extend_from_slice(&pack_u32(sig_info.pid)); // ssi_pid
+                        buf.extend_from_slice(&pack_u32(sig_info.uid)); // ssi_uid
+                        buf.extend_from_slice(&pack_i32(0)); // ssi_fd
+                        buf.extend_from_slice(&pack_u32(sig_info.tid)); // ssi_tid
+                        buf.extend_from_slice(&pack_u32(0)); // ssi_band
+                        buf.extend_from_slice(&pack_u32(0)); // ssi_overrun
+                        buf.extend_from_slice(&pack_u32(0)); // ssi_trapno
+                        buf.extend_from_slice(&pack_i32(sig_info.status)); // ssi_status
+                        buf.resize(SIGNALFD_SIGINFO_SIZE, 0u8);
+                        content[len..len + SIGNALFD_SIGINFO_SIZE].copy_from_slice(&buf);
+                        len += SIGNALFD_SIGINFO_SIZE;
+                    } else {
+                        break;
+                    }
+                }
+                if len == 0 {
+                    return Err(());
+                }
+                len
+            }
+            Some(AnonFile::Inotify(inotify)) => inotify.read(content)?,
+            Some(AnonFile::IncSocket(socket)) => socket.recv(content)? as usize,
+            Some(AnonFile::Pipe(pipe_end)) => pipe_end.read(content)?,
+            _ => return Err(()),
+        };
+        drop(files);
+
+        self.notifier.notify();
+        Ok(res as u64)
+    }
+
+    fn write(&mut self, fd: i32, content: &[u8]) -> Result<u64, ()> {
+        let mut files = self.files.lock().unwrap();
+        let res = match files.get_mut(&fd) {
+            Some(AnonFile::EventFd(event_fd)) => {
+                if content.len() < 8 {
+                    return Err(());
+                }
+                let value = u64::from_le_bytes(content[0..8].try_into().unwrap());
+                match event_fd.counter.checked_add(value) {
+                    Some(counter) if counter < u64::MAX => event_fd.counter = counter,
+                    _ => return Err(()),
+                }
+                8
+            }
+            Some(AnonFile::IncSocket(socket)) => socket.send(content)? as usize,
+            Some(AnonFile::Pipe(pipe_end)) => pipe_end.write(content)?,
+            _ => return Err(()),
+        };
+        drop(files);
+
+        self.notifier.notify();
+        Ok(res as u64)
+    }
+
+    fn truncate(&mut self, _fd: i32, _length: u32) -> Result<(), ()> {
+        Err(())
+    }
+
+    fn poll(&mut self, fd: i32) -> PollEvents {
+        match self.files.lock().unwrap().get_mut(&fd) {
+            Some(AnonFile::EventFd(event_fd)) => {
+                let mut events = PollEvents::NONE;
+                if event_fd.counter > 0 {
+                    events |= PollEvents::IN;
+                }
+                if event_fd.counter < u64::MAX - 1 {
+                    events |= PollEvents::OUT;
+                }
+                events
+            }
+            Some(AnonFile::TimerFd(timer_fd)) => {
+                timer_fd.update();
+                if timer_fd.expirations > 0 {
+                    PollEvents::IN
+                } else {
+                    PollEvents::NONE
+                }
+            }
+            Some(AnonFile::SignalFd(signal_fd)) => {
+                if signal_fd
+                    .pending_signals
+                    .lock()
+                    .unwrap()
+                    .has_pending(signal_fd.mask)
+                {
+                    PollEvents::IN
+                } else {
+                    PollEvents::NONE
+                }
+            }
+            // readiness of epoll descriptor is checked by `MountFileSystem`,
+            // as it needs access to all the file descriptors
+            Some(AnonFile::Epoll(_)) => PollEvents::NONE,
+            Some(AnonFile::Inotify(inotify)) => {
+                if inotify.events.is_empty() {
+                    PollEvents::NONE
+                } else {
+                    PollEvents::IN
+                }
+            }
+            Some(AnonFile::IncSocket(socket)) => {
+                if socket.has_messages() {
+                    PollEvents::IN | PollEvents::OUT
+                } else {
+                    PollEvents::OUT
+                }
+            }
+            Some(AnonFile::Pipe(pipe_end)) => pipe_end.poll(),
+            None => PollEvents::NONE,
+        }
+    }
+
+    fn ioctl(
+        &mut self,
+        _unicorn: &mut Unicorn<Context>,
+        _fd: i32,
+        _request: u32,
+        _addr: u32,
+    ) -> i32 {
+        -1i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emulator::clock::CLOCK_MONOTONIC;
+
+    fn timer(next_expiration: Duration, interval: Duration) -> TimerFd {
+        TimerFd {
+            clock_id: CLOCK_MONOTONIC,
+            next_expiration: Some(next_expiration),
+            interval,
+            expirations: 0,
+        }
+    }
+
+    #[test]
+    fn test_timer_fd_one_shot() {
+        let mut timer_fd = timer(Duration::from_secs(10), Duration::ZERO);
+        timer_fd.update_at(Duration::from_secs(9));
+        assert_eq!(timer_fd.expirations, 0);
+        assert_eq!(timer_fd.next_expiration, Some(Duration::from_secs(10)));
+
+        timer_fd.update_at(Duration::from_secs(100));
+        assert_eq!(timer_fd.expirations, 1);
+        assert_eq!(timer_fd.next_expiration, None);
+
+        timer_fd.update_at(Duration::from_secs(200));
+        assert_eq!(timer_fd.expirations, 1);
+    }
+
+    #[test]
+    fn test_timer_fd_periodic() {
+        let mut timer_fd = timer(Duration::from_secs(10), Duration::from_millis(300));
+        timer_fd.update_at(Duration::from_secs(10));
+        assert_eq!(timer_fd.expirations, 1);
+        assert_eq!(
+            timer_fd.next_expiration,
+            Some(Duration::from_millis(10_300))
+        );
+
+        // 10.3, 10.6 and 10.9 s
+        timer_fd.update_at(Duration::from_millis(10_950));
+        assert_eq!(timer_fd.expirations, 4);
+        assert_eq!(
+            timer_fd.next_expiration,
+            Some(Duration::from_millis(11_200))
+        );
+    }
+
+    #[test]
+    fn test_timer_fd_many_expirations() {
+        // more expirations than fit into u32
+        let mut timer_fd = timer(Duration::ZERO, Duration::from_nanos(1));
+        let now = Duration::from_secs(10);
+        timer_fd.update_at(now);
+        assert_eq!(timer_fd.expirations, 10_000_000_001);
+        assert_eq!(
+            timer_fd.next_expiration,
+            Some(now + Duration::from_nanos(1))
+        );
+
+        // expiration of the realtime clock with a long interval
+        let start = Duration::from_secs(1_700_000_000);
+        let mut timer_fd = timer(start, Duration::from_secs(3600));
+        timer_fd.update_at(start + Duration::from_secs(3 * 3600 + 1));
+        assert_eq!(timer_fd.expirations, 4);
+        assert_eq!(
+            timer_fd.next_expiration,
+            Some(start + Duration::from_secs(4 * 3600))
+        );
+    }
+}
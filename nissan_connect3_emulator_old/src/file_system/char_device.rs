@@ -0,0 +1,118 @@
+use crate::emulator::context::Context;
+use crate::file_system::{OpenFileError, OpenFileFlags, PollEvents, SharedMemory};
+use std::collections::BTreeMap;
+use std::io::SeekFrom;
+use std::sync::{Arc, Mutex};
+use unicorn_engine::Unicorn;
+
+/// Returns device number encoded like `st_rdev` of `stat64` (`new_encode_dev()` in Linux).
+pub fn make_device_number(major: u32, minor: u32) -> u64 {
+    ((minor & 0xff) | (major << 8) | ((minor & !0xff) << 12)) as u64
+}
+
+/// Returns major and minor number of the encoded device number.
+pub fn split_device_number(device: u64) -> (u32, u32) {
+    let device = device as u32;
+    let major = (device & 0xfff00) >> 8;
+    let minor = (device & 0xff) | ((device >> 12) & 0xfff00);
+    (major, minor)
+}
+
+///
+/// Model of the character device (like the driver in the kernel).
+///
+/// All opened file descriptors of the device are served by the same model,
+/// `fd` identifies them if the device keeps state per opened file.
+///
+pub trait CharDevice: Send {
+    fn open(&mut self, _fd: i32, _flags: OpenFileFlags) -> Result<(), OpenFileError> {
+        Ok(())
+    }
+
+    fn close(&mut self, _fd: i32) {}
+
+    fn read(&mut self, fd: i32, content: &mut [u8]) -> Result<u64, ()>;
+
+    fn write(&mut self, fd: i32, content: &[u8]) -> Result<u64, ()>;
+
+    /// Moves position of the opened file, streams can not be positioned.
+    fn seek(&mut self, _fd: i32, _pos: SeekFrom) -> Result<u64, ()> {
+        // ESPIPE
+        Err(())
+    }
+
+    /// Returns result of the request (negative error number if it fails).
+    fn ioctl(
+        &mut self,
+        _unicorn: &mut Unicorn<Context>,
+        _fd: i32,
+        _request: u32,
+        _addr: u32,
+    ) -> i32 {
+        -25i32 // -ENOTTY
+    }
+
+    /// Returns memory of the device mapped by `mmap()` (at least `size` bytes long).
+    fn mmap(&mut self, _fd: i32, _size: usize) -> Option<Arc<SharedMemory>> {
+        None
+    }
+
+    fn poll(&mut self, _fd: i32) -> PollEvents {
+        PollEvents::IN | PollEvents::OUT
+    }
+}
+
+pub type SharedCharDevice = Arc<Mutex<dyn CharDevice>>;
+
+/// Device node created when the dev file system is mounted.
+pub struct CharDeviceNode {
+    /// Path relative to `/dev` mount point (like `/input/event0`).
+    pub path: String,
+    pub major: u32,
+    pub minor: u32,
+}
+
+///
+/// Device models by their major and minor numbers.
+///
+/// Device nodes (also the ones created by `mknod()`) are bound to the models
+/// when they are opened.
+///
+#[derive(Default)]
+pub struct CharDeviceRegistry {
+    devices: BTreeMap<(u32, u32), SharedCharDevice>,
+    nodes: Vec<CharDeviceNode>,
+}
+
+impl CharDeviceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the device model with its node (the returned handle allows
+    /// the emulator to access the model while it is used by the guest).
+    pub fn register<D: CharDevice + 'static>(
+        &mut self,
+        path: &str,
+        major: u32,
+        minor: u32,
+        device: D,
+    ) -> Arc<Mutex<D>> {
+        let device = Arc::new(Mutex::new(device));
+        self.devices.insert((major, minor), device.clone());
+        self.nodes.push(CharDeviceNode {
+            path: path.to_string(),
+            major,
+            minor,
+        });
+        device
+    }
+
+    pub fn get(&self, major: u32, minor: u32) -> Option<SharedCharDevice> {
+        self.devices.get(&(major, minor)).cloned()
+    }
+
+    pub fn nodes(&self) -> &[CharDeviceNode] {
+        &self.nodes
+    }
+}
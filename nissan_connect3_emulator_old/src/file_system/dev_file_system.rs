@@ -1,21 +1,29 @@
 use crate::emulator::context::Context;
 use crate::file_system::file_info::FileDetails;
 use crate::file_system::{
-    CloseFileError, FileSystem, FileSystemType, FileType, OpenFileError, OpenFileFlags,
-    TmpFileSystem,
+    make_device_number, split_device_number, CharDeviceRegistry, CloseFileError, FileSystem,
+    FileSystemType, FileType, OpenFileError, OpenFileFlags, PollEvents, SharedCharDevice,
+    SharedMemory, TmpFileSystem,
 };
+use std::collections::HashMap;
 use std::io::SeekFrom;
+use std::sync::Arc;
 use unicorn_engine::Unicorn;
 
 ///
 /// Dev file system.
 ///
+/// Device nodes are kept in the temp file system, opened nodes are served
+/// by the device models from the registry.
+///
 pub struct DevFileSystem {
     tmp_fs: TmpFileSystem,
+    registry: CharDeviceRegistry,
+    opened_devices: HashMap<i32, SharedCharDevice>,
 }
 
 impl DevFileSystem {
-    pub fn new() -> Self {
+    pub fn new(registry: CharDeviceRegistry) -> Self {
         let mut tmp_fs = TmpFileSystem::new();
         tmp_fs.insert_entry(
             "/iosc",
@@ -23,12 +31,20 @@ impl DevFileSystem {
             //"rw dualosoff=false".to_string().as_bytes().to_vec(),
             "????????????".to_string().as_bytes().to_vec(),
         );
-        tmp_fs.insert_entry(
-            "/errmem",
-            FileType::File,
-            "???????????????".to_string().as_bytes().to_vec(),
-        );
-        Self { tmp_fs }
+
+        for node in registry.nodes() {
+            tmp_fs.insert_device(
+                &node.path,
+                FileType::CharacterDevice,
+                make_device_number(node.major, node.minor),
+            );
+        }
+
+        Self {
+            tmp_fs,
+            registry,
+            opened_devices: HashMap::new(),
+        }
     }
 }
 
@@ -42,18 +58,15 @@ impl FileSystem for DevFileSystem {
     }
 
     fn exists(&mut self, file_path: &str) -> bool {
-        match file_path {
-            "/cmdline" => true,
-            _ => self.tmp_fs.exists(file_path),
-        }
+        self.tmp_fs.exists(file_path)
     }
 
-    fn mkdir(&mut self, _file_path: &str, _mode: u32) -> Result<(), OpenFileError> {
-        Err(OpenFileError::NoPermission)
+    fn mkdir(&mut self, file_path: &str, mode: u32) -> Result<(), OpenFileError> {
+        self.tmp_fs.mkdir(file_path, mode)
     }
 
-    fn read_dir(&mut self, _dir_path: &str) -> Result<Vec<String>, ()> {
-        Err(())
+    fn read_dir(&mut self, dir_path: &str) -> Result<Vec<String>, ()> {
+        self.tmp_fs.read_dir(dir_path)
     }
 
     fn open(
@@ -62,10 +75,36 @@ impl FileSystem for DevFileSystem {
         flags: OpenFileFlags,
         fd: i32,
     ) -> Result<(), OpenFileError> {
-        self.tmp_fs.open(file_path, flags, fd)
+        self.tmp_fs.open(file_path, flags, fd)?;
+
+        let file_details = self.tmp_fs.get_file_details(fd).unwrap();
+        if file_details.file_type == FileType::CharacterDevice {
+            let (major, minor) = split_device_number(file_details.device);
+            let res = match self.registry.get(major, minor) {
+                Some(device) => {
+                    let res = device.lock().unwrap().open(fd, flags);
+                    res.map(|_| device)
+                }
+                // the node has no device model
+                None => Err(OpenFileError::NoSuchDevice),
+            };
+            match res {
+                Ok(device) => {
+                    self.opened_devices.insert(fd, device);
+                }
+                Err(err) => {
+                    self.tmp_fs.close(fd).unwrap();
+                    return Err(err);
+                }
+            }
+        }
+        Ok(())
     }
 
     fn close(&mut self, fd: i32) -> Result<(), CloseFileError> {
+        if let Some(device) = self.opened_devices.remove(&fd) {
+            device.lock().unwrap().close(fd);
+        }
         self.tmp_fs.close(fd)
     }
 
@@ -73,8 +112,29 @@ impl FileSystem for DevFileSystem {
         Err(OpenFileError::NoPermission)
     }
 
-    fn unlink(&mut self, _file_path: &str) -> Result<(), OpenFileError> {
-        Err(OpenFileError::NoPermission)
+    fn unlink(&mut self, file_path: &str) -> Result<(), OpenFileError> {
+        self.tmp_fs.unlink(file_path)
+    }
+
+    fn read_link(&mut self, file_path: &str) -> Option<String> {
+        self.tmp_fs.read_link(file_path)
+    }
+
+    fn mknod(
+        &mut self,
+        file_path: &str,
+        file_type: FileType,
+        device: u64,
+    ) -> Result<(), OpenFileError> {
+        self.tmp_fs.mknod(file_path, file_type, device)
+    }
+
+    fn set_mode(&mut self, file_path: &str, mode: u32) -> Result<(), OpenFileError> {
+        self.tmp_fs.set_mode(file_path, mode)
+    }
+
+    fn set_owner(&mut self, file_path: &str, uid: u32, gid: u32) -> Result<(), OpenFileError> {
+        self.tmp_fs.set_owner(file_path, uid, gid)
     }
 
     fn get_file_details(&mut self, fd: i32) -> Option<FileDetails> {
@@ -94,28 +154,46 @@ impl FileSystem for DevFileSystem {
     }
 
     fn seek(&mut self, fd: i32, pos: SeekFrom) -> Result<u64, ()> {
-        self.tmp_fs.seek(fd, pos)
+        match self.opened_devices.get(&fd) {
+            Some(device) => device.lock().unwrap().seek(fd, pos),
+            None => self.tmp_fs.seek(fd, pos),
+        }
     }
 
     fn read(&mut self, fd: i32, content: &mut [u8]) -> Result<u64, ()> {
-        self.tmp_fs.read(fd, content)
+        match self.opened_devices.get(&fd) {
+            Some(device) => device.lock().unwrap().read(fd, content),
+            None => self.tmp_fs.read(fd, content),
+        }
     }
 
     fn write(&mut self, fd: i32, content: &[u8]) -> Result<u64, ()> {
-        self.tmp_fs.write(fd, content)
+        match self.opened_devices.get(&fd) {
+            Some(device) => device.lock().unwrap().write(fd, content),
+            None => self.tmp_fs.write(fd, content),
+        }
     }
 
     fn truncate(&mut self, _fd: i32, _length: u32) -> Result<(), ()> {
         Err(())
     }
 
-    fn ioctl(
-        &mut self,
-        _unicorn: &mut Unicorn<Context>,
-        _fd: i32,
-        _request: u32,
-        _addr: u32,
-    ) -> i32 {
-        0i32
+    fn poll(&mut self, fd: i32) -> PollEvents {
+        match self.opened_devices.get(&fd) {
+            Some(device) => device.lock().unwrap().poll(fd),
+            None => self.tmp_fs.poll(fd),
+        }
+    }
+
+    fn ioctl(&mut self, unicorn: &mut Unicorn<Context>, fd: i32, request: u32, addr: u32) -> i32 {
+        match self.opened_devices.get(&fd) {
+            Some(device) => device.lock().unwrap().ioctl(unicorn, fd, request, addr),
+            // placeholder files are not devices
+            None => -25i32, // -ENOTTY
+        }
+    }
+
+    fn mmap(&mut self, fd: i32, size: usize) -> Option<Arc<SharedMemory>> {
+        self.opened_devices.get(&fd)?.lock().unwrap().mmap(fd, size)
     }
 }
@@ -0,0 +1,39 @@
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+///
+/// Wakes up threads waiting in blocking calls (`read()`, `poll()`, `epoll_wait()`)
+/// when the state of any file descriptor changes.
+///
+pub struct FdNotifier {
+    generation: Mutex<u64>,
+    changed: Condvar,
+}
+
+impl FdNotifier {
+    pub fn new() -> Self {
+        Self {
+            generation: Mutex::new(0),
+            changed: Condvar::new(),
+        }
+    }
+
+    /// Returns the counter of state changes. It should be read before checking
+    /// the file descriptors and passed to `wait()`, so no notification is lost.
+    pub fn generation(&self) -> u64 {
+        *self.generation.lock().unwrap()
+    }
+
+    pub fn notify(&self) {
+        *self.generation.lock().unwrap() += 1;
+        self.changed.notify_all();
+    }
+
+    /// Waits until `notify()` is called after `generation` was read or the `timeout` elapses.
+    pub fn wait(&self, generation: u64, timeout: Duration) {
+        let current = self.generation.lock().unwrap();
+        if *current == generation {
+            let _ = self.changed.wait_timeout(current, timeout).unwrap();
+        }
+    }
+}
@@ -1,3 +1,6 @@
+use crate::file_system::AccessMode;
+use std::time::SystemTime;
+
 /// Managed by MountFileSystem
 pub struct FileInfo {
     pub file_details: FileDetails,
@@ -24,4 +27,99 @@ pub struct FileDetails {
     pub file_type: FileType,
     pub is_readonly: bool,
     pub length: u64,
+    /// Permission bits (including set-user-ID, set-group-ID and sticky bits).
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+    /// Device number of the device file (`st_rdev`).
+    pub device: u64,
+    /// Number of hard links (`st_nlink`).
+    pub links_count: u32,
+    /// Timestamps of the file (`None` if the file system does not track them).
+    pub times: Option<FileTimes>,
+}
+
+/// Timestamps of the file (`st_atime`, `st_mtime` and `st_ctime`).
+#[derive(Debug, Clone, Copy)]
+pub struct FileTimes {
+    pub accessed: SystemTime,
+    pub modified: SystemTime,
+    pub changed: SystemTime,
+}
+
+impl FileTimes {
+    pub fn now() -> Self {
+        let now = SystemTime::now();
+        Self {
+            accessed: now,
+            modified: now,
+            changed: now,
+        }
+    }
+}
+
+impl FileDetails {
+    /// Checks access of the user to the file (like `generic_permission()` in Linux).
+    pub fn is_accessible(&self, uid: u32, gid: u32, access: AccessMode) -> bool {
+        if uid == 0 {
+            // root can execute the file only if anyone can
+            return !access.contains(AccessMode::EXECUTE)
+                || self.file_type == FileType::Directory
+                || self.mode & 0o111 != 0;
+        }
+
+        let permissions = if self.uid == uid {
+            self.mode >> 6
+        } else if self.gid == gid {
+            self.mode >> 3
+        } else {
+            self.mode
+        };
+        AccessMode::from_bits_truncate(permissions).contains(access)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details(file_type: FileType, mode: u32) -> FileDetails {
+        FileDetails {
+            file_type,
+            is_readonly: false,
+            length: 0,
+            mode,
+            uid: 1000,
+            gid: 1001,
+            device: 0,
+            links_count: 1,
+            times: None,
+        }
+    }
+
+    #[test]
+    fn test_owner_group_other() {
+        let file = details(FileType::File, 0o640);
+        let read_write = AccessMode::READ | AccessMode::WRITE;
+        assert!(file.is_accessible(1000, 1001, read_write));
+        assert!(!file.is_accessible(1000, 1001, AccessMode::EXECUTE));
+        assert!(file.is_accessible(2000, 1001, AccessMode::READ));
+        assert!(!file.is_accessible(2000, 1001, AccessMode::WRITE));
+        assert!(!file.is_accessible(2000, 2001, AccessMode::READ));
+        assert!(file.is_accessible(2000, 2001, AccessMode::NONE));
+
+        // the owner class is used even if the group or others have more rights
+        let file = details(FileType::File, 0o077);
+        assert!(!file.is_accessible(1000, 1001, AccessMode::READ));
+        assert!(file.is_accessible(2000, 2001, AccessMode::READ));
+    }
+
+    #[test]
+    fn test_root() {
+        let file = details(FileType::File, 0o600);
+        assert!(file.is_accessible(0, 0, AccessMode::READ | AccessMode::WRITE));
+        assert!(!file.is_accessible(0, 0, AccessMode::EXECUTE));
+        assert!(details(FileType::File, 0o001).is_accessible(0, 0, AccessMode::EXECUTE));
+        assert!(details(FileType::Directory, 0o000).is_accessible(0, 0, AccessMode::EXECUTE));
+    }
 }
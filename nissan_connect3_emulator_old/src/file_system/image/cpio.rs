@@ -0,0 +1,326 @@
+use crate::file_system::image::{
+    file_type_from_mode, invalid_data, make_device, normalize_path, read_bytes_at, ImageEntry,
+    ImageReader, ImageSource,
+};
+use crate::file_system::FileType;
+use std::collections::HashMap;
+use std::io;
+use std::io::{Seek, SeekFrom};
+
+// header of the "new" portable format (hexadecimal fields)
+const NEWC_HEADER_SIZE: u64 = 110;
+// header of the "old" portable format (octal fields)
+const ODC_HEADER_SIZE: u64 = 76;
+
+const TRAILER_NAME: &str = "TRAILER!!!";
+
+///
+/// Reader of the cpio archive (`newc`, `crc` and `odc` formats, e.g. initramfs).
+///
+pub struct CpioReader {
+    source: Box<dyn ImageSource>,
+}
+
+struct CpioHeader {
+    inode: u64,
+    mode: u32,
+    uid: u32,
+    gid: u32,
+    device: u64,
+    name_size: u64,
+    file_size: u64,
+}
+
+impl CpioReader {
+    pub fn new(source: Box<dyn ImageSource>) -> Self {
+        Self { source }
+    }
+
+    pub fn is_valid(source: &mut Box<dyn ImageSource>) -> io::Result<bool> {
+        match read_bytes_at(source.as_mut(), 0, 6) {
+            Ok(magic) => {
+                Ok(&magic[..] == b"070701" || &magic[..] == b"070702" || &magic[..] == b"070707")
+            }
+            Err(_) => Ok(false),
+        }
+    }
+
+    /// Reads header at `pos`, returns it with the header size and whether the data are aligned.
+    fn read_header(&mut self, pos: u64) -> io::Result<(CpioHeader, u64, bool)> {
+        let magic = read_bytes_at(self.source.as_mut(), pos, 6)?;
+        if &magic[..] == b"070707" {
+            let header = read_bytes_at(self.source.as_mut(), pos, ODC_HEADER_SIZE as usize)?;
+            let field = |start: usize, len: usize| parse_number(&header[start..start + len], 8);
+            let rdev = field(42, 6) as u32;
+            Ok((
+                CpioHeader {
+                    inode: (field(6, 6) << 32) | field(12, 6),
+                    mode: field(18, 6) as u32,
+                    uid: field(24, 6) as u32,
+                    gid: field(30, 6) as u32,
+                    // old encoding of the device number
+                    device: make_device(rdev >> 8, rdev & 0xff),
+                    name_size: field(59, 6),
+                    file_size: field(65, 11),
+                },
+                ODC_HEADER_SIZE,
+                false,
+            ))
+        } else if &magic[..] == b"070701" || &magic[..] == b"070702" {
+            let header = read_bytes_at(self.source.as_mut(), pos, NEWC_HEADER_SIZE as usize)?;
+            let field = |index: usize| parse_number(&header[6 + index * 8..14 + index * 8], 16);
+            Ok((
+                CpioHeader {
+                    // device of the file is part of its identity (for hard links)
+                    inode: (field(7) << 40) | (field(8) << 32) | field(0),
+                    mode: field(1) as u32,
+                    uid: field(2) as u32,
+                    gid: field(3) as u32,
+                    device: make_device(field(9) as u32, field(10) as u32),
+                    name_size: field(11),
+                    file_size: field(6),
+                },
+                NEWC_HEADER_SIZE,
+                true,
+            ))
+        } else {
+            Err(invalid_data("invalid cpio header"))
+        }
+    }
+}
+
+impl ImageReader for CpioReader {
+    fn read_entries(&mut self) -> io::Result<Vec<(String, ImageEntry)>> {
+        let image_length = self.source.seek(SeekFrom::End(0))?;
+
+        let mut entries: Vec<(String, ImageEntry)> = Vec::new();
+        // files with more links store the content only in the last entry
+        let mut hard_links: HashMap<u64, Vec<usize>> = HashMap::new();
+
+        let mut pos = 0;
+        while pos < image_length {
+            let (header, header_size, is_aligned) = self.read_header(pos)?;
+            let align = |value: u64| {
+                if is_aligned {
+                    (value + 3) & !3
+                } else {
+                    value
+                }
+            };
+
+            let name = read_bytes_at(
+                self.source.as_mut(),
+                pos + header_size,
+                header.name_size as usize,
+            )?;
+            let name = String::from_utf8_lossy(&name)
+                .trim_end_matches('\0')
+                .to_string();
+            let data_pos = align(pos + header_size + header.name_size);
+            pos = align(data_pos + header.file_size);
+
+            if name == TRAILER_NAME {
+                break;
+            }
+
+            let path = normalize_path(&name);
+            let file_type = match file_type_from_mode(header.mode) {
+                Some(file_type) => file_type,
+                None => {
+                    log::warn!("Unsupported cpio entry mode {:#o} ({})", header.mode, path);
+                    continue;
+                }
+            };
+
+            let link_target = if file_type == FileType::Link {
+                let target =
+                    read_bytes_at(self.source.as_mut(), data_pos, header.file_size as usize)?;
+                Some(String::from_utf8_lossy(&target).to_string())
+            } else {
+                None
+            };
+
+            if file_type == FileType::File {
+                hard_links
+                    .entry(header.inode)
+                    .or_default()
+                    .push(entries.len());
+            }
+
+            entries.push((
+                path,
+                ImageEntry {
+                    size: if file_type == FileType::File {
+                        header.file_size
+                    } else {
+                        0
+                    },
+                    device: match file_type {
+                        FileType::CharacterDevice | FileType::BlockDevice => header.device,
+                        _ => 0,
+                    },
+                    file_type,
+                    mode: header.mode & 0o7777,
+                    uid: header.uid,
+                    gid: header.gid,
+                    link_target,
+                    location: data_pos,
+                },
+            ));
+        }
+
+        for indexes in hard_links.values().filter(|indexes| indexes.len() > 1) {
+            if let Some(&index) = indexes.iter().find(|&&index| entries[index].1.size > 0) {
+                let (size, location) = (entries[index].1.size, entries[index].1.location);
+                for &index in indexes {
+                    entries[index].1.size = size;
+                    entries[index].1.location = location;
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn read_content(&mut self, entry: &ImageEntry) -> io::Result<Vec<u8>> {
+        if entry.file_type != FileType::File {
+            return Err(invalid_data("not a regular file"));
+        }
+        read_bytes_at(self.source.as_mut(), entry.location, entry.size as usize)
+    }
+}
+
+fn parse_number(field: &[u8], radix: u32) -> u64 {
+    u64::from_str_radix(&String::from_utf8_lossy(field), radix).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Appends the `newc` entry (`device` is the major and minor number of the device node).
+    fn append_newc(
+        archive: &mut Vec<u8>,
+        name: &str,
+        inode: u32,
+        mode: u32,
+        device: (u32, u32),
+        data: &[u8],
+    ) {
+        let fields = [
+            inode,
+            mode,
+            1000,
+            1001,
+            1,
+            0,
+            data.len() as u32,
+            8,
+            1,
+            device.0,
+            device.1,
+            name.len() as u32 + 1,
+            0,
+        ];
+        archive.extend(b"070701");
+        for field in fields {
+            archive.extend(format!("{:08x}", field).as_bytes());
+        }
+        archive.extend(name.as_bytes());
+        archive.push(0);
+        archive.resize((archive.len() + 3) & !3, 0);
+        archive.extend(data);
+        archive.resize((archive.len() + 3) & !3, 0);
+    }
+
+    fn open(archive: Vec<u8>) -> CpioReader {
+        let mut source: Box<dyn ImageSource> = Box::new(io::Cursor::new(archive));
+        assert!(CpioReader::is_valid(&mut source).unwrap());
+        CpioReader::new(source)
+    }
+
+    #[test]
+    fn test_read_newc() {
+        let mut archive = Vec::new();
+        append_newc(&mut archive, ".", 1, 0o040755, (0, 0), &[]);
+        append_newc(&mut archive, "init", 2, 0o100750, (0, 0), b"#!/bin/sh\n");
+        append_newc(&mut archive, "sbin/init", 3, 0o120777, (0, 0), b"../init");
+        append_newc(&mut archive, "dev/ttyS0", 4, 0o020620, (4, 64), &[]);
+        // hard links, the content is stored with the last one
+        append_newc(&mut archive, "bin/a", 5, 0o100755, (0, 0), &[]);
+        append_newc(&mut archive, "bin/b", 5, 0o100755, (0, 0), b"busybox");
+        append_newc(&mut archive, TRAILER_NAME, 0, 0, (0, 0), &[]);
+        // padding of the block
+        archive.resize(archive.len() + 512, 0);
+
+        let mut reader = open(archive);
+        let entries: HashMap<String, ImageEntry> =
+            reader.read_entries().unwrap().into_iter().collect();
+        assert_eq!(entries.len(), 6);
+
+        assert_eq!(entries["/"].file_type, FileType::Directory);
+        let init = &entries["/init"];
+        assert_eq!(init.file_type, FileType::File);
+        assert_eq!((init.mode, init.uid, init.gid), (0o750, 1000, 1001));
+        assert_eq!(reader.read_content(init).unwrap(), b"#!/bin/sh\n");
+
+        let link = &entries["/sbin/init"];
+        assert_eq!(link.file_type, FileType::Link);
+        assert_eq!(link.link_target.as_deref(), Some("../init"));
+
+        let tty = &entries["/dev/ttyS0"];
+        assert_eq!(tty.file_type, FileType::CharacterDevice);
+        assert_eq!(tty.device, make_device(4, 64));
+
+        assert_eq!(reader.read_content(&entries["/bin/a"]).unwrap(), b"busybox");
+        assert_eq!(reader.read_content(&entries["/bin/b"]).unwrap(), b"busybox");
+    }
+
+    #[test]
+    fn test_read_odc() {
+        let mut archive = Vec::new();
+        for (name, mode, rdev, data) in [
+            ("etc/motd", 0o100644, 0, &b"hello"[..]),
+            ("dev/null", 0o020666, 0x0103, &b""[..]),
+            (TRAILER_NAME, 0, 0, &b""[..]),
+        ] {
+            archive.extend(
+                format!(
+                    "070707{:06o}{:06o}{:06o}{:06o}{:06o}{:06o}{:06o}{:011o}{:06o}{:011o}",
+                    0,
+                    1,
+                    mode,
+                    0,
+                    0,
+                    1,
+                    rdev,
+                    0,
+                    name.len() + 1,
+                    data.len()
+                )
+                .as_bytes(),
+            );
+            archive.extend(name.as_bytes());
+            archive.push(0);
+            archive.extend(data);
+        }
+
+        let mut reader = open(archive);
+        let entries: HashMap<String, ImageEntry> =
+            reader.read_entries().unwrap().into_iter().collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            reader.read_content(&entries["/etc/motd"]).unwrap(),
+            b"hello"
+        );
+        assert_eq!(entries["/dev/null"].device, make_device(1, 3));
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        let mut archive = Vec::new();
+        append_newc(&mut archive, "init", 2, 0o100750, (0, 0), b"data");
+        archive.extend(b"garbage");
+        assert!(open(archive).read_entries().is_err());
+    }
+}
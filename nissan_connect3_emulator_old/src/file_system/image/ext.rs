@@ -0,0 +1,542 @@
+use crate::file_system::image::{
+    file_type_from_mode, invalid_data, make_device, read_bytes_at, ImageEntry, ImageReader,
+    ImageSource,
+};
+use crate::file_system::FileType;
+use byteorder::{ByteOrder, LittleEndian};
+use std::collections::HashSet;
+use std::io;
+
+const SUPER_BLOCK_OFFSET: u64 = 1024;
+const EXT_MAGIC: u16 = 0xef53;
+const ROOT_INODE: u32 = 2;
+
+const INCOMPAT_64BIT: u32 = 0x80;
+
+const INODE_FLAG_EXTENTS: u32 = 0x80000;
+const INODE_FLAG_INLINE_DATA: u32 = 0x10000000;
+
+const EXTENT_MAGIC: u16 = 0xf30a;
+
+// `i_block` field (block map, extent tree, inline data or target of the fast symbolic link)
+const INODE_BLOCK_OFFSET: usize = 0x28;
+const INODE_BLOCK_SIZE: usize = 60;
+
+///
+/// Reader of the ext2, ext3 and ext4 file system image.
+///
+pub struct ExtReader {
+    source: Box<dyn ImageSource>,
+    block_size: u64,
+    inodes_per_group: u32,
+    inode_size: u64,
+    // location of the inode table of every block group
+    inode_tables: Vec<u64>,
+}
+
+struct ExtInode {
+    mode: u32,
+    uid: u32,
+    gid: u32,
+    size: u64,
+    flags: u32,
+    block: Vec<u8>,
+}
+
+impl ExtReader {
+    pub fn new(mut source: Box<dyn ImageSource>) -> io::Result<Self> {
+        let super_block = read_bytes_at(source.as_mut(), SUPER_BLOCK_OFFSET, 1024)?;
+
+        let inodes_count = LittleEndian::read_u32(&super_block[0..]);
+        let first_data_block = LittleEndian::read_u32(&super_block[20..]) as u64;
+        let block_size = 1024u64 << LittleEndian::read_u32(&super_block[24..]);
+        let inodes_per_group = LittleEndian::read_u32(&super_block[40..]);
+        let revision = LittleEndian::read_u32(&super_block[76..]);
+        let inode_size = if revision >= 1 {
+            LittleEndian::read_u16(&super_block[88..]) as u64
+        } else {
+            128
+        };
+        let incompat_features = LittleEndian::read_u32(&super_block[96..]);
+        let descriptor_size = if incompat_features & INCOMPAT_64BIT != 0 {
+            LittleEndian::read_u16(&super_block[254..]) as u64
+        } else {
+            32
+        };
+
+        if inodes_per_group == 0 {
+            return Err(invalid_data("invalid ext super block"));
+        }
+        let groups_count = inodes_count.div_ceil(inodes_per_group);
+
+        // group descriptors are in the block after the super block
+        let descriptors = read_bytes_at(
+            source.as_mut(),
+            (first_data_block + 1) * block_size,
+            (groups_count as u64 * descriptor_size) as usize,
+        )?;
+        let inode_tables = descriptors
+            .chunks(descriptor_size as usize)
+            .map(|descriptor| {
+                let mut block = LittleEndian::read_u32(&descriptor[8..]) as u64;
+                if descriptor_size >= 64 {
+                    block |= (LittleEndian::read_u32(&descriptor[0x28..]) as u64) << 32;
+                }
+                block * block_size
+            })
+            .collect();
+
+        Ok(Self {
+            source,
+            block_size,
+            inodes_per_group,
+            inode_size,
+            inode_tables,
+        })
+    }
+
+    pub fn is_valid(source: &mut Box<dyn ImageSource>) -> io::Result<bool> {
+        match read_bytes_at(source.as_mut(), SUPER_BLOCK_OFFSET + 56, 2) {
+            Ok(magic) => Ok(LittleEndian::read_u16(&magic) == EXT_MAGIC),
+            Err(_) => Ok(false),
+        }
+    }
+
+    fn read_inode(&mut self, inode_number: u32) -> io::Result<ExtInode> {
+        let group = ((inode_number - 1) / self.inodes_per_group) as usize;
+        let index = ((inode_number - 1) % self.inodes_per_group) as u64;
+        let table = *self
+            .inode_tables
+            .get(group)
+            .ok_or_else(|| invalid_data("invalid inode number"))?;
+        let inode = read_bytes_at(
+            self.source.as_mut(),
+            table + index * self.inode_size,
+            self.inode_size.min(256) as usize,
+        )?;
+
+        Ok(ExtInode {
+            mode: LittleEndian::read_u16(&inode[0..]) as u32,
+            uid: LittleEndian::read_u16(&inode[0x02..]) as u32
+                | (LittleEndian::read_u16(&inode[0x78..]) as u32) << 16,
+            gid: LittleEndian::read_u16(&inode[0x18..]) as u32
+                | (LittleEndian::read_u16(&inode[0x7a..]) as u32) << 16,
+            size: LittleEndian::read_u32(&inode[4..]) as u64
+                | (LittleEndian::read_u32(&inode[0x6c..]) as u64) << 32,
+            flags: LittleEndian::read_u32(&inode[0x20..]),
+            block: inode[INODE_BLOCK_OFFSET..INODE_BLOCK_OFFSET + INODE_BLOCK_SIZE].to_vec(),
+        })
+    }
+
+    /// Returns physical blocks of the file content (`0` for the holes).
+    fn read_block_list(&mut self, inode: &ExtInode) -> io::Result<Vec<u64>> {
+        let blocks_count = inode.size.div_ceil(self.block_size) as usize;
+        let mut blocks = vec![0u64; blocks_count];
+
+        if inode.flags & INODE_FLAG_EXTENTS != 0 {
+            self.read_extent_node(&inode.block, &mut blocks, 0)?;
+        } else {
+            // 12 direct blocks, then single, double and triple indirect blocks
+            let mut logical = 0usize;
+            for index in 0..15 {
+                if logical >= blocks_count {
+                    break;
+                }
+                let block = LittleEndian::read_u32(&inode.block[index * 4..]) as u64;
+                let depth = index.saturating_sub(11) as u32;
+                self.read_indirect_block(block, depth, &mut blocks, &mut logical)?;
+            }
+        }
+
+        Ok(blocks)
+    }
+
+    fn read_indirect_block(
+        &mut self,
+        block: u64,
+        depth: u32,
+        blocks: &mut Vec<u64>,
+        logical: &mut usize,
+    ) -> io::Result<()> {
+        let pointers_per_block = (self.block_size / 4) as usize;
+        if depth == 0 {
+            if *logical < blocks.len() {
+                blocks[*logical] = block;
+            }
+            *logical += 1;
+            return Ok(());
+        }
+
+        if block == 0 {
+            // hole covering all blocks referenced by the indirect block
+            *logical += pointers_per_block.pow(depth);
+            return Ok(());
+        }
+
+        let data = read_bytes_at(
+            self.source.as_mut(),
+            block * self.block_size,
+            self.block_size as usize,
+        )?;
+        for index in 0..pointers_per_block {
+            if *logical >= blocks.len() {
+                break;
+            }
+            let block = LittleEndian::read_u32(&data[index * 4..]) as u64;
+            self.read_indirect_block(block, depth - 1, blocks, logical)?;
+        }
+        Ok(())
+    }
+
+    fn read_extent_node(
+        &mut self,
+        node: &[u8],
+        blocks: &mut Vec<u64>,
+        level: u32,
+    ) -> io::Result<()> {
+        if LittleEndian::read_u16(&node[0..]) != EXTENT_MAGIC || level > 5 {
+            return Err(invalid_data("invalid extent tree"));
+        }
+        let entries = LittleEndian::read_u16(&node[2..]) as usize;
+        let depth = LittleEndian::read_u16(&node[6..]);
+
+        for index in 0..entries {
+            let entry = &node[12 + index * 12..24 + index * 12];
+            if depth == 0 {
+                let logical = LittleEndian::read_u32(&entry[0..]) as usize;
+                let mut length = LittleEndian::read_u16(&entry[4..]) as usize;
+                if length > 32768 {
+                    // uninitialized extent is read as zeros
+                    continue;
+                }
+                let start = (LittleEndian::read_u16(&entry[6..]) as u64) << 32
+                    | LittleEndian::read_u32(&entry[8..]) as u64;
+                length = length.min(blocks.len().saturating_sub(logical));
+                for offset in 0..length {
+                    blocks[logical + offset] = start + offset as u64;
+                }
+            } else {
+                let leaf = (LittleEndian::read_u16(&entry[8..]) as u64) << 32
+                    | LittleEndian::read_u32(&entry[4..]) as u64;
+                let child = read_bytes_at(
+                    self.source.as_mut(),
+                    leaf * self.block_size,
+                    self.block_size as usize,
+                )?;
+                self.read_extent_node(&child, blocks, level + 1)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_inode_content(&mut self, inode: &ExtInode) -> io::Result<Vec<u8>> {
+        if inode.flags & INODE_FLAG_INLINE_DATA != 0 {
+            // only the part stored in `i_block` is supported
+            let length = (inode.size as usize).min(INODE_BLOCK_SIZE);
+            return Ok(inode.block[..length].to_vec());
+        }
+
+        let blocks = self.read_block_list(inode)?;
+        let mut data = Vec::with_capacity(blocks.len() * self.block_size as usize);
+        for block in blocks {
+            if block == 0 {
+                data.resize(data.len() + self.block_size as usize, 0);
+            } else {
+                data.extend(read_bytes_at(
+                    self.source.as_mut(),
+                    block * self.block_size,
+                    self.block_size as usize,
+                )?);
+            }
+        }
+        data.truncate(inode.size as usize);
+        Ok(data)
+    }
+
+    fn read_directory(
+        &mut self,
+        dir_path: &str,
+        inode_number: u32,
+        entries: &mut Vec<(String, ImageEntry)>,
+        visited: &mut HashSet<u32>,
+    ) -> io::Result<()> {
+        let inode = self.read_inode(inode_number)?;
+        let data = self.read_inode_content(&inode)?;
+
+        let mut pos = 0;
+        while pos + 8 <= data.len() {
+            let child_inode = LittleEndian::read_u32(&data[pos..]);
+            let record_length = LittleEndian::read_u16(&data[pos + 4..]) as usize;
+            let name_length = data[pos + 6] as usize;
+            if record_length < 8 || pos + 8 + name_length > data.len() {
+                break;
+            }
+            let name = String::from_utf8_lossy(&data[pos + 8..pos + 8 + name_length]).to_string();
+            pos += record_length;
+
+            // unused entries (also hash tree nodes and checksums)
+            if child_inode == 0 || name == "." || name == ".." {
+                continue;
+            }
+
+            let path = if dir_path == "/" {
+                format!("/{}", name)
+            } else {
+                format!("{}/{}", dir_path, name)
+            };
+            let child = self.read_inode(child_inode)?;
+            let file_type = match file_type_from_mode(child.mode) {
+                Some(file_type) => file_type,
+                None => {
+                    log::warn!("Unsupported ext inode mode {:#o} ({})", child.mode, path);
+                    continue;
+                }
+            };
+
+            let link_target = if file_type == FileType::Link {
+                let target = if child.size < INODE_BLOCK_SIZE as u64
+                    && child.flags & (INODE_FLAG_EXTENTS | INODE_FLAG_INLINE_DATA) == 0
+                {
+                    // fast symbolic link stored in the inode
+                    child.block[..child.size as usize].to_vec()
+                } else {
+                    self.read_inode_content(&child)?
+                };
+                Some(String::from_utf8_lossy(&target).to_string())
+            } else {
+                None
+            };
+
+            let device = match file_type {
+                FileType::CharacterDevice | FileType::BlockDevice => {
+                    let old_device = LittleEndian::read_u32(&child.block[0..]);
+                    if old_device != 0 {
+                        make_device((old_device >> 8) & 0xff, old_device & 0xff)
+                    } else {
+                        let new_device = LittleEndian::read_u32(&child.block[4..]);
+                        make_device(
+                            (new_device & 0xfff00) >> 8,
+                            (new_device & 0xff) | ((new_device >> 12) & 0xfff00),
+                        )
+                    }
+                }
+                _ => 0,
+            };
+
+            entries.push((
+                path.clone(),
+                ImageEntry {
+                    size: if file_type == FileType::File {
+                        child.size
+                    } else {
+                        0
+                    },
+                    mode: child.mode & 0o7777,
+                    uid: child.uid,
+                    gid: child.gid,
+                    device,
+                    link_target,
+                    location: child_inode as u64,
+                    file_type: file_type.clone(),
+                },
+            ));
+
+            if file_type == FileType::Directory && visited.insert(child_inode) {
+                self.read_directory(&path, child_inode, entries, visited)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ImageReader for ExtReader {
+    fn read_entries(&mut self) -> io::Result<Vec<(String, ImageEntry)>> {
+        let root = self.read_inode(ROOT_INODE)?;
+        let mut entries = vec![(
+            "/".to_string(),
+            ImageEntry {
+                file_type: FileType::Directory,
+                mode: root.mode & 0o7777,
+                uid: root.uid,
+                gid: root.gid,
+                size: 0,
+                device: 0,
+                link_target: None,
+                location: ROOT_INODE as u64,
+            },
+        )];
+        let mut visited = HashSet::from([ROOT_INODE]);
+        self.read_directory("/", ROOT_INODE, &mut entries, &mut visited)?;
+        Ok(entries)
+    }
+
+    fn read_content(&mut self, entry: &ImageEntry) -> io::Result<Vec<u8>> {
+        let inode = self.read_inode(entry.location as u32)?;
+        self.read_inode_content(&inode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const BLOCK_SIZE: usize = 1024;
+    const INODE_TABLE_BLOCK: usize = 5;
+    const INODE_SIZE: usize = 128;
+
+    /// Minimal ext2 image (one block group) with 1 KiB blocks.
+    struct ExtImageBuilder {
+        image: Vec<u8>,
+    }
+
+    impl ExtImageBuilder {
+        fn new() -> Self {
+            let mut image = vec![0u8; 32 * BLOCK_SIZE];
+            let super_block = &mut image[SUPER_BLOCK_OFFSET as usize..];
+            LittleEndian::write_u32(&mut super_block[0..], 16);
+            LittleEndian::write_u32(&mut super_block[20..], 1);
+            LittleEndian::write_u32(&mut super_block[24..], 0);
+            LittleEndian::write_u32(&mut super_block[40..], 16);
+            LittleEndian::write_u16(&mut super_block[56..], EXT_MAGIC);
+            LittleEndian::write_u32(&mut super_block[76..], 1);
+            LittleEndian::write_u16(&mut super_block[88..], INODE_SIZE as u16);
+            // group descriptor
+            LittleEndian::write_u32(&mut image[2 * BLOCK_SIZE + 8..], INODE_TABLE_BLOCK as u32);
+            Self { image }
+        }
+
+        fn inode(&mut self, number: usize, mode: u32, size: u64, flags: u32, block: &[u8]) {
+            let inode =
+                &mut self.image[INODE_TABLE_BLOCK * BLOCK_SIZE + (number - 1) * INODE_SIZE..];
+            LittleEndian::write_u16(&mut inode[0..], mode as u16);
+            LittleEndian::write_u16(&mut inode[0x02..], 1000);
+            LittleEndian::write_u32(&mut inode[4..], size as u32);
+            LittleEndian::write_u16(&mut inode[0x18..], 1001);
+            LittleEndian::write_u32(&mut inode[0x20..], flags);
+            inode[INODE_BLOCK_OFFSET..INODE_BLOCK_OFFSET + block.len()].copy_from_slice(block);
+        }
+
+        fn block(&mut self, number: usize, data: &[u8]) {
+            self.image[number * BLOCK_SIZE..number * BLOCK_SIZE + data.len()].copy_from_slice(data);
+        }
+
+        fn build(self) -> ExtReader {
+            let mut source: Box<dyn ImageSource> = Box::new(io::Cursor::new(self.image));
+            assert!(ExtReader::is_valid(&mut source).unwrap());
+            ExtReader::new(source).unwrap()
+        }
+    }
+
+    fn directory(entries: &[(u32, &str)]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for (index, (inode, name)) in entries.iter().enumerate() {
+            let record_length = if index + 1 == entries.len() {
+                BLOCK_SIZE - data.len()
+            } else {
+                (8 + name.len() + 3) & !3
+            };
+            let start = data.len();
+            data.extend(inode.to_le_bytes());
+            data.extend((record_length as u16).to_le_bytes());
+            data.extend([name.len() as u8, 0]);
+            data.extend(name.as_bytes());
+            data.resize(start + record_length, 0);
+        }
+        data
+    }
+
+    fn block_map(blocks: &[u32]) -> Vec<u8> {
+        blocks
+            .iter()
+            .flat_map(|block| block.to_le_bytes())
+            .collect()
+    }
+
+    fn extents(start: u32, length: u16) -> Vec<u8> {
+        let mut node = Vec::new();
+        for value in [EXTENT_MAGIC, 1, 4, 0, 0, 0] {
+            node.extend(value.to_le_bytes());
+        }
+        node.extend(0u32.to_le_bytes());
+        node.extend(length.to_le_bytes());
+        node.extend(0u16.to_le_bytes());
+        node.extend(start.to_le_bytes());
+        node
+    }
+
+    #[test]
+    fn test_read_entries() {
+        let mut builder = ExtImageBuilder::new();
+        builder.inode(2, 0o040755, BLOCK_SIZE as u64, 0, &block_map(&[10]));
+        builder.block(
+            10,
+            &directory(&[
+                (2, "."),
+                (2, ".."),
+                (12, "etc"),
+                (13, "init"),
+                (14, "console"),
+                (15, "data.bin"),
+            ]),
+        );
+        builder.inode(12, 0o040700, BLOCK_SIZE as u64, 0, &block_map(&[11]));
+        builder.block(11, &directory(&[(12, "."), (2, ".."), (16, "version")]));
+        builder.inode(13, 0o120777, 10, 0, b"/sbin/init");
+        builder.inode(14, 0o020600, 0, 0, &0x0501u32.to_le_bytes());
+        builder.inode(15, 0o100644, 1500, INODE_FLAG_EXTENTS, &extents(20, 2));
+        builder.block(20, &[0xaa; BLOCK_SIZE]);
+        builder.block(21, &[0xbb; BLOCK_SIZE]);
+        builder.inode(16, 0o100444, 6, 0, &block_map(&[12]));
+        builder.block(12, b"1.2.3\n");
+
+        let mut reader = builder.build();
+        let entries: HashMap<String, ImageEntry> =
+            reader.read_entries().unwrap().into_iter().collect();
+        assert_eq!(entries.len(), 6);
+
+        let root = &entries["/"];
+        assert_eq!((root.mode, root.uid, root.gid), (0o755, 1000, 1001));
+        assert_eq!(entries["/etc"].file_type, FileType::Directory);
+        assert_eq!(entries["/etc"].mode, 0o700);
+
+        let version = &entries["/etc/version"];
+        assert_eq!(version.file_type, FileType::File);
+        assert_eq!(reader.read_content(version).unwrap(), b"1.2.3\n");
+
+        let init = &entries["/init"];
+        assert_eq!(init.file_type, FileType::Link);
+        assert_eq!(init.link_target.as_deref(), Some("/sbin/init"));
+
+        let console = &entries["/console"];
+        assert_eq!(console.file_type, FileType::CharacterDevice);
+        assert_eq!(console.device, make_device(5, 1));
+
+        let data = reader.read_content(&entries["/data.bin"]).unwrap();
+        assert_eq!(data.len(), 1500);
+        assert!(data[..BLOCK_SIZE].iter().all(|b| *b == 0xaa));
+        assert!(data[BLOCK_SIZE..].iter().all(|b| *b == 0xbb));
+    }
+
+    #[test]
+    fn test_sparse_file() {
+        let mut builder = ExtImageBuilder::new();
+        builder.inode(2, 0o040755, BLOCK_SIZE as u64, 0, &block_map(&[10]));
+        builder.block(10, &directory(&[(2, "."), (2, ".."), (12, "sparse")]));
+        builder.inode(
+            12,
+            0o100644,
+            3 * BLOCK_SIZE as u64,
+            0,
+            &block_map(&[0, 11, 0]),
+        );
+        builder.block(11, &[1; BLOCK_SIZE]);
+
+        let mut reader = builder.build();
+        let entries = reader.read_entries().unwrap();
+        let data = reader.read_content(&entries[1].1).unwrap();
+        assert_eq!(data.len(), 3 * BLOCK_SIZE);
+        assert_eq!(data.iter().map(|b| *b as usize).sum::<usize>(), BLOCK_SIZE);
+        assert_eq!(data[BLOCK_SIZE], 1);
+    }
+}
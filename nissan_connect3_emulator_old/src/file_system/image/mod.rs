@@ -0,0 +1,314 @@
+// readers of the file system images (squashfs, ext2/3/4, cpio and tar archives)
+// and of the directories of the extracted partitions
+
+use crate::file_system::FileType;
+use byteorder::{ByteOrder, LittleEndian};
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+mod cpio;
+mod ext;
+mod partition_dir;
+mod squashfs;
+mod tar;
+
+pub use partition_dir::*;
+
+// compressed images can be compressed again (e.g. by the update package)
+const MAX_COMPRESSION_LAYERS: u32 = 2;
+// length of the header needed to detect the compression
+const COMPRESSION_MAGIC_SIZE: usize = 13;
+// zlib has only 2 byte header, so the beginning of the stream is unpacked to confirm it
+const ZLIB_PROBE_SIZE: usize = 0x10000;
+
+/// File (or directory) stored in the image.
+#[derive(Debug, Clone)]
+pub struct ImageEntry {
+    pub file_type: FileType,
+    /// Permission bits (including set-user-ID, set-group-ID and sticky bits).
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+    pub size: u64,
+    /// Device number (encoded like `st_rdev`) of the device node.
+    pub device: u64,
+    pub link_target: Option<String>,
+    /// Position of the content in the image (meaning depends on the image format).
+    pub location: u64,
+}
+
+/// Format specific reader of the image.
+pub trait ImageReader {
+    /// Reads all entries of the image, paths are absolute (the image root is "/").
+    fn read_entries(&mut self) -> io::Result<Vec<(String, ImageEntry)>>;
+
+    /// Reads content of the regular file.
+    fn read_content(&mut self, entry: &ImageEntry) -> io::Result<Vec<u8>>;
+}
+
+/// Image data (file on the host or decompressed archive in memory).
+pub trait ImageSource: Read + Seek + Send + Sync {}
+
+impl<T: Read + Seek + Send + Sync> ImageSource for T {}
+
+/// Opens the image, the format is detected from its content.
+pub fn open_image(image_path: &Path) -> io::Result<Box<dyn ImageReader + Send + Sync>> {
+    open_image_source(Box::new(BufReader::new(File::open(image_path)?)))
+}
+
+/// Opens the image from the source, compressed images are unpacked to the memory.
+fn open_image_source(
+    mut source: Box<dyn ImageSource>,
+) -> io::Result<Box<dyn ImageReader + Send + Sync>> {
+    for _ in 0..=MAX_COMPRESSION_LAYERS {
+        if squashfs::SquashFsReader::is_valid(&mut source)? {
+            return Ok(Box::new(squashfs::SquashFsReader::new(source)?));
+        } else if ext::ExtReader::is_valid(&mut source)? {
+            return Ok(Box::new(ext::ExtReader::new(source)?));
+        } else if cpio::CpioReader::is_valid(&mut source)? {
+            return Ok(Box::new(cpio::CpioReader::new(source)));
+        } else if tar::TarReader::is_valid(&mut source)? {
+            return Ok(Box::new(tar::TarReader::new(source)));
+        }
+
+        // e.g. `.tar.gz`, `.cpio.gz` or `.squashfs.xz`
+        source = match decompress_source(source)? {
+            Some(source) => source,
+            None => break,
+        };
+    }
+    Err(invalid_data("unknown image format"))
+}
+
+/// Checks whether the source is a known image or a compressed stream (without unpacking it).
+fn is_image_or_compressed(source: &mut Box<dyn ImageSource>) -> io::Result<bool> {
+    Ok(detect_compression(source)?.is_some() || is_image(source)?)
+}
+
+fn is_image(source: &mut Box<dyn ImageSource>) -> io::Result<bool> {
+    Ok(squashfs::SquashFsReader::is_valid(source)?
+        || ext::ExtReader::is_valid(source)?
+        || cpio::CpioReader::is_valid(source)?
+        || tar::TarReader::is_valid(source)?)
+}
+
+enum Compression {
+    Gzip,
+    Xz,
+    Lzma,
+    Zlib,
+}
+
+/// Detects compression of the stream, zlib is accepted only if its beginning
+/// unpacks to an image or to another compressed stream.
+fn detect_compression(source: &mut Box<dyn ImageSource>) -> io::Result<Option<Compression>> {
+    let compression = match read_bytes_at(source.as_mut(), 0, COMPRESSION_MAGIC_SIZE) {
+        Ok(magic) => compression_of(&magic),
+        Err(_) => None,
+    };
+    match compression {
+        Some(Compression::Zlib) if !is_zlib_stream(source)? => Ok(None),
+        compression => Ok(compression),
+    }
+}
+
+/// Unpacks the beginning of the (possible) zlib stream and checks its content.
+fn is_zlib_stream(source: &mut Box<dyn ImageSource>) -> io::Result<bool> {
+    let mut input = Vec::new();
+    source.seek(SeekFrom::Start(0))?;
+    source
+        .as_mut()
+        .take(ZLIB_PROBE_SIZE as u64)
+        .read_to_end(&mut input)?;
+
+    let mut output = Vec::with_capacity(ZLIB_PROBE_SIZE);
+    let mut decompress = flate2::Decompress::new(true);
+    if decompress
+        .decompress_vec(&input, &mut output, flate2::FlushDecompress::None)
+        .is_err()
+        || output.is_empty()
+    {
+        return Ok(false);
+    }
+
+    let mut content: Box<dyn ImageSource> = Box::new(io::Cursor::new(output));
+    let is_compressed = match read_bytes_at(content.as_mut(), 0, COMPRESSION_MAGIC_SIZE) {
+        // nested zlib streams are not expected
+        Ok(magic) => !matches!(compression_of(&magic), None | Some(Compression::Zlib)),
+        Err(_) => false,
+    };
+    Ok(is_compressed || is_image(&mut content)?)
+}
+
+/// Detects compression from the beginning of the stream (zlib only by its header).
+fn compression_of(magic: &[u8]) -> Option<Compression> {
+    if magic[0..2] == [0x1f, 0x8b] {
+        Some(Compression::Gzip)
+    } else if magic[0..6] == [0xfd, b'7', b'z', b'X', b'Z', 0x00] {
+        Some(Compression::Xz)
+    } else if is_lzma_header(magic) {
+        Some(Compression::Lzma)
+    } else if is_zlib_header(magic) {
+        Some(Compression::Zlib)
+    } else {
+        None
+    }
+}
+
+/// Checks header of the zlib stream (deflate with at most 32 KiB window, no preset dictionary).
+fn is_zlib_header(header: &[u8]) -> bool {
+    header[0] & 0x0f == 8
+        && header[0] >> 4 <= 7
+        && header[1] & 0x20 == 0
+        && (header[0] as u16 * 256 + header[1] as u16).is_multiple_of(31)
+}
+
+/// Checks header of the `.lzma` stream (properties, dictionary size and unpacked size).
+fn is_lzma_header(header: &[u8]) -> bool {
+    let dictionary_size = LittleEndian::read_u32(&header[1..]);
+    let unpacked_size = LittleEndian::read_u64(&header[5..]);
+    header[0] == 0x5d
+        && dictionary_size.is_power_of_two()
+        && (unpacked_size == u64::MAX || unpacked_size < 1 << 40)
+}
+
+/// Decompresses the gzip, xz, lzma or zlib stream, returns `None` for the other data.
+fn decompress_source(mut source: Box<dyn ImageSource>) -> io::Result<Option<Box<dyn ImageSource>>> {
+    let compression = detect_compression(&mut source)?;
+    source.seek(SeekFrom::Start(0))?;
+
+    let mut data = Vec::new();
+    match compression {
+        Some(Compression::Gzip) => {
+            flate2::read::MultiGzDecoder::new(source).read_to_end(&mut data)?;
+        }
+        Some(Compression::Xz) => lzma_rs::xz_decompress(&mut BufReader::new(source), &mut data)
+            .map_err(|e| invalid_data(&format!("xz: {:?}", e)))?,
+        Some(Compression::Lzma) => lzma_rs::lzma_decompress(&mut BufReader::new(source), &mut data)
+            .map_err(|e| invalid_data(&format!("lzma: {:?}", e)))?,
+        Some(Compression::Zlib) => {
+            flate2::read::ZlibDecoder::new(source).read_to_end(&mut data)?;
+        }
+        None => return Ok(None),
+    }
+    Ok(Some(Box::new(io::Cursor::new(data))))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn read_bytes_at(source: &mut dyn ImageSource, pos: u64, len: usize) -> io::Result<Vec<u8>> {
+    let mut data = vec![0u8; len];
+    source.seek(SeekFrom::Start(pos))?;
+    source.read_exact(&mut data)?;
+    Ok(data)
+}
+
+/// Returns file type from `st_mode` like value.
+fn file_type_from_mode(mode: u32) -> Option<FileType> {
+    match mode & 0o170000 {
+        0o100000 => Some(FileType::File),
+        0o120000 => Some(FileType::Link),
+        0o040000 => Some(FileType::Directory),
+        0o140000 => Some(FileType::Socket),
+        0o060000 => Some(FileType::BlockDevice),
+        0o020000 => Some(FileType::CharacterDevice),
+        0o010000 => Some(FileType::NamedPipe),
+        _ => None,
+    }
+}
+
+/// Encodes the device number (like `new_encode_dev()` in Linux).
+fn make_device(major: u32, minor: u32) -> u64 {
+    ((minor & 0xff) | (major << 8) | ((minor & !0xff) << 12)) as u64
+}
+
+/// Converts path from the archive (e.g. `./etc/passwd`) to the absolute one.
+fn normalize_path(path: &str) -> String {
+    let parts: Vec<&str> = path
+        .split('/')
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect();
+    format!("/{}", parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn zlib(data: &[u8]) -> Vec<u8> {
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn tar_archive() -> Vec<u8> {
+        let mut archive = vec![0u8; 3 * 512];
+        archive[0..4].copy_from_slice(b"file");
+        archive[124..136].copy_from_slice(b"00000000002\0");
+        archive[156] = b'0';
+        archive[257..263].copy_from_slice(b"ustar\0");
+        archive[512..514].copy_from_slice(b"ok");
+        archive
+    }
+
+    fn source(data: Vec<u8>) -> Box<dyn ImageSource> {
+        Box::new(io::Cursor::new(data))
+    }
+
+    #[test]
+    fn test_zlib_image() {
+        let mut compressed = source(zlib(&tar_archive()));
+        assert!(matches!(
+            detect_compression(&mut compressed).unwrap(),
+            Some(Compression::Zlib)
+        ));
+        assert!(is_image_or_compressed(&mut compressed).unwrap());
+
+        let mut reader = open_image_source(compressed).unwrap();
+        let entries = reader.read_entries().unwrap();
+        assert_eq!(entries[0].0, "/file");
+        assert_eq!(reader.read_content(&entries[0].1).unwrap(), b"ok");
+    }
+
+    #[test]
+    fn test_zlib_false_positive() {
+        // valid zlib header followed by data that do not unpack
+        let mut data = vec![0x78, 0x9c];
+        data.extend((0..4096u32).map(|i| (i.wrapping_mul(2654435761) >> 24) as u8 | 0x07));
+        assert!(is_zlib_header(&data));
+        assert!(detect_compression(&mut source(data.clone()))
+            .unwrap()
+            .is_none());
+        assert!(!is_image_or_compressed(&mut source(data.clone())).unwrap());
+        assert!(open_image_source(source(data)).is_err());
+
+        // zlib stream with other content is not an image
+        let text = zlib(b"plain text, not an image");
+        assert!(detect_compression(&mut source(text)).unwrap().is_none());
+
+        // bytes that only pass the header checksum
+        assert!(!is_zlib_header(&[0x88, 0x1c]));
+        assert!(!is_zlib_header(&[0x78, 0xbb]));
+    }
+
+    #[test]
+    fn test_compression_magic() {
+        let mut magic = vec![0u8; COMPRESSION_MAGIC_SIZE];
+        magic[0..2].copy_from_slice(&[0x1f, 0x8b]);
+        assert!(matches!(compression_of(&magic), Some(Compression::Gzip)));
+        magic[0..6].copy_from_slice(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]);
+        assert!(matches!(compression_of(&magic), Some(Compression::Xz)));
+        magic.copy_from_slice(&[
+            0x5d, 0, 0, 0x80, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        ]);
+        assert!(matches!(compression_of(&magic), Some(Compression::Lzma)));
+        magic.fill(0);
+        assert!(compression_of(&magic).is_none());
+    }
+}
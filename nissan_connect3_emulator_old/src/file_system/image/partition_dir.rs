@@ -0,0 +1,97 @@
+use crate::file_system::image::{
+    is_image_or_compressed, open_image_source, ImageReader, ImageSource,
+};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+// suffixes removed from the partition file names (e.g. `rootfs.squashfs.xz` is `rootfs`)
+const PARTITION_SUFFIXES: [&str; 11] = [
+    ".gz",
+    ".xz",
+    ".lzma",
+    ".bin",
+    ".dat",
+    ".img",
+    ".squashfs",
+    ".ext4",
+    ".ext3",
+    ".ext2",
+    ".tar",
+];
+
+///
+/// Directory of the partitions extracted from the update package (e.g. of the LCN2kai
+/// dealer update medium). Every partition is a (compressed) image named after the partition,
+/// the other files (descriptions, checksums and signatures) are ignored. The container
+/// format of the update package itself is not parsed.
+///
+pub struct PartitionDirectory {
+    // partition name -> path of the partition image
+    partitions: BTreeMap<String, PathBuf>,
+}
+
+impl PartitionDirectory {
+    pub fn open(dir_path: &Path) -> io::Result<Self> {
+        let mut partitions = BTreeMap::new();
+        find_partitions(dir_path, dir_path, &mut partitions)?;
+        if partitions.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no partition found in {}", dir_path.display()),
+            ));
+        }
+
+        for (name, path) in &partitions {
+            log::info!("Partition {} ({})", name, path.display());
+        }
+        Ok(Self { partitions })
+    }
+
+    /// Opens image of the partition (decompressed to the memory if needed).
+    pub fn open_partition(&self, name: &str) -> io::Result<Box<dyn ImageReader + Send + Sync>> {
+        let path = self.partitions.get(name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("unknown partition {}", name),
+            )
+        })?;
+        open_image_source(Box::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+fn find_partitions(
+    root_path: &Path,
+    dir_path: &Path,
+    partitions: &mut BTreeMap<String, PathBuf>,
+) -> io::Result<()> {
+    let mut dir_entries = std::fs::read_dir(dir_path)?.collect::<Result<Vec<_>, _>>()?;
+    dir_entries.sort_by_key(|dir_entry| dir_entry.file_name());
+
+    for dir_entry in dir_entries {
+        let path = dir_entry.path();
+        if path.is_dir() {
+            find_partitions(root_path, &path, partitions)?;
+            continue;
+        }
+
+        let mut source: Box<dyn ImageSource> = Box::new(BufReader::new(File::open(&path)?));
+        if !is_image_or_compressed(&mut source)? {
+            log::trace!("File {} skipped (not a partition)", path.display());
+            continue;
+        }
+
+        let mut name = path
+            .strip_prefix(root_path)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .to_string();
+        while let Some(suffix) = PARTITION_SUFFIXES.iter().find(|s| name.ends_with(*s)) {
+            name.truncate(name.len() - suffix.len());
+        }
+        partitions.insert(name, path);
+    }
+    Ok(())
+}
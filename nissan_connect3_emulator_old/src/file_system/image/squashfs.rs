@@ -0,0 +1,608 @@
+use crate::file_system::image::{
+    invalid_data, make_device, read_bytes_at, ImageEntry, ImageReader, ImageSource,
+};
+use crate::file_system::FileType;
+use byteorder::{ByteOrder, LittleEndian};
+use std::collections::HashMap;
+use std::io;
+use std::io::Read;
+
+const SQUASHFS_MAGIC: u32 = 0x73717368;
+const SUPER_BLOCK_SIZE: usize = 96;
+
+const METADATA_SIZE: usize = 8192;
+const METADATA_UNCOMPRESSED: u16 = 0x8000;
+const DATA_BLOCK_UNCOMPRESSED: u32 = 0x1000000;
+const NO_FRAGMENT: u32 = 0xffffffff;
+// size of the fragment table entry
+const FRAGMENT_ENTRY_SIZE: usize = 16;
+
+const COMPRESSION_GZIP: u16 = 1;
+const COMPRESSION_LZMA: u16 = 2;
+const COMPRESSION_XZ: u16 = 4;
+
+///
+/// Reader of the squashfs 4.0 image (gzip, lzma and xz compressed).
+///
+pub struct SquashFsReader {
+    source: Box<dyn ImageSource>,
+    compression: u16,
+    block_size: u32,
+    root_inode: u64,
+    inode_table_start: u64,
+    directory_table_start: u64,
+    fragment_table_start: u64,
+    // user and group ids referenced by the inodes
+    ids: Vec<u32>,
+    // decompressed metadata blocks with position of the next block
+    metadata_cache: HashMap<u64, (Vec<u8>, u64)>,
+}
+
+/// Position in the metadata table (block relative to the table and offset in the block).
+#[derive(Clone, Copy)]
+struct MetadataPos {
+    block: u64,
+    offset: usize,
+}
+
+struct SquashFsInode {
+    file_type: FileType,
+    mode: u32,
+    uid: u32,
+    gid: u32,
+    size: u64,
+    device: u64,
+    link_target: Option<String>,
+    // directory listing
+    dir_start_block: u32,
+    dir_offset: u16,
+}
+
+impl SquashFsReader {
+    pub fn new(mut source: Box<dyn ImageSource>) -> io::Result<Self> {
+        let super_block = read_bytes_at(source.as_mut(), 0, SUPER_BLOCK_SIZE)?;
+
+        let compression = LittleEndian::read_u16(&super_block[20..]);
+        let version_major = LittleEndian::read_u16(&super_block[28..]);
+        if version_major != 4 {
+            return Err(invalid_data("unsupported squashfs version"));
+        }
+        if ![COMPRESSION_GZIP, COMPRESSION_LZMA, COMPRESSION_XZ].contains(&compression) {
+            return Err(invalid_data("unsupported squashfs compression"));
+        }
+
+        let mut reader = Self {
+            source,
+            compression,
+            block_size: LittleEndian::read_u32(&super_block[12..]),
+            root_inode: LittleEndian::read_u64(&super_block[32..]),
+            inode_table_start: LittleEndian::read_u64(&super_block[64..]),
+            directory_table_start: LittleEndian::read_u64(&super_block[72..]),
+            fragment_table_start: LittleEndian::read_u64(&super_block[80..]),
+            ids: vec![],
+            metadata_cache: HashMap::new(),
+        };
+
+        let ids_count = LittleEndian::read_u16(&super_block[26..]) as usize;
+        let id_table_start = LittleEndian::read_u64(&super_block[48..]);
+        reader.ids = reader.read_id_table(id_table_start, ids_count)?;
+        Ok(reader)
+    }
+
+    /// Reads the id table (list of metadata blocks with 32-bit ids).
+    fn read_id_table(&mut self, id_table_start: u64, ids_count: usize) -> io::Result<Vec<u32>> {
+        let blocks_count = (ids_count * 4).div_ceil(METADATA_SIZE);
+        let block_list = read_bytes_at(self.source.as_mut(), id_table_start, blocks_count * 8)?;
+
+        let mut ids = Vec::with_capacity(ids_count);
+        for block_pos in block_list.chunks(8) {
+            let (block, _) = self.read_metadata_block(LittleEndian::read_u64(block_pos))?;
+            ids.extend(block.chunks(4).map(LittleEndian::read_u32));
+        }
+        ids.truncate(ids_count);
+        Ok(ids)
+    }
+
+    pub fn is_valid(source: &mut Box<dyn ImageSource>) -> io::Result<bool> {
+        match read_bytes_at(source.as_mut(), 0, 4) {
+            Ok(magic) => Ok(LittleEndian::read_u32(&magic) == SQUASHFS_MAGIC),
+            Err(_) => Ok(false),
+        }
+    }
+
+    fn decompress(&self, data: &[u8], max_size: usize) -> io::Result<Vec<u8>> {
+        let mut output = Vec::with_capacity(max_size);
+        match self.compression {
+            COMPRESSION_GZIP => {
+                flate2::read::ZlibDecoder::new(data).read_to_end(&mut output)?;
+            }
+            COMPRESSION_LZMA => {
+                lzma_rs::lzma_decompress(&mut io::BufReader::new(data), &mut output)
+                    .map_err(|e| invalid_data(&format!("lzma: {:?}", e)))?
+            }
+            COMPRESSION_XZ => lzma_rs::xz_decompress(&mut io::BufReader::new(data), &mut output)
+                .map_err(|e| invalid_data(&format!("xz: {:?}", e)))?,
+            _ => unreachable!(),
+        }
+        Ok(output)
+    }
+
+    /// Reads metadata block at the absolute position, returns it with position of the next block.
+    fn read_metadata_block(&mut self, pos: u64) -> io::Result<(Vec<u8>, u64)> {
+        if let Some(block) = self.metadata_cache.get(&pos) {
+            return Ok(block.clone());
+        }
+
+        let header = LittleEndian::read_u16(&read_bytes_at(self.source.as_mut(), pos, 2)?);
+        let size = (header & !METADATA_UNCOMPRESSED) as usize;
+        let data = read_bytes_at(self.source.as_mut(), pos + 2, size)?;
+        let data = if header & METADATA_UNCOMPRESSED != 0 {
+            data
+        } else {
+            self.decompress(&data, METADATA_SIZE)?
+        };
+
+        let block = (data, pos + 2 + size as u64);
+        self.metadata_cache.insert(pos, block.clone());
+        Ok(block)
+    }
+
+    /// Reads `len` bytes of the metadata table (the position is moved behind them).
+    fn read_metadata(
+        &mut self,
+        table_start: u64,
+        pos: &mut MetadataPos,
+        len: usize,
+    ) -> io::Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(len);
+        while data.len() < len {
+            let (block, next_block) = self.read_metadata_block(table_start + pos.block)?;
+            if pos.offset >= block.len() {
+                if next_block == table_start + pos.block {
+                    return Err(invalid_data("invalid metadata"));
+                }
+                pos.block = next_block - table_start;
+                pos.offset = 0;
+                continue;
+            }
+            let count = (len - data.len()).min(block.len() - pos.offset);
+            data.extend_from_slice(&block[pos.offset..pos.offset + count]);
+            pos.offset += count;
+        }
+        Ok(data)
+    }
+
+    fn read_inode(&mut self, inode_ref: u64) -> io::Result<SquashFsInode> {
+        let mut pos = MetadataPos {
+            block: inode_ref >> 16,
+            offset: (inode_ref & 0xffff) as usize,
+        };
+        let table = self.inode_table_start;
+        let header = self.read_metadata(table, &mut pos, 16)?;
+        let inode_type = LittleEndian::read_u16(&header[0..]);
+        let mode = LittleEndian::read_u16(&header[2..]) as u32 & 0o7777;
+        // owner and group are indexes to the id table
+        let uid_index = LittleEndian::read_u16(&header[4..]) as usize;
+        let gid_index = LittleEndian::read_u16(&header[6..]) as usize;
+
+        let mut inode = SquashFsInode {
+            file_type: FileType::File,
+            mode,
+            uid: self.ids.get(uid_index).cloned().unwrap_or(0),
+            gid: self.ids.get(gid_index).cloned().unwrap_or(0),
+            size: 0,
+            device: 0,
+            link_target: None,
+            dir_start_block: 0,
+            dir_offset: 0,
+        };
+
+        match inode_type {
+            // basic directory
+            1 => {
+                let data = self.read_metadata(table, &mut pos, 16)?;
+                inode.file_type = FileType::Directory;
+                inode.dir_start_block = LittleEndian::read_u32(&data[0..]);
+                inode.size = LittleEndian::read_u16(&data[8..]) as u64;
+                inode.dir_offset = LittleEndian::read_u16(&data[10..]);
+            }
+            // extended directory
+            8 => {
+                let data = self.read_metadata(table, &mut pos, 24)?;
+                inode.file_type = FileType::Directory;
+                inode.size = LittleEndian::read_u32(&data[4..]) as u64;
+                inode.dir_start_block = LittleEndian::read_u32(&data[8..]);
+                inode.dir_offset = LittleEndian::read_u16(&data[18..]);
+            }
+            // basic file
+            2 => {
+                let data = self.read_metadata(table, &mut pos, 16)?;
+                inode.size = LittleEndian::read_u32(&data[12..]) as u64;
+            }
+            // extended file
+            9 => {
+                let data = self.read_metadata(table, &mut pos, 40)?;
+                inode.size = LittleEndian::read_u64(&data[8..]);
+            }
+            // symbolic link
+            3 | 10 => {
+                let data = self.read_metadata(table, &mut pos, 8)?;
+                let target_size = LittleEndian::read_u32(&data[4..]) as usize;
+                let target = self.read_metadata(table, &mut pos, target_size)?;
+                inode.file_type = FileType::Link;
+                inode.link_target = Some(String::from_utf8_lossy(&target).to_string());
+            }
+            // device
+            4 | 5 | 11 | 12 => {
+                let data = self.read_metadata(table, &mut pos, 8)?;
+                let device = LittleEndian::read_u32(&data[4..]);
+                inode.file_type = if inode_type == 4 || inode_type == 11 {
+                    FileType::BlockDevice
+                } else {
+                    FileType::CharacterDevice
+                };
+                inode.device = make_device(
+                    (device >> 8) & 0xfff,
+                    (device & 0xff) | ((device >> 12) & 0xfff00),
+                );
+            }
+            6 | 13 => inode.file_type = FileType::NamedPipe,
+            7 | 14 => inode.file_type = FileType::Socket,
+            _ => return Err(invalid_data("invalid squashfs inode type")),
+        }
+
+        Ok(inode)
+    }
+
+    fn read_directory(
+        &mut self,
+        dir_path: &str,
+        dir: &SquashFsInode,
+        entries: &mut Vec<(String, ImageEntry)>,
+    ) -> io::Result<()> {
+        let table = self.directory_table_start;
+        let mut pos = MetadataPos {
+            block: dir.dir_start_block as u64,
+            offset: dir.dir_offset as usize,
+        };
+        // size includes "." and ".." which are not stored
+        let mut remaining = dir.size.saturating_sub(3) as usize;
+
+        while remaining >= 12 {
+            let header = self.read_metadata(table, &mut pos, 12)?;
+            remaining -= 12;
+            let count = LittleEndian::read_u32(&header[0..]) + 1;
+            let start_block = LittleEndian::read_u32(&header[4..]) as u64;
+
+            for _ in 0..count {
+                let entry = self.read_metadata(table, &mut pos, 8)?;
+                let offset = LittleEndian::read_u16(&entry[0..]) as u64;
+                let name_size = LittleEndian::read_u16(&entry[6..]) as usize + 1;
+                let name = self.read_metadata(table, &mut pos, name_size)?;
+                remaining = remaining.saturating_sub(8 + name_size);
+
+                let name = String::from_utf8_lossy(&name).to_string();
+                let path = if dir_path == "/" {
+                    format!("/{}", name)
+                } else {
+                    format!("{}/{}", dir_path, name)
+                };
+
+                let inode_ref = (start_block << 16) | offset;
+                let inode = self.read_inode(inode_ref)?;
+                entries.push((
+                    path.clone(),
+                    ImageEntry {
+                        file_type: inode.file_type.clone(),
+                        mode: inode.mode,
+                        uid: inode.uid,
+                        gid: inode.gid,
+                        size: if inode.file_type == FileType::File {
+                            inode.size
+                        } else {
+                            0
+                        },
+                        device: inode.device,
+                        link_target: inode.link_target.clone(),
+                        location: inode_ref,
+                    },
+                ));
+
+                if inode.file_type == FileType::Directory {
+                    self.read_directory(&path, &inode, entries)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the file tail stored in the fragment block.
+    fn read_fragment(&mut self, index: u32, offset: usize, len: usize) -> io::Result<Vec<u8>> {
+        // the fragment table is indexed by the table of metadata block positions
+        let entries_per_block = METADATA_SIZE / FRAGMENT_ENTRY_SIZE;
+        let lookup_pos =
+            self.fragment_table_start + (index as usize / entries_per_block) as u64 * 8;
+        let metadata_block =
+            LittleEndian::read_u64(&read_bytes_at(self.source.as_mut(), lookup_pos, 8)?);
+        let (block, _) = self.read_metadata_block(metadata_block)?;
+        let entry_pos = (index as usize % entries_per_block) * FRAGMENT_ENTRY_SIZE;
+        let entry = block
+            .get(entry_pos..entry_pos + FRAGMENT_ENTRY_SIZE)
+            .ok_or_else(|| invalid_data("invalid fragment entry"))?;
+
+        let start = LittleEndian::read_u64(&entry[0..]);
+        let size = LittleEndian::read_u32(&entry[8..]);
+        let data = self.read_data_block(start, size)?;
+        data.get(offset..offset + len)
+            .map(|data| data.to_vec())
+            .ok_or_else(|| invalid_data("invalid fragment"))
+    }
+
+    fn read_data_block(&mut self, pos: u64, size: u32) -> io::Result<Vec<u8>> {
+        let stored_size = size & !DATA_BLOCK_UNCOMPRESSED;
+        let data = read_bytes_at(self.source.as_mut(), pos, stored_size as usize)?;
+        if size & DATA_BLOCK_UNCOMPRESSED != 0 {
+            Ok(data)
+        } else {
+            self.decompress(&data, self.block_size as usize)
+        }
+    }
+}
+
+impl ImageReader for SquashFsReader {
+    fn read_entries(&mut self) -> io::Result<Vec<(String, ImageEntry)>> {
+        let root = self.read_inode(self.root_inode)?;
+        let mut entries = vec![(
+            "/".to_string(),
+            ImageEntry {
+                file_type: FileType::Directory,
+                mode: root.mode,
+                uid: root.uid,
+                gid: root.gid,
+                size: 0,
+                device: 0,
+                link_target: None,
+                location: self.root_inode,
+            },
+        )];
+        self.read_directory("/", &root, &mut entries)?;
+        Ok(entries)
+    }
+
+    fn read_content(&mut self, entry: &ImageEntry) -> io::Result<Vec<u8>> {
+        let mut pos = MetadataPos {
+            block: entry.location >> 16,
+            offset: (entry.location & 0xffff) as usize,
+        };
+        let table = self.inode_table_start;
+        let header = self.read_metadata(table, &mut pos, 16)?;
+
+        let (blocks_start, fragment, fragment_offset, file_size) =
+            match LittleEndian::read_u16(&header[0..]) {
+                2 => {
+                    let data = self.read_metadata(table, &mut pos, 16)?;
+                    (
+                        LittleEndian::read_u32(&data[0..]) as u64,
+                        LittleEndian::read_u32(&data[4..]),
+                        LittleEndian::read_u32(&data[8..]) as usize,
+                        LittleEndian::read_u32(&data[12..]) as u64,
+                    )
+                }
+                9 => {
+                    let data = self.read_metadata(table, &mut pos, 40)?;
+                    (
+                        LittleEndian::read_u64(&data[0..]),
+                        LittleEndian::read_u32(&data[28..]),
+                        LittleEndian::read_u32(&data[32..]) as usize,
+                        LittleEndian::read_u64(&data[8..]),
+                    )
+                }
+                _ => return Err(invalid_data("not a regular file")),
+            };
+
+        let block_size = self.block_size as u64;
+        let blocks_count = if fragment == NO_FRAGMENT {
+            file_size.div_ceil(block_size)
+        } else {
+            file_size / block_size
+        } as usize;
+        let block_sizes = self.read_metadata(table, &mut pos, blocks_count * 4)?;
+
+        let mut data = Vec::with_capacity(file_size as usize);
+        let mut block_pos = blocks_start;
+        for index in 0..blocks_count {
+            let size = LittleEndian::read_u32(&block_sizes[index * 4..]);
+            if size == 0 {
+                // sparse block
+                data.resize(data.len() + block_size as usize, 0);
+                continue;
+            }
+            let mut block = self.read_data_block(block_pos, size)?;
+            block.resize(block_size as usize, 0);
+            data.extend(block);
+            block_pos += (size & !DATA_BLOCK_UNCOMPRESSED) as u64;
+        }
+
+        if fragment != NO_FRAGMENT {
+            let tail_size = (file_size % block_size) as usize;
+            data.extend(self.read_fragment(fragment, fragment_offset, tail_size)?);
+        }
+
+        data.truncate(file_size as usize);
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const BLOCK_SIZE: u32 = 4096;
+
+    fn metadata_block(data: &[u8]) -> Vec<u8> {
+        let mut block = (data.len() as u16 | METADATA_UNCOMPRESSED)
+            .to_le_bytes()
+            .to_vec();
+        block.extend(data);
+        block
+    }
+
+    fn inode_header(inode_type: u16, mode: u16, uid_index: u16, number: u32) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend(inode_type.to_le_bytes());
+        header.extend(mode.to_le_bytes());
+        header.extend(uid_index.to_le_bytes());
+        header.extend(0u16.to_le_bytes());
+        header.extend(0u32.to_le_bytes());
+        header.extend(number.to_le_bytes());
+        header
+    }
+
+    fn u32s(values: &[u32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    /// Builds the image with a compressed file, a file stored in the fragment,
+    /// a symbolic link and a device (all in the root directory).
+    fn build_image() -> Vec<u8> {
+        let mut image = vec![0u8; SUPER_BLOCK_SIZE];
+
+        // data of the first file (one compressed block)
+        let file_data = vec![0x5au8; BLOCK_SIZE as usize + 100];
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder
+            .write_all(&file_data[..BLOCK_SIZE as usize])
+            .unwrap();
+        let compressed = encoder.finish().unwrap();
+        let blocks_start = image.len() as u32;
+        image.extend(&compressed);
+        // fragment block with the tails of both files
+        let fragment_start = image.len() as u64;
+        let mut fragment = file_data[BLOCK_SIZE as usize..].to_vec();
+        fragment.extend(b"tail of the small file");
+        image.extend(&fragment);
+
+        // inode table
+        let mut inodes = Vec::new();
+        let names = ["big", "small", "link", "tty"];
+        let mut offsets = Vec::new();
+        offsets.push(inodes.len() as u16);
+        inodes.extend(inode_header(2, 0o644, 1, 2));
+        inodes.extend(u32s(&[blocks_start, 0, 0, file_data.len() as u32]));
+        inodes.extend(u32s(&[compressed.len() as u32]));
+        offsets.push(inodes.len() as u16);
+        inodes.extend(inode_header(2, 0o600, 0, 3));
+        inodes.extend(u32s(&[0, 0, 100, 22]));
+        offsets.push(inodes.len() as u16);
+        inodes.extend(inode_header(3, 0o777, 0, 4));
+        inodes.extend(u32s(&[1, 5]));
+        inodes.extend(b"small");
+        offsets.push(inodes.len() as u16);
+        inodes.extend(inode_header(5, 0o620, 0, 5));
+        inodes.extend(u32s(&[1, (4 << 8) | 64]));
+
+        // directory table with one header
+        let mut listing = u32s(&[names.len() as u32 - 1, 0, 2]);
+        for (index, (name, offset)) in names.iter().zip(&offsets).enumerate() {
+            listing.extend(offset.to_le_bytes());
+            listing.extend((index as i16).to_le_bytes());
+            listing.extend(2u16.to_le_bytes());
+            listing.extend((name.len() as u16 - 1).to_le_bytes());
+            listing.extend(name.as_bytes());
+        }
+        let root_offset = inodes.len() as u16;
+        inodes.extend(inode_header(1, 0o755, 0, 1));
+        inodes.extend(u32s(&[0, 2]));
+        inodes.extend((listing.len() as u16 + 3).to_le_bytes());
+        inodes.extend(0u16.to_le_bytes());
+        inodes.extend(u32s(&[6]));
+
+        let inode_table_start = image.len() as u64;
+        image.extend(metadata_block(&inodes));
+        let directory_table_start = image.len() as u64;
+        image.extend(metadata_block(&listing));
+
+        let fragment_entries_start = image.len() as u64;
+        let mut fragment_entry = fragment_start.to_le_bytes().to_vec();
+        fragment_entry.extend(u32s(&[fragment.len() as u32 | DATA_BLOCK_UNCOMPRESSED, 0]));
+        image.extend(metadata_block(&fragment_entry));
+        let fragment_table_start = image.len() as u64;
+        image.extend(fragment_entries_start.to_le_bytes());
+
+        let ids_start = image.len() as u64;
+        image.extend(metadata_block(&u32s(&[0, 1000])));
+        let id_table_start = image.len() as u64;
+        image.extend(ids_start.to_le_bytes());
+
+        let super_block = &mut image[..SUPER_BLOCK_SIZE];
+        LittleEndian::write_u32(&mut super_block[0..], SQUASHFS_MAGIC);
+        LittleEndian::write_u32(&mut super_block[4..], 6);
+        LittleEndian::write_u32(&mut super_block[12..], BLOCK_SIZE);
+        LittleEndian::write_u32(&mut super_block[16..], 1);
+        LittleEndian::write_u16(&mut super_block[20..], COMPRESSION_GZIP);
+        LittleEndian::write_u16(&mut super_block[22..], 12);
+        LittleEndian::write_u16(&mut super_block[26..], 2);
+        LittleEndian::write_u16(&mut super_block[28..], 4);
+        LittleEndian::write_u64(&mut super_block[32..], root_offset as u64);
+        LittleEndian::write_u64(&mut super_block[48..], id_table_start);
+        LittleEndian::write_u64(&mut super_block[64..], inode_table_start);
+        LittleEndian::write_u64(&mut super_block[72..], directory_table_start);
+        LittleEndian::write_u64(&mut super_block[80..], fragment_table_start);
+        image
+    }
+
+    fn open(image: Vec<u8>) -> io::Result<SquashFsReader> {
+        let mut source: Box<dyn ImageSource> = Box::new(io::Cursor::new(image));
+        assert!(SquashFsReader::is_valid(&mut source).unwrap());
+        SquashFsReader::new(source)
+    }
+
+    #[test]
+    fn test_read_entries() {
+        let mut reader = open(build_image()).unwrap();
+        let entries: HashMap<String, ImageEntry> =
+            reader.read_entries().unwrap().into_iter().collect();
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries["/"].file_type, FileType::Directory);
+        assert_eq!(entries["/"].mode, 0o755);
+
+        let big = &entries["/big"];
+        assert_eq!((big.file_type.clone(), big.mode), (FileType::File, 0o644));
+        assert_eq!((big.uid, big.size), (1000, BLOCK_SIZE as u64 + 100));
+        let data = reader.read_content(big).unwrap();
+        assert_eq!(data.len(), BLOCK_SIZE as usize + 100);
+        assert!(data.iter().all(|b| *b == 0x5a));
+
+        let small = &entries["/small"];
+        assert_eq!(small.uid, 0);
+        assert_eq!(
+            reader.read_content(small).unwrap(),
+            b"tail of the small file"
+        );
+
+        let link = &entries["/link"];
+        assert_eq!(link.file_type, FileType::Link);
+        assert_eq!(link.link_target.as_deref(), Some("small"));
+        assert!(reader.read_content(link).is_err());
+
+        let tty = &entries["/tty"];
+        assert_eq!(tty.file_type, FileType::CharacterDevice);
+        assert_eq!(tty.device, make_device(4, 64));
+    }
+
+    #[test]
+    fn test_unsupported_image() {
+        let mut image = build_image();
+        LittleEndian::write_u16(&mut image[28..], 3);
+        assert!(open(image).is_err());
+
+        let mut image = build_image();
+        LittleEndian::write_u16(&mut image[20..], 5);
+        assert!(open(image).is_err());
+    }
+}
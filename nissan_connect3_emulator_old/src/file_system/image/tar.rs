@@ -0,0 +1,333 @@
+use crate::file_system::image::{
+    invalid_data, make_device, normalize_path, read_bytes_at, ImageEntry, ImageReader, ImageSource,
+};
+use crate::file_system::FileType;
+use std::collections::HashMap;
+use std::io;
+use std::io::{Seek, SeekFrom};
+
+const BLOCK_SIZE: u64 = 512;
+
+///
+/// Reader of the tar archive (ustar, GNU and pax extensions).
+///
+pub struct TarReader {
+    source: Box<dyn ImageSource>,
+}
+
+impl TarReader {
+    pub fn new(source: Box<dyn ImageSource>) -> Self {
+        Self { source }
+    }
+
+    pub fn is_valid(source: &mut Box<dyn ImageSource>) -> io::Result<bool> {
+        let header = match read_bytes_at(source.as_mut(), 0, BLOCK_SIZE as usize) {
+            Ok(header) => header,
+            Err(_) => return Ok(false),
+        };
+        // "ustar\0" (POSIX) or "ustar " (GNU)
+        Ok(&header[257..262] == b"ustar")
+    }
+}
+
+impl ImageReader for TarReader {
+    fn read_entries(&mut self) -> io::Result<Vec<(String, ImageEntry)>> {
+        let image_length = self.source.seek(SeekFrom::End(0))?;
+
+        let mut entries = Vec::new();
+        let mut hard_links = Vec::new();
+        // overrides from the GNU long name and pax headers for the next entry
+        let mut long_name = None;
+        let mut long_link_target = None;
+
+        let mut pos = 0;
+        while pos + BLOCK_SIZE <= image_length {
+            let header = read_bytes_at(self.source.as_mut(), pos, BLOCK_SIZE as usize)?;
+            if header.iter().all(|b| *b == 0) {
+                // end of the archive
+                break;
+            }
+
+            let size = parse_number(&header[124..136]);
+            let data_pos = pos + BLOCK_SIZE;
+            pos = data_pos + size.div_ceil(BLOCK_SIZE) * BLOCK_SIZE;
+
+            let type_flag = header[156];
+            match type_flag {
+                b'L' | b'K' => {
+                    let data = read_bytes_at(self.source.as_mut(), data_pos, size as usize)?;
+                    let value = parse_string(&data);
+                    if type_flag == b'L' {
+                        long_name = Some(value);
+                    } else {
+                        long_link_target = Some(value);
+                    }
+                    continue;
+                }
+                b'x' => {
+                    let data = read_bytes_at(self.source.as_mut(), data_pos, size as usize)?;
+                    for (key, value) in parse_pax_records(&data) {
+                        match key.as_str() {
+                            "path" => long_name = Some(value),
+                            "linkpath" => long_link_target = Some(value),
+                            _ => {}
+                        }
+                    }
+                    continue;
+                }
+                b'g' => continue,
+                _ => {}
+            }
+
+            let name = long_name.take().unwrap_or_else(|| {
+                let prefix = parse_string(&header[345..500]);
+                let name = parse_string(&header[0..100]);
+                if prefix.is_empty() {
+                    name
+                } else {
+                    format!("{}/{}", prefix, name)
+                }
+            });
+            let link_target = long_link_target
+                .take()
+                .unwrap_or_else(|| parse_string(&header[157..257]));
+            let path = normalize_path(&name);
+
+            let file_type = match type_flag {
+                b'0' | b'\0' | b'7' => FileType::File,
+                b'1' => {
+                    hard_links.push((path, normalize_path(&link_target)));
+                    continue;
+                }
+                b'2' => FileType::Link,
+                b'3' => FileType::CharacterDevice,
+                b'4' => FileType::BlockDevice,
+                b'5' => FileType::Directory,
+                b'6' => FileType::NamedPipe,
+                _ => {
+                    log::warn!("Unsupported tar entry type {} ({})", type_flag, path);
+                    continue;
+                }
+            };
+
+            let device = match file_type {
+                FileType::CharacterDevice | FileType::BlockDevice => make_device(
+                    parse_number(&header[329..337]) as u32,
+                    parse_number(&header[337..345]) as u32,
+                ),
+                _ => 0,
+            };
+
+            entries.push((
+                path,
+                ImageEntry {
+                    size: if file_type == FileType::File { size } else { 0 },
+                    link_target: if file_type == FileType::Link {
+                        Some(link_target)
+                    } else {
+                        None
+                    },
+                    file_type,
+                    mode: parse_number(&header[100..108]) as u32 & 0o7777,
+                    uid: parse_number(&header[108..116]) as u32,
+                    gid: parse_number(&header[116..124]) as u32,
+                    device,
+                    location: data_pos,
+                },
+            ));
+        }
+
+        // hard links share content with the file stored before them
+        let files: HashMap<String, ImageEntry> = entries.iter().cloned().collect();
+        for (path, target) in hard_links {
+            match files.get(&target) {
+                Some(entry) => entries.push((path, entry.clone())),
+                None => log::warn!("Missing target of hard link {} -> {}", path, target),
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn read_content(&mut self, entry: &ImageEntry) -> io::Result<Vec<u8>> {
+        if entry.file_type != FileType::File {
+            return Err(invalid_data("not a regular file"));
+        }
+        read_bytes_at(self.source.as_mut(), entry.location, entry.size as usize)
+    }
+}
+
+/// Parses null-terminated string of the header field.
+fn parse_string(field: &[u8]) -> String {
+    let end = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).to_string()
+}
+
+/// Parses octal number (or base-256 number used by GNU tar for big values).
+fn parse_number(field: &[u8]) -> u64 {
+    if field[0] & 0x80 != 0 {
+        let mut value = (field[0] & 0x7f) as u64;
+        for b in &field[1..] {
+            value = (value << 8) | *b as u64;
+        }
+        return value;
+    }
+
+    let text = parse_string(field);
+    u64::from_str_radix(text.trim_matches(|c: char| c == ' ' || c == '\0'), 8).unwrap_or(0)
+}
+
+/// Parses records of the pax extended header ("<length> <key>=<value>\n").
+fn parse_pax_records(data: &[u8]) -> Vec<(String, String)> {
+    let mut records = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let space = match data[pos..].iter().position(|b| *b == b' ') {
+            Some(space) => pos + space,
+            None => break,
+        };
+        let length: usize = match String::from_utf8_lossy(&data[pos..space]).parse() {
+            Ok(length) if length > 0 && pos + length <= data.len() => length,
+            _ => break,
+        };
+        let record = String::from_utf8_lossy(&data[space + 1..pos + length - 1]).to_string();
+        if let Some((key, value)) = record.split_once('=') {
+            records.push((key.to_string(), value.to_string()));
+        }
+        pos += length;
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds the ustar header (with the checksum) of the entry.
+    fn header(name: &str, type_flag: u8, size: usize, link_target: &str) -> Vec<u8> {
+        let mut header = vec![0u8; BLOCK_SIZE as usize];
+        let mut put = |start: usize, value: &[u8]| {
+            header[start..start + value.len()].copy_from_slice(value);
+        };
+        put(0, name.as_bytes());
+        put(100, b"0000750\0");
+        put(108, b"0001750\0");
+        put(116, b"0000012\0");
+        put(124, format!("{:011o}\0", size).as_bytes());
+        put(156, &[type_flag]);
+        put(157, link_target.as_bytes());
+        put(257, b"ustar\0");
+        put(263, b"00");
+        put(329, b"0000005\0");
+        put(337, b"0000001\0");
+
+        header[148..156].fill(b' ');
+        let checksum: u32 = header.iter().map(|b| *b as u32).sum();
+        header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+        header
+    }
+
+    fn append(archive: &mut Vec<u8>, header: Vec<u8>, data: &[u8]) {
+        archive.extend(header);
+        archive.extend(data);
+        archive.resize(archive.len().next_multiple_of(BLOCK_SIZE as usize), 0);
+    }
+
+    fn open(archive: Vec<u8>) -> TarReader {
+        let mut source: Box<dyn ImageSource> = Box::new(io::Cursor::new(archive));
+        assert!(TarReader::is_valid(&mut source).unwrap());
+        TarReader::new(source)
+    }
+
+    #[test]
+    fn test_read_entries() {
+        let long_name = format!("./etc/{}", "x".repeat(120));
+        let mut archive = Vec::new();
+        append(&mut archive, header("./etc/", b'5', 0, ""), &[]);
+        append(
+            &mut archive,
+            header("./etc/hosts", b'0', 10, ""),
+            b"127.0.0.1\n",
+        );
+        append(
+            &mut archive,
+            header("./etc/localhost", b'1', 0, "./etc/hosts"),
+            &[],
+        );
+        append(
+            &mut archive,
+            header("./etc/mtab", b'2', 0, "/proc/mounts"),
+            &[],
+        );
+        append(&mut archive, header("./dev/console", b'3', 0, ""), &[]);
+        append(
+            &mut archive,
+            header("././@LongLink", b'L', long_name.len() + 1, ""),
+            format!("{}\0", long_name).as_bytes(),
+        );
+        append(&mut archive, header("./etc/xxx", b'0', 4, ""), b"long");
+        archive.extend([0u8; 2 * BLOCK_SIZE as usize]);
+
+        let mut reader = open(archive);
+        let entries: HashMap<String, ImageEntry> =
+            reader.read_entries().unwrap().into_iter().collect();
+        assert_eq!(entries.len(), 6);
+
+        let dir = &entries["/etc"];
+        assert_eq!(dir.file_type, FileType::Directory);
+        assert_eq!((dir.mode, dir.uid, dir.gid), (0o750, 1000, 10));
+
+        let hosts = &entries["/etc/hosts"];
+        assert_eq!(hosts.file_type, FileType::File);
+        assert_eq!(reader.read_content(hosts).unwrap(), b"127.0.0.1\n");
+        let link = &entries["/etc/localhost"];
+        assert_eq!(reader.read_content(link).unwrap(), b"127.0.0.1\n");
+
+        let mtab = &entries["/etc/mtab"];
+        assert_eq!(mtab.file_type, FileType::Link);
+        assert_eq!(mtab.link_target.as_deref(), Some("/proc/mounts"));
+        assert!(reader.read_content(mtab).is_err());
+
+        let console = &entries["/dev/console"];
+        assert_eq!(console.file_type, FileType::CharacterDevice);
+        assert_eq!(console.device, make_device(5, 1));
+
+        let long = &entries[&normalize_path(&long_name)];
+        assert_eq!(reader.read_content(long).unwrap(), b"long");
+    }
+
+    #[test]
+    fn test_pax_header() {
+        let records = "23 path=./very/long/na\n";
+        let mut archive = Vec::new();
+        append(
+            &mut archive,
+            header("./PaxHeaders/na", b'x', records.len(), ""),
+            records.as_bytes(),
+        );
+        append(&mut archive, header("./na", b'0', 2, ""), b"ok");
+
+        let entries = open(archive).read_entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, "/very/long/na");
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number(b"0000644 \0"), 0o644);
+        assert_eq!(parse_number(b"\0\0\0\0"), 0);
+        assert_eq!(
+            parse_number(&[0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0x02, 0, 0]),
+            0x0002_0000
+        );
+    }
+
+    #[test]
+    fn test_is_valid() {
+        let mut source: Box<dyn ImageSource> = Box::new(io::Cursor::new(vec![0u8; 100]));
+        assert!(!TarReader::is_valid(&mut source).unwrap());
+        let mut source: Box<dyn ImageSource> =
+            Box::new(io::Cursor::new(vec![0u8; BLOCK_SIZE as usize]));
+        assert!(!TarReader::is_valid(&mut source).unwrap());
+    }
+}
@@ -0,0 +1,268 @@
+use crate::emulator::context::Context;
+use crate::file_system::file_info::FileDetails;
+use crate::file_system::image::{open_image, ImageEntry, ImageReader, PartitionDirectory};
+use crate::file_system::{
+    CloseFileError, FileSystem, FileSystemType, FileType, OpenFileError, OpenFileFlags, PollEvents,
+};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::io::SeekFrom;
+use std::path::Path;
+use unicorn_engine::Unicorn;
+
+struct ImageOpenedFile {
+    entry: ImageEntry,
+    data: Vec<u8>,
+    pos: usize,
+}
+
+///
+/// Read-only file system stored in the image (squashfs, ext2/3/4, cpio or tar).
+///
+pub struct ImageFileSystem {
+    reader: Box<dyn ImageReader + Send + Sync>,
+    entries: HashMap<String, ImageEntry>,
+    opened_files: HashMap<i32, ImageOpenedFile>,
+}
+
+impl ImageFileSystem {
+    pub fn new(image_path: &Path) -> io::Result<Self> {
+        Self::from_reader(open_image(image_path)?, &image_path.display().to_string())
+    }
+
+    /// Mounts partition from the directory of the extracted partitions (e.g. `rootfs`).
+    pub fn from_partition_directory(dir_path: &Path, partition: &str) -> io::Result<Self> {
+        let partition_directory = PartitionDirectory::open(dir_path)?;
+        Self::from_reader(
+            partition_directory.open_partition(partition)?,
+            &format!("{}:{}", dir_path.display(), partition),
+        )
+    }
+
+    fn from_reader(mut reader: Box<dyn ImageReader + Send + Sync>, name: &str) -> io::Result<Self> {
+        let mut entries: HashMap<String, ImageEntry> = reader.read_entries()?.into_iter().collect();
+
+        // archives do not have to contain all parent directories
+        let mut missing_dirs = HashSet::new();
+        for path in entries.keys() {
+            let mut parent = path.as_str();
+            while let Some(index) = parent.rfind('/') {
+                parent = if index == 0 { "/" } else { &parent[..index] };
+                if !entries.contains_key(parent) {
+                    missing_dirs.insert(parent.to_string());
+                }
+                if parent == "/" {
+                    break;
+                }
+            }
+        }
+        for dir_path in missing_dirs.into_iter().chain(["/".to_string()]) {
+            entries.entry(dir_path).or_insert(ImageEntry {
+                file_type: FileType::Directory,
+                mode: 0o755,
+                uid: 0,
+                gid: 0,
+                size: 0,
+                device: 0,
+                link_target: None,
+                location: 0,
+            });
+        }
+
+        log::info!("Image {} mounted ({} entries)", name, entries.len());
+
+        Ok(Self {
+            reader,
+            entries,
+            opened_files: HashMap::new(),
+        })
+    }
+}
+
+impl FileSystem for ImageFileSystem {
+    fn support_file_paths(&self) -> bool {
+        true
+    }
+
+    fn file_system_type(&self) -> FileSystemType {
+        FileSystemType::Normal
+    }
+
+    fn exists(&mut self, file_path: &str) -> bool {
+        self.entries.contains_key(file_path)
+    }
+
+    fn mkdir(&mut self, _file_path: &str, _mode: u32) -> Result<(), OpenFileError> {
+        Err(OpenFileError::ReadOnlyFileSystem)
+    }
+
+    fn read_dir(&mut self, dir_path: &str) -> Result<Vec<String>, ()> {
+        match self.entries.get(dir_path) {
+            Some(entry) if entry.file_type == FileType::Directory => {}
+            _ => return Err(()),
+        }
+
+        let prefix = if dir_path == "/" {
+            "/".to_string()
+        } else {
+            format!("{}/", dir_path)
+        };
+        Ok(self
+            .entries
+            .keys()
+            .filter_map(|path| path.strip_prefix(&prefix))
+            .filter(|name| !name.is_empty() && !name.contains('/'))
+            .map(String::from)
+            .collect())
+    }
+
+    fn open(
+        &mut self,
+        file_path: &str,
+        flags: OpenFileFlags,
+        fd: i32,
+    ) -> Result<(), OpenFileError> {
+        let entry = match self.entries.get(file_path) {
+            Some(entry) => entry.clone(),
+            None if flags.contains(OpenFileFlags::CREATE) => {
+                return Err(OpenFileError::ReadOnlyFileSystem)
+            }
+            None => return Err(OpenFileError::NoSuchFileOrDirectory),
+        };
+        if flags.intersects(OpenFileFlags::WRITE | OpenFileFlags::TRUNC) {
+            return Err(OpenFileError::ReadOnlyFileSystem);
+        }
+
+        let data = if entry.file_type == FileType::File {
+            self.reader.read_content(&entry).map_err(|e| {
+                log::error!("Reading of {} from image failed: {}", file_path, e);
+                OpenFileError::NoPermission
+            })?
+        } else {
+            vec![]
+        };
+
+        self.opened_files.insert(
+            fd,
+            ImageOpenedFile {
+                entry,
+                data,
+                pos: 0,
+            },
+        );
+        Ok(())
+    }
+
+    fn close(&mut self, fd: i32) -> Result<(), CloseFileError> {
+        match self.opened_files.remove(&fd) {
+            Some(_) => Ok(()),
+            None => Err(CloseFileError::FileNotOpened),
+        }
+    }
+
+    fn link(&mut self, _old_path: &str, _new_path: &str) -> Result<(), OpenFileError> {
+        Err(OpenFileError::ReadOnlyFileSystem)
+    }
+
+    fn unlink(&mut self, _file_path: &str) -> Result<(), OpenFileError> {
+        Err(OpenFileError::ReadOnlyFileSystem)
+    }
+
+    fn set_mode(&mut self, _file_path: &str, _mode: u32) -> Result<(), OpenFileError> {
+        Err(OpenFileError::ReadOnlyFileSystem)
+    }
+
+    fn set_owner(&mut self, _file_path: &str, _uid: u32, _gid: u32) -> Result<(), OpenFileError> {
+        Err(OpenFileError::ReadOnlyFileSystem)
+    }
+
+    fn read_link(&mut self, file_path: &str) -> Option<String> {
+        self.entries.get(file_path)?.link_target.clone()
+    }
+
+    fn get_file_details(&mut self, fd: i32) -> Option<FileDetails> {
+        let opened_file = self.opened_files.get(&fd)?;
+        Some(FileDetails {
+            file_type: opened_file.entry.file_type.clone(),
+            is_readonly: true,
+            length: match &opened_file.entry.link_target {
+                Some(target) => target.len() as u64,
+                None => opened_file.data.len() as u64,
+            },
+            mode: opened_file.entry.mode,
+            uid: opened_file.entry.uid,
+            gid: opened_file.entry.gid,
+            device: opened_file.entry.device,
+            links_count: 1,
+            times: None,
+        })
+    }
+
+    fn is_open(&self, fd: i32) -> bool {
+        self.opened_files.contains_key(&fd)
+    }
+
+    fn get_length(&mut self, fd: i32) -> u64 {
+        self.opened_files
+            .get(&fd)
+            .map(|opened_file| opened_file.data.len() as u64)
+            .unwrap_or(0)
+    }
+
+    fn stream_position(&mut self, fd: i32) -> Result<u64, ()> {
+        let opened_file = self.opened_files.get(&fd).ok_or(())?;
+        Ok(opened_file.pos as u64)
+    }
+
+    fn seek(&mut self, fd: i32, pos: SeekFrom) -> Result<u64, ()> {
+        let opened_file = self.opened_files.get_mut(&fd).ok_or(())?;
+        let new_pos = match pos {
+            SeekFrom::Start(pos) => pos as i64,
+            SeekFrom::End(offset) => opened_file.data.len() as i64 + offset,
+            SeekFrom::Current(offset) => opened_file.pos as i64 + offset,
+        };
+        if new_pos < 0 {
+            return Err(());
+        }
+        opened_file.pos = new_pos as usize;
+        Ok(new_pos as u64)
+    }
+
+    fn read(&mut self, fd: i32, content: &mut [u8]) -> Result<u64, ()> {
+        let opened_file = self.opened_files.get_mut(&fd).ok_or(())?;
+        if opened_file.entry.file_type == FileType::Directory {
+            return Err(());
+        }
+        let start = opened_file.pos.min(opened_file.data.len());
+        let bytes_to_read = (opened_file.data.len() - start).min(content.len());
+        content[..bytes_to_read].copy_from_slice(&opened_file.data[start..start + bytes_to_read]);
+        opened_file.pos = start + bytes_to_read;
+        Ok(bytes_to_read as u64)
+    }
+
+    fn write(&mut self, _fd: i32, _content: &[u8]) -> Result<u64, ()> {
+        Err(())
+    }
+
+    fn truncate(&mut self, _fd: i32, _length: u32) -> Result<(), ()> {
+        Err(())
+    }
+
+    fn poll(&mut self, fd: i32) -> PollEvents {
+        if self.opened_files.contains_key(&fd) {
+            PollEvents::IN
+        } else {
+            PollEvents::NONE
+        }
+    }
+
+    fn ioctl(
+        &mut self,
+        _unicorn: &mut Unicorn<Context>,
+        _fd: i32,
+        _request: u32,
+        _addr: u32,
+    ) -> i32 {
+        -1i32
+    }
+}
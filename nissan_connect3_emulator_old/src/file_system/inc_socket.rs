@@ -0,0 +1,153 @@
+use crate::emulator::clock::{clock_now, CLOCK_MONOTONIC};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// address families of the INC sockets (added by the Bosch kernel patches)
+pub const AF_BOSCH_INC_ADR: u32 = 41;
+pub const AF_BOSCH_INC_LINUX: u32 = 42;
+pub const AF_BOSCH_INC_AUTOSAR: u32 = 43;
+
+pub const SOCK_STREAM: u32 = 1;
+pub const SOCK_DGRAM: u32 = 2;
+
+// size of the length of the message in the stream (as framed by the `dgram_service` library)
+const STREAM_HEADER_SIZE: usize = 2;
+
+///
+/// Model of the MCU on the other side of the INC sockets.
+/// The services of the MCU are identified by the ports.
+///
+pub trait McuModel: Send {
+    /// Returns messages sent by the MCU when a socket connects to the `port`.
+    fn connect(&mut self, port: u16) -> Vec<Vec<u8>>;
+
+    /// Returns responses to the message sent to the `port`.
+    fn handle_message(&mut self, port: u16, message: &[u8]) -> Vec<Vec<u8>>;
+
+    /// Returns messages sent by the MCU on its own to the `port`
+    /// between the times `from` (excluded) and `to` (measured with the monotonic clock).
+    fn periodic_messages(&mut self, port: u16, from: Duration, to: Duration) -> Vec<Vec<u8>>;
+}
+
+///
+/// INC (inter-node communication) socket connected to the MCU, created by `socket(AF_BOSCH_INC_*)`.
+/// Messages of the stream sockets are preceded by their 16-bit length (like `dgram_service` does).
+///
+pub struct IncSocket {
+    pub domain: u32,
+    pub socket_type: u32,
+    pub local_port: Option<u16>,
+    pub remote_port: Option<u16>,
+
+    mcu: Arc<Mutex<dyn McuModel>>,
+    // time of the last check of the periodic messages
+    last_update: Duration,
+    // received messages (including the length of stream messages), the first one can be partially read
+    received: VecDeque<Vec<u8>>,
+    // incomplete message written to the stream
+    pending_send: Vec<u8>,
+}
+
+impl IncSocket {
+    pub fn new(domain: u32, socket_type: u32, mcu: Arc<Mutex<dyn McuModel>>) -> Self {
+        Self {
+            domain,
+            socket_type,
+            local_port: None,
+            remote_port: None,
+            mcu,
+            last_update: Duration::ZERO,
+            received: VecDeque::new(),
+            pending_send: Vec::new(),
+        }
+    }
+
+    /// Connects the socket to the service of the MCU.
+    pub fn connect(&mut self, port: u16) -> Result<(), ()> {
+        if self.remote_port.is_some() {
+            return Err(());
+        }
+
+        self.remote_port = Some(port);
+        self.last_update = clock_now(CLOCK_MONOTONIC);
+        let messages = self.mcu.lock().unwrap().connect(port);
+        self.queue_messages(messages);
+        Ok(())
+    }
+
+    /// Sends the data to the MCU (it must be the whole message for datagram sockets).
+    pub fn send(&mut self, content: &[u8]) -> Result<u64, ()> {
+        let port = self.remote_port.ok_or(())?;
+
+        let mut messages = Vec::new();
+        if self.socket_type == SOCK_STREAM {
+            self.pending_send.extend_from_slice(content);
+            while self.pending_send.len() >= STREAM_HEADER_SIZE {
+                let len = u16::from_le_bytes([self.pending_send[0], self.pending_send[1]]) as usize;
+                if self.pending_send.len() < STREAM_HEADER_SIZE + len {
+                    break;
+                }
+                let message: Vec<u8> = self
+                    .pending_send
+                    .drain(0..STREAM_HEADER_SIZE + len)
+                    .skip(STREAM_HEADER_SIZE)
+                    .collect();
+                messages.push(message);
+            }
+        } else {
+            messages.push(content.to_vec());
+        }
+
+        for message in messages {
+            let responses = self.mcu.lock().unwrap().handle_message(port, &message);
+            self.queue_messages(responses);
+        }
+        Ok(content.len() as u64)
+    }
+
+    /// Receives the next message (or part of the stream).
+    pub fn recv(&mut self, content: &mut [u8]) -> Result<u64, ()> {
+        self.update();
+        let message = self.received.pop_front().ok_or(())?;
+
+        let len = message.len().min(content.len());
+        content[0..len].copy_from_slice(&message[0..len]);
+        // rest of the datagram is discarded
+        if self.socket_type == SOCK_STREAM && len < message.len() {
+            self.received.push_front(message[len..].to_vec());
+        }
+        Ok(len as u64)
+    }
+
+    pub fn has_messages(&mut self) -> bool {
+        self.update();
+        !self.received.is_empty()
+    }
+
+    /// Receives the periodic messages of the MCU sent until now.
+    fn update(&mut self) {
+        if let Some(port) = self.remote_port {
+            let now = clock_now(CLOCK_MONOTONIC);
+            let messages = self
+                .mcu
+                .lock()
+                .unwrap()
+                .periodic_messages(port, self.last_update, now);
+            self.last_update = now;
+            self.queue_messages(messages);
+        }
+    }
+
+    fn queue_messages(&mut self, messages: Vec<Vec<u8>>) {
+        for message in messages {
+            if self.socket_type == SOCK_STREAM {
+                let mut buf = (message.len() as u16).to_le_bytes().to_vec();
+                buf.extend_from_slice(&message);
+                self.received.push_back(buf);
+            } else {
+                self.received.push_back(message);
+            }
+        }
+    }
+}
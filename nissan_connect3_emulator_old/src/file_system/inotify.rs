@@ -0,0 +1,226 @@
+use crate::emulator::utils::{pack_i32, pack_u32};
+use std::collections::{BTreeMap, VecDeque};
+
+pub const IN_MODIFY: u32 = 0x00000002;
+pub const IN_ATTRIB: u32 = 0x00000004;
+pub const IN_CLOSE_WRITE: u32 = 0x00000008;
+pub const IN_CLOSE_NOWRITE: u32 = 0x00000010;
+pub const IN_OPEN: u32 = 0x00000020;
+pub const IN_MOVED_FROM: u32 = 0x00000040;
+pub const IN_MOVED_TO: u32 = 0x00000080;
+pub const IN_CREATE: u32 = 0x00000100;
+pub const IN_DELETE: u32 = 0x00000200;
+pub const IN_DELETE_SELF: u32 = 0x00000400;
+pub const IN_MOVE_SELF: u32 = 0x00000800;
+pub const IN_Q_OVERFLOW: u32 = 0x00004000;
+pub const IN_IGNORED: u32 = 0x00008000;
+pub const IN_ONLYDIR: u32 = 0x01000000;
+pub const IN_DONT_FOLLOW: u32 = 0x02000000;
+pub const IN_MASK_CREATE: u32 = 0x10000000;
+pub const IN_MASK_ADD: u32 = 0x20000000;
+pub const IN_ISDIR: u32 = 0x40000000;
+pub const IN_ONESHOT: u32 = 0x80000000;
+
+pub const IN_ALL_EVENTS: u32 = 0x00000fff;
+
+// events about the entry of the directory (not reported to the watch of the entry itself)
+const IN_DIR_ENTRY_EVENTS: u32 = IN_MOVED_FROM | IN_MOVED_TO | IN_CREATE | IN_DELETE;
+// events about the watched file itself (not reported to the watch of its directory)
+const IN_SELF_EVENTS: u32 = IN_DELETE_SELF | IN_MOVE_SELF;
+
+// size of `struct inotify_event` without the name
+const INOTIFY_EVENT_SIZE: usize = 16;
+// maximum number of queued events (as `max_queued_events` in Linux)
+const MAX_QUEUED_EVENTS: usize = 16384;
+
+pub struct InotifyWatch {
+    /// Absolute path of the watched file or directory.
+    pub path: String,
+    pub mask: u32,
+}
+
+pub struct InotifyEvent {
+    pub wd: i32,
+    pub mask: u32,
+    pub cookie: u32,
+    pub name: String,
+}
+
+impl InotifyEvent {
+    /// Returns `struct inotify_event` followed by the name padded with zeros.
+    fn pack(&self) -> Vec<u8> {
+        let name_len = if self.name.is_empty() {
+            0
+        } else {
+            (self.name.len() + 1).next_multiple_of(INOTIFY_EVENT_SIZE)
+        };
+
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&pack_i32(self.wd));
+        buf.extend_from_slice(&pack_u32(self.mask));
+        buf.extend_from_slice(&pack_u32(self.cookie));
+        buf.extend_from_slice(&pack_u32(name_len as u32));
+        buf.extend_from_slice(self.name.as_bytes());
+        buf.resize(INOTIFY_EVENT_SIZE + name_len, 0u8);
+        buf
+    }
+}
+
+///
+/// Inotify instance created by `inotify_init()`.
+/// Watches are matched by paths, the events are generated by `MountFileSystem`.
+///
+#[derive(Default)]
+pub struct Inotify {
+    pub watches: BTreeMap<i32, InotifyWatch>,
+    pub events: VecDeque<InotifyEvent>,
+    next_wd: i32,
+}
+
+impl Inotify {
+    pub fn new() -> Self {
+        Self {
+            next_wd: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Adds the watch (or updates the mask of the existing watch of the same path).
+    pub fn add_watch(&mut self, path: &str, mask: u32) -> Result<i32, ()> {
+        if let Some((wd, watch)) = self.watches.iter_mut().find(|(_, w)| w.path == path) {
+            if mask & IN_MASK_CREATE != 0 {
+                return Err(());
+            }
+            if mask & IN_MASK_ADD != 0 {
+                watch.mask |= mask;
+            } else {
+                watch.mask = mask;
+            }
+            return Ok(*wd);
+        }
+
+        let wd = self.next_wd;
+        self.next_wd += 1;
+        self.watches.insert(
+            wd,
+            InotifyWatch {
+                path: path.to_string(),
+                mask,
+            },
+        );
+        Ok(wd)
+    }
+
+    pub fn remove_watch(&mut self, wd: i32) -> Result<(), ()> {
+        self.watches.remove(&wd).ok_or(())?;
+        self.push_event(wd, IN_IGNORED, 0, "");
+        Ok(())
+    }
+
+    /// Queues the event of the file at `path` for all matching watches
+    /// (returns `true` if any event was queued).
+    pub fn handle_event(&mut self, path: &str, mask: u32, cookie: u32) -> bool {
+        let (parent_path, name) = match path.rfind('/') {
+            Some(0) => ("/", &path[1..]),
+            Some(index) => (&path[..index], &path[index + 1..]),
+            None => return false,
+        };
+
+        let mut matches = Vec::new();
+        for (wd, watch) in &self.watches {
+            if watch.path == path && mask & IN_DIR_ENTRY_EVENTS == 0 {
+                matches.push((*wd, watch.mask, ""));
+            } else if watch.path == parent_path && mask & IN_SELF_EVENTS == 0 && !name.is_empty() {
+                matches.push((*wd, watch.mask, name));
+            }
+        }
+
+        let mut is_queued = false;
+        for (wd, watch_mask, name) in matches {
+            if mask & watch_mask & IN_ALL_EVENTS == 0 {
+                continue;
+            }
+            self.push_event(wd, mask & (watch_mask | IN_ISDIR), cookie, name);
+            is_queued = true;
+
+            if watch_mask & IN_ONESHOT != 0 {
+                self.remove_watch(wd).unwrap();
+            }
+        }
+
+        // the watch is removed together with the watched file
+        if mask & IN_DELETE_SELF != 0 {
+            let deleted: Vec<i32> = self
+                .watches
+                .iter()
+                .filter(|(_, watch)| watch.path == path)
+                .map(|(wd, _)| *wd)
+                .collect();
+            for wd in deleted {
+                self.remove_watch(wd).unwrap();
+                is_queued = true;
+            }
+        }
+
+        is_queued
+    }
+
+    /// Updates paths of the watches after the file or directory was moved.
+    pub fn handle_move(&mut self, old_path: &str, new_path: &str) {
+        for watch in self.watches.values_mut() {
+            if watch.path == old_path {
+                watch.path = new_path.to_string();
+            } else if let Some(rest) = watch.path.strip_prefix(&format!("{}/", old_path)) {
+                watch.path = format!("{}/{}", new_path, rest);
+            }
+        }
+    }
+
+    /// Reads as many whole events as fit into `content`.
+    pub fn read(&mut self, content: &mut [u8]) -> Result<usize, ()> {
+        let mut len = 0;
+        while let Some(event) = self.events.front() {
+            let buf = event.pack();
+            if len + buf.len() > content.len() {
+                break;
+            }
+            content[len..len + buf.len()].copy_from_slice(&buf);
+            len += buf.len();
+            self.events.pop_front();
+        }
+
+        // no events or the buffer is too small for the first one
+        if len == 0 {
+            return Err(());
+        }
+        Ok(len)
+    }
+
+    fn push_event(&mut self, wd: i32, mask: u32, cookie: u32, name: &str) {
+        // identical consecutive events are merged
+        if let Some(last) = self.events.back() {
+            if last.wd == wd && last.mask == mask && last.cookie == cookie && last.name == name {
+                return;
+            }
+        }
+
+        if self.events.len() >= MAX_QUEUED_EVENTS {
+            if self.events.back().map(|event| event.mask) != Some(IN_Q_OVERFLOW) {
+                self.events.push_back(InotifyEvent {
+                    wd: -1,
+                    mask: IN_Q_OVERFLOW,
+                    cookie: 0,
+                    name: String::new(),
+                });
+            }
+            return;
+        }
+
+        self.events.push_back(InotifyEvent {
+            wd,
+            mask,
+            cookie,
+            name: name.to_string(),
+        });
+    }
+}
@@ -0,0 +1,198 @@
+use crate::emulator::context::Context;
+use crate::file_system::file_info::{FileDetails, FileType};
+use crate::file_system::SharedMemory;
+use bitflags::bitflags;
+use std::collections::BTreeMap;
+use std::io::SeekFrom;
+use std::sync::Arc;
+use unicorn_engine::Unicorn;
+
+bitflags! {
+    pub struct OpenFileFlags: u32 {
+        const NONE = 0x00000000;
+        const READ = 0x00000001;
+        const WRITE = 0x00000002;
+        const CREATE = 0x00000004;
+        const EXCLUSIVE = 0x00000008;
+        const TRUNC = 0x00000010;
+        const APPEND = 0x00000020;
+        const DIRECTORY = 0x00000040;
+        const TEMP_FILE = 0x00000080;
+        const NO_FOLLOW = 0x00000100;
+        const NONBLOCK = 0x00000200;
+    }
+}
+
+bitflags! {
+    /// Requested access to the file (same values as `access()` mode).
+    pub struct AccessMode: u32 {
+        const NONE = 0x00000000;
+        const EXECUTE = 0x00000001;
+        const WRITE = 0x00000002;
+        const READ = 0x00000004;
+    }
+}
+
+bitflags! {
+    /// Readiness of the opened file (same values as `poll()` events).
+    pub struct PollEvents: u32 {
+        const NONE = 0x00000000;
+        const IN = 0x00000001;
+        const PRI = 0x00000002;
+        const OUT = 0x00000004;
+        const ERR = 0x00000008;
+        const HUP = 0x00000010;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OpenFileError {
+    FileSystemNotMounted,
+    NoSuchFileOrDirectory,
+    FileExists,
+    NoPermission,
+    AccessDenied,
+    TooManySymbolicLinks,
+    NotSymbolicLink,
+    NotDirectory,
+    IsDirectory,
+    DirectoryNotEmpty,
+    ReadOnlyFileSystem,
+    CrossDeviceLink,
+    InvalidArgument,
+    NoSuchDevice,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CloseFileError {
+    FileNotOpened,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileSystemType {
+    Normal,
+    Dev,
+    Proc,
+    Temp,
+    Stream,
+    Anon,
+    Overlay,
+}
+
+/// Mounted file system (listed in `/proc/mounts`).
+#[derive(Debug, Clone)]
+pub struct MountInfo {
+    pub mount_point: String,
+    pub file_system_type: FileSystemType,
+    pub is_read_only: bool,
+    /// Block device of the media mounted at runtime.
+    pub device: Option<String>,
+}
+
+/// State of the mount file system provided to the proc file system.
+#[derive(Debug, Clone, Default)]
+pub struct MountState {
+    pub mounts: Vec<MountInfo>,
+    /// Number of changes of the mounted file systems.
+    pub mount_event: u64,
+    /// Paths of the opened files by pids and their file descriptors.
+    pub opened_files: BTreeMap<u32, BTreeMap<i32, String>>,
+    /// Process that accesses the file system (target of `/proc/self`).
+    pub current_pid: Option<u32>,
+}
+
+pub trait FileSystem {
+    fn support_file_paths(&self) -> bool;
+
+    fn file_system_type(&self) -> FileSystemType;
+
+    fn exists(&mut self, file_path: &str) -> bool;
+
+    fn mkdir(&mut self, file_path: &str, mode: u32) -> Result<(), OpenFileError>;
+
+    fn read_dir(&mut self, dir_path: &str) -> Result<Vec<String>, ()>;
+
+    /// Open file from specified path and assign it with the provided `fd` file descriptor id.
+    fn open(&mut self, file_path: &str, flags: OpenFileFlags, fd: i32)
+        -> Result<(), OpenFileError>;
+
+    /// Close the file.
+    fn close(&mut self, fd: i32) -> Result<(), CloseFileError>;
+
+    fn link(&mut self, old_path: &str, new_path: &str) -> Result<(), OpenFileError>;
+
+    fn unlink(&mut self, file_path: &str) -> Result<(), OpenFileError>;
+
+    /// Removes the empty directory.
+    fn rmdir(&mut self, _dir_path: &str) -> Result<(), OpenFileError> {
+        Err(OpenFileError::NoPermission)
+    }
+
+    /// Moves the file or directory (replaces existing `new_path` like `rename()`).
+    fn rename(&mut self, _old_path: &str, _new_path: &str) -> Result<(), OpenFileError> {
+        Err(OpenFileError::NoPermission)
+    }
+
+    /// Creates symbolic link at `link_path` pointing to `target` (stored as it is).
+    fn symlink(&mut self, _target: &str, _link_path: &str) -> Result<(), OpenFileError> {
+        Err(OpenFileError::NoPermission)
+    }
+
+    /// Creates special file (device numbers are encoded like the `st_rdev` of `stat64`).
+    fn mknod(
+        &mut self,
+        _file_path: &str,
+        _file_type: FileType,
+        _device: u64,
+    ) -> Result<(), OpenFileError> {
+        Err(OpenFileError::NoPermission)
+    }
+
+    /// Changes permission bits of the file (including set-user-ID, set-group-ID and sticky bits).
+    fn set_mode(&mut self, _file_path: &str, _mode: u32) -> Result<(), OpenFileError> {
+        Err(OpenFileError::NoPermission)
+    }
+
+    /// Changes owner and group of the file (symbolic links are not followed).
+    fn set_owner(&mut self, _file_path: &str, _uid: u32, _gid: u32) -> Result<(), OpenFileError> {
+        Err(OpenFileError::NoPermission)
+    }
+
+    fn get_file_details(&mut self, fd: i32) -> Option<FileDetails>;
+
+    fn is_open(&self, fd: i32) -> bool;
+
+    fn get_length(&mut self, fd: i32) -> u64;
+
+    fn stream_position(&mut self, fd: i32) -> Result<u64, ()>;
+
+    fn seek(&mut self, fd: i32, pos: SeekFrom) -> Result<u64, ()>;
+
+    fn read(&mut self, fd: i32, content: &mut [u8]) -> Result<u64, ()>;
+
+    fn write(&mut self, fd: i32, content: &[u8]) -> Result<u64, ()>;
+
+    fn truncate(&mut self, fd: i32, length: u32) -> Result<(), ()>;
+
+    /// Returns readiness of the opened file for `poll()` / `epoll_wait()`.
+    fn poll(&mut self, fd: i32) -> PollEvents;
+
+    fn ioctl(&mut self, unicorn: &mut Unicorn<Context>, fd: i32, request: u32, addr: u32) -> i32;
+
+    /// Returns memory of the device for `MAP_SHARED` mappings (at least `size` bytes long).
+    ///
+    /// Regular files return `None`, their mappings are backed by the file content.
+    fn mmap(&mut self, _fd: i32, _size: usize) -> Option<Arc<SharedMemory>> {
+        None
+    }
+
+    /// Returns target of the symbolic link (`None` if the file is not a link).
+    ///
+    /// Links in the path are already resolved by the mount file system.
+    fn read_link(&mut self, _file_path: &str) -> Option<String> {
+        None
+    }
+
+    /// Called before the file system is accessed by a path (only for `FileSystemType::Proc`).
+    fn set_mount_state(&mut self, _mount_state: MountState) {}
+}
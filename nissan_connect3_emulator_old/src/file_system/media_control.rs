@@ -0,0 +1,108 @@
+use crate::devices::CommandControl;
+use crate::file_system::{MountFileSystem, MountPoint, OpenFileError, OsFileSystem};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+///
+/// Inserts and removes media (SD card with maps, USB sticks) at runtime by text commands
+/// (one command per line, `#` starts a comment):
+///
+/// - `wait <ms>`
+/// - `mount <mount point> <host directory> [<block device>] [ro]`
+/// - `unmount <mount point>`
+///
+/// Processes are notified by `/proc/mounts` (like with the automounter of the real unit).
+///
+/// The SD card with maps is mounted at `/var/opt/bosch/dynamic` from the start, so a script
+/// that changes the card removes it first:
+///
+/// ```text
+/// unmount /var/opt/bosch/dynamic
+/// wait 5000
+/// mount /var/opt/bosch/dynamic /home/user/sd_card_2 /dev/mmcblk1p1 ro
+/// ```
+///
+#[derive(Clone)]
+pub struct MediaControl {
+    file_system: Arc<Mutex<MountFileSystem>>,
+}
+
+impl MediaControl {
+    pub fn new(file_system: Arc<Mutex<MountFileSystem>>) -> Self {
+        Self { file_system }
+    }
+}
+
+impl CommandControl for MediaControl {
+    const NAME: &'static str = "media";
+
+    fn execute(&self, line: &str) -> Result<(), String> {
+        let line = line.split('#').next().unwrap().trim();
+        let args: Vec<&str> = line.split_whitespace().collect();
+        let argument = |index: usize| -> Result<&str, String> {
+            args.get(index)
+                .copied()
+                .ok_or(format!("missing argument of `{}`", line))
+        };
+
+        match args.first().copied() {
+            None => {}
+            Some("wait") => {
+                let ms = argument(1)?
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid number in `{}`", line))?;
+                std::thread::sleep(Duration::from_millis(ms));
+            }
+            Some("mount") => {
+                let mount_point = argument(1)?.trim_end_matches('/');
+                if !mount_point.starts_with('/') {
+                    return Err(format!("invalid mount point `{}`", mount_point));
+                }
+                let host_path = PathBuf::from(argument(2)?);
+                if !host_path.is_dir() {
+                    return Err(format!("{} is not a directory", host_path.display()));
+                }
+                let mut device = None;
+                let mut is_read_only = false;
+                for arg in &args[3..] {
+                    match *arg {
+                        "ro" => is_read_only = true,
+                        "rw" => is_read_only = false,
+                        _ if arg.starts_with("/dev/") => device = Some(arg.to_string()),
+                        _ => return Err(format!("invalid option `{}`", arg)),
+                    }
+                }
+
+                self.file_system
+                    .lock()
+                    .unwrap()
+                    .mount(
+                        MountPoint {
+                            mount_point: mount_point.to_string(),
+                            file_system: Box::new(OsFileSystem::new(host_path)),
+                            is_read_only,
+                        },
+                        device,
+                    )
+                    .map_err(|err| match err {
+                        OpenFileError::FileExists => format!("{} is already mounted", mount_point),
+                        OpenFileError::NotDirectory => {
+                            format!("{} is not a directory", mount_point)
+                        }
+                        _ => format!("{} does not exist", mount_point),
+                    })?;
+            }
+            Some("unmount") => {
+                let mount_point = argument(1)?.trim_end_matches('/');
+                self.file_system
+                    .lock()
+                    .unwrap()
+                    .unmount(mount_point)
+                    .map_err(|_| format!("{} is not mounted", mount_point))?;
+            }
+            Some(command) => return Err(format!("unknown command `{}`", command)),
+        }
+        Ok(())
+    }
+}
@@ -1,17 +1,38 @@
+mod anon_file_system;
+mod char_device;
 mod dev_file_system;
+mod fd_notifier;
 mod file_info;
-mod file_system;
+mod image;
+mod image_file_system;
+mod inc_socket;
+mod inotify;
+mod interface;
+mod media_control;
 mod mount_file_system;
 mod os_file_system;
+mod overlay_file_system;
+mod pipe;
 mod proc_file_system;
+mod shared_memory;
 mod std_file_system;
 mod tmp_file_system;
 
+pub use anon_file_system::*;
+pub use char_device::*;
 pub use dev_file_system::*;
+pub use fd_notifier::*;
 pub use file_info::*;
-pub use file_system::*;
+pub use image_file_system::*;
+pub use inc_socket::*;
+pub use inotify::*;
+pub use interface::*;
+pub use media_control::*;
 pub use mount_file_system::*;
 pub use os_file_system::*;
+pub use overlay_file_system::*;
+pub use pipe::*;
 pub use proc_file_system::*;
+pub use shared_memory::*;
 pub use std_file_system::*;
 pub use tmp_file_system::*;
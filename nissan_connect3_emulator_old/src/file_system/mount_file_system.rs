@@ -1,12 +1,28 @@
 use crate::emulator::context::Context;
-use crate::file_system::file_info::FileInfo;
-use crate::file_system::{CloseFileError, FileSystem, OpenFileError, OpenFileFlags};
+use crate::emulator::users::{egid, euid};
+use crate::file_system::file_info::FileType;
+use crate::file_system::file_info::{FileDetails, FileInfo};
+use crate::file_system::{
+    AccessMode, AnonFile, AnonFileSystem, CloseFileError, EpollInterest, FdNotifier, FileSystem,
+    FileSystemType, McuModel, MountInfo, MountState, OpenFileError, OpenFileFlags, PipeEnd,
+    PollEvents, SharedMemory, IN_ATTRIB, IN_CLOSE_NOWRITE, IN_CLOSE_WRITE, IN_CREATE, IN_DELETE,
+    IN_DELETE_SELF, IN_ISDIR, IN_MODIFY, IN_MOVED_FROM, IN_MOVED_TO, IN_MOVE_SELF, IN_OPEN,
+};
 use path_absolutize::Absolutize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::io::SeekFrom;
+use std::ops::{Deref, DerefMut};
 use std::path::Path;
+use std::sync::{Arc, LockResult, Mutex, MutexGuard, PoisonError, Weak};
 use unicorn_engine::Unicorn;
 
+// maximum number of symbolic links resolved in a path (as `MAXSYMLINKS` in Linux)
+const MAX_SYMBOLIC_LINKS: u32 = 40;
+// maximum depth of nested epoll descriptors (as `EPOLL_MAX_NESTS` in Linux)
+const EPOLL_MAX_NESTS: usize = 4;
+// `FD_CLOEXEC` file descriptor flag
+const FD_CLOEXEC: u32 = 0x1;
+
 pub struct MountPoint {
     pub mount_point: String,
     pub file_system: Box<dyn FileSystem + Send + Sync>,
@@ -14,22 +30,52 @@ pub struct MountPoint {
 }
 
 impl MountPoint {
+    /// Checks if the path is the mount point itself or a path inside it
+    /// (`/mnt/sd` does not contain `/mnt/sdcard`).
+    pub fn contains_path(&self, global_path: &str) -> bool {
+        match global_path.strip_prefix(self.mount_point.trim_end_matches('/')) {
+            Some(rest) => rest.is_empty() || rest.starts_with('/'),
+            None => false,
+        }
+    }
+
     pub fn translate_path(&self, global_path: &str) -> Result<String, ()> {
-        if global_path.starts_with(&self.mount_point) {
+        if self.contains_path(global_path) {
             let mut start_index = self.mount_point.len();
             if self.mount_point.ends_with("/") {
                 start_index -= 1;
             }
-            Ok(global_path[start_index..].to_string())
+            let file_path = &global_path[start_index..];
+            // the mount point directory itself
+            if file_path.is_empty() {
+                Ok("/".to_string())
+            } else {
+                Ok(file_path.to_string())
+            }
         } else {
             Err(())
         }
     }
 }
 
+/// Open file (file description) shared by the file descriptors that refer to it.
 pub struct MountFsFileData {
     pub file_path: String,
-    pub file_status_flags: u32,
+    pub is_nonblocking: bool,
+    /// Inotify event reported when the file is closed (`0` for files opened by the emulator).
+    pub close_event: u32,
+
+    // number of file descriptors (of all processes) that refer to the file
+    pub ref_count: u32,
+}
+
+/// Entry of the file descriptor table of a process.
+#[derive(Clone)]
+pub struct FdEntry {
+    /// Id of the open file in the mounted file system.
+    pub file: i32,
+    /// File descriptor flags (`FD_CLOEXEC`).
+    pub flags: u32,
 }
 
 ///
@@ -39,34 +85,185 @@ pub struct MountFileSystem {
     pub current_working_dir: String,
 
     mount_points: Vec<MountPoint>,
+    // unmounted file systems with opened files (they are dropped when the files are closed)
+    detached_mount_points: Vec<MountPoint>,
+    // block devices of the mounted media (by mount point)
+    mount_devices: HashMap<String, String>,
+    // number of changes of the mounted file systems (reported by `/proc/mounts`)
+    mount_event: u64,
     inodes: HashMap<String, u64>,
+    // open files by their ids in the mounted file systems
     file_data: HashMap<i32, MountFsFileData>,
+    // file descriptor tables of the processes (by pid)
+    fd_tables: HashMap<u32, BTreeMap<i32, FdEntry>>,
+    // process that locked the file system through `ProcessFileSystem`, its file descriptors
+    // are passed to the methods (the emulator itself uses ids of the open files)
+    current_pid: Option<u32>,
+
+    // memory of MAP_SHARED mappings (by absolute file path)
+    shared_memory: HashMap<String, Weak<SharedMemory>>,
+    // paths of the devices with mapped memory (their mappings are not written back)
+    mapped_devices: HashSet<String>,
+
+    anon_file_system: AnonFileSystem,
+    notifier: Arc<FdNotifier>,
+
+    // MCU on the other side of the INC sockets (they are not supported without it)
+    inc_mcu: Option<Arc<Mutex<dyn McuModel>>>,
+
+    // cookie that pairs IN_MOVED_FROM and IN_MOVED_TO inotify events of the last `rename()`
+    move_cookie: u32,
 }
 
 impl MountFileSystem {
     pub fn new(mut mount_points: Vec<MountPoint>) -> Self {
-        // sort mount points from longest to shortest to allow matching paths in order
-        mount_points.sort_by(|a, b| b.mount_point.cmp(&a.mount_point));
+        // sort mount points from longest to shortest path, so the first one that contains
+        // a path is the innermost one
+        mount_points.sort_by_key(|mp| std::cmp::Reverse(mp.mount_point.len()));
+
+        // files without paths (eventfd, timerfd, signalfd, epoll)
+        let notifier = Arc::new(FdNotifier::new());
+        let anon_file_system = AnonFileSystem::new(notifier.clone());
+        mount_points.push(MountPoint {
+            mount_point: "anon_inode:".to_string(),
+            file_system: Box::new(anon_file_system.clone()),
+            is_read_only: false,
+        });
 
         Self {
             current_working_dir: "/".to_string(),
 
             mount_points,
+            detached_mount_points: Vec::new(),
+            mount_devices: HashMap::new(),
+            mount_event: 0,
             inodes: HashMap::new(),
             file_data: HashMap::new(),
+            fd_tables: HashMap::new(),
+            current_pid: None,
+
+            shared_memory: HashMap::new(),
+            mapped_devices: HashSet::new(),
+
+            anon_file_system,
+            notifier,
+
+            inc_mcu: None,
+
+            move_cookie: 0,
         }
     }
 
+    /// Used to wait for changes of the file descriptors.
+    pub fn notifier(&self) -> Arc<FdNotifier> {
+        self.notifier.clone()
+    }
+
+    /// Sets the MCU model that communicates through the INC sockets.
+    pub fn set_inc_mcu(&mut self, mcu: Arc<Mutex<dyn McuModel>>) {
+        self.inc_mcu = Some(mcu);
+    }
+
+    pub fn inc_mcu(&self) -> Option<Arc<Mutex<dyn McuModel>>> {
+        self.inc_mcu.clone()
+    }
+
+    /// Mounts the file system at runtime (like an inserted SD card or USB stick),
+    /// `device` is the block device listed in `/proc/mounts`.
+    ///
+    /// The mount point must be an existing directory that is not a mount point yet.
+    pub fn mount(
+        &mut self,
+        mut mount_point: MountPoint,
+        device: Option<String>,
+    ) -> Result<(), OpenFileError> {
+        mount_point.mount_point = self.resolve_path(&mount_point.mount_point, true)?;
+        if self
+            .mount_points
+            .iter()
+            .any(|mp| mp.mount_point == mount_point.mount_point)
+        {
+            return Err(OpenFileError::FileExists);
+        }
+        match self.get_file_info_from_filepath(&mount_point.mount_point) {
+            Some(file_info) if file_info.file_details.file_type == FileType::Directory => {}
+            Some(_) => return Err(OpenFileError::NotDirectory),
+            None => return Err(OpenFileError::NoSuchFileOrDirectory),
+        }
+
+        log::info!("mounting {}", mount_point.mount_point);
+        if let Some(device) = device {
+            self.mount_devices
+                .insert(mount_point.mount_point.clone(), device);
+        }
+        // keep the order from longest to shortest path
+        let index = self
+            .mount_points
+            .iter()
+            .position(|mp| mp.mount_point.len() < mount_point.mount_point.len())
+            .unwrap_or(self.mount_points.len());
+        self.mount_points.insert(index, mount_point);
+        self.mount_changed();
+        Ok(())
+    }
+
+    /// Unmounts the file system at runtime (like a removed SD card).
+    ///
+    /// Opened files stay usable until they are closed (like `umount -l`).
+    pub fn unmount(&mut self, mount_point: &str) -> Result<(), OpenFileError> {
+        let mount_point = self.resolve_path(mount_point, true)?;
+        let index = self
+            .mount_points
+            .iter()
+            .position(|mp| mp.mount_point == mount_point && mp.file_system.support_file_paths())
+            .ok_or(OpenFileError::InvalidArgument)?;
+
+        let mount_point = self.mount_points.remove(index);
+        self.mount_devices.remove(&mount_point.mount_point);
+        let opened_files = self
+            .file_data
+            .iter()
+            .filter(|(fd, _)| mount_point.file_system.is_open(**fd))
+            .count();
+        log::info!(
+            "unmounting {} ({} opened files)",
+            mount_point.mount_point,
+            opened_files
+        );
+        if opened_files > 0 {
+            self.detached_mount_points.push(mount_point);
+        }
+        self.mount_changed();
+        Ok(())
+    }
+
+    fn mount_changed(&mut self) {
+        self.mount_event += 1;
+        // pollers of `/proc/mounts` are woken up
+        self.notifier.notify();
+    }
+
     pub fn get_mount_point(&self, fd: i32) -> Option<&MountPoint> {
+        let file = self.file_of(fd)?;
         self.mount_points
             .iter()
-            .find(|mp| mp.file_system.is_open(fd))
+            .chain(self.detached_mount_points.iter())
+            .find(|mp| mp.file_system.is_open(file))
     }
 
     pub fn get_mount_point_mut(&mut self, fd: i32) -> Option<&mut MountPoint> {
+        self.get_open_file_mut(fd)
+            .map(|(mount_point, _)| mount_point)
+    }
+
+    /// Returns mount point of the file opened by `fd` and id of the file in its file system.
+    fn get_open_file_mut(&mut self, fd: i32) -> Option<(&mut MountPoint, i32)> {
+        let file = self.file_of(fd)?;
         self.mount_points
             .iter_mut()
-            .find(|mp| mp.file_system.is_open(fd))
+            .chain(self.detached_mount_points.iter_mut())
+            .find(|mp| mp.file_system.is_open(file))
+            .map(|mount_point| (mount_point, file))
     }
 
     pub fn get_mount_point_from_filepath_mut(
@@ -74,25 +271,137 @@ impl MountFileSystem {
         file_path: &str,
     ) -> Option<(&mut MountPoint, String)> {
         let file_path = self.path_convert_to_absolute(file_path);
-        self.mount_points
-            .iter_mut()
+        let index = self
+            .mount_points
+            .iter()
+            .position(|mp| mp.file_system.support_file_paths() && mp.contains_path(&file_path))?;
+
+        // proc-fs generates files from the current mounts and opened files
+        if self.mount_points[index].file_system.file_system_type() == FileSystemType::Proc {
+            let mount_state = self.get_mount_state();
+            self.mount_points[index]
+                .file_system
+                .set_mount_state(mount_state);
+        }
+
+        let mount_point = &mut self.mount_points[index];
+        let file_path = mount_point.translate_path(&file_path).unwrap();
+        Some((mount_point, file_path))
+    }
+
+    fn get_mount_state(&self) -> MountState {
+        let mounts = self
+            .mount_points
+            .iter()
             .filter(|mp| mp.file_system.support_file_paths())
-            .find(|mp| file_path.starts_with(&mp.mount_point))
-            .map(|mp| {
-                let file_path = mp.translate_path(&file_path).unwrap();
-                (mp, file_path)
+            .map(|mp| MountInfo {
+                mount_point: mp.mount_point.clone(),
+                file_system_type: mp.file_system.file_system_type(),
+                is_read_only: mp.is_read_only,
+                device: self.mount_devices.get(&mp.mount_point).cloned(),
+            })
+            .collect();
+
+        let opened_files = self
+            .fd_tables
+            .iter()
+            .map(|(pid, fd_table)| {
+                let file_paths = fd_table
+                    .iter()
+                    .filter_map(|(fd, fd_entry)| {
+                        let file_data = self.file_data.get(&fd_entry.file)?;
+                        Some((*fd, file_data.file_path.clone()))
+                    })
+                    .collect();
+                (*pid, file_paths)
             })
+            .collect();
+
+        MountState {
+            mounts,
+            mount_event: self.mount_event,
+            opened_files,
+            current_pid: self.current_pid,
+        }
+    }
+
+    /// Resolves symbolic links in the path component by component (the last one only
+    /// when `follow_last` is set).
+    ///
+    /// Links are resolved against the mounted file systems, so absolute targets never
+    /// point to the host file system.
+    pub fn resolve_path(&mut self, path: &str, follow_last: bool) -> Result<String, OpenFileError> {
+        let path = if path.starts_with("/") {
+            path.to_string()
+        } else {
+            format!("{}/{}", self.current_working_dir, path)
+        };
+
+        let mut pending: VecDeque<String> = path.split('/').map(String::from).collect();
+        let mut resolved: Vec<String> = Vec::new();
+        let mut links_count = 0;
+
+        while let Some(component) = pending.pop_front() {
+            match component.as_str() {
+                "" | "." => continue,
+                ".." => {
+                    resolved.pop();
+                    continue;
+                }
+                _ => resolved.push(component),
+            }
+
+            if !follow_last && pending.iter().all(|c| c.is_empty()) {
+                break;
+            }
+
+            let current_path = format!("/{}", resolved.join("/"));
+            if let Some(target) = self.read_link_internal(&current_path) {
+                links_count += 1;
+                if links_count > MAX_SYMBOLIC_LINKS {
+                    return Err(OpenFileError::TooManySymbolicLinks);
+                }
+
+                resolved.pop();
+                if target.starts_with("/") {
+                    resolved.clear();
+                }
+                for component in target.split('/').rev() {
+                    pending.push_front(component.to_string());
+                }
+            }
+        }
+
+        Ok(format!("/{}", resolved.join("/")))
+    }
+
+    /// Returns target of the symbolic link (`readlink()`).
+    pub fn read_link(&mut self, file_path: &str) -> Result<String, OpenFileError> {
+        let file_path = self.resolve_path(file_path, false)?;
+        if let Some(target) = self.read_link_internal(&file_path) {
+            Ok(target)
+        } else if self.exists_internal(&file_path) {
+            Err(OpenFileError::NotSymbolicLink)
+        } else {
+            Err(OpenFileError::NoSuchFileOrDirectory)
+        }
+    }
+
+    fn read_link_internal(&mut self, file_path: &str) -> Option<String> {
+        let (mount_point, file_path) = self.get_mount_point_from_filepath_mut(file_path)?;
+        mount_point.file_system.read_link(&file_path)
     }
 
     pub fn read_dir(&mut self, dir_path: &str) -> Result<Vec<String>, ()> {
-        if let Some((mount_point, file_path)) = self.get_mount_point_from_filepath_mut(dir_path) {
+        let dir_path = self.resolve_path(dir_path, true).map_err(|_| ())?;
+        if let Some((mount_point, file_path)) = self.get_mount_point_from_filepath_mut(&dir_path) {
             mount_point.file_system.read_dir(&file_path)
         } else {
             Err(())
         }
     }
 
-    pub fn exists(&mut self, file_path: &str) -> bool {
+    fn exists_internal(&mut self, file_path: &str) -> bool {
         if let Some((mount_point, file_path)) = self.get_mount_point_from_filepath_mut(file_path) {
             mount_point.file_system.exists(&file_path)
         } else {
@@ -101,16 +410,86 @@ impl MountFileSystem {
     }
 
     pub fn mkdir(&mut self, file_path: &str, mode: u32) -> Result<(), OpenFileError> {
-        if let Some((mount_point, file_path)) = self.get_mount_point_from_filepath_mut(file_path) {
-            mount_point.file_system.mkdir(&file_path, mode)
-        } else {
-            Err(OpenFileError::FileSystemNotMounted)
+        let global_path = self.resolve_path(file_path, false)?;
+        self.check_parent_access(&global_path)?;
+        let (mount_point, file_path) =
+            self.get_writable_mount_point_from_filepath_mut(&global_path)?;
+        mount_point.file_system.mkdir(&file_path, mode)?;
+        // the host file system applies its own umask
+        mount_point.file_system.set_mode(&file_path, mode).ok();
+        self.notify_path_event(&global_path, IN_CREATE | IN_ISDIR, 0);
+        Ok(())
+    }
+
+    /// Opens the file for the emulated process (access of the user is checked),
+    /// created file gets `mode` permissions (umask must be already applied).
+    pub fn open_checked(
+        &mut self,
+        file_path: &str,
+        flags: OpenFileFlags,
+        mode: u32,
+    ) -> Result<i32, OpenFileError> {
+        let absolute_file_path =
+            self.resolve_path(file_path, !flags.contains(OpenFileFlags::NO_FOLLOW))?;
+
+        let file_details = self.get_file_details_from_filepath(&absolute_file_path);
+        let is_dir = matches!(&file_details, Some(d) if d.file_type == FileType::Directory);
+        let is_created = match file_details {
+            Some(file_details) => {
+                let mut access = AccessMode::NONE;
+                if flags.contains(OpenFileFlags::READ) {
+                    access |= AccessMode::READ;
+                }
+                if flags.intersects(OpenFileFlags::WRITE | OpenFileFlags::TRUNC) {
+                    access |= AccessMode::WRITE;
+                }
+                if !file_details.is_accessible(euid(), egid(), access) {
+                    return Err(OpenFileError::AccessDenied);
+                }
+                false
+            }
+            None if flags.contains(OpenFileFlags::CREATE) => {
+                self.check_parent_access(&absolute_file_path)?;
+                true
+            }
+            None => false,
+        };
+
+        let fd = self.open(&absolute_file_path, flags)?;
+        if is_created {
+            if let Some((mount_point, file_path)) =
+                self.get_mount_point_from_filepath_mut(&absolute_file_path)
+            {
+                mount_point.file_system.set_mode(&file_path, mode).ok();
+            }
+            self.notify_path_event(&absolute_file_path, IN_CREATE, 0);
+        } else if flags.contains(OpenFileFlags::TRUNC | OpenFileFlags::WRITE) {
+            self.notify_path_event(&absolute_file_path, IN_MODIFY, 0);
         }
+
+        let is_dir_mask = if is_dir { IN_ISDIR } else { 0 };
+        self.notify_path_event(&absolute_file_path, IN_OPEN | is_dir_mask, 0);
+        if let Some(file_data) = self
+            .file_of(fd)
+            .and_then(|file| self.file_data.get_mut(&file))
+        {
+            file_data.close_event = if flags.contains(OpenFileFlags::WRITE) {
+                IN_CLOSE_WRITE
+            } else {
+                IN_CLOSE_NOWRITE | is_dir_mask
+            };
+        }
+        Ok(fd)
     }
 
+    /// Opens the file for the emulator itself (access is not checked).
     pub fn open(&mut self, file_path: &str, flags: OpenFileFlags) -> Result<i32, OpenFileError> {
-        let fd = self.get_unique_fd();
-        if let Some((mount_point, file_path)) = self.get_mount_point_from_filepath_mut(file_path) {
+        let file = self.get_unique_file();
+        let absolute_file_path =
+            self.resolve_path(file_path, !flags.contains(OpenFileFlags::NO_FOLLOW))?;
+        if let Some((mount_point, file_path)) =
+            self.get_mount_point_from_filepath_mut(&absolute_file_path)
+        {
             if mount_point.is_read_only
                 && (flags.contains(OpenFileFlags::WRITE)
                     || flags.contains(OpenFileFlags::CREATE)
@@ -124,70 +503,533 @@ impl MountFileSystem {
                 return Err(OpenFileError::NoPermission);
             }
 
-            let res = mount_point
-                .file_system
-                .open(&file_path, flags, fd)
-                .map(|_| fd);
-
-            if res.is_ok() {
-                let mount_fs_file_data = MountFsFileData {
-                    file_path: file_path.to_string(),
-                    file_status_flags: 0,
-                };
+            mount_point.file_system.open(&file_path, flags, file)?;
 
-                self.file_data.insert(fd, mount_fs_file_data);
-            }
+            let mount_fs_file_data = MountFsFileData {
+                file_path: absolute_file_path,
+                is_nonblocking: flags.contains(OpenFileFlags::NONBLOCK),
+                close_event: 0,
+                ref_count: 1,
+            };
+            self.file_data.insert(file, mount_fs_file_data);
 
-            res
+            Ok(self.install_fd(file))
         } else {
             Err(OpenFileError::FileSystemNotMounted)
         }
     }
 
     pub fn close(&mut self, fd: i32) -> Result<(), CloseFileError> {
-        let res = if let Some(mount_point) = self.get_mount_point_mut(fd) {
-            mount_point.file_system.close(fd)
-        } else {
-            Err(CloseFileError::FileNotOpened)
+        let file = match self.current_pid {
+            Some(pid) => {
+                self.fd_tables
+                    .get_mut(&pid)
+                    .and_then(|fd_table| fd_table.remove(&fd))
+                    .ok_or(CloseFileError::FileNotOpened)?
+                    .file
+            }
+            None => fd,
         };
+        self.release_file(file)
+    }
 
-        self.file_data.remove(&fd);
+    /// Drops one reference to the open file, the file is closed with the last one.
+    fn release_file(&mut self, file: i32) -> Result<(), CloseFileError> {
+        // the file stays opened for other file descriptors
+        if let Some(file_data) = self.file_data.get_mut(&file) {
+            if file_data.ref_count > 1 {
+                file_data.ref_count -= 1;
+                return Ok(());
+            }
+        }
+
+        let res = match self
+            .mount_points
+            .iter_mut()
+            .chain(self.detached_mount_points.iter_mut())
+            .find(|mp| mp.file_system.is_open(file))
+        {
+            Some(mount_point) => mount_point.file_system.close(file),
+            None => Err(CloseFileError::FileNotOpened),
+        };
+
+        if let Some(file_data) = self.file_data.remove(&file) {
+            if file_data.close_event != 0 {
+                self.notify_path_event(&file_data.file_path, file_data.close_event, 0);
+            }
+        }
+        self.anon_file_system.forget_file(file);
+        // unmounted file system is dropped with its last opened file
+        let file_data = &self.file_data;
+        self.detached_mount_points
+            .retain(|mp| file_data.keys().any(|file| mp.file_system.is_open(*file)));
 
         res
     }
 
-    pub fn link(&mut self, old_path: &str, new_path: &str) -> Result<(), OpenFileError> {
-        if let Some((mount_point, old_file_path)) = self.get_mount_point_from_filepath_mut(old_path)
+    /// Returns id of the open file referred by the file descriptor of the current process
+    /// (the emulator itself uses the ids directly).
+    pub fn file_of(&self, fd: i32) -> Option<i32> {
+        match self.current_pid {
+            Some(pid) => self
+                .fd_tables
+                .get(&pid)?
+                .get(&fd)
+                .map(|fd_entry| fd_entry.file),
+            None => Some(fd),
+        }
+    }
+
+    /// Assigns the lowest free file descriptor of the current process to the open file.
+    fn install_fd(&mut self, file: i32) -> i32 {
+        let Some(pid) = self.current_pid else {
+            return file;
+        };
+        let fd_table = self.fd_tables.entry(pid).or_default();
+        let fd = (0..).find(|fd| !fd_table.contains_key(fd)).unwrap();
+        fd_table.insert(fd, FdEntry { file, flags: 0 });
+        fd
+    }
+
+    /// Duplicates the file descriptor of the current process (`dup()`, `F_DUPFD`), the new
+    /// descriptor is the lowest free one not less than `min_fd` and it gets `flags` (`FD_CLOEXEC`).
+    pub fn dup(&mut self, fd: i32, min_fd: i32, flags: u32) -> Result<i32, ()> {
+        let pid = self.current_pid.ok_or(())?;
+        let file = self.file_of(fd).ok_or(())?;
+        let fd_table = self.fd_tables.entry(pid).or_default();
+        let new_fd = (min_fd.max(0)..)
+            .find(|fd| !fd_table.contains_key(fd))
+            .unwrap();
+        fd_table.insert(new_fd, FdEntry { file, flags });
+        if let Some(file_data) = self.file_data.get_mut(&file) {
+            file_data.ref_count += 1;
+        }
+        Ok(new_fd)
+    }
+
+    /// Duplicates the file descriptor of the current process to `new_fd` (`dup2()`, `dup3()`),
+    /// the file opened as `new_fd` is closed first.
+    pub fn dup_to(&mut self, fd: i32, new_fd: i32, flags: u32) -> Result<i32, ()> {
+        let pid = self.current_pid.ok_or(())?;
+        let file = self.file_of(fd).ok_or(())?;
+        if new_fd < 0 {
+            return Err(());
+        }
+        if let Some(file_data) = self.file_data.get_mut(&file) {
+            file_data.ref_count += 1;
+        }
+        let replaced = self
+            .fd_tables
+            .entry(pid)
+            .or_default()
+            .insert(new_fd, FdEntry { file, flags });
+        if let Some(fd_entry) = replaced {
+            self.release_file(fd_entry.file).ok();
+        }
+        Ok(new_fd)
+    }
+
+    /// Creates file descriptor table of a new process with stdin, stdout and stderr.
+    pub fn create_fd_table(&mut self, pid: u32) {
+        let mut fd_table = BTreeMap::new();
+        for fd in 0..=2 {
+            // the standard streams are not opened by path
+            self.file_data
+                .entry(fd)
+                .or_insert_with(|| MountFsFileData {
+                    file_path: "/dev/console".to_string(),
+                    is_nonblocking: false,
+                    close_event: 0,
+                    ref_count: 0,
+                })
+                .ref_count += 1;
+            fd_table.insert(fd, FdEntry { file: fd, flags: 0 });
+        }
+        self.fd_tables.insert(pid, fd_table);
+    }
+
+    /// Copies file descriptor table of the parent to the child (used by `fork()`),
+    /// the descriptors of both processes share the open files.
+    pub fn fork_fds(&mut self, parent_pid: u32, child_pid: u32) {
+        let fd_table = self.fd_tables.get(&parent_pid).cloned().unwrap_or_default();
+        for fd_entry in fd_table.values() {
+            if let Some(file_data) = self.file_data.get_mut(&fd_entry.file) {
+                file_data.ref_count += 1;
+            }
+        }
+        self.fd_tables.insert(child_pid, fd_table);
+    }
+
+    /// Closes file descriptors of the process with `FD_CLOEXEC` flag (used by `execve()`).
+    pub fn close_on_exec(&mut self, pid: u32) {
+        let Some(fd_table) = self.fd_tables.get_mut(&pid) else {
+            return;
+        };
+        let mut closed_files = Vec::new();
+        fd_table.retain(|_, fd_entry| {
+            let is_kept = fd_entry.flags & FD_CLOEXEC == 0;
+            if !is_kept {
+                closed_files.push(fd_entry.file);
+            }
+            is_kept
+        });
+        for file in closed_files {
+            self.release_file(file).ok();
+        }
+    }
+
+    /// Closes all file descriptors of the exited process.
+    pub fn remove_fd_table(&mut self, pid: u32) {
+        for fd_entry in self
+            .fd_tables
+            .remove(&pid)
+            .unwrap_or_default()
+            .into_values()
         {
-            if let Ok(new_file_path) = mount_point.translate_path(new_path) {
-                mount_point.file_system.link(&old_file_path, &new_file_path)
-            } else {
-                Err(OpenFileError::FileSystemNotMounted)
+            self.release_file(fd_entry.file).ok();
+        }
+    }
+
+    /// Assigns file descriptor to the file without a path.
+    pub fn open_anon(&mut self, anon_file: AnonFile, flags: OpenFileFlags) -> i32 {
+        let file = self.get_unique_file();
+
+        self.file_data.insert(
+            file,
+            MountFsFileData {
+                file_path: format!("anon_inode:[{}]", anon_file.name()),
+                is_nonblocking: flags.contains(OpenFileFlags::NONBLOCK),
+                close_event: 0,
+                ref_count: 1,
+            },
+        );
+        self.anon_file_system.insert(file, anon_file);
+
+        self.install_fd(file)
+    }
+
+    /// Creates a pipe, returns the file descriptors of its read end and write end.
+    pub fn open_pipe(&mut self, flags: OpenFileFlags) -> (i32, i32) {
+        let (read_end, write_end) = PipeEnd::new_pair();
+        (
+            self.open_anon(AnonFile::Pipe(read_end), flags | OpenFileFlags::READ),
+            self.open_anon(AnonFile::Pipe(write_end), flags | OpenFileFlags::WRITE),
+        )
+    }
+
+    /// Gives read-only access to the file without a path
+    /// (returns `None` if `fd` is not such file).
+    pub fn with_anon_file<R>(&self, fd: i32, f: impl FnOnce(&AnonFile) -> R) -> Option<R> {
+        self.anon_file_system.with_file(self.file_of(fd)?, f)
+    }
+
+    /// Changes the file without a path and wakes up the waiting threads
+    /// (returns `None` if `fd` is not such file).
+    pub fn with_anon_file_mut<R>(
+        &mut self,
+        fd: i32,
+        f: impl FnOnce(&mut AnonFile) -> R,
+    ) -> Option<R> {
+        let file = self.file_of(fd)?;
+        self.anon_file_system.with_file_mut(file, f)
+    }
+
+    /// Returns the interest list if `fd` is an epoll descriptor.
+    fn epoll_interests(&self, fd: i32) -> Option<BTreeMap<i32, EpollInterest>> {
+        self.with_anon_file(fd, |file| match file {
+            AnonFile::Epoll(epoll) => Some(epoll.interests.clone()),
+            _ => None,
+        })
+        .flatten()
+    }
+
+    /// Checks if watching `fd` by the epoll descriptor `epfd` would create a loop
+    /// or nest epoll descriptors too deep.
+    pub fn is_epoll_loop(&self, epfd: i32, fd: i32) -> bool {
+        // depth of the epoll descriptors watched (directly or indirectly) by `fd`
+        fn nesting_depth(file_system: &MountFileSystem, epfd: i32, fd: i32) -> Option<usize> {
+            if fd == epfd {
+                return None;
+            }
+            match file_system.epoll_interests(fd) {
+                None => Some(0),
+                Some(interests) => {
+                    let mut depth = 0;
+                    for fd in interests.keys() {
+                        depth = depth.max(nesting_depth(file_system, epfd, *fd)?);
+                        if depth >= EPOLL_MAX_NESTS {
+                            return None;
+                        }
+                    }
+                    Some(depth + 1)
+                }
             }
-        } else {
-            Err(OpenFileError::FileSystemNotMounted)
         }
+
+        !matches!(nesting_depth(self, epfd, fd), Some(depth) if depth < EPOLL_MAX_NESTS)
+    }
+
+    pub fn link(&mut self, old_path: &str, new_path: &str) -> Result<(), OpenFileError> {
+        let old_path = self.resolve_path(old_path, false)?;
+        let new_path = self.resolve_path(new_path, false)?;
+        self.check_parent_access(&new_path)?;
+        let (mount_point, old_file_path, new_file_path) =
+            self.get_writable_mount_point_for_filepaths_mut(&old_path, &new_path)?;
+        mount_point
+            .file_system
+            .link(&old_file_path, &new_file_path)?;
+        // link count of the file is changed
+        self.notify_path_event(&old_path, IN_ATTRIB, 0);
+        self.notify_path_event(&new_path, IN_CREATE, 0);
+        Ok(())
     }
 
     pub fn unlink(&mut self, file_path: &str) -> Result<(), OpenFileError> {
-        if let Some((mount_point, file_path)) = self.get_mount_point_from_filepath_mut(file_path) {
-            mount_point.file_system.unlink(&file_path)
+        // the link itself is removed, not its target
+        let global_path = self.resolve_path(file_path, false)?;
+        self.check_parent_access(&global_path)?;
+        let (mount_point, file_path) =
+            self.get_writable_mount_point_from_filepath_mut(&global_path)?;
+        mount_point.file_system.unlink(&file_path)?;
+        self.notify_path_event(&global_path, IN_DELETE, 0);
+        self.notify_path_event(&global_path, IN_DELETE_SELF, 0);
+        Ok(())
+    }
+
+    pub fn rmdir(&mut self, dir_path: &str) -> Result<(), OpenFileError> {
+        let global_path = self.resolve_path(dir_path, false)?;
+        self.check_parent_access(&global_path)?;
+        let (mount_point, dir_path) =
+            self.get_writable_mount_point_from_filepath_mut(&global_path)?;
+        mount_point.file_system.rmdir(&dir_path)?;
+        self.notify_path_event(&global_path, IN_DELETE | IN_ISDIR, 0);
+        self.notify_path_event(&global_path, IN_DELETE_SELF, 0);
+        Ok(())
+    }
+
+    pub fn rename(&mut self, old_path: &str, new_path: &str) -> Result<(), OpenFileError> {
+        let old_path = self.resolve_path(old_path, false)?;
+        let new_path = self.resolve_path(new_path, false)?;
+        self.check_parent_access(&old_path)?;
+        self.check_parent_access(&new_path)?;
+        let is_dir = matches!(
+            self.get_file_details_from_filepath(&old_path),
+            Some(d) if d.file_type == FileType::Directory
+        );
+        let (mount_point, old_file_path, new_file_path) =
+            self.get_writable_mount_point_for_filepaths_mut(&old_path, &new_path)?;
+        mount_point
+            .file_system
+            .rename(&old_file_path, &new_file_path)?;
+
+        self.move_cookie = self.move_cookie.wrapping_add(1);
+        let is_dir_mask = if is_dir { IN_ISDIR } else { 0 };
+        self.notify_path_event(&old_path, IN_MOVED_FROM | is_dir_mask, self.move_cookie);
+        self.notify_path_event(&new_path, IN_MOVED_TO | is_dir_mask, self.move_cookie);
+        self.notify_path_event(&old_path, IN_MOVE_SELF, 0);
+        self.anon_file_system.notify_path_move(&old_path, &new_path);
+        Ok(())
+    }
+
+    /// Creates symbolic link (the `target` is not checked).
+    pub fn symlink(&mut self, target: &str, link_path: &str) -> Result<(), OpenFileError> {
+        let global_path = self.resolve_path(link_path, false)?;
+        self.check_parent_access(&global_path)?;
+        let (mount_point, link_path) =
+            self.get_writable_mount_point_from_filepath_mut(&global_path)?;
+        mount_point.file_system.symlink(target, &link_path)?;
+        self.notify_path_event(&global_path, IN_CREATE, 0);
+        Ok(())
+    }
+
+    /// Creates special file with `mode` permissions (umask must be already applied).
+    ///
+    /// Only root can create device files.
+    pub fn mknod(
+        &mut self,
+        file_path: &str,
+        file_type: FileType,
+        mode: u32,
+        device: u64,
+    ) -> Result<(), OpenFileError> {
+        if euid() != 0 && matches!(file_type, FileType::CharacterDevice | FileType::BlockDevice) {
+            return Err(OpenFileError::NoPermission);
+        }
+
+        let global_path = self.resolve_path(file_path, false)?;
+        self.check_parent_access(&global_path)?;
+        let (mount_point, file_path) =
+            self.get_writable_mount_point_from_filepath_mut(&global_path)?;
+        mount_point
+            .file_system
+            .mknod(&file_path, file_type, device)?;
+        mount_point.file_system.set_mode(&file_path, mode).ok();
+        self.notify_path_event(&global_path, IN_CREATE, 0);
+        Ok(())
+    }
+
+    /// Changes permission bits of the file (only its owner can do it).
+    pub fn set_mode(&mut self, file_path: &str, mode: u32) -> Result<(), OpenFileError> {
+        let global_path = self.resolve_path(file_path, true)?;
+        let file_details = self
+            .get_file_details_from_filepath(&global_path)
+            .ok_or(OpenFileError::NoSuchFileOrDirectory)?;
+        if euid() != 0 && file_details.uid != euid() {
+            return Err(OpenFileError::NoPermission);
+        }
+
+        let (mount_point, file_path) =
+            self.get_writable_mount_point_from_filepath_mut(&global_path)?;
+        mount_point
+            .file_system
+            .set_mode(&file_path, mode & 0o7777)?;
+        self.notify_attrib_event(&global_path, &file_details);
+        Ok(())
+    }
+
+    /// Changes owner and group of the file (`None` keeps the current one).
+    ///
+    /// Only root can change the owner, the owner can change the group to its own group.
+    pub fn set_owner(
+        &mut self,
+        file_path: &str,
+        uid: Option<u32>,
+        gid: Option<u32>,
+        follow_link: bool,
+    ) -> Result<(), OpenFileError> {
+        let global_path = self.resolve_path(file_path, follow_link)?;
+        let file_details = self
+            .get_file_details_from_filepath(&global_path)
+            .ok_or(OpenFileError::NoSuchFileOrDirectory)?;
+        let uid = uid.unwrap_or(file_details.uid);
+        let gid = gid.unwrap_or(file_details.gid);
+        if euid() != 0
+            && (uid != file_details.uid
+                || gid != file_details.gid && (file_details.uid != euid() || gid != egid()))
+        {
+            return Err(OpenFileError::NoPermission);
+        }
+
+        let (mount_point, file_path) =
+            self.get_writable_mount_point_from_filepath_mut(&global_path)?;
+        mount_point.file_system.set_owner(&file_path, uid, gid)?;
+        self.notify_attrib_event(&global_path, &file_details);
+        Ok(())
+    }
+
+    /// Checks access of the user to the file (`F_OK` only checks that it exists).
+    pub fn check_access(
+        &mut self,
+        file_path: &str,
+        access: AccessMode,
+    ) -> Result<(), OpenFileError> {
+        let file_path = self.resolve_path(file_path, true)?;
+        let file_details = self
+            .get_file_details_from_filepath(&file_path)
+            .ok_or(OpenFileError::NoSuchFileOrDirectory)?;
+        if file_details.is_accessible(euid(), egid(), access) {
+            Ok(())
         } else {
-            Err(OpenFileError::FileSystemNotMounted)
+            Err(OpenFileError::AccessDenied)
         }
     }
 
-    pub fn get_file_info(&mut self, fd: i32) -> Option<FileInfo> {
-        let mut file_path = String::new();
-        let mut file_status_flags = 0;
+    /// Checks that entries can be added to or removed from the parent directory of the file.
+    fn check_parent_access(&mut self, file_path: &str) -> Result<(), OpenFileError> {
+        let parent_path = match Path::new(file_path).parent() {
+            Some(parent_path) => parent_path.to_str().unwrap().to_string(),
+            None => return Ok(()),
+        };
+        match self.get_file_details_from_filepath(&parent_path) {
+            Some(file_details)
+                if !file_details.is_accessible(
+                    euid(),
+                    egid(),
+                    AccessMode::WRITE | AccessMode::EXECUTE,
+                ) =>
+            {
+                Err(OpenFileError::AccessDenied)
+            }
+            // missing parent is reported by the file system
+            _ => Ok(()),
+        }
+    }
 
-        if let Some(file_data) = self.file_data.get(&fd) {
-            file_path = file_data.file_path.clone();
-            file_status_flags = file_data.file_status_flags;
+    /// Passes the event of the file to inotify instances.
+    fn notify_path_event(&mut self, file_path: &str, mask: u32, cookie: u32) {
+        self.anon_file_system
+            .notify_path_event(file_path, mask, cookie);
+    }
+
+    /// Passes the event of the opened file to inotify instances.
+    fn notify_fd_event(&mut self, fd: i32, mask: u32) {
+        if let Some(file_data) = self.file_of(fd).and_then(|file| self.file_data.get(&file)) {
+            let file_path = file_data.file_path.clone();
+            self.notify_path_event(&file_path, mask, 0);
         }
+    }
+
+    fn notify_attrib_event(&mut self, file_path: &str, file_details: &FileDetails) {
+        let mask = if file_details.file_type == FileType::Directory {
+            IN_ATTRIB | IN_ISDIR
+        } else {
+            IN_ATTRIB
+        };
+        self.notify_path_event(file_path, mask, 0);
+    }
+
+    fn get_file_details_from_filepath(&mut self, file_path: &str) -> Option<FileDetails> {
+        self.get_file_info_from_filepath(file_path)
+            .map(|file_info| file_info.file_details)
+    }
+
+    fn get_writable_mount_point_from_filepath_mut(
+        &mut self,
+        file_path: &str,
+    ) -> Result<(&mut MountPoint, String), OpenFileError> {
+        match self.get_mount_point_from_filepath_mut(file_path) {
+            Some((mount_point, _)) if mount_point.is_read_only => {
+                Err(OpenFileError::ReadOnlyFileSystem)
+            }
+            Some(res) => Ok(res),
+            None => Err(OpenFileError::FileSystemNotMounted),
+        }
+    }
+
+    /// Returns mount point of both paths (they can not be on different file systems).
+    fn get_writable_mount_point_for_filepaths_mut(
+        &mut self,
+        old_path: &str,
+        new_path: &str,
+    ) -> Result<(&mut MountPoint, String, String), OpenFileError> {
+        let (mount_point, old_file_path) =
+            self.get_writable_mount_point_from_filepath_mut(old_path)?;
+        // the longest mount point must match also the new path
+        let mount_point_path = mount_point.mount_point.clone();
+        let new_mount_point_path = self
+            .get_mount_point_from_filepath_mut(new_path)
+            .map(|(mount_point, _)| mount_point.mount_point.clone());
+        if new_mount_point_path != Some(mount_point_path) {
+            return Err(OpenFileError::CrossDeviceLink);
+        }
+        let (mount_point, _) = self.get_mount_point_from_filepath_mut(old_path).unwrap();
+        let new_file_path = mount_point.translate_path(new_path).unwrap();
+        Ok((mount_point, old_file_path, new_file_path))
+    }
+
+    pub fn get_file_info(&mut self, fd: i32) -> Option<FileInfo> {
+        let file = self.file_of(fd)?;
+        let file_path = self
+            .file_data
+            .get(&file)
+            .map(|file_data| file_data.file_path.clone())
+            .unwrap_or_default();
+        let file_status_flags = self
+            .get_fd_entry_mut(fd)
+            .map_or(0, |fd_entry| fd_entry.flags);
 
-        if let Some(mount_point) = self.get_mount_point_mut(fd) {
-            if let Some(file_details) = mount_point.file_system.get_file_details(fd) {
+        if let Some((mount_point, file)) = self.get_open_file_mut(fd) {
+            if let Some(file_details) = mount_point.file_system.get_file_details(file) {
                 let inode = self.get_inode_for_filepath(file_path.clone());
                 Some(FileInfo {
                     file_details,
@@ -203,9 +1045,10 @@ impl MountFileSystem {
         }
     }
 
+    /// Returns information about the file (symbolic links are not followed).
     pub fn get_file_info_from_filepath(&mut self, file_path: &str) -> Option<FileInfo> {
         let file_path = self.path_convert_to_absolute(file_path);
-        if let Ok(fd) = self.open(&file_path, OpenFileFlags::READ) {
+        if let Ok(fd) = self.open(&file_path, OpenFileFlags::READ | OpenFileFlags::NO_FOLLOW) {
             let res = self.get_file_info(fd);
             self.close(fd).unwrap();
             res
@@ -214,61 +1057,123 @@ impl MountFileSystem {
         }
     }
 
+    /// Sets file descriptor flags (`FD_CLOEXEC`) of the current process.
     pub fn set_file_status_flags(&mut self, fd: i32, status_flags: u32) -> Result<(), ()> {
-        if let Some(mut file_data) = self.file_data.get_mut(&fd) {
-            file_data.file_status_flags = status_flags;
-            Ok(())
-        } else {
-            Err(())
+        let fd_entry = self.get_fd_entry_mut(fd).ok_or(())?;
+        fd_entry.flags = status_flags;
+        Ok(())
+    }
+
+    fn get_fd_entry_mut(&mut self, fd: i32) -> Option<&mut FdEntry> {
+        self.fd_tables.get_mut(&self.current_pid?)?.get_mut(&fd)
+    }
+
+    /// Checks if the open file cannot be changed (read-only file or file system).
+    pub fn is_read_only(&mut self, fd: i32) -> bool {
+        match self.get_open_file_mut(fd) {
+            Some((mount_point, file)) => {
+                mount_point.is_read_only
+                    || mount_point
+                        .file_system
+                        .get_file_details(file)
+                        .is_none_or(|file_details| file_details.is_readonly)
+            }
+            None => true,
         }
     }
 
+    pub fn is_nonblocking(&self, fd: i32) -> bool {
+        self.file_of(fd)
+            .and_then(|file| self.file_data.get(&file))
+            .map(|file_data| file_data.is_nonblocking)
+            .unwrap_or(false)
+    }
+
+    pub fn set_nonblocking(&mut self, fd: i32, is_nonblocking: bool) -> Result<(), ()> {
+        let file = self.file_of(fd).ok_or(())?;
+        let file_data = self.file_data.get_mut(&file).ok_or(())?;
+        file_data.is_nonblocking = is_nonblocking;
+        Ok(())
+    }
+
     pub fn is_open(&self, fd: i32) -> bool {
-        self.mount_points
-            .iter()
-            .any(|mp| mp.file_system.is_open(fd))
+        self.get_mount_point(fd).is_some()
     }
 
     pub fn get_length(&mut self, fd: i32) -> u64 {
-        if let Some(mount_point) = self.get_mount_point_mut(fd) {
-            mount_point.file_system.get_length(fd)
+        if let Some((mount_point, file)) = self.get_open_file_mut(fd) {
+            mount_point.file_system.get_length(file)
         } else {
             0
         }
     }
 
     pub fn stream_position(&mut self, fd: i32) -> Result<u64, ()> {
-        if let Some(mount_point) = self.get_mount_point_mut(fd) {
-            mount_point.file_system.stream_position(fd)
+        if let Some((mount_point, file)) = self.get_open_file_mut(fd) {
+            mount_point.file_system.stream_position(file)
         } else {
             Err(())
         }
     }
 
     pub fn seek(&mut self, fd: i32, pos: SeekFrom) -> Result<u64, ()> {
-        if let Some(mount_point) = self.get_mount_point_mut(fd) {
-            mount_point.file_system.seek(fd, pos)
+        if let Some((mount_point, file)) = self.get_open_file_mut(fd) {
+            mount_point.file_system.seek(file, pos)
         } else {
             Err(())
         }
     }
 
+    /// Returns error number of the failed `seek()` (pipes, sockets and devices are streams).
+    pub fn seek_error(&mut self, fd: i32) -> i32 {
+        let Some((mount_point, file)) = self.get_open_file_mut(fd) else {
+            return -9i32; // -EBADF
+        };
+        let file_system = &mut mount_point.file_system;
+        let is_stream = matches!(
+            file_system.file_system_type(),
+            FileSystemType::Stream | FileSystemType::Anon
+        ) || file_system
+            .get_file_details(file)
+            .is_some_and(|file_details| {
+                matches!(
+                    file_details.file_type,
+                    FileType::NamedPipe | FileType::Socket | FileType::CharacterDevice
+                )
+            });
+        if is_stream {
+            -29i32 // -ESPIPE
+        } else {
+            -22i32 // -EINVAL
+        }
+    }
+
     pub fn read(&mut self, fd: i32, content: &mut [u8]) -> Result<u64, ()> {
-        if let Some(mount_point) = self.get_mount_point_mut(fd) {
-            mount_point.file_system.read(fd, content)
+        let res = if let Some((mount_point, file)) = self.get_open_file_mut(fd) {
+            mount_point.file_system.read(file, content)
         } else {
             Err(())
+        };
+
+        // the file could be modified through the shared mapping
+        if let Ok(len) = res {
+            if let Some(shared_memory) = self.find_shared_memory(fd) {
+                let pos = self.stream_position(fd).unwrap() - len;
+                shared_memory.read(pos as usize, &mut content[0..len as usize]);
+            }
         }
+
+        res
     }
 
     pub fn read_all(&mut self, fd: i32, content: &mut [u8]) -> Result<(), ()> {
-        if let Some(mount_point) = self.get_mount_point_mut(fd) {
+        if let Some((mount_point, file)) = self.get_open_file_mut(fd) {
             let len = content.len();
             let mut bytes_to_read = len;
             while bytes_to_read > 0 {
                 match mount_point
                     .file_system
-                    .read(fd, &mut content[len - bytes_to_read..])
+                    .read(file, &mut content[len - bytes_to_read..])
                 {
                     Ok(bytes) => bytes_to_read -= bytes as usize,
                     Err(e) => return Err(e),
@@ -281,12 +1186,17 @@ impl MountFileSystem {
     }
 
     pub fn write(&mut self, fd: i32, content: &[u8]) -> Result<u64, ()> {
-        if let Some(mount_point) = self.get_mount_point_mut(fd) {
+        if let Some((mount_point, file)) = self.get_open_file_mut(fd) {
             if mount_point.is_read_only {
                 log::warn!("skipped writing to read only file system");
                 Err(())
             } else {
-                mount_point.file_system.write(fd, content)
+                let res = mount_point.file_system.write(file, content);
+                if let Ok(len) = res {
+                    self.update_shared_memory(fd, &content[0..len as usize]);
+                    self.notify_fd_event(fd, IN_MODIFY);
+                }
+                res
             }
         } else {
             Err(())
@@ -294,7 +1204,7 @@ impl MountFileSystem {
     }
 
     pub fn write_all(&mut self, fd: i32, content: &[u8]) -> Result<(), ()> {
-        if let Some(mount_point) = self.get_mount_point_mut(fd) {
+        if let Some((mount_point, file)) = self.get_open_file_mut(fd) {
             if mount_point.is_read_only {
                 log::warn!("skipped writing to read only file system");
                 Err(())
@@ -304,12 +1214,14 @@ impl MountFileSystem {
                 while bytes_to_write > 0 {
                     match mount_point
                         .file_system
-                        .write(fd, &content[len - bytes_to_write..])
+                        .write(file, &content[len - bytes_to_write..])
                     {
                         Ok(bytes) => bytes_to_write -= bytes as usize,
                         Err(e) => return Err(e),
                     }
                 }
+                self.update_shared_memory(fd, content);
+                self.notify_fd_event(fd, IN_MODIFY);
                 Ok(())
             }
         } else {
@@ -318,11 +1230,182 @@ impl MountFileSystem {
     }
 
     pub fn ftruncate(&mut self, fd: i32, length: u32) -> Result<(), ()> {
-        if let Some(mount_point) = self.get_mount_point_mut(fd) {
-            mount_point.file_system.truncate(fd, length)
+        let res = if let Some((mount_point, file)) = self.get_open_file_mut(fd) {
+            if mount_point.is_read_only {
+                log::warn!("skipped truncating file on read only file system");
+                return Err(());
+            }
+            mount_point.file_system.truncate(file, length)
         } else {
             Err(())
+        };
+
+        // the part of the shared mapping behind the end of file is discarded
+        if res.is_ok() {
+            if let Some(shared_memory) = self.find_shared_memory(fd) {
+                shared_memory.clear_from(length as usize);
+            }
+            self.notify_fd_event(fd, IN_MODIFY);
         }
+
+        res
+    }
+
+    /// Returns memory for `MAP_SHARED` mapping of the file (at least `size` bytes long).
+    ///
+    /// All mappings of the same file use the same memory as long as any of them exists.
+    pub fn get_shared_memory(&mut self, fd: i32, size: usize) -> Option<Arc<SharedMemory>> {
+        let file_path = self.file_data.get(&self.file_of(fd)?)?.file_path.clone();
+
+        // memory of the device is provided by its model
+        let (mount_point, file) = self.get_open_file_mut(fd)?;
+        if let Some(device_memory) = mount_point.file_system.mmap(file, size) {
+            self.mapped_devices.insert(file_path);
+            return Some(device_memory);
+        }
+
+        if let Some(shared_memory) = self.find_shared_memory(fd) {
+            if shared_memory.len() >= size {
+                return Some(shared_memory);
+            }
+
+            // existing memory cannot be resized as it is mapped into the guest
+            log::warn!(
+                "shared mapping of {} extended to {:#x} bytes, older mappings will not be coherent",
+                file_path,
+                size
+            );
+            let _ = self.write_back_shared_memory(
+                &file_path,
+                &shared_memory,
+                0,
+                shared_memory.len() as u64,
+            );
+        }
+
+        let length = self.get_length(fd);
+        let shared_memory = Arc::new(SharedMemory::new(size.max(length as usize)));
+
+        // load file content
+        let file_pos = self.stream_position(fd).ok()?;
+        self.seek(fd, SeekFrom::Start(0)).ok()?;
+        let mut buf = vec![0u8; length as usize];
+        let res = self.read_all(fd, &mut buf);
+        self.seek(fd, SeekFrom::Start(file_pos)).ok()?;
+        res.ok()?;
+        shared_memory.write(0, &buf);
+
+        self.shared_memory
+            .insert(file_path, Arc::downgrade(&shared_memory));
+
+        Some(shared_memory)
+    }
+
+    /// Writes the range of the shared mapping memory back to the file
+    /// (the file is never extended).
+    pub fn write_back_shared_memory(
+        &mut self,
+        file_path: &str,
+        shared_memory: &SharedMemory,
+        offset: u64,
+        length: u64,
+    ) -> Result<(), ()> {
+        if self.mapped_devices.contains(file_path) {
+            return Ok(());
+        }
+
+        let fd = self.open(file_path, OpenFileFlags::WRITE).map_err(|_| ())?;
+
+        let file_length = self.get_length(fd);
+        let res = if offset < file_length {
+            let mut buf = vec![0u8; length.min(file_length - offset) as usize];
+            shared_memory.read(offset as usize, &mut buf);
+            self.seek(fd, SeekFrom::Start(offset))
+                .and_then(|_| self.write_all(fd, &buf))
+        } else {
+            Ok(())
+        };
+
+        self.close(fd).unwrap();
+
+        log::trace!(
+            "shared memory write back: {} [{:#x}..{:#x}] => {:?}",
+            file_path,
+            offset,
+            offset + length,
+            res
+        );
+
+        res
+    }
+
+    /// Returns readiness of the file descriptor.
+    pub fn poll(&mut self, fd: i32) -> PollEvents {
+        self.poll_nested(fd, 0)
+    }
+
+    fn poll_nested(&mut self, fd: i32, depth: usize) -> PollEvents {
+        if let Some(interests) = self.epoll_interests(fd) {
+            // epoll descriptor is readable when any of the watched descriptors is ready
+            // (deeper nesting than `epoll_ctl()` allows is never ready)
+            return if depth >= EPOLL_MAX_NESTS
+                || self
+                    .epoll_ready_events_nested(interests, 1, depth + 1)
+                    .is_empty()
+            {
+                PollEvents::NONE
+            } else {
+                PollEvents::IN
+            };
+        }
+
+        // `/proc/mounts` reports changes of the mounted file systems
+        let is_proc = self
+            .get_mount_point(fd)
+            .is_some_and(|mp| mp.file_system.file_system_type() == FileSystemType::Proc);
+        if is_proc {
+            let mount_state = self.get_mount_state();
+            let mount_point = self.get_mount_point_mut(fd).unwrap();
+            mount_point.file_system.set_mount_state(mount_state);
+        }
+
+        if let Some((mount_point, file)) = self.get_open_file_mut(fd) {
+            mount_point.file_system.poll(file)
+        } else {
+            PollEvents::NONE
+        }
+    }
+
+    /// Returns up to `max_events` file descriptors (with their events and user data)
+    /// from the epoll interest list that are ready.
+    pub fn epoll_ready_events(
+        &mut self,
+        interests: BTreeMap<i32, EpollInterest>,
+        max_events: usize,
+    ) -> Vec<(i32, u32, u64)> {
+        self.epoll_ready_events_nested(interests, max_events, 0)
+    }
+
+    fn epoll_ready_events_nested(
+        &mut self,
+        interests: BTreeMap<i32, EpollInterest>,
+        max_events: usize,
+        depth: usize,
+    ) -> Vec<(i32, u32, u64)> {
+        let mut res = Vec::new();
+        for (fd, interest) in interests {
+            if res.len() >= max_events {
+                break;
+            }
+
+            // EPOLLERR and EPOLLHUP are always reported
+            let events = self.poll_nested(fd, depth).bits()
+                & (interest.events | PollEvents::ERR.bits() | PollEvents::HUP.bits());
+            if events != 0 {
+                res.push((fd, events, interest.data));
+            }
+        }
+        res
     }
 
     pub fn ioctl(
@@ -332,8 +1415,8 @@ impl MountFileSystem {
         request: u32,
         addr: u32,
     ) -> i32 {
-        if let Some(mount_point) = self.get_mount_point_mut(fd) {
-            mount_point.file_system.ioctl(unicorn, fd, request, addr)
+        if let Some((mount_point, file)) = self.get_open_file_mut(fd) {
+            mount_point.file_system.ioctl(unicorn, file, request, addr)
         } else {
             -1i32
         }
@@ -341,12 +1424,35 @@ impl MountFileSystem {
 }
 
 impl MountFileSystem {
-    fn get_unique_fd(&self) -> i32 {
-        let mut fd = 0i32;
-        while let Some(_) = self.get_mount_point(fd) {
-            fd += 1;
+    fn find_shared_memory(&mut self, fd: i32) -> Option<Arc<SharedMemory>> {
+        let file_path = &self.file_data.get(&self.file_of(fd)?)?.file_path;
+        let shared_memory = self.shared_memory.get(file_path)?.upgrade();
+        if shared_memory.is_none() {
+            // all mappings of the file were removed
+            self.shared_memory.remove(file_path);
+        }
+        shared_memory
+    }
+
+    fn update_shared_memory(&mut self, fd: i32, content: &[u8]) {
+        if let Some(shared_memory) = self.find_shared_memory(fd) {
+            let pos = self.stream_position(fd).unwrap() - content.len() as u64;
+            shared_memory.write(pos as usize, content);
         }
-        fd
+    }
+
+    /// Returns id for a new open file (unique in all mounted file systems).
+    fn get_unique_file(&self) -> i32 {
+        let mut file = 0i32;
+        while self
+            .mount_points
+            .iter()
+            .chain(self.detached_mount_points.iter())
+            .any(|mp| mp.file_system.is_open(file))
+        {
+            file += 1;
+        }
+        file
     }
 
     fn get_inode_for_filepath(&mut self, file_path: String) -> u64 {
@@ -376,3 +1482,175 @@ impl MountFileSystem {
         }
     }
 }
+
+///
+/// Access of a process to the shared `MountFileSystem`.
+///
+/// While it is locked, file descriptors passed to the file system are looked up
+/// in the file descriptor table of the process.
+///
+#[derive(Clone)]
+pub struct ProcessFileSystem {
+    pid: u32,
+    file_system: Arc<Mutex<MountFileSystem>>,
+}
+
+pub struct ProcessFileSystemGuard<'a> {
+    guard: MutexGuard<'a, MountFileSystem>,
+}
+
+impl ProcessFileSystem {
+    pub fn new(pid: u32, file_system: Arc<Mutex<MountFileSystem>>) -> Self {
+        Self { pid, file_system }
+    }
+
+    pub fn lock(&self) -> LockResult<ProcessFileSystemGuard<'_>> {
+        let (mut guard, is_poisoned) = match self.file_system.lock() {
+            Ok(guard) => (guard, false),
+            Err(err) => (err.into_inner(), true),
+        };
+        guard.current_pid = Some(self.pid);
+        let guard = ProcessFileSystemGuard { guard };
+        if is_poisoned {
+            Err(PoisonError::new(guard))
+        } else {
+            Ok(guard)
+        }
+    }
+
+    /// File system shared by all processes.
+    pub fn shared(&self) -> Arc<Mutex<MountFileSystem>> {
+        self.file_system.clone()
+    }
+}
+
+impl Deref for ProcessFileSystemGuard<'_> {
+    type Target = MountFileSystem;
+
+    fn deref(&self) -> &MountFileSystem {
+        &self.guard
+    }
+}
+
+impl DerefMut for ProcessFileSystemGuard<'_> {
+    fn deref_mut(&mut self) -> &mut MountFileSystem {
+        &mut self.guard
+    }
+}
+
+impl Drop for ProcessFileSystemGuard<'_> {
+    fn drop(&mut self) {
+        self.guard.current_pid = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_system::TmpFileSystem;
+
+    fn tmp_mount_point(mount_point: &str) -> MountPoint {
+        MountPoint {
+            mount_point: mount_point.to_string(),
+            file_system: Box::new(TmpFileSystem::new()),
+            is_read_only: false,
+        }
+    }
+
+    fn create_file(file_system: &mut MountFileSystem, file_path: &str) {
+        let fd = file_system
+            .open(file_path, OpenFileFlags::WRITE | OpenFileFlags::CREATE)
+            .unwrap();
+        file_system.close(fd).unwrap();
+    }
+
+    fn mount_point_of(file_system: &mut MountFileSystem, file_path: &str) -> String {
+        let (mount_point, _) = file_system
+            .get_mount_point_from_filepath_mut(file_path)
+            .unwrap();
+        mount_point.mount_point.clone()
+    }
+
+    #[test]
+    fn test_innermost_mount_point() {
+        // not sorted by length
+        let mut file_system = MountFileSystem::new(vec![
+            tmp_mount_point("/"),
+            tmp_mount_point("/var/volatile"),
+            tmp_mount_point("/var/lib"),
+        ]);
+        assert_eq!(mount_point_of(&mut file_system, "/var/lib/a"), "/var/lib");
+        assert_eq!(mount_point_of(&mut file_system, "/var/lib"), "/var/lib");
+        assert_eq!(
+            mount_point_of(&mut file_system, "/var/volatile/a"),
+            "/var/volatile"
+        );
+        assert_eq!(mount_point_of(&mut file_system, "/var/library"), "/");
+        assert_eq!(mount_point_of(&mut file_system, "/etc"), "/");
+    }
+
+    #[test]
+    fn test_mount_point_must_be_directory() {
+        let mut file_system = MountFileSystem::new(vec![tmp_mount_point("/")]);
+        assert_eq!(
+            file_system.mount(tmp_mount_point("/media"), None),
+            Err(OpenFileError::NoSuchFileOrDirectory)
+        );
+        create_file(&mut file_system, "/file");
+        assert_eq!(
+            file_system.mount(tmp_mount_point("/file"), None),
+            Err(OpenFileError::NotDirectory)
+        );
+
+        file_system.mkdir("/media", 0o755).unwrap();
+        assert_eq!(file_system.mount(tmp_mount_point("/media"), None), Ok(()));
+        assert_eq!(
+            file_system.mount(tmp_mount_point("/media/"), None),
+            Err(OpenFileError::FileExists)
+        );
+    }
+
+    #[test]
+    fn test_nested_mounts() {
+        let mut file_system = MountFileSystem::new(vec![tmp_mount_point("/")]);
+        file_system.mkdir("/media", 0o755).unwrap();
+        file_system.mkdir("/media/sd", 0o755).unwrap();
+        file_system.mkdir("/media/sdcard", 0o755).unwrap();
+        create_file(&mut file_system, "/media/sd/root_file");
+
+        file_system
+            .mount(tmp_mount_point("/media/sd"), Some("/dev/sda1".to_string()))
+            .unwrap();
+        assert!(!file_system.exists_internal("/media/sd/root_file"));
+        assert_eq!(mount_point_of(&mut file_system, "/media/sdcard/a"), "/");
+
+        // the inner mount point is matched first even if it was mounted before the outer one
+        file_system.mkdir("/media/sd/usb", 0o755).unwrap();
+        file_system
+            .mount(tmp_mount_point("/media/sd/usb"), None)
+            .unwrap();
+        assert_eq!(
+            mount_point_of(&mut file_system, "/media/sd/usb/a"),
+            "/media/sd/usb"
+        );
+        assert_eq!(mount_point_of(&mut file_system, "/media/sd/a"), "/media/sd");
+
+        file_system.unmount("/media/sd/usb").unwrap();
+        assert_eq!(
+            mount_point_of(&mut file_system, "/media/sd/usb/a"),
+            "/media/sd"
+        );
+        file_system.unmount("/media/sd").unwrap();
+        assert!(file_system.exists_internal("/media/sd/root_file"));
+        assert!(!file_system.mount_devices.contains_key("/media/sd"));
+
+        assert_eq!(
+            file_system.unmount("/media/sd"),
+            Err(OpenFileError::InvalidArgument)
+        );
+        assert_eq!(
+            file_system.unmount("/media"),
+            Err(OpenFileError::InvalidArgument)
+        );
+    }
+}
@@ -1,11 +1,16 @@
 use crate::emulator::context::Context;
-use crate::file_system::file_info::{FileDetails, FileType};
-use crate::file_system::file_system::FileSystem;
-use crate::file_system::{CloseFileError, FileSystemType, OpenFileError, OpenFileFlags};
+use crate::emulator::users::{egid, euid};
+use crate::file_system::file_info::{FileDetails, FileTimes, FileType};
+use crate::file_system::interface::FileSystem;
+use crate::file_system::{
+    CloseFileError, FileSystemType, OpenFileError, OpenFileFlags, PollEvents,
+};
 use std::collections::HashMap;
-use std::fs::{File, OpenOptions};
+use std::fs::{self, DirBuilder, File, OpenOptions};
 use std::io::{Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, MetadataExt, OpenOptionsExt, PermissionsExt};
 use std::path::PathBuf;
+use std::time::{Duration, UNIX_EPOCH};
 use unicorn_engine::Unicorn;
 
 struct OpenedFileData {
@@ -31,15 +36,20 @@ impl FileSystem for OsFileSystem {
 
     fn exists(&mut self, file_path: &str) -> bool {
         let path = self.path_transform_to_real(file_path);
-        path.exists()
+        // links are resolved by the mount file system (dangling ones also exist)
+        path.symlink_metadata().is_ok()
     }
 
-    fn mkdir(&mut self, _file_path: &str, _mode: u32) -> Result<(), OpenFileError> {
-        Err(OpenFileError::NoPermission)
+    fn mkdir(&mut self, file_path: &str, mode: u32) -> Result<(), OpenFileError> {
+        let full_path_name = self.path_transform_to_real(file_path);
+        DirBuilder::new()
+            .mode(mode & 0o7777)
+            .create(full_path_name)
+            .map_err(convert_io_error)
     }
 
     fn read_dir(&mut self, dir_path: &str) -> Result<Vec<String>, ()> {
-        let full_path_name = self.path_transform_to_real(&dir_path);
+        let full_path_name = self.path_transform_to_real(dir_path);
 
         if full_path_name.is_dir() {
             if let Ok(read_dir) = full_path_name.read_dir() {
@@ -66,7 +76,7 @@ impl FileSystem for OsFileSystem {
         flags: OpenFileFlags,
         fd: i32,
     ) -> Result<(), OpenFileError> {
-        let full_path_name = self.path_transform_to_real(&file_path);
+        let full_path_name = self.path_transform_to_real(file_path);
 
         log::debug!(
             "Opening: {}, flags: {:?}",
@@ -74,46 +84,122 @@ impl FileSystem for OsFileSystem {
             flags
         );
 
-        let open_options = self.get_open_options(flags);
+        let mut open_options = self.get_open_options(flags);
+        if flags.contains(OpenFileFlags::NO_FOLLOW) && full_path_name.is_symlink() {
+            // opens the link itself, only its details can be read
+            open_options
+                .read(true)
+                .write(false)
+                .append(false)
+                .truncate(false)
+                .create(false)
+                .create_new(false)
+                .custom_flags(libc::O_PATH | libc::O_NOFOLLOW);
+        }
 
-        if let Ok(file) = open_options.open(full_path_name) {
-            let opened_file_data = OpenedFileData { file };
-            self.opened_files.insert(fd, opened_file_data);
-            Ok(())
-        } else {
-            Err(OpenFileError::NoSuchFileOrDirectory)
+        let file = open_options
+            .open(&full_path_name)
+            .map_err(convert_io_error)?;
+        if flags.contains(OpenFileFlags::DIRECTORY) && !full_path_name.is_dir() {
+            return Err(OpenFileError::NotDirectory);
         }
+        self.opened_files.insert(fd, OpenedFileData { file });
+        Ok(())
     }
 
     fn close(&mut self, fd: i32) -> Result<(), CloseFileError> {
-        if let Some(_) = self.opened_files.remove(&fd) {
+        if self.opened_files.remove(&fd).is_some() {
             Ok(())
         } else {
             Err(CloseFileError::FileNotOpened)
         }
     }
 
-    fn link(&mut self, _old_path: &str, _new_path: &str) -> Result<(), OpenFileError> {
-        Err(OpenFileError::NoPermission)
+    fn link(&mut self, old_path: &str, new_path: &str) -> Result<(), OpenFileError> {
+        let old_path = self.path_transform_to_real(old_path);
+        let new_path = self.path_transform_to_real(new_path);
+        fs::hard_link(old_path, new_path).map_err(convert_io_error)
+    }
+
+    fn unlink(&mut self, file_path: &str) -> Result<(), OpenFileError> {
+        let full_path_name = self.path_transform_to_real(file_path);
+        if full_path_name.is_dir() && !full_path_name.is_symlink() {
+            return Err(OpenFileError::IsDirectory);
+        }
+        fs::remove_file(full_path_name).map_err(convert_io_error)
+    }
+
+    fn rmdir(&mut self, dir_path: &str) -> Result<(), OpenFileError> {
+        let full_path_name = self.path_transform_to_real(dir_path);
+        fs::remove_dir(full_path_name).map_err(convert_io_error)
+    }
+
+    fn rename(&mut self, old_path: &str, new_path: &str) -> Result<(), OpenFileError> {
+        let old_path = self.path_transform_to_real(old_path);
+        let new_path = self.path_transform_to_real(new_path);
+        fs::rename(old_path, new_path).map_err(convert_io_error)
+    }
+
+    fn symlink(&mut self, target: &str, link_path: &str) -> Result<(), OpenFileError> {
+        // the target is a guest path, it is resolved by the mount file system
+        let full_path_name = self.path_transform_to_real(link_path);
+        std::os::unix::fs::symlink(target, full_path_name).map_err(convert_io_error)
+    }
+
+    fn read_link(&mut self, file_path: &str) -> Option<String> {
+        let full_path_name = self.path_transform_to_real(file_path);
+        std::fs::read_link(full_path_name)
+            .ok()
+            .map(|target| target.to_str().unwrap().to_string())
+    }
+
+    fn set_mode(&mut self, file_path: &str, mode: u32) -> Result<(), OpenFileError> {
+        let full_path_name = self.path_transform_to_real(file_path);
+        fs::set_permissions(full_path_name, fs::Permissions::from_mode(mode & 0o7777))
+            .map_err(convert_io_error)
     }
 
-    fn unlink(&mut self, _file_path: &str) -> Result<(), OpenFileError> {
-        Err(OpenFileError::NoPermission)
+    fn set_owner(&mut self, file_path: &str, uid: u32, gid: u32) -> Result<(), OpenFileError> {
+        // fails if the emulator is not allowed to change the owner on the host
+        let full_path_name = self.path_transform_to_real(file_path);
+        let uid = map_id(uid, euid(), unsafe { libc::geteuid() });
+        let gid = map_id(gid, egid(), unsafe { libc::getegid() });
+        std::os::unix::fs::lchown(full_path_name, Some(uid), Some(gid)).map_err(convert_io_error)
     }
 
     fn get_file_details(&mut self, fd: i32) -> Option<FileDetails> {
         if let Some(file) = self.opened_files.get_mut(&fd).map(|el| &mut el.file) {
             let metadata = file.metadata().unwrap();
+            let file_type = metadata.file_type();
             Some(FileDetails {
-                file_type: if metadata.is_dir() {
+                file_type: if file_type.is_dir() {
                     FileType::Directory
-                } else if metadata.is_symlink() {
+                } else if file_type.is_symlink() {
                     FileType::Link
+                } else if file_type.is_char_device() {
+                    FileType::CharacterDevice
+                } else if file_type.is_block_device() {
+                    FileType::BlockDevice
+                } else if file_type.is_fifo() {
+                    FileType::NamedPipe
+                } else if file_type.is_socket() {
+                    FileType::Socket
                 } else {
                     FileType::File
                 },
                 is_readonly: metadata.permissions().readonly(),
                 length: metadata.len(),
+                mode: metadata.mode() & 0o7777,
+                uid: map_id(metadata.uid(), unsafe { libc::geteuid() }, euid()),
+                gid: map_id(metadata.gid(), unsafe { libc::getegid() }, egid()),
+                device: metadata.rdev(),
+                links_count: metadata.nlink() as u32,
+                times: Some(FileTimes {
+                    accessed: metadata.accessed().unwrap(),
+                    modified: metadata.modified().unwrap(),
+                    changed: UNIX_EPOCH
+                        + Duration::new(metadata.ctime() as u64, metadata.ctime_nsec() as u32),
+                }),
             })
         } else {
             None
@@ -168,8 +254,21 @@ impl FileSystem for OsFileSystem {
         }
     }
 
-    fn truncate(&mut self, _fd: i32, _length: u32) -> Result<(), ()> {
-        Err(())
+    fn truncate(&mut self, fd: i32, length: u32) -> Result<(), ()> {
+        if let Some(file) = self.opened_files.get_mut(&fd).map(|el| &mut el.file) {
+            file.set_len(length as u64).map_err(|_| ())
+        } else {
+            Err(())
+        }
+    }
+
+    fn poll(&mut self, fd: i32) -> PollEvents {
+        if self.is_open(fd) {
+            // regular files are always ready
+            PollEvents::IN | PollEvents::OUT
+        } else {
+            PollEvents::NONE
+        }
     }
 
     fn ioctl(
@@ -192,8 +291,8 @@ impl OsFileSystem {
     }
 
     fn path_transform_to_real(&self, guest_path: &str) -> PathBuf {
-        if guest_path.starts_with("/") {
-            self.host_path.join(&guest_path[1..])
+        if let Some(relative_path) = guest_path.strip_prefix('/') {
+            self.host_path.join(relative_path)
         } else {
             panic!(
                 "Only mount file system handle relative paths: {}!",
@@ -219,3 +318,29 @@ impl OsFileSystem {
         open_options
     }
 }
+
+/// Files of the user running the emulator belong to the emulated user (and vice versa).
+fn map_id(id: u32, from_id: u32, to_id: u32) -> u32 {
+    if id == from_id {
+        to_id
+    } else {
+        id
+    }
+}
+
+/// Converts error of the host file system operation.
+fn convert_io_error(err: std::io::Error) -> OpenFileError {
+    match err.raw_os_error() {
+        Some(libc::ENOENT) => OpenFileError::NoSuchFileOrDirectory,
+        Some(libc::EEXIST) => OpenFileError::FileExists,
+        Some(libc::ENOTDIR) => OpenFileError::NotDirectory,
+        Some(libc::EISDIR) => OpenFileError::IsDirectory,
+        Some(libc::ENOTEMPTY) => OpenFileError::DirectoryNotEmpty,
+        Some(libc::ELOOP) => OpenFileError::TooManySymbolicLinks,
+        Some(libc::EROFS) => OpenFileError::ReadOnlyFileSystem,
+        Some(libc::EXDEV) => OpenFileError::CrossDeviceLink,
+        Some(libc::EACCES) => OpenFileError::AccessDenied,
+        Some(libc::EINVAL) => OpenFileError::InvalidArgument,
+        _ => OpenFileError::NoPermission,
+    }
+}
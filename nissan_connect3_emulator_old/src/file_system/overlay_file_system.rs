@@ -0,0 +1,624 @@
+use crate::emulator::context::Context;
+use crate::file_system::file_info::FileDetails;
+use crate::file_system::{
+    CloseFileError, FileSystem, FileSystemType, FileType, OpenFileError, OpenFileFlags, PollEvents,
+};
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use unicorn_engine::Unicorn;
+
+// prefix of the whiteout files that hide removed files of the lower layer (as in aufs)
+const WHITEOUT_PREFIX: &str = ".wh.";
+// marker of the directory that hides the whole directory of the lower layer
+const OPAQUE_MARKER: &str = ".wh..wh..opq";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Layer {
+    Lower,
+    Upper,
+}
+
+///
+/// File system that stacks writable upper layer over read-only lower layer.
+///
+/// Files of the lower layer are copied to the upper layer when they are opened
+/// for writing, so the lower layer is never modified. Removed files are hidden by
+/// whiteouts stored in the upper layer (`.wh.<name>` files), directories replacing
+/// removed ones are marked as opaque (`.wh..wh..opq`). All changes are files of the upper
+/// layer, so they persist if the upper layer does (like a host directory).
+///
+pub struct OverlayFileSystem {
+    lower: Box<dyn FileSystem + Send + Sync>,
+    upper: Box<dyn FileSystem + Send + Sync>,
+    opened_files: HashMap<i32, Layer>,
+}
+
+impl OverlayFileSystem {
+    pub fn new(
+        lower: Box<dyn FileSystem + Send + Sync>,
+        upper: Box<dyn FileSystem + Send + Sync>,
+    ) -> Self {
+        Self {
+            lower,
+            upper,
+            opened_files: HashMap::new(),
+        }
+    }
+
+    fn layer_mut(&mut self, layer: Layer) -> &mut Box<dyn FileSystem + Send + Sync> {
+        match layer {
+            Layer::Lower => &mut self.lower,
+            Layer::Upper => &mut self.upper,
+        }
+    }
+
+    fn opened_layer_mut(&mut self, fd: i32) -> Option<&mut Box<dyn FileSystem + Send + Sync>> {
+        let layer = *self.opened_files.get(&fd)?;
+        Some(self.layer_mut(layer))
+    }
+
+    /// Checks if the file of the lower layer is hidden (it or any of its parents was removed).
+    fn is_whited_out(&mut self, file_path: &str) -> bool {
+        let paths: Vec<&str> = ancestors(file_path).collect();
+        for (index, path) in paths.iter().enumerate() {
+            if self.upper.exists(&whiteout_path(path)) {
+                return true;
+            }
+            // opaque directory hides the lower files below it
+            if index > 0 && self.upper.exists(&format!("{}/{}", path, OPAQUE_MARKER)) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Hides the file of the lower layer.
+    fn white_out(&mut self, file_path: &str) -> Result<(), OpenFileError> {
+        if self.is_whited_out(file_path) || !self.lower.exists(file_path) {
+            return Ok(());
+        }
+        let whiteout_path = whiteout_path(file_path);
+        self.create_upper_parents(&whiteout_path);
+        self.create_upper_file(&whiteout_path)
+    }
+
+    /// Removes the whiteout of the file created in the upper layer, the new directory
+    /// becomes opaque, so the files of the removed one stay hidden.
+    fn remove_whiteout(&mut self, file_path: &str) {
+        let whiteout_path = whiteout_path(file_path);
+        if self.upper.exists(&whiteout_path) {
+            self.upper.unlink(&whiteout_path).ok();
+            if self.upper.read_dir(file_path).is_ok() {
+                self.create_upper_file(&format!("{}/{}", file_path, OPAQUE_MARKER))
+                    .ok();
+            }
+        }
+    }
+
+    /// Creates empty file in the upper layer (the `i32::MAX` fd is used temporarily).
+    fn create_upper_file(&mut self, file_path: &str) -> Result<(), OpenFileError> {
+        self.upper.open(
+            file_path,
+            OpenFileFlags::WRITE | OpenFileFlags::CREATE | OpenFileFlags::TRUNC,
+            i32::MAX,
+        )?;
+        self.upper.close(i32::MAX).unwrap();
+        Ok(())
+    }
+
+    /// Returns layer that provides the file (upper layer hides the lower one).
+    fn find_layer(&mut self, file_path: &str) -> Option<Layer> {
+        let file_name = file_path.rsplit('/').next().unwrap_or_default();
+        if file_name.starts_with(WHITEOUT_PREFIX) {
+            // whiteouts are not visible
+            None
+        } else if self.upper.exists(file_path) {
+            Some(Layer::Upper)
+        } else if !self.is_whited_out(file_path) && self.lower.exists(file_path) {
+            Some(Layer::Lower)
+        } else {
+            None
+        }
+    }
+
+    /// Creates parent directories of the file in the upper layer.
+    fn create_upper_parents(&mut self, file_path: &str) {
+        let parents: Vec<&str> = ancestors(file_path).skip(1).collect();
+        for parent in parents.into_iter().rev() {
+            if !self.upper.exists(parent) {
+                // upper layer may store files without directories (like tmp-fs)
+                self.upper.mkdir(parent, 0o755).ok();
+            }
+        }
+    }
+
+    /// Copies the file from the lower layer to the upper layer (the `fd` is used temporarily).
+    fn copy_up(&mut self, file_path: &str, fd: i32) -> Result<(), OpenFileError> {
+        self.create_upper_parents(file_path);
+
+        // symbolic links are copied as links (they keep their own permissions)
+        if let Some(target) = self.lower.read_link(file_path) {
+            log::debug!("Copying up symbolic link to overlay: {}", file_path);
+            self.upper.symlink(&target, file_path)?;
+            return Ok(());
+        }
+
+        self.lower.open(
+            file_path,
+            OpenFileFlags::READ | OpenFileFlags::NO_FOLLOW,
+            fd,
+        )?;
+        let file_details = self.lower.get_file_details(fd);
+        let file_type = file_details.as_ref().map(|d| d.file_type.clone());
+        let mut data = vec![0u8; self.lower.get_length(fd) as usize];
+        let mut pos = 0;
+        while pos < data.len() {
+            match self.lower.read(fd, &mut data[pos..]) {
+                Ok(0) | Err(_) => break,
+                Ok(len) => pos += len as usize,
+            }
+        }
+        self.lower.close(fd).unwrap();
+
+        if file_type == Some(FileType::Directory) {
+            let mode = file_details.as_ref().map_or(0o755, |d| d.mode);
+            self.upper.mkdir(file_path, mode)?;
+        } else if let Some(
+            file_type @ (FileType::CharacterDevice | FileType::BlockDevice | FileType::NamedPipe),
+        ) = file_type
+        {
+            let device = file_details.as_ref().map_or(0, |d| d.device);
+            self.upper.mknod(file_path, file_type, device)?;
+        } else {
+            log::debug!("Copying up to overlay: {}", file_path);
+
+            self.upper.open(
+                file_path,
+                OpenFileFlags::WRITE | OpenFileFlags::CREATE | OpenFileFlags::TRUNC,
+                fd,
+            )?;
+            let mut written = 0;
+            while written < pos {
+                match self.upper.write(fd, &data[written..pos]) {
+                    Ok(0) | Err(_) => break,
+                    Ok(len) => written += len as usize,
+                }
+            }
+            self.upper.close(fd).unwrap();
+        }
+
+        // the copy keeps permissions and owner of the original file
+        if let Some(file_details) = file_details {
+            self.upper.set_mode(file_path, file_details.mode).ok();
+            self.upper
+                .set_owner(file_path, file_details.uid, file_details.gid)
+                .ok();
+        }
+        Ok(())
+    }
+}
+
+impl FileSystem for OverlayFileSystem {
+    fn support_file_paths(&self) -> bool {
+        true
+    }
+
+    fn file_system_type(&self) -> FileSystemType {
+        FileSystemType::Overlay
+    }
+
+    fn exists(&mut self, file_path: &str) -> bool {
+        self.find_layer(file_path).is_some()
+    }
+
+    fn mkdir(&mut self, file_path: &str, mode: u32) -> Result<(), OpenFileError> {
+        if self.find_layer(file_path).is_some() {
+            return Err(OpenFileError::FileExists);
+        }
+        self.create_upper_parents(file_path);
+        self.upper.mkdir(file_path, mode)?;
+        self.remove_whiteout(file_path);
+        Ok(())
+    }
+
+    fn read_dir(&mut self, dir_path: &str) -> Result<Vec<String>, ()> {
+        let upper_entries = self.upper.read_dir(dir_path);
+        let is_opaque = self.upper.exists(&format!(
+            "{}/{}",
+            dir_path.trim_end_matches('/'),
+            OPAQUE_MARKER
+        ));
+        let lower_entries = if is_opaque || self.is_whited_out(dir_path) {
+            Err(())
+        } else {
+            self.lower.read_dir(dir_path)
+        };
+        if upper_entries.is_err() && lower_entries.is_err() {
+            return Err(());
+        }
+
+        let upper_entries = upper_entries.unwrap_or_default();
+        let mut entries: Vec<String> = lower_entries
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|entry| {
+                !upper_entries.contains(entry)
+                    && !upper_entries.contains(&format!("{}{}", WHITEOUT_PREFIX, entry))
+            })
+            .collect();
+        entries.extend(
+            upper_entries
+                .into_iter()
+                .filter(|entry| !entry.starts_with(WHITEOUT_PREFIX)),
+        );
+        Ok(entries)
+    }
+
+    fn open(
+        &mut self,
+        file_path: &str,
+        flags: OpenFileFlags,
+        fd: i32,
+    ) -> Result<(), OpenFileError> {
+        let is_write = flags.intersects(
+            OpenFileFlags::WRITE
+                | OpenFileFlags::TRUNC
+                | OpenFileFlags::APPEND
+                | OpenFileFlags::TEMP_FILE,
+        );
+
+        let layer = match self.find_layer(file_path) {
+            Some(Layer::Lower) if is_write => {
+                if flags.contains(OpenFileFlags::CREATE | OpenFileFlags::EXCLUSIVE) {
+                    return Err(OpenFileError::FileExists);
+                }
+                self.copy_up(file_path, fd)?;
+                Layer::Upper
+            }
+            Some(layer) => layer,
+            None if flags.contains(OpenFileFlags::CREATE) => {
+                let parents: Vec<&str> = ancestors(file_path).skip(1).collect();
+                if parents
+                    .into_iter()
+                    .any(|parent| self.upper.exists(&whiteout_path(parent)))
+                {
+                    return Err(OpenFileError::NoSuchFileOrDirectory);
+                }
+                self.create_upper_parents(file_path);
+                self.remove_whiteout(file_path);
+                Layer::Upper
+            }
+            None => return Err(OpenFileError::NoSuchFileOrDirectory),
+        };
+
+        self.layer_mut(layer).open(file_path, flags, fd)?;
+        self.opened_files.insert(fd, layer);
+        Ok(())
+    }
+
+    fn close(&mut self, fd: i32) -> Result<(), CloseFileError> {
+        match self.opened_files.remove(&fd) {
+            Some(layer) => self.layer_mut(layer).close(fd),
+            None => Err(CloseFileError::FileNotOpened),
+        }
+    }
+
+    fn link(&mut self, old_path: &str, new_path: &str) -> Result<(), OpenFileError> {
+        if self.find_layer(new_path).is_some() {
+            return Err(OpenFileError::FileExists);
+        }
+        match self.find_layer(old_path) {
+            Some(Layer::Lower) => {
+                // any free fd works, the file is closed before returning
+                self.copy_up(old_path, i32::MAX)?;
+            }
+            Some(Layer::Upper) => {}
+            None => return Err(OpenFileError::NoSuchFileOrDirectory),
+        }
+        self.create_upper_parents(new_path);
+        self.upper.link(old_path, new_path)?;
+        self.remove_whiteout(new_path);
+        Ok(())
+    }
+
+    fn unlink(&mut self, file_path: &str) -> Result<(), OpenFileError> {
+        if self.find_layer(file_path).is_none() {
+            return Err(OpenFileError::NoSuchFileOrDirectory);
+        }
+        if self.upper.exists(file_path) {
+            self.upper.unlink(file_path)?;
+        }
+        self.white_out(file_path)
+    }
+
+    fn rmdir(&mut self, dir_path: &str) -> Result<(), OpenFileError> {
+        if self.find_layer(dir_path).is_none() {
+            return Err(OpenFileError::NoSuchFileOrDirectory);
+        }
+        if !self
+            .read_dir(dir_path)
+            .map_err(|_| OpenFileError::NotDirectory)?
+            .is_empty()
+        {
+            return Err(OpenFileError::DirectoryNotEmpty);
+        }
+        if self.upper.exists(dir_path) {
+            // the directory may contain whiteouts and the opaque marker only
+            for entry in self.upper.read_dir(dir_path).unwrap_or_default() {
+                self.upper
+                    .unlink(&format!("{}/{}", dir_path.trim_end_matches('/'), entry))?;
+            }
+            self.upper.rmdir(dir_path)?;
+        }
+        self.white_out(dir_path)
+    }
+
+    fn rename(&mut self, old_path: &str, new_path: &str) -> Result<(), OpenFileError> {
+        match self.find_layer(old_path) {
+            Some(Layer::Lower) => {
+                if self.lower.read_dir(old_path).is_ok() {
+                    // directories of the lower layer are not moved (like overlayfs
+                    // without `redirect_dir`), callers fall back to copying
+                    return Err(OpenFileError::CrossDeviceLink);
+                }
+                // any free fd works, the file is closed before returning
+                self.copy_up(old_path, i32::MAX)?;
+            }
+            Some(Layer::Upper) => {}
+            None => return Err(OpenFileError::NoSuchFileOrDirectory),
+        }
+        self.create_upper_parents(new_path);
+        self.upper.rename(old_path, new_path)?;
+        self.white_out(old_path)?;
+        self.remove_whiteout(new_path);
+        Ok(())
+    }
+
+    fn symlink(&mut self, target: &str, link_path: &str) -> Result<(), OpenFileError> {
+        if self.find_layer(link_path).is_some() {
+            return Err(OpenFileError::FileExists);
+        }
+        self.create_upper_parents(link_path);
+        self.upper.symlink(target, link_path)?;
+        self.remove_whiteout(link_path);
+        Ok(())
+    }
+
+    fn set_mode(&mut self, file_path: &str, mode: u32) -> Result<(), OpenFileError> {
+        match self.find_layer(file_path) {
+            Some(Layer::Lower) => self.copy_up(file_path, i32::MAX)?,
+            Some(Layer::Upper) => {}
+            None => return Err(OpenFileError::NoSuchFileOrDirectory),
+        }
+        self.upper.set_mode(file_path, mode)
+    }
+
+    fn set_owner(&mut self, file_path: &str, uid: u32, gid: u32) -> Result<(), OpenFileError> {
+        match self.find_layer(file_path) {
+            Some(Layer::Lower) => self.copy_up(file_path, i32::MAX)?,
+            Some(Layer::Upper) => {}
+            None => return Err(OpenFileError::NoSuchFileOrDirectory),
+        }
+        self.upper.set_owner(file_path, uid, gid)
+    }
+
+    fn get_file_details(&mut self, fd: i32) -> Option<FileDetails> {
+        let layer = *self.opened_files.get(&fd)?;
+        let mut file_details = self.layer_mut(layer).get_file_details(fd)?;
+        // lower files are writable through the copy-up
+        file_details.is_readonly = false;
+        Some(file_details)
+    }
+
+    fn is_open(&self, fd: i32) -> bool {
+        self.opened_files.contains_key(&fd)
+    }
+
+    fn get_length(&mut self, fd: i32) -> u64 {
+        self.opened_layer_mut(fd)
+            .map(|fs| fs.get_length(fd))
+            .unwrap_or(0)
+    }
+
+    fn stream_position(&mut self, fd: i32) -> Result<u64, ()> {
+        self.opened_layer_mut(fd).ok_or(())?.stream_position(fd)
+    }
+
+    fn seek(&mut self, fd: i32, pos: SeekFrom) -> Result<u64, ()> {
+        self.opened_layer_mut(fd).ok_or(())?.seek(fd, pos)
+    }
+
+    fn read(&mut self, fd: i32, content: &mut [u8]) -> Result<u64, ()> {
+        self.opened_layer_mut(fd).ok_or(())?.read(fd, content)
+    }
+
+    fn write(&mut self, fd: i32, content: &[u8]) -> Result<u64, ()> {
+        match self.opened_files.get(&fd) {
+            Some(Layer::Upper) => self.upper.write(fd, content),
+            // opened for reading only
+            _ => Err(()),
+        }
+    }
+
+    fn truncate(&mut self, fd: i32, length: u32) -> Result<(), ()> {
+        match self.opened_files.get(&fd) {
+            Some(Layer::Upper) => self.upper.truncate(fd, length),
+            _ => Err(()),
+        }
+    }
+
+    fn poll(&mut self, fd: i32) -> PollEvents {
+        self.opened_layer_mut(fd)
+            .map(|fs| fs.poll(fd))
+            .unwrap_or(PollEvents::NONE)
+    }
+
+    fn ioctl(&mut self, unicorn: &mut Unicorn<Context>, fd: i32, request: u32, addr: u32) -> i32 {
+        match self.opened_layer_mut(fd) {
+            Some(fs) => fs.ioctl(unicorn, fd, request, addr),
+            None => -1i32,
+        }
+    }
+
+    fn read_link(&mut self, file_path: &str) -> Option<String> {
+        let layer = self.find_layer(file_path)?;
+        self.layer_mut(layer).read_link(file_path)
+    }
+}
+
+/// Returns path of the whiteout that hides the file of the lower layer.
+fn whiteout_path(file_path: &str) -> String {
+    let file_path = file_path.trim_end_matches('/');
+    let (parent, name) = file_path.rsplit_once('/').unwrap_or(("", file_path));
+    format!("{}/{}{}", parent, WHITEOUT_PREFIX, name)
+}
+
+/// Iterates over the path and all its parents (without the root), e.g. `/a/b`, `/a`.
+fn ancestors(file_path: &str) -> impl Iterator<Item = &str> {
+    let file_path = file_path.trim_end_matches('/');
+    std::iter::successors(Some(file_path), |path| {
+        path.rfind('/').map(|index| &path[..index])
+    })
+    .filter(|path| !path.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_system::{OsFileSystem, TmpFileSystem};
+    use std::path::PathBuf;
+
+    fn write_file(file_system: &mut dyn FileSystem, file_path: &str, content: &[u8]) {
+        file_system
+            .open(
+                file_path,
+                OpenFileFlags::WRITE | OpenFileFlags::CREATE | OpenFileFlags::TRUNC,
+                1,
+            )
+            .unwrap();
+        assert_eq!(file_system.write(1, content), Ok(content.len() as u64));
+        file_system.close(1).unwrap();
+    }
+
+    fn read_file(file_system: &mut dyn FileSystem, file_path: &str) -> Vec<u8> {
+        file_system.open(file_path, OpenFileFlags::READ, 1).unwrap();
+        let mut content = vec![0u8; file_system.get_length(1) as usize];
+        file_system.read(1, &mut content).unwrap();
+        file_system.close(1).unwrap();
+        content
+    }
+
+    fn sorted_dir(file_system: &mut dyn FileSystem, dir_path: &str) -> Vec<String> {
+        let mut entries = file_system.read_dir(dir_path).unwrap();
+        entries.sort();
+        entries
+    }
+
+    /// Lower layer with `/etc/a`, `/etc/b` and `/etc/sub/c`.
+    fn lower() -> Box<dyn FileSystem + Send + Sync> {
+        let mut lower = TmpFileSystem::new();
+        lower.mkdir("/etc", 0o755).unwrap();
+        lower.mkdir("/etc/sub", 0o755).unwrap();
+        write_file(&mut lower, "/etc/a", b"lower a");
+        write_file(&mut lower, "/etc/b", b"lower b");
+        write_file(&mut lower, "/etc/sub/c", b"lower c");
+        Box::new(lower)
+    }
+
+    /// Host directory removed when the test ends.
+    struct ScratchDir(PathBuf);
+
+    impl ScratchDir {
+        fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("overlay-test-{}-{}", std::process::id(), name));
+            std::fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for ScratchDir {
+        fn drop(&mut self) {
+            std::fs::remove_dir_all(&self.0).ok();
+        }
+    }
+
+    #[test]
+    fn test_copy_up() {
+        let mut overlay = OverlayFileSystem::new(lower(), Box::new(TmpFileSystem::new()));
+        write_file(&mut overlay, "/etc/a", b"upper a");
+        write_file(&mut overlay, "/etc/new", b"new");
+
+        assert_eq!(read_file(&mut overlay, "/etc/a"), b"upper a");
+        assert_eq!(read_file(overlay.lower.as_mut(), "/etc/a"), b"lower a");
+        assert_eq!(sorted_dir(&mut overlay, "/etc"), ["a", "b", "new", "sub"]);
+    }
+
+    #[test]
+    fn test_whiteout() {
+        let mut overlay = OverlayFileSystem::new(lower(), Box::new(TmpFileSystem::new()));
+        overlay.unlink("/etc/a").unwrap();
+
+        assert!(!overlay.exists("/etc/a"));
+        assert!(overlay.upper.exists("/etc/.wh.a"));
+        assert!(overlay.lower.exists("/etc/a"));
+        assert_eq!(sorted_dir(&mut overlay, "/etc"), ["b", "sub"]);
+        assert_eq!(
+            overlay.open("/etc/a", OpenFileFlags::READ, 1),
+            Err(OpenFileError::NoSuchFileOrDirectory)
+        );
+        // whiteouts themselves are not visible
+        assert!(!overlay.exists("/etc/.wh.a"));
+
+        // the file created again replaces the whiteout
+        write_file(&mut overlay, "/etc/a", b"again");
+        assert!(!overlay.upper.exists("/etc/.wh.a"));
+        assert_eq!(read_file(&mut overlay, "/etc/a"), b"again");
+        assert_eq!(sorted_dir(&mut overlay, "/etc"), ["a", "b", "sub"]);
+    }
+
+    #[test]
+    fn test_opaque_directory() {
+        let mut overlay = OverlayFileSystem::new(lower(), Box::new(TmpFileSystem::new()));
+        assert_eq!(
+            overlay.rmdir("/etc/sub"),
+            Err(OpenFileError::DirectoryNotEmpty)
+        );
+        overlay.unlink("/etc/sub/c").unwrap();
+        overlay.rmdir("/etc/sub").unwrap();
+        assert!(!overlay.exists("/etc/sub"));
+        assert!(!overlay.exists("/etc/sub/c"));
+
+        // the new directory does not show the files of the removed one
+        overlay.mkdir("/etc/sub", 0o755).unwrap();
+        assert!(overlay.upper.exists(&format!("/etc/sub/{}", OPAQUE_MARKER)));
+        assert!(overlay.read_dir("/etc/sub").unwrap().is_empty());
+        assert!(!overlay.exists("/etc/sub/c"));
+        assert!(overlay.lower.exists("/etc/sub/c"));
+
+        write_file(&mut overlay, "/etc/sub/d", b"d");
+        assert_eq!(sorted_dir(&mut overlay, "/etc/sub"), ["d"]);
+    }
+
+    #[test]
+    fn test_changes_persist_in_host_directory() {
+        let scratch_dir = ScratchDir::new("persist");
+        {
+            let upper = OsFileSystem::new(scratch_dir.0.clone());
+            let mut overlay = OverlayFileSystem::new(lower(), Box::new(upper));
+            overlay.unlink("/etc/a").unwrap();
+            write_file(&mut overlay, "/etc/b", b"upper b");
+            overlay.unlink("/etc/sub/c").unwrap();
+            overlay.rmdir("/etc/sub").unwrap();
+            overlay.mkdir("/etc/sub", 0o755).unwrap();
+        }
+
+        // next run over the same host directory
+        let upper = OsFileSystem::new(scratch_dir.0.clone());
+        let mut overlay = OverlayFileSystem::new(lower(), Box::new(upper));
+        assert!(!overlay.exists("/etc/a"));
+        assert_eq!(read_file(&mut overlay, "/etc/b"), b"upper b");
+        assert!(overlay.read_dir("/etc/sub").unwrap().is_empty());
+        assert_eq!(sorted_dir(&mut overlay, "/etc"), ["b", "sub"]);
+    }
+}
@@ -0,0 +1,160 @@
+use crate::file_system::PollEvents;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+// capacity of the pipe (the default one of Linux)
+const PIPE_BUF_SIZE: usize = 0x10000;
+
+struct PipeBuffer {
+    data: VecDeque<u8>,
+    readers: usize,
+    writers: usize,
+}
+
+///
+/// One end of a pipe created by `pipe()`, the ends share the buffer.
+/// The other end is notified about closing of this one when it is dropped.
+///
+pub struct PipeEnd {
+    buffer: Arc<Mutex<PipeBuffer>>,
+    is_write_end: bool,
+}
+
+impl PipeEnd {
+    /// Creates the read end and the write end of a new pipe.
+    pub fn new_pair() -> (PipeEnd, PipeEnd) {
+        let buffer = Arc::new(Mutex::new(PipeBuffer {
+            data: VecDeque::new(),
+            readers: 1,
+            writers: 1,
+        }));
+        (
+            PipeEnd {
+                buffer: buffer.clone(),
+                is_write_end: false,
+            },
+            PipeEnd {
+                buffer,
+                is_write_end: true,
+            },
+        )
+    }
+
+    /// Reads from the pipe, returns 0 (end of file) if the write end is closed
+    /// and `Err` if there is nothing to read yet.
+    pub fn read(&mut self, content: &mut [u8]) -> Result<usize, ()> {
+        let mut buffer = self.buffer.lock().unwrap();
+        if self.is_write_end || buffer.data.is_empty() && buffer.writers > 0 {
+            return Err(());
+        }
+        let len = content.len().min(buffer.data.len());
+        for (dst, src) in content.iter_mut().zip(buffer.data.drain(..len)) {
+            *dst = src;
+        }
+        Ok(len)
+    }
+
+    /// Writes to the pipe as much as fits in it, returns `Err` if the pipe is full
+    /// or the read end is closed.
+    pub fn write(&mut self, content: &[u8]) -> Result<usize, ()> {
+        let mut buffer = self.buffer.lock().unwrap();
+        if !self.is_write_end || buffer.readers == 0 {
+            return Err(());
+        }
+        let len = content.len().min(PIPE_BUF_SIZE - buffer.data.len());
+        if len == 0 && !content.is_empty() {
+            return Err(());
+        }
+        buffer.data.extend(&content[..len]);
+        Ok(len)
+    }
+
+    pub fn poll(&self) -> PollEvents {
+        let buffer = self.buffer.lock().unwrap();
+        if self.is_write_end {
+            if buffer.readers == 0 {
+                PollEvents::ERR
+            } else if buffer.data.len() < PIPE_BUF_SIZE {
+                PollEvents::OUT
+            } else {
+                PollEvents::NONE
+            }
+        } else {
+            let mut events = PollEvents::NONE;
+            if !buffer.data.is_empty() {
+                events |= PollEvents::IN;
+            }
+            if buffer.writers == 0 {
+                events |= PollEvents::HUP;
+            }
+            events
+        }
+    }
+}
+
+impl Drop for PipeEnd {
+    fn drop(&mut self) {
+        let mut buffer = self.buffer.lock().unwrap();
+        if self.is_write_end {
+            buffer.writers -= 1;
+        } else {
+            buffer.readers -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_write() {
+        let (mut read_end, mut write_end) = PipeEnd::new_pair();
+        let mut buf = [0u8; 4];
+        assert_eq!(read_end.read(&mut buf), Err(()));
+        assert_eq!(read_end.poll(), PollEvents::NONE);
+        assert_eq!(write_end.poll(), PollEvents::OUT);
+
+        assert_eq!(write_end.write(b"abcdef"), Ok(6));
+        assert_eq!(read_end.poll(), PollEvents::IN);
+        assert_eq!(read_end.read(&mut buf), Ok(4));
+        assert_eq!(&buf, b"abcd");
+        assert_eq!(read_end.read(&mut buf), Ok(2));
+        assert_eq!(&buf[..2], b"ef");
+
+        // wrong ends
+        assert_eq!(write_end.read(&mut buf), Err(()));
+        assert_eq!(read_end.write(b"a"), Err(()));
+    }
+
+    #[test]
+    fn test_full() {
+        let (mut read_end, mut write_end) = PipeEnd::new_pair();
+        let data = vec![1u8; PIPE_BUF_SIZE + 1];
+        assert_eq!(write_end.write(&data), Ok(PIPE_BUF_SIZE));
+        assert_eq!(write_end.poll(), PollEvents::NONE);
+        assert_eq!(write_end.write(&data), Err(()));
+
+        let mut buf = [0u8; 1];
+        assert_eq!(read_end.read(&mut buf), Ok(1));
+        assert_eq!(write_end.poll(), PollEvents::OUT);
+    }
+
+    #[test]
+    fn test_closed_ends() {
+        let (mut read_end, mut write_end) = PipeEnd::new_pair();
+        write_end.write(b"a").unwrap();
+        drop(write_end);
+
+        // the rest of data is read before the end of file
+        let mut buf = [0u8; 4];
+        assert_eq!(read_end.poll(), PollEvents::IN | PollEvents::HUP);
+        assert_eq!(read_end.read(&mut buf), Ok(1));
+        assert_eq!(read_end.read(&mut buf), Ok(0));
+
+        let (read_end, mut write_end) = PipeEnd::new_pair();
+        drop(read_end);
+        assert_eq!(write_end.poll(), PollEvents::ERR);
+        assert_eq!(write_end.write(b"a"), Err(()));
+    }
+}
@@ -1,21 +1,105 @@
 use crate::emulator::context::Context;
+use crate::emulator::mmu::Mmu;
+use crate::emulator::process_table::{ProcessState, ProcessTable};
+use crate::emulator::users::{egid, euid, gid, uid};
 use crate::file_system::file_info::FileDetails;
 use crate::file_system::{
-    CloseFileError, FileSystem, FileSystemType, FileType, OpenFileError, OpenFileFlags,
-    TmpFileSystem,
+    CloseFileError, FileSystem, FileSystemType, FileType, MountState, OpenFileError, OpenFileFlags,
+    PollEvents, TmpFileSystem,
 };
+use std::collections::HashMap;
 use std::io::SeekFrom;
+use std::sync::{Arc, Mutex};
+use unicorn_engine::unicorn_const::Permission;
 use unicorn_engine::Unicorn;
 
+// entries of `/proc/<pid>`
+const PROCESS_ENTRIES: [&str; 9] = [
+    "cmdline", "comm", "environ", "exe", "fd", "maps", "stat", "status", "task",
+];
+
+// entries of `/proc/<pid>/task/<tid>`
+const TASK_ENTRIES: [&str; 3] = ["comm", "stat", "status"];
+
+const CPU_INFO: &str = "processor\t: 0
+model name\t: ARMv7 Processor rev 10 (v7l)
+BogoMIPS\t: 790.52
+Features\t: half thumb fastmult vfp edsp neon vfpv3 tls vfpd32
+CPU implementer\t: 0x41
+CPU architecture: 7
+CPU variant\t: 0x2
+CPU part\t: 0xc09
+CPU revision\t: 10
+
+processor\t: 1
+model name\t: ARMv7 Processor rev 10 (v7l)
+BogoMIPS\t: 790.52
+Features\t: half thumb fastmult vfp edsp neon vfpv3 tls vfpd32
+CPU implementer\t: 0x41
+CPU architecture: 7
+CPU variant\t: 0x2
+CPU part\t: 0xc09
+CPU revision\t: 10
+
+Hardware\t: Freescale i.MX 6Quad/DualLite (Device Tree)
+Revision\t: 0000
+Serial\t\t: 0000000000000000
+";
+
+const MEM_INFO: &str = "MemTotal:        1021608 kB
+MemFree:          524288 kB
+MemAvailable:     786432 kB
+Buffers:           16384 kB
+Cached:           262144 kB
+SwapCached:            0 kB
+Active:           262144 kB
+Inactive:         131072 kB
+SwapTotal:             0 kB
+SwapFree:              0 kB
+Shmem:             16384 kB
+";
+
 ///
 /// Proc file system.
 ///
+/// Files of processes (`/proc/<pid>/...`) are generated from the emulator state when they
+/// are opened. `/proc/self` is a symbolic link to the directory of the process that accesses
+/// the file system (it exists only for the emulated processes).
+///
 pub struct ProcFileSystem {
+    // static files
     tmp_fs: TmpFileSystem,
+
+    process_table: Arc<Mutex<ProcessTable>>,
+    mount_state: MountState,
+
+    // generated files
+    opened_files: HashMap<i32, ProcOpenedFile>,
+}
+
+struct ProcOpenedFile {
+    file_type: FileType,
+    data: Vec<u8>,
+    pos: usize,
+    // change of the mounts seen by the reader of `/proc/mounts`
+    mount_event: Option<u64>,
+}
+
+/// Copy of the process state used to generate the files.
+struct ProcessInfo {
+    pid: u32,
+    parent_pid: u32,
+    is_zombie: bool,
+    elf_filepath: String,
+    program_args: Vec<String>,
+    program_envs: Vec<(String, String)>,
+    // thread ids with names
+    threads: Vec<(u32, String)>,
+    mmu: Option<Arc<Mutex<Mmu>>>,
 }
 
 impl ProcFileSystem {
-    pub fn new() -> Self {
+    pub fn new(process_table: Arc<Mutex<ProcessTable>>) -> Self {
         let mut tmp_fs = TmpFileSystem::new();
         tmp_fs.insert_entry(
             "/cmdline",
@@ -23,24 +107,324 @@ impl ProcFileSystem {
             //"rw dualosoff=false".to_string().as_bytes().to_vec(),
             "rw".to_string().as_bytes().to_vec(),
         );
-        tmp_fs.insert_entry(
-            "/self/cmdline",
-            FileType::File,
-            "my_program".to_string().as_bytes().to_vec(),
-        );
-        tmp_fs.insert_entry(
-            "/1/comm",
-            FileType::File,
-            "systemd".to_string().as_bytes().to_vec(),
-        );
         tmp_fs.insert_entry(
             "/iosc/status",
             FileType::File,
             "???????????".to_string().as_bytes().to_vec(),
         );
 
-        Self { tmp_fs }
+        Self {
+            tmp_fs,
+            process_table,
+            mount_state: MountState::default(),
+            opened_files: HashMap::new(),
+        }
     }
+
+    fn get_process_info(&self, pid: &str) -> Option<ProcessInfo> {
+        let pid = pid.parse::<u32>().ok()?;
+        let process_table = self.process_table.lock().unwrap();
+        let process = process_table.get(pid)?;
+
+        let mut threads = Vec::new();
+        let mut mmu = None;
+        for thread in process.threads.lock().unwrap().iter() {
+            if !thread.is_exited() {
+                let thread_id = thread.unicorn.get_data().inner.thread_id;
+                threads.push((thread_id, process.get_thread_name(thread_id)));
+                mmu = Some(thread.unicorn.get_data().inner.mmu.clone());
+            }
+        }
+        threads.sort();
+
+        Some(ProcessInfo {
+            pid,
+            parent_pid: process.parent_pid,
+            is_zombie: process.state != ProcessState::Running,
+            elf_filepath: process.elf_filepath.clone(),
+            program_args: process.program_args.clone(),
+            program_envs: process.program_envs.clone(),
+            threads,
+            mmu,
+        })
+    }
+
+    /// Returns type and content of the generated file (`None` if it is not generated).
+    fn generate(&self, file_path: &str) -> Option<(FileType, Vec<u8>)> {
+        let parts: Vec<&str> = file_path.split('/').filter(|p| !p.is_empty()).collect();
+
+        match parts.as_slice() {
+            ["cpuinfo"] => Some((FileType::File, CPU_INFO.as_bytes().to_vec())),
+            ["meminfo"] => Some((FileType::File, MEM_INFO.as_bytes().to_vec())),
+            ["mounts"] => Some((FileType::File, self.generate_mounts().into_bytes())),
+            ["self"] => {
+                let pid = self.mount_state.current_pid?;
+                Some((FileType::Link, pid.to_string().into_bytes()))
+            }
+            [pid, rest @ ..] => {
+                let process = self.get_process_info(pid)?;
+                match rest {
+                    [] | ["fd"] | ["task"] => Some((FileType::Directory, vec![])),
+                    ["cmdline"] => Some((FileType::File, generate_cmdline(&process))),
+                    ["comm"] => Some((
+                        FileType::File,
+                        format!("{}\n", get_thread_name(&process, process.pid)).into_bytes(),
+                    )),
+                    ["environ"] => Some((FileType::File, generate_environ(&process))),
+                    ["exe"] => Some((FileType::Link, process.elf_filepath.clone().into_bytes())),
+                    ["fd", fd] => {
+                        let fd = fd.parse::<i32>().ok()?;
+                        let file_path =
+                            self.mount_state.opened_files.get(&process.pid)?.get(&fd)?;
+                        Some((FileType::Link, file_path.clone().into_bytes()))
+                    }
+                    ["maps"] => Some((FileType::File, generate_maps(&process).into_bytes())),
+                    ["stat"] => Some((
+                        FileType::File,
+                        generate_stat(&process, process.pid).into_bytes(),
+                    )),
+                    ["status"] => Some((
+                        FileType::File,
+                        generate_status(&process, process.pid).into_bytes(),
+                    )),
+                    ["task", tid, task_rest @ ..] => {
+                        let tid = tid.parse::<u32>().ok()?;
+                        if !process
+                            .threads
+                            .iter()
+                            .any(|(thread_id, _)| *thread_id == tid)
+                        {
+                            return None;
+                        }
+                        match task_rest {
+                            [] => Some((FileType::Directory, vec![])),
+                            ["comm"] => Some((
+                                FileType::File,
+                                format!("{}\n", get_thread_name(&process, tid)).into_bytes(),
+                            )),
+                            ["stat"] => {
+                                Some((FileType::File, generate_stat(&process, tid).into_bytes()))
+                            }
+                            ["status"] => {
+                                Some((FileType::File, generate_status(&process, tid).into_bytes()))
+                            }
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn generate_mounts(&self) -> String {
+        let mut mounts = String::new();
+
+        // mount points are sorted from the longest path, root is the first in `/proc/mounts`
+        for mount in self.mount_state.mounts.iter().rev() {
+            let (device, fs_type) = match mount.file_system_type {
+                _ if mount.device.is_some() => (mount.device.as_deref().unwrap(), "vfat"),
+                FileSystemType::Normal if mount.mount_point == "/" => ("/dev/root", "ext4"),
+                FileSystemType::Normal => ("/dev/mmcblk0p1", "ext4"),
+                FileSystemType::Dev => ("devtmpfs", "devtmpfs"),
+                FileSystemType::Proc => ("proc", "proc"),
+                FileSystemType::Temp => ("tmpfs", "tmpfs"),
+                FileSystemType::Overlay => ("overlay", "overlay"),
+                _ => continue,
+            };
+
+            let mount_point = if mount.mount_point.len() > 1 {
+                mount.mount_point.trim_end_matches('/')
+            } else {
+                &mount.mount_point
+            };
+
+            mounts.push_str(&format!(
+                "{} {} {} {},relatime 0 0\n",
+                device,
+                mount_point,
+                fs_type,
+                if mount.is_read_only { "ro" } else { "rw" }
+            ));
+        }
+
+        mounts
+    }
+
+    fn get_opened(&mut self, fd: i32) -> Option<&mut ProcOpenedFile> {
+        self.opened_files.get_mut(&fd)
+    }
+}
+
+fn get_thread_name(process: &ProcessInfo, thread_id: u32) -> String {
+    process
+        .threads
+        .iter()
+        .find(|(tid, _)| *tid == thread_id)
+        .map(|(_, name)| name.clone())
+        .unwrap_or_default()
+}
+
+fn generate_cmdline(process: &ProcessInfo) -> Vec<u8> {
+    // zombie processes have empty command line
+    if process.is_zombie {
+        return vec![];
+    }
+
+    let mut cmdline = Vec::new();
+    for arg in &process.program_args {
+        cmdline.extend_from_slice(arg.as_bytes());
+        cmdline.push(0);
+    }
+    cmdline
+}
+
+fn generate_environ(process: &ProcessInfo) -> Vec<u8> {
+    let mut environ = Vec::new();
+    for (key, value) in &process.program_envs {
+        environ.extend_from_slice(format!("{}={}", key, value).as_bytes());
+        environ.push(0);
+    }
+    environ
+}
+
+fn generate_maps(process: &ProcessInfo) -> String {
+    let mmu = match &process.mmu {
+        Some(mmu) => mmu.clone(),
+        None => return String::new(),
+    };
+    let mmu = mmu.lock().unwrap();
+
+    let mut regions: Vec<_> = mmu.get_regions().iter().collect();
+    regions.sort_by_key(|region| region.memory_start);
+
+    let mut maps = String::new();
+    for region in regions {
+        let mut line = format!(
+            "{:08x}-{:08x} {}{}{}{} 00000000 00:00 0",
+            region.memory_start,
+            region.memory_end as u64 + 1,
+            if region.memory_perms.contains(Permission::READ) {
+                "r"
+            } else {
+                "-"
+            },
+            if region.memory_perms.contains(Permission::WRITE) {
+                "w"
+            } else {
+                "-"
+            },
+            if region.memory_perms.contains(Permission::EXEC) {
+                "x"
+            } else {
+                "-"
+            },
+            if region.is_shared() { "s" } else { "p" },
+        );
+
+        let name = match region.description.as_str() {
+            "[stack]" => "[stack]",
+            "[brk]" => "[heap]",
+            _ => region.filepath.as_str(),
+        };
+        if !name.is_empty() {
+            line = format!("{:<72} {}", line, name);
+        }
+
+        maps.push_str(&line);
+        maps.push('\n');
+    }
+    maps
+}
+
+/// Returns size of the mapped memory in kB.
+fn get_vm_size(process: &ProcessInfo) -> u64 {
+    match &process.mmu {
+        Some(mmu) => {
+            mmu.lock()
+                .unwrap()
+                .get_regions()
+                .iter()
+                .map(|region| (region.memory_end - region.memory_start) as u64 + 1)
+                .sum::<u64>()
+                / 1024
+        }
+        None => 0,
+    }
+}
+
+fn generate_stat(process: &ProcessInfo, thread_id: u32) -> String {
+    let vm_size = get_vm_size(process);
+    let state = if process.is_zombie { "Z" } else { "S" };
+
+    // fields after `comm`, see `man 5 proc`
+    let mut fields: Vec<String> = vec![
+        state.to_string(),
+        process.parent_pid.to_string(), // ppid
+        process.pid.to_string(),        // pgrp
+        process.pid.to_string(),        // session
+        "0".to_string(),                // tty_nr
+        "-1".to_string(),               // tpgid
+    ];
+    // flags, minflt, cminflt, majflt, cmajflt, utime, stime, cutime, cstime
+    fields.extend(std::iter::repeat_n("0".to_string(), 9));
+    fields.push("20".to_string()); // priority
+    fields.push("0".to_string()); // nice
+    fields.push(process.threads.len().to_string()); // num_threads
+    fields.push("0".to_string()); // itrealvalue
+    fields.push("0".to_string()); // starttime
+    fields.push((vm_size * 1024).to_string()); // vsize
+    fields.push((vm_size / 4).to_string()); // rss (pages)
+    fields.push("4294967295".to_string()); // rsslim
+                                           // startcode .. exit_code
+    fields.extend(std::iter::repeat_n("0".to_string(), 27));
+
+    format!(
+        "{} ({}) {}\n",
+        thread_id,
+        get_thread_name(process, thread_id),
+        fields.join(" ")
+    )
+}
+
+fn generate_status(process: &ProcessInfo, thread_id: u32) -> String {
+    let vm_size = get_vm_size(process);
+
+    format!(
+        "Name:\t{}
+State:\t{}
+Tgid:\t{}
+Pid:\t{}
+PPid:\t{}
+TracerPid:\t0
+Uid:\t{}\t{}\t{}\t{}
+Gid:\t{}\t{}\t{}\t{}
+VmSize:\t{:>8} kB
+VmRSS:\t{:>8} kB
+Threads:\t{}
+",
+        get_thread_name(process, thread_id),
+        if process.is_zombie {
+            "Z (zombie)"
+        } else {
+            "S (sleeping)"
+        },
+        process.pid,
+        thread_id,
+        process.parent_pid,
+        uid(),
+        euid(),
+        uid(),
+        uid(),
+        gid(),
+        egid(),
+        gid(),
+        gid(),
+        vm_size,
+        vm_size,
+        process.threads.len()
+    )
 }
 
 impl FileSystem for ProcFileSystem {
@@ -53,18 +437,50 @@ impl FileSystem for ProcFileSystem {
     }
 
     fn exists(&mut self, file_path: &str) -> bool {
-        match file_path {
-            "/cmdline" => true,
-            _ => self.tmp_fs.exists(file_path),
-        }
+        self.generate(file_path).is_some() || self.tmp_fs.exists(file_path)
     }
 
     fn mkdir(&mut self, _file_path: &str, _mode: u32) -> Result<(), OpenFileError> {
         Err(OpenFileError::NoPermission)
     }
 
-    fn read_dir(&mut self, _dir_path: &str) -> Result<Vec<String>, ()> {
-        Err(())
+    fn read_dir(&mut self, dir_path: &str) -> Result<Vec<String>, ()> {
+        let parts: Vec<&str> = dir_path.split('/').filter(|p| !p.is_empty()).collect();
+
+        match parts.as_slice() {
+            [] => {
+                let mut entries = self.tmp_fs.read_dir(dir_path)?;
+                entries.extend(["cpuinfo", "meminfo", "mounts"].map(String::from));
+                let pids = self.process_table.lock().unwrap().pids();
+                entries.extend(pids.iter().map(|pid| pid.to_string()));
+                if self.mount_state.current_pid.is_some() {
+                    entries.push("self".to_string());
+                }
+                Ok(entries)
+            }
+            [pid, rest @ ..] if pid.parse::<u32>().is_ok() => {
+                let process = self.get_process_info(pid).ok_or(())?;
+                match rest {
+                    [] => Ok(PROCESS_ENTRIES.map(String::from).to_vec()),
+                    ["fd"] => Ok(self
+                        .mount_state
+                        .opened_files
+                        .get(&process.pid)
+                        .map(|fds| fds.keys().map(|fd| fd.to_string()).collect())
+                        .unwrap_or_default()),
+                    ["task"] => Ok(process
+                        .threads
+                        .iter()
+                        .map(|(tid, _)| tid.to_string())
+                        .collect()),
+                    ["task", tid] if process.threads.iter().any(|(t, _)| t.to_string() == *tid) => {
+                        Ok(TASK_ENTRIES.map(String::from).to_vec())
+                    }
+                    _ => Err(()),
+                }
+            }
+            _ => self.tmp_fs.read_dir(dir_path),
+        }
     }
 
     fn open(
@@ -73,10 +489,32 @@ impl FileSystem for ProcFileSystem {
         flags: OpenFileFlags,
         fd: i32,
     ) -> Result<(), OpenFileError> {
+        if let Some((file_type, data)) = self.generate(file_path) {
+            if flags.contains(OpenFileFlags::WRITE) {
+                return Err(OpenFileError::NoPermission);
+            }
+
+            let mount_event =
+                (file_path.trim_matches('/') == "mounts").then_some(self.mount_state.mount_event);
+            self.opened_files.insert(
+                fd,
+                ProcOpenedFile {
+                    file_type,
+                    data,
+                    pos: 0,
+                    mount_event,
+                },
+            );
+            return Ok(());
+        }
+
         self.tmp_fs.open(file_path, flags, fd)
     }
 
     fn close(&mut self, fd: i32) -> Result<(), CloseFileError> {
+        if self.opened_files.remove(&fd).is_some() {
+            return Ok(());
+        }
         self.tmp_fs.close(fd)
     }
 
@@ -88,31 +526,85 @@ impl FileSystem for ProcFileSystem {
         Err(OpenFileError::NoPermission)
     }
 
+    fn read_link(&mut self, file_path: &str) -> Option<String> {
+        match self.generate(file_path) {
+            Some((FileType::Link, target)) => Some(String::from_utf8(target).unwrap()),
+            Some(_) => None,
+            None => self.tmp_fs.read_link(file_path),
+        }
+    }
+
     fn get_file_details(&mut self, fd: i32) -> Option<FileDetails> {
+        if let Some(opened_file) = self.get_opened(fd) {
+            return Some(FileDetails {
+                file_type: opened_file.file_type.clone(),
+                is_readonly: true,
+                length: opened_file.data.len() as u64,
+                mode: match opened_file.file_type {
+                    FileType::Directory => 0o555,
+                    FileType::Link => 0o777,
+                    _ => 0o444,
+                },
+                uid: euid(),
+                gid: egid(),
+                device: 0,
+                links_count: 1,
+                times: None,
+            });
+        }
         self.tmp_fs.get_file_details(fd)
     }
 
     fn is_open(&self, fd: i32) -> bool {
-        self.tmp_fs.is_open(fd)
+        self.opened_files.contains_key(&fd) || self.tmp_fs.is_open(fd)
     }
 
     fn get_length(&mut self, fd: i32) -> u64 {
+        if let Some(opened_file) = self.get_opened(fd) {
+            return opened_file.data.len() as u64;
+        }
         self.tmp_fs.get_length(fd)
     }
 
     fn stream_position(&mut self, fd: i32) -> Result<u64, ()> {
+        if let Some(opened_file) = self.get_opened(fd) {
+            return Ok(opened_file.pos as u64);
+        }
         self.tmp_fs.stream_position(fd)
     }
 
     fn seek(&mut self, fd: i32, pos: SeekFrom) -> Result<u64, ()> {
+        if let Some(opened_file) = self.get_opened(fd) {
+            let new_pos = match pos {
+                SeekFrom::Start(pos) => pos as i64,
+                SeekFrom::End(offset) => opened_file.data.len() as i64 + offset,
+                SeekFrom::Current(offset) => opened_file.pos as i64 + offset,
+            };
+            if new_pos < 0 || new_pos > opened_file.data.len() as i64 {
+                return Err(());
+            }
+            opened_file.pos = new_pos as usize;
+            return Ok(new_pos as u64);
+        }
         self.tmp_fs.seek(fd, pos)
     }
 
     fn read(&mut self, fd: i32, content: &mut [u8]) -> Result<u64, ()> {
+        if let Some(opened_file) = self.get_opened(fd) {
+            let bytes_to_read = (opened_file.data.len() - opened_file.pos).min(content.len());
+            content[..bytes_to_read].copy_from_slice(
+                &opened_file.data[opened_file.pos..opened_file.pos + bytes_to_read],
+            );
+            opened_file.pos += bytes_to_read;
+            return Ok(bytes_to_read as u64);
+        }
         self.tmp_fs.read(fd, content)
     }
 
     fn write(&mut self, fd: i32, content: &[u8]) -> Result<u64, ()> {
+        if self.opened_files.contains_key(&fd) {
+            return Err(());
+        }
         self.tmp_fs.write(fd, content)
     }
 
@@ -120,6 +612,28 @@ impl FileSystem for ProcFileSystem {
         Err(())
     }
 
+    fn poll(&mut self, fd: i32) -> PollEvents {
+        // like Linux, `/proc/mounts` reports the change once and is read again from the start
+        let mount_event = self.mount_state.mount_event;
+        let is_mount_changed = self.get_opened(fd).is_some_and(|opened_file| {
+            opened_file
+                .mount_event
+                .is_some_and(|seen_event| seen_event != mount_event)
+        });
+        if is_mount_changed {
+            let mounts = self.generate_mounts().into_bytes();
+            let opened_file = self.get_opened(fd).unwrap();
+            opened_file.mount_event = Some(mount_event);
+            opened_file.data = mounts;
+            return PollEvents::IN | PollEvents::PRI | PollEvents::ERR;
+        }
+
+        if self.opened_files.contains_key(&fd) {
+            return PollEvents::IN;
+        }
+        self.tmp_fs.poll(fd)
+    }
+
     fn ioctl(
         &mut self,
         _unicorn: &mut Unicorn<Context>,
@@ -129,4 +643,80 @@ impl FileSystem for ProcFileSystem {
     ) -> i32 {
         todo!()
     }
+
+    fn set_mount_state(&mut self, mount_state: MountState) {
+        self.mount_state = mount_state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emulator::process_table::ProcessEntry;
+    use crate::emulator::signals::PendingSignals;
+    use crate::file_system::{MountFileSystem, MountPoint, ProcessFileSystem};
+
+    fn file_system() -> Arc<Mutex<MountFileSystem>> {
+        let mut process_table = ProcessTable::new();
+        process_table.insert(ProcessEntry {
+            pid: 7,
+            parent_pid: 0,
+            state: ProcessState::Running,
+            elf_filepath: "/bin/test".to_string(),
+            program_args: vec!["/bin/test".to_string()],
+            program_envs: Vec::new(),
+            threads: Arc::new(Mutex::new(Vec::new())),
+            pending_signals: Arc::new(Mutex::new(PendingSignals::new())),
+            thread_names: HashMap::new(),
+            is_vfork_pending: false,
+            umask: 0o022,
+        });
+        Arc::new(Mutex::new(MountFileSystem::new(vec![MountPoint {
+            mount_point: "/proc".to_string(),
+            file_system: Box::new(ProcFileSystem::new(Arc::new(Mutex::new(process_table)))),
+            is_read_only: false,
+        }])))
+    }
+
+    #[test]
+    fn test_self_link() {
+        let file_system = file_system();
+        let process_file_system = ProcessFileSystem::new(7, file_system.clone());
+        let mut process_file_system = process_file_system.lock().unwrap();
+
+        assert_eq!(
+            process_file_system.read_link("/proc/self"),
+            Ok("7".to_string())
+        );
+        assert_eq!(
+            process_file_system.resolve_path("/proc/self/cmdline", true),
+            Ok("/proc/7/cmdline".to_string())
+        );
+        assert_eq!(
+            process_file_system.read_link("/proc/self/exe"),
+            Ok("/bin/test".to_string())
+        );
+        let file_info = process_file_system
+            .get_file_info_from_filepath("/proc/self")
+            .unwrap();
+        assert_eq!(file_info.file_details.file_type, FileType::Link);
+        assert!(process_file_system
+            .read_dir("/proc")
+            .unwrap()
+            .contains(&"self".to_string()));
+    }
+
+    #[test]
+    fn test_no_self_link_for_emulator() {
+        let file_system = file_system();
+        let mut file_system = file_system.lock().unwrap();
+        assert_eq!(
+            file_system.read_link("/proc/self"),
+            Err(OpenFileError::NoSuchFileOrDirectory)
+        );
+        assert!(!file_system
+            .read_dir("/proc")
+            .unwrap()
+            .contains(&"self".to_string()));
+    }
 }
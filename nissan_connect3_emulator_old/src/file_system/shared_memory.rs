@@ -0,0 +1,67 @@
+use std::cell::UnsafeCell;
+
+///
+/// Memory shared by all `MAP_SHARED` mappings of a single file.
+///
+/// The memory is mapped directly into the guest (in every thread and process),
+/// so writes made by the guest are immediately visible to other mappings. Its
+/// content mirrors the beginning of the file and is written back by `msync()`
+/// and `munmap()`.
+///
+pub struct SharedMemory {
+    // important! the buffer must never be resized, guest memory points to it
+    data: UnsafeCell<Box<[u8]>>,
+}
+
+// the memory is accessed by the guest threads without any synchronization anyway
+unsafe impl Send for SharedMemory {}
+unsafe impl Sync for SharedMemory {}
+
+impl SharedMemory {
+    pub fn new(size: usize) -> Self {
+        Self {
+            data: UnsafeCell::new(vec![0u8; size].into_boxed_slice()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        unsafe { (&*self.data.get()).len() }
+    }
+
+    pub fn as_mut_ptr(&self) -> *mut u8 {
+        unsafe { (&mut *self.data.get()).as_mut_ptr() }
+    }
+
+    /// Copies the memory starting at `offset` into `content` (as much as fits).
+    pub fn read(&self, offset: usize, content: &mut [u8]) -> usize {
+        if offset >= self.len() {
+            return 0;
+        }
+        let len = content.len().min(self.len() - offset);
+        unsafe {
+            std::ptr::copy(self.as_mut_ptr().add(offset), content.as_mut_ptr(), len);
+        }
+        len
+    }
+
+    /// Copies `content` into the memory starting at `offset` (as much as fits).
+    pub fn write(&self, offset: usize, content: &[u8]) -> usize {
+        if offset >= self.len() {
+            return 0;
+        }
+        let len = content.len().min(self.len() - offset);
+        unsafe {
+            std::ptr::copy(content.as_ptr(), self.as_mut_ptr().add(offset), len);
+        }
+        len
+    }
+
+    /// Zeroes the memory starting at `offset` (used when the file is truncated).
+    pub fn clear_from(&self, offset: usize) {
+        if offset < self.len() {
+            unsafe {
+                std::ptr::write_bytes(self.as_mut_ptr().add(offset), 0u8, self.len() - offset);
+            }
+        }
+    }
+}
@@ -1,8 +1,9 @@
 use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
 use crate::emulator::utils::pack_u16;
 use crate::file_system::file_info::FileDetails;
 use crate::file_system::{
-    CloseFileError, FileSystem, FileSystemType, OpenFileError, OpenFileFlags,
+    CloseFileError, FileSystem, FileSystemType, OpenFileError, OpenFileFlags, PollEvents,
 };
 use std::io;
 use std::io::{Read, SeekFrom, Write};
@@ -46,7 +47,7 @@ impl FileSystem for StdFileSystem {
         _flags: OpenFileFlags,
         fd: i32,
     ) -> Result<(), OpenFileError> {
-        if fd >= 0 && fd <= 2 {
+        if (0..=2).contains(&fd) {
             Ok(())
         } else {
             Err(OpenFileError::NoSuchFileOrDirectory)
@@ -54,7 +55,7 @@ impl FileSystem for StdFileSystem {
     }
 
     fn close(&mut self, fd: i32) -> Result<(), CloseFileError> {
-        if fd >= 0 && fd <= 2 {
+        if (0..=2).contains(&fd) {
             Ok(())
         } else {
             Err(CloseFileError::FileNotOpened)
@@ -74,11 +75,7 @@ impl FileSystem for StdFileSystem {
     }
 
     fn is_open(&self, fd: i32) -> bool {
-        if fd >= 0 && fd <= 2 {
-            true
-        } else {
-            false
-        }
+        (0..=2).contains(&fd)
     }
 
     fn get_length(&mut self, _fd: i32) -> u64 {
@@ -121,33 +118,36 @@ impl FileSystem for StdFileSystem {
         Err(())
     }
 
+    fn poll(&mut self, fd: i32) -> PollEvents {
+        if self.is_open(fd) {
+            // regular files are always ready
+            PollEvents::IN | PollEvents::OUT
+        } else {
+            PollEvents::NONE
+        }
+    }
+
     fn ioctl(&mut self, unicorn: &mut Unicorn<Context>, fd: i32, request: u32, addr: u32) -> i32 {
         match request {
-            0x5401 => {
+            0x5401
                 // TCGETS
-                if fd == 0 || fd == 1 {
+                if (fd == 0 || fd == 1) => {
                     let buf = vec![0u8, 0u8, 0u8, 0u8];
-                    unicorn.mem_write(addr as u64, &buf).unwrap();
+                    unicorn.mem_write_guest(addr as u64, &buf).unwrap();
                     0i32
-                } else {
-                    -1i32
                 }
-            }
 
-            0x5413 => {
+            0x5413
                 // TIOCGWINSZ
-                if fd == 0 || fd == 1 {
+                if (fd == 0 || fd == 1) => {
                     let mut buf = Vec::new();
                     buf.extend_from_slice(&pack_u16(1000u16)); // rows in characters
                     buf.extend_from_slice(&pack_u16(360u16)); // columns, in characters
                     buf.extend_from_slice(&pack_u16(1000u16)); // horizontal size, pixels
                     buf.extend_from_slice(&pack_u16(1000u16)); // vertical size, pixels
-                    unicorn.mem_write(addr as u64, &buf).unwrap();
+                    unicorn.mem_write_guest(addr as u64, &buf).unwrap();
                     0i32
-                } else {
-                    -1i32
                 }
-            }
 
             _ => -1i32,
         }
@@ -1,10 +1,13 @@
 use crate::emulator::context::Context;
+use crate::emulator::users::{egid, euid};
 use crate::file_system::{
-    CloseFileError, FileDetails, FileSystem, FileSystemType, FileType, OpenFileError, OpenFileFlags,
+    CloseFileError, FileDetails, FileSystem, FileSystemType, FileTimes, FileType, OpenFileError,
+    OpenFileFlags, PollEvents,
 };
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::io::SeekFrom;
 use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 use unicorn_engine::Unicorn;
 
 ///
@@ -20,6 +23,53 @@ pub struct TmpFileSystem {
 struct TmpFsFileData {
     pub file_type: FileType,
     pub data: Vec<u8>,
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+    /// Device number of the device file.
+    pub device: u64,
+    pub links_count: u32,
+    pub times: FileTimes,
+}
+
+impl TmpFsFileData {
+    fn new(file_type: FileType, data: Vec<u8>) -> Self {
+        let mode = match file_type {
+            FileType::Directory => 0o755,
+            FileType::Link => 0o777,
+            FileType::File => 0o644,
+            _ => 0o666,
+        };
+        // directory is also linked by its own "." entry
+        let links_count = match file_type {
+            FileType::Directory => 2,
+            _ => 1,
+        };
+        Self {
+            file_type,
+            data,
+            mode,
+            uid: euid(),
+            gid: egid(),
+            device: 0,
+            links_count,
+            times: FileTimes::now(),
+        }
+    }
+
+    fn touch_accessed(&mut self) {
+        self.times.accessed = SystemTime::now();
+    }
+
+    fn touch_modified(&mut self) {
+        let now = SystemTime::now();
+        self.times.modified = now;
+        self.times.changed = now;
+    }
+
+    fn touch_changed(&mut self) {
+        self.times.changed = SystemTime::now();
+    }
 }
 
 struct TmpFsOpenedFileData {
@@ -38,12 +88,77 @@ impl TmpFileSystem {
         tmp_fs
     }
 
+    /// Inserts the entry (missing parent directories are created too).
     pub fn insert_entry(&mut self, path: &str, file_type: FileType, data: Vec<u8>) {
+        if path != "/" {
+            let parent_path = parent_path(path);
+            if !self.files.contains_key(parent_path) {
+                self.insert_entry(parent_path, FileType::Directory, vec![]);
+            }
+            if file_type == FileType::Directory {
+                self.files[parent_path].lock().unwrap().links_count += 1;
+            }
+        }
         self.files.insert(
             path.to_string(),
-            Arc::new(Mutex::new(TmpFsFileData { file_type, data })),
+            Arc::new(Mutex::new(TmpFsFileData::new(file_type, data))),
         );
     }
+
+    /// Inserts the device file (missing parent directories are created too).
+    pub fn insert_device(&mut self, path: &str, file_type: FileType, device: u64) {
+        self.insert_entry(path, file_type, vec![]);
+        self.files[path].lock().unwrap().device = device;
+    }
+
+    /// Returns the directory (fails if it does not exist or is not a directory).
+    fn get_dir(&self, dir_path: &str) -> Result<Arc<Mutex<TmpFsFileData>>, OpenFileError> {
+        let dir = self
+            .files
+            .get(dir_path)
+            .ok_or(OpenFileError::NoSuchFileOrDirectory)?;
+        if dir.lock().unwrap().file_type != FileType::Directory {
+            return Err(OpenFileError::NotDirectory);
+        }
+        Ok(dir.clone())
+    }
+
+    /// Returns paths of the direct children of the directory.
+    fn children(&self, dir_path: &str) -> Vec<String> {
+        self.files
+            .keys()
+            .filter(|path| path.as_str() != "/" && parent_path(path) == dir_path)
+            .cloned()
+            .collect()
+    }
+
+    /// Removes the entry and updates link counts (entry must exist).
+    fn remove_entry(&mut self, path: &str) {
+        let file_data = self.files.remove(path).unwrap();
+        let mut file_data = file_data.lock().unwrap();
+        let mut parent = self.files[parent_path(path)].lock().unwrap();
+        if file_data.file_type == FileType::Directory {
+            file_data.links_count = 0;
+            parent.links_count -= 1;
+        } else {
+            file_data.links_count -= 1;
+        }
+        file_data.touch_changed();
+        parent.touch_modified();
+    }
+}
+
+/// Returns path of the parent directory (paths are normalized, root is the parent of itself).
+fn parent_path(path: &str) -> &str {
+    match path.rfind('/') {
+        Some(0) | None => "/",
+        Some(index) => &path[..index],
+    }
+}
+
+/// Checks if the path is inside of the directory.
+fn is_inside(path: &str, dir_path: &str) -> bool {
+    dir_path == "/" || path.starts_with(&format!("{}/", dir_path))
 }
 
 impl FileSystem for TmpFileSystem {
@@ -59,8 +174,20 @@ impl FileSystem for TmpFileSystem {
         self.files.contains_key(file_path)
     }
 
-    fn mkdir(&mut self, _file_path: &str, _mode: u32) -> Result<(), OpenFileError> {
-        Err(OpenFileError::NoPermission)
+    fn mkdir(&mut self, file_path: &str, mode: u32) -> Result<(), OpenFileError> {
+        if self.files.contains_key(file_path) {
+            return Err(OpenFileError::FileExists);
+        }
+        let parent = self.get_dir(parent_path(file_path))?;
+        let mut parent = parent.lock().unwrap();
+        parent.links_count += 1;
+        parent.touch_modified();
+
+        let mut dir_data = TmpFsFileData::new(FileType::Directory, vec![]);
+        dir_data.mode = mode & 0o7777;
+        self.files
+            .insert(file_path.to_string(), Arc::new(Mutex::new(dir_data)));
+        Ok(())
     }
 
     fn read_dir(&mut self, dir_path: &str) -> Result<Vec<String>, ()> {
@@ -69,28 +196,14 @@ impl FileSystem for TmpFileSystem {
             dir_path.truncate(dir_path.len() - 1);
         }
 
-        if let Some(folder) = self.files.get(&dir_path) {
-            if folder.lock().unwrap().file_type != FileType::Directory {
-                // this is not a directory
-                return Err(());
-            }
-        } else {
-            // there is no such folder
-            return Err(());
-        }
-
-        let mut folders = HashSet::new();
-        for key in self.files.keys() {
-            if key.starts_with(&dir_path) {
-                let folder = &key[dir_path.len()..];
-                if folder.len() > 0 {
-                    let folder = folder.split("/").next().unwrap();
-                    folders.insert(folder);
-                }
-            }
-        }
+        let dir = self.get_dir(&dir_path).map_err(|_| ())?;
+        dir.lock().unwrap().touch_accessed();
 
-        Ok(folders.iter().map(|f| f.to_string()).collect())
+        Ok(self
+            .children(&dir_path)
+            .iter()
+            .map(|path| path[path.rfind('/').unwrap() + 1..].to_string())
+            .collect())
     }
 
     fn open(
@@ -105,12 +218,11 @@ impl FileSystem for TmpFileSystem {
                     return Err(OpenFileError::FileExists);
                 }
             } else {
+                let parent = self.get_dir(parent_path(file_path))?;
+                parent.lock().unwrap().touch_modified();
                 self.files.insert(
                     file_path.to_string(),
-                    Arc::new(Mutex::new(TmpFsFileData {
-                        file_type: FileType::File,
-                        data: vec![],
-                    })),
+                    Arc::new(Mutex::new(TmpFsFileData::new(FileType::File, vec![]))),
                 );
             }
         } else {
@@ -121,13 +233,9 @@ impl FileSystem for TmpFileSystem {
         }
 
         if flags.contains(OpenFileFlags::TRUNC) && flags.contains(OpenFileFlags::WRITE) {
-            self.files
-                .get_mut(file_path)
-                .unwrap()
-                .lock()
-                .unwrap()
-                .data
-                .clear();
+            let mut file_data = self.files[file_path].lock().unwrap();
+            file_data.data.clear();
+            file_data.touch_modified();
         }
 
         let file_data = self.files.get_mut(file_path).unwrap();
@@ -147,7 +255,7 @@ impl FileSystem for TmpFileSystem {
             },
         );
 
-        return Ok(());
+        Ok(())
     }
 
     fn close(&mut self, fd: i32) -> Result<(), CloseFileError> {
@@ -158,19 +266,177 @@ impl FileSystem for TmpFileSystem {
     }
 
     fn link(&mut self, old_path: &str, new_path: &str) -> Result<(), OpenFileError> {
-        if let Some(file_data) = self.files.get(old_path) {
-            self.files.insert(new_path.to_string(), file_data.clone());
-            Ok(())
-        } else {
-            Err(OpenFileError::NoSuchFileOrDirectory)
+        let file_data = self
+            .files
+            .get(old_path)
+            .ok_or(OpenFileError::NoSuchFileOrDirectory)?
+            .clone();
+        if file_data.lock().unwrap().file_type == FileType::Directory {
+            // hard links to directories are not allowed
+            return Err(OpenFileError::NoPermission);
+        }
+        if self.files.contains_key(new_path) {
+            return Err(OpenFileError::FileExists);
         }
+        self.get_dir(parent_path(new_path))?
+            .lock()
+            .unwrap()
+            .touch_modified();
+
+        {
+            let mut file_data = file_data.lock().unwrap();
+            file_data.links_count += 1;
+            file_data.touch_changed();
+        }
+        self.files.insert(new_path.to_string(), file_data);
+        Ok(())
     }
 
     fn unlink(&mut self, file_path: &str) -> Result<(), OpenFileError> {
+        let file_data = self
+            .files
+            .get(file_path)
+            .ok_or(OpenFileError::NoSuchFileOrDirectory)?;
+        if file_data.lock().unwrap().file_type == FileType::Directory {
+            return Err(OpenFileError::IsDirectory);
+        }
+        self.remove_entry(file_path);
+        Ok(())
+    }
+
+    fn rmdir(&mut self, dir_path: &str) -> Result<(), OpenFileError> {
+        if dir_path == "/" {
+            return Err(OpenFileError::NoPermission);
+        }
+        self.get_dir(dir_path)?;
+        if !self.children(dir_path).is_empty() {
+            return Err(OpenFileError::DirectoryNotEmpty);
+        }
+        self.remove_entry(dir_path);
+        Ok(())
+    }
+
+    fn rename(&mut self, old_path: &str, new_path: &str) -> Result<(), OpenFileError> {
+        let file_data = self
+            .files
+            .get(old_path)
+            .ok_or(OpenFileError::NoSuchFileOrDirectory)?
+            .clone();
+        self.get_dir(parent_path(new_path))?;
+        let is_dir = file_data.lock().unwrap().file_type == FileType::Directory;
+        if is_dir && (old_path == "/" || is_inside(new_path, old_path)) {
+            // directory can not be moved into itself
+            return Err(OpenFileError::InvalidArgument);
+        }
+        if let Some(replaced) = self.files.get(new_path) {
+            if Arc::ptr_eq(replaced, &file_data) {
+                // both paths are links to the same file
+                return Ok(());
+            }
+            let is_replaced_dir = replaced.lock().unwrap().file_type == FileType::Directory;
+            match (is_dir, is_replaced_dir) {
+                (true, false) => return Err(OpenFileError::NotDirectory),
+                (false, true) => return Err(OpenFileError::IsDirectory),
+                (true, true) if !self.children(new_path).is_empty() => {
+                    return Err(OpenFileError::DirectoryNotEmpty)
+                }
+                _ => {}
+            }
+            self.remove_entry(new_path);
+        }
+
+        // move the entry together with everything inside of it
+        let moved_paths: Vec<String> = self
+            .files
+            .keys()
+            .filter(|path| *path == old_path || (is_dir && is_inside(path, old_path)))
+            .cloned()
+            .collect();
+        for path in moved_paths {
+            let entry = self.files.remove(&path).unwrap();
+            let path = format!("{}{}", new_path, &path[old_path.len()..]);
+            self.files.insert(path, entry);
+        }
+
+        for dir_path in [parent_path(old_path), parent_path(new_path)] {
+            self.files[dir_path].lock().unwrap().touch_modified();
+        }
+        if is_dir {
+            self.files[parent_path(old_path)]
+                .lock()
+                .unwrap()
+                .links_count -= 1;
+            self.files[parent_path(new_path)]
+                .lock()
+                .unwrap()
+                .links_count += 1;
+        }
+        file_data.lock().unwrap().touch_changed();
+        Ok(())
+    }
+
+    fn read_link(&mut self, file_path: &str) -> Option<String> {
+        let file_data = self.files.get(file_path)?.lock().unwrap();
+        match file_data.file_type {
+            FileType::Link => Some(String::from_utf8(file_data.data.clone()).unwrap()),
+            _ => None,
+        }
+    }
+
+    fn symlink(&mut self, target: &str, link_path: &str) -> Result<(), OpenFileError> {
+        if self.files.contains_key(link_path) {
+            return Err(OpenFileError::FileExists);
+        }
+        self.get_dir(parent_path(link_path))?
+            .lock()
+            .unwrap()
+            .touch_modified();
+        self.insert_entry(link_path, FileType::Link, target.as_bytes().to_vec());
+        Ok(())
+    }
+
+    fn mknod(
+        &mut self,
+        file_path: &str,
+        file_type: FileType,
+        device: u64,
+    ) -> Result<(), OpenFileError> {
+        if self.files.contains_key(file_path) {
+            return Err(OpenFileError::FileExists);
+        }
+        self.get_dir(parent_path(file_path))?
+            .lock()
+            .unwrap()
+            .touch_modified();
+
+        let mut file_data = TmpFsFileData::new(file_type, vec![]);
+        file_data.device = device;
         self.files
-            .remove(file_path)
-            .map(|_file_data| ())
-            .ok_or(OpenFileError::NoSuchFileOrDirectory)
+            .insert(file_path.to_string(), Arc::new(Mutex::new(file_data)));
+        Ok(())
+    }
+
+    fn set_mode(&mut self, file_path: &str, mode: u32) -> Result<(), OpenFileError> {
+        let file_data = self
+            .files
+            .get(file_path)
+            .ok_or(OpenFileError::NoSuchFileOrDirectory)?;
+        let mut file_data = file_data.lock().unwrap();
+        file_data.mode = mode & 0o7777;
+        file_data.touch_changed();
+        Ok(())
+    }
+
+    fn set_owner(&mut self, file_path: &str, uid: u32, gid: u32) -> Result<(), OpenFileError> {
+        let file_data = self
+            .files
+            .get(file_path)
+            .ok_or(OpenFileError::NoSuchFileOrDirectory)?;
+        let mut file_data = file_data.lock().unwrap();
+        file_data.uid = uid;
+        file_data.gid = gid;
+        file_data.touch_changed();
+        Ok(())
     }
 
     fn get_file_details(&mut self, fd: i32) -> Option<FileDetails> {
@@ -180,9 +446,15 @@ impl FileSystem for TmpFileSystem {
                 file_type: file_data.file_type.clone(),
                 is_readonly: false,
                 length: file_data.data.len() as u64,
+                mode: file_data.mode,
+                uid: file_data.uid,
+                gid: file_data.gid,
+                device: file_data.device,
+                links_count: file_data.links_count,
+                times: Some(file_data.times),
             });
         }
-        return None;
+        None
     }
 
     fn is_open(&self, fd: i32) -> bool {
@@ -193,7 +465,7 @@ impl FileSystem for TmpFileSystem {
         if let Some(opened_file) = self.opened_files.get(&fd) {
             return opened_file.file_data.lock().unwrap().data.len() as u64;
         }
-        return 0;
+        0
     }
 
     fn stream_position(&mut self, fd: i32) -> Result<u64, ()> {
@@ -233,7 +505,7 @@ impl FileSystem for TmpFileSystem {
                 }
             }
         }
-        return Err(());
+        Err(())
     }
 
     fn read(&mut self, fd: i32, content: &mut [u8]) -> Result<u64, ()> {
@@ -242,17 +514,15 @@ impl FileSystem for TmpFileSystem {
                 return Err(());
             }
 
-            let bytes_to_read = (opened_file.file_data.lock().unwrap().data.len()
-                - opened_file.pos)
-                .min(content.len());
-            content[0..bytes_to_read].copy_from_slice(
-                &opened_file.file_data.lock().unwrap().data
-                    [opened_file.pos..opened_file.pos + bytes_to_read],
-            );
+            let mut file_data = opened_file.file_data.lock().unwrap();
+            let bytes_to_read = (file_data.data.len() - opened_file.pos).min(content.len());
+            content[0..bytes_to_read]
+                .copy_from_slice(&file_data.data[opened_file.pos..opened_file.pos + bytes_to_read]);
+            file_data.touch_accessed();
             opened_file.pos += bytes_to_read;
             return Ok(bytes_to_read as u64);
         }
-        return Err(());
+        Err(())
     }
 
     fn write(&mut self, fd: i32, content: &[u8]) -> Result<u64, ()> {
@@ -261,25 +531,18 @@ impl FileSystem for TmpFileSystem {
                 return Err(());
             }
 
-            let bytes_to_override = (opened_file.file_data.lock().unwrap().data.len()
-                - opened_file.pos)
-                .min(content.len());
-            opened_file.file_data.lock().unwrap().data
-                [opened_file.pos..opened_file.pos + bytes_to_override]
+            let mut file_data = opened_file.file_data.lock().unwrap();
+            let bytes_to_override = (file_data.data.len() - opened_file.pos).min(content.len());
+            file_data.data[opened_file.pos..opened_file.pos + bytes_to_override]
                 .copy_from_slice(&content[0..bytes_to_override]);
-            let bytes_to_append = content.len() - bytes_to_override;
-            opened_file
-                .file_data
-                .lock()
-                .unwrap()
+            file_data
                 .data
-                .extend_from_slice(
-                    &content[bytes_to_override..bytes_to_override + bytes_to_append],
-                );
+                .extend_from_slice(&content[bytes_to_override..]);
+            file_data.touch_modified();
             opened_file.pos += content.len();
             return Ok(content.len() as u64);
         }
-        return Err(());
+        Err(())
     }
 
     fn truncate(&mut self, fd: i32, length: u32) -> Result<(), ()> {
@@ -288,12 +551,9 @@ impl FileSystem for TmpFileSystem {
                 return Err(());
             }
 
-            opened_file
-                .file_data
-                .lock()
-                .unwrap()
-                .data
-                .resize(length as usize, 0u8);
+            let mut file_data = opened_file.file_data.lock().unwrap();
+            file_data.data.resize(length as usize, 0u8);
+            file_data.touch_modified();
 
             Ok(())
         } else {
@@ -301,6 +561,15 @@ impl FileSystem for TmpFileSystem {
         }
     }
 
+    fn poll(&mut self, fd: i32) -> PollEvents {
+        if self.is_open(fd) {
+            // regular files are always ready
+            PollEvents::IN | PollEvents::OUT
+        } else {
+            PollEvents::NONE
+        }
+    }
+
     fn ioctl(
         &mut self,
         _unicorn: &mut Unicorn<Context>,
@@ -0,0 +1,20 @@
+//!
+//! Emulator of the Nissan Connect 3 head unit firmware (ARM Linux user space on Unicorn).
+//!
+//! The binary runs the firmware with the default setup, other setups (e.g. own device models
+//! registered in `file_system::CharDeviceRegistry` next to `devices::register_standard_devices`)
+//! can be built on this library.
+//!
+
+// the modules are shared with the binary rather than designed as a public API, so lints of
+// public APIs are not applied to their conventions (like `Result<_, ()>` of the file systems)
+#![allow(
+    clippy::len_without_is_empty,
+    clippy::new_without_default,
+    clippy::result_unit_err
+)]
+
+pub mod devices;
+pub mod emulator;
+pub mod file_system;
+pub mod os;
@@ -1,20 +1,186 @@
-use crate::emulator::emulator::Emulator;
-use crate::file_system::{
-    DevFileSystem, MountFileSystem, MountPoint, OsFileSystem, ProcFileSystem, StdFileSystem,
-    TmpFileSystem,
+use nissan_connect3_emulator::devices::{
+    AcousticOutDevice, AcousticSrcDevice, BtAsipDevice, CommandControl, ErrMemDevice, FfdDevice,
+    FramebufferDevice, GnssDevice, GnssTrack, InputControl, InputDevice, RegistryDevice,
+    ScriptedMcu,
 };
+use nissan_connect3_emulator::emulator::machine::Emulator;
+use nissan_connect3_emulator::emulator::process_table::ProcessTable;
+use nissan_connect3_emulator::file_system::{
+    CharDeviceRegistry, DevFileSystem, FileSystem, ImageFileSystem, MediaControl, MountFileSystem,
+    MountPoint, OsFileSystem, OverlayFileSystem, ProcFileSystem, StdFileSystem, TmpFileSystem,
+};
+use nissan_connect3_emulator::{devices, emulator};
 use std::path::PathBuf;
-
-mod emulator;
-mod file_system;
-mod os;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
     pretty_env_logger::init();
 
-    // mounted file systems
-    // (currently must be sorted from longest to shortest path)
-    let file_system = MountFileSystem::new(vec![
+    // processes run as an ordinary user unless `--uid <uid>` and `--gid <gid>` are given
+    // (e.g. `--uid 0 --gid 0` to run them as root)
+    let uid = number_option("--uid")?.unwrap_or(emulator::users::uid());
+    let gid = number_option("--gid")?.unwrap_or(emulator::users::gid());
+    emulator::users::set_credentials(uid, gid);
+
+    // all processes of the emulator (also used by proc-fs)
+    let process_table = Arc::new(Mutex::new(ProcessTable::new()));
+
+    // persistent data survives across emulator runs
+    let persistent_path =
+        PathBuf::from("/mnt/hdd_media/ZInternetu/Firmware/NissanConnect/persistent");
+    std::fs::create_dir_all(&persistent_path)?;
+
+    // error memory of all runs, `dump-errmem` prints it instead of running the emulator
+    let errmem_path = persistent_path.with_file_name("errmem.txt");
+    if std::env::args().nth(1).as_deref() == Some("dump-errmem") {
+        if errmem_path.is_file() {
+            for entry in devices::read_errmem(&errmem_path)? {
+                println!("{}", entry);
+            }
+        }
+        return Ok(());
+    }
+
+    // firmware can be unpacked, an image (squashfs, ext2/3/4, cpio or tar)
+    // or a directory of the partitions extracted from the update package (the root one is mounted)
+    let firmware_path =
+        PathBuf::from("/mnt/hdd_media/ZInternetu/Firmware/NissanConnect/firmware_d605_unpacked");
+    let firmware_file_system: Box<dyn FileSystem + Send + Sync> = if firmware_path.is_file() {
+        Box::new(ImageFileSystem::new(&firmware_path)?)
+    } else if firmware_path.join("bin").is_dir() {
+        Box::new(OsFileSystem::new(firmware_path))
+    } else {
+        Box::new(ImageFileSystem::from_partition_directory(
+            &firmware_path,
+            "rootfs",
+        )?)
+    };
+
+    // seed of /dev/random and /dev/urandom given by `--random-seed <number>`
+    // (set it to get the same numbers in every run)
+    let random_seed = number_option::<u64>("--random-seed")?;
+
+    // device models available in /dev
+    let mut devices = CharDeviceRegistry::new();
+    devices::register_standard_devices(&mut devices, random_seed);
+    devices.register("/errmem", 240, 3, ErrMemDevice::new(errmem_path));
+
+    // flash data (calibration and configuration), the first run imports the dump of a real unit
+    devices.register(
+        "/ffd",
+        240,
+        4,
+        FfdDevice::new(
+            persistent_path.with_file_name("ffd.bin"),
+            Some(persistent_path.with_file_name("ffd_dump")),
+        ),
+    );
+
+    // OSAL registry (loaded from the firmware when the file systems are mounted)
+    let registry = devices.register("/registry", 240, 5, RegistryDevice::new());
+    let registry_overrides_path = persistent_path.with_file_name("registry.reg");
+
+    // BT module answering the ASIP packets by the script (recorded from a real unit or written by hand)
+    let mut bt_asip = BtAsipDevice::new();
+    let bt_asip_script_path = persistent_path.with_file_name("bt_asip.txt");
+    if bt_asip_script_path.is_file() {
+        bt_asip.load_script(&bt_asip_script_path)?;
+    }
+    devices.register("/btasip", 240, 6, bt_asip);
+
+    let framebuffer = devices.register("/fb0", 29, 0, FramebufferDevice::new(800, 480, 32));
+
+    // screen is saved as PNG every 5 seconds (when it changes) and on SIGUSR1
+    devices::start_frame_capture(
+        framebuffer,
+        persistent_path.with_file_name("screenshots"),
+        Some(Duration::from_secs(5)),
+    )?;
+
+    // audio of ACOUSTICOUT streams (like the navigation voice prompts) is saved as WAV
+    devices.register(
+        "/acousticout",
+        240,
+        0,
+        AcousticOutDevice::new(persistent_path.with_file_name("audio")),
+    );
+
+    // microphone plays the recordings (like voice commands) in order of their names
+    let recordings_path = persistent_path.with_file_name("recordings");
+    let mut recordings: Vec<PathBuf> = std::fs::read_dir(&recordings_path)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    recordings.retain(|path| path.extension().is_some_and(|ext| ext == "wav"));
+    recordings.sort();
+    devices.register("/acousticsrc", 240, 1, AcousticSrcDevice::new(recordings));
+
+    // GNSS receiver replays the track (GPX or NMEA log) or stands at the fixed position
+    let gnss_track_path = persistent_path.with_file_name("track.gpx");
+    let gnss_track = if gnss_track_path.is_file() {
+        GnssTrack::from_file(&gnss_track_path)?
+    } else {
+        GnssTrack::fixed(48.8584, 2.2945, 35.0)
+    };
+    let gnss_speed = 1.0;
+    devices.register("/gnss", 240, 2, GnssDevice::new(gnss_track, gnss_speed));
+
+    // input devices controlled by the script and commands on localhost
+    // (the server is started by `--input-port <port>`)
+    let hard_keys: Vec<u16> = devices::HARD_KEYS.iter().map(|(_, code)| *code).collect();
+    let input_control = InputControl {
+        touchscreen: devices.register(
+            "/input/event0",
+            13,
+            64,
+            InputDevice::touchscreen("emulator touchscreen", 800, 480),
+        ),
+        keys: devices.register(
+            "/input/event1",
+            13,
+            65,
+            InputDevice::keys("emulator keys", &hard_keys),
+        ),
+        rotary: devices.register(
+            "/input/event2",
+            13,
+            66,
+            InputDevice::rotary("emulator rotary encoder"),
+        ),
+    };
+    if let Some(port) = number_option::<u16>("--input-port")? {
+        if let Err(err) = input_control.start_server(port) {
+            log::warn!("Input control server on port {} not started: {}", port, err);
+        }
+    }
+    let input_script_path = persistent_path.with_file_name("input_script.txt");
+    if input_script_path.is_file() {
+        input_control.run_script(input_script_path)?;
+    }
+
+    // MCU behind the INC sockets, the vehicle state and the messages are set by the script
+    let mcu = ScriptedMcu::new();
+    let mcu_script_path = persistent_path.with_file_name("mcu_script.txt");
+    if mcu_script_path.is_file() {
+        mcu.run_script(mcu_script_path)?;
+    }
+
+    // changes of the firmware files (with the whiteouts of the removed ones) survive across
+    // emulator runs, the firmware dump itself is never modified
+    let firmware_changes_path = persistent_path.with_file_name("firmware_changes");
+    std::fs::create_dir_all(&firmware_changes_path)?;
+
+    // script of the SD card and USB sticks inserted and removed at runtime
+    let media_script_path = persistent_path.with_file_name("media_script.txt");
+
+    // mounted file systems (they are sorted from longest to shortest path)
+    let mut file_system = MountFileSystem::new(vec![
+        // persistent storage
+        MountPoint {
+            mount_point: "/var/opt/bosch/persistent".to_string(),
+            file_system: Box::new(OsFileSystem::new(persistent_path)),
+            is_read_only: false,
+        },
         // sd-card with maps
         MountPoint {
             mount_point: "/var/opt/bosch/dynamic".to_string(),
@@ -44,22 +210,23 @@ fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
         // proc-fs
         MountPoint {
             mount_point: "/proc".to_string(),
-            file_system: Box::new(ProcFileSystem::new()),
+            file_system: Box::new(ProcFileSystem::new(process_table.clone())),
             is_read_only: false,
         },
         // dev-fs
         MountPoint {
             mount_point: "/dev".to_string(),
-            file_system: Box::new(DevFileSystem::new()),
+            file_system: Box::new(DevFileSystem::new(devices)),
             is_read_only: false,
         },
-        // firmware
+        // firmware (changes are stored in the host directory, the dump is not modified)
         MountPoint {
             mount_point: "/".to_string(),
-            file_system: Box::new(OsFileSystem::new(PathBuf::from(
-                "/mnt/hdd_media/ZInternetu/Firmware/NissanConnect/firmware_d605_unpacked",
-            ))),
-            is_read_only: true,
+            file_system: Box::new(OverlayFileSystem::new(
+                firmware_file_system,
+                Box::new(OsFileSystem::new(firmware_changes_path)),
+            )),
+            is_read_only: false,
         },
         // stdin, stdout, stderr
         MountPoint {
@@ -68,6 +235,17 @@ fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
             is_read_only: false,
         },
     ]);
+    file_system.set_inc_mcu(Arc::new(Mutex::new(mcu)));
+
+    // registry of the firmware, values of registry.reg override it
+    {
+        let mut registry = registry.lock().unwrap();
+        registry.load_firmware_files(&mut file_system, "/opt/bosch");
+        if registry_overrides_path.is_file() {
+            let overrides = std::fs::read_to_string(&registry_overrides_path)?;
+            registry.load(&overrides, &registry_overrides_path.display().to_string());
+        }
+    }
 
     // environment variables
     let envs = vec![
@@ -80,7 +258,19 @@ fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
         //("LD_DEBUG".to_string(), "files".to_string()),
     ];
 
-    let mut emulator = Emulator::new(file_system).unwrap();
+    let mut emulator = Emulator::new(file_system, process_table).unwrap();
+
+    // media are also mounted and unmounted by commands on the port given by `--media-port <port>`
+    // (see `MediaControl`)
+    let media_control = MediaControl::new(emulator.file_system());
+    if let Some(port) = number_option::<u16>("--media-port")? {
+        if let Err(err) = media_control.start_server(port) {
+            log::warn!("Media control server on port {} not started: {}", port, err);
+        }
+    }
+    if media_script_path.is_file() {
+        media_control.run_script(media_script_path)?;
+    }
 
     /*emulator.run_process(
         "/bin/echo.coreutils".to_string(),
@@ -103,3 +293,22 @@ fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
 
     Ok(())
 }
+
+/// Returns number given by the optional `--name <number>` or `--name=<number>` argument.
+fn number_option<T: std::str::FromStr>(name: &str) -> Result<Option<T>, String> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let value = args.iter().enumerate().find_map(|(index, arg)| {
+        if arg == name {
+            Some(args.get(index + 1).map(String::as_str).unwrap_or_default())
+        } else {
+            arg.strip_prefix(name)?.strip_prefix('=')
+        }
+    });
+    value
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| format!("invalid value `{}` of {}", value, name))
+        })
+        .transpose()
+}
@@ -0,0 +1,195 @@
+use crate::devices::{ACOUSTICSRC_IOCTRL_READ, ACOUSTICSRC_IOCTRL_START};
+use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
+use crate::emulator::utils::{pack_u32, unpack_u32};
+use crate::file_system::OpenFileFlags;
+use crate::os::add_code_hook;
+use crate::os::libosal_linux::driver::{
+    device_close, device_control, device_open, to_osal_error, OSAL_E_BADFILEDESCRIPTOR,
+    OSAL_E_INVALIDVALUE, OSAL_E_NOERROR,
+};
+use crate::os::syscalls::wait_for_fd;
+use unicorn_engine::{RegisterARM, Unicorn};
+
+// device models of the drivers (see `devices::AcousticOutDevice` and `devices::AcousticSrcDevice`)
+const ACOUSTICOUT_DEVICE_PATH: &str = "/dev/acousticout";
+const ACOUSTICSRC_DEVICE_PATH: &str = "/dev/acousticsrc";
+
+pub fn hook_acoustic_code(unicorn: &mut Unicorn<Context>, base_address: u32) {
+    // original base address: 0x484d8000
+    add_code_hook!(
+        unicorn,
+        "LIBOSAL",
+        base_address + 0x4C0A4,
+        acoustic_out_init
+    );
+    add_code_hook!(
+        unicorn,
+        "LIBOSAL",
+        base_address + 0x4C060,
+        acoustic_out_deinit
+    );
+    add_code_hook!(
+        unicorn,
+        "LIBOSAL",
+        base_address + 0x4E558,
+        acoustic_out_io_open
+    );
+    add_code_hook!(
+        unicorn,
+        "LIBOSAL",
+        base_address + 0x4E160,
+        acoustic_out_io_close
+    );
+    add_code_hook!(
+        unicorn,
+        "LIBOSAL",
+        base_address + 0x4CD54,
+        acoustic_out_io_write
+    );
+    add_code_hook!(
+        unicorn,
+        "LIBOSAL",
+        base_address + 0x4DD00,
+        acoustic_out_io_control
+    );
+    add_code_hook!(
+        unicorn,
+        "LIBOSAL",
+        base_address + 0x4EC5C,
+        acoustic_src_init
+    );
+    add_code_hook!(
+        unicorn,
+        "LIBOSAL",
+        base_address + 0x4EC18,
+        acoustic_src_deinit
+    );
+    add_code_hook!(
+        unicorn,
+        "LIBOSAL",
+        base_address + 0x50D34,
+        acoustic_src_io_open
+    );
+    add_code_hook!(
+        unicorn,
+        "LIBOSAL",
+        base_address + 0x50A14,
+        acoustic_src_io_close
+    );
+    add_code_hook!(
+        unicorn,
+        "LIBOSAL",
+        base_address + 0x4F038,
+        acoustic_src_io_control
+    );
+}
+
+pub fn acoustic_out_init(_unicorn: &mut Unicorn<Context>) -> u32 {
+    OSAL_E_NOERROR
+}
+
+pub fn acoustic_out_deinit(_unicorn: &mut Unicorn<Context>) -> u32 {
+    OSAL_E_NOERROR
+}
+
+pub fn acoustic_out_io_open(unicorn: &mut Unicorn<Context>) -> u32 {
+    device_open(unicorn, ACOUSTICOUT_DEVICE_PATH, OpenFileFlags::WRITE)
+}
+
+pub fn acoustic_out_io_close(unicorn: &mut Unicorn<Context>) -> u32 {
+    device_close(unicorn)
+}
+
+pub fn acoustic_out_io_write(unicorn: &mut Unicorn<Context>) -> u32 {
+    // ACOUSTICOUT_s32IOWrite(s32ID, u32FD, pcs8Buffer, u32Size, pu32RetSize)
+    let fd = unicorn.reg_read(RegisterARM::R1).unwrap() as i32;
+    let buffer = unicorn.reg_read(RegisterARM::R2).unwrap();
+    let size = unicorn.reg_read(RegisterARM::R3).unwrap() as usize;
+    let mut ret_size_addr = [0u8; 4];
+    unicorn
+        .mem_read(
+            unicorn.reg_read(RegisterARM::SP).unwrap(),
+            &mut ret_size_addr,
+        )
+        .unwrap();
+    let ret_size_addr = unpack_u32(&ret_size_addr);
+
+    let mut content = vec![0u8; size];
+    unicorn.mem_read(buffer, &mut content).unwrap();
+
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let res = file_system.lock().unwrap().write(fd, &content);
+    match res {
+        Ok(len) => {
+            if ret_size_addr != 0 {
+                unicorn
+                    .mem_write_guest(ret_size_addr as u64, &pack_u32(len as u32))
+                    .unwrap();
+            }
+            OSAL_E_NOERROR
+        }
+        Err(_) => OSAL_E_BADFILEDESCRIPTOR,
+    }
+}
+
+pub fn acoustic_out_io_control(unicorn: &mut Unicorn<Context>) -> u32 {
+    device_control(unicorn)
+}
+
+pub fn acoustic_src_init(_unicorn: &mut Unicorn<Context>) -> u32 {
+    OSAL_E_NOERROR
+}
+
+pub fn acoustic_src_deinit(_unicorn: &mut Unicorn<Context>) -> u32 {
+    OSAL_E_NOERROR
+}
+
+pub fn acoustic_src_io_open(unicorn: &mut Unicorn<Context>) -> u32 {
+    device_open(unicorn, ACOUSTICSRC_DEVICE_PATH, OpenFileFlags::READ)
+}
+
+pub fn acoustic_src_io_close(unicorn: &mut Unicorn<Context>) -> u32 {
+    device_close(unicorn)
+}
+
+pub fn acoustic_src_io_control(unicorn: &mut Unicorn<Context>) -> u32 {
+    let fd = unicorn.reg_read(RegisterARM::R1).unwrap() as i32;
+    let function = unicorn.reg_read(RegisterARM::R2).unwrap() as u32;
+    if function != ACOUSTICSRC_IOCTRL_READ {
+        return device_control(unicorn);
+    }
+
+    // `{ buffer, size, read_size }`
+    let arg = unicorn.reg_read(RegisterARM::R3).unwrap();
+    let mut buf = [0u8; 8];
+    unicorn.mem_read(arg, &mut buf).unwrap();
+    let buffer = unpack_u32(&buf[0..4]);
+    let size = unpack_u32(&buf[4..8]);
+
+    // the capture is started implicitly by the first read
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let res = file_system
+        .lock()
+        .unwrap()
+        .ioctl(unicorn, fd, ACOUSTICSRC_IOCTRL_START, 0);
+    if res != 0 {
+        return to_osal_error(res);
+    }
+
+    // blocks until the whole period is recorded
+    wait_for_fd(unicorn, fd, false);
+    let mut content = vec![0u8; size as usize];
+    let res = file_system.lock().unwrap().read(fd, &mut content);
+    let len = res.unwrap_or(0) as usize;
+    unicorn
+        .mem_write_guest(buffer as u64, &content[0..len])
+        .unwrap();
+    unicorn
+        .mem_write_guest(arg + 8, &pack_u32(len as u32))
+        .unwrap();
+    if len == 0 {
+        return OSAL_E_INVALIDVALUE;
+    }
+    OSAL_E_NOERROR
+}
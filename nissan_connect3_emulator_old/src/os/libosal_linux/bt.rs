@@ -0,0 +1,105 @@
+use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
+use crate::emulator::utils::pack_u32;
+use crate::file_system::OpenFileFlags;
+use crate::os::add_code_hook;
+use crate::os::libosal_linux::driver::{
+    device_close, device_control, device_open, transfer_args, OSAL_E_BADFILEDESCRIPTOR,
+    OSAL_E_NOERROR,
+};
+use crate::os::syscalls::wait_for_fd;
+use unicorn_engine::{RegisterARM, Unicorn};
+
+// device model of the BT module behind the driver (see `devices::BtAsipDevice`)
+const BT_ASIP_DEVICE_PATH: &str = "/dev/btasip";
+
+pub fn hook_bt_code(unicorn: &mut Unicorn<Context>, base_address: u32) {
+    // original base address: 0x484d8000
+    add_code_hook!(
+        unicorn,
+        "LIBOSAL",
+        base_address + 0x51F84,
+        bt_io_device_init
+    );
+    add_code_hook!(
+        unicorn,
+        "LIBOSAL",
+        base_address + 0x51F34,
+        bt_io_device_remove
+    );
+    add_code_hook!(unicorn, "LIBOSAL", base_address + 0x51DB8, bt_io_open);
+    add_code_hook!(unicorn, "LIBOSAL", base_address + 0x51C88, bt_io_close);
+    add_code_hook!(unicorn, "LIBOSAL", base_address + 0x51A2C, bt_io_read);
+    add_code_hook!(unicorn, "LIBOSAL", base_address + 0x51B10, bt_io_write);
+    add_code_hook!(unicorn, "LIBOSAL", base_address + 0x51BF8, bt_io_control);
+}
+
+pub fn bt_io_device_init(_unicorn: &mut Unicorn<Context>) -> u32 {
+    // the UART of the module is not opened (`bDrvBtAsipInit()`)
+    OSAL_E_NOERROR
+}
+
+pub fn bt_io_device_remove(_unicorn: &mut Unicorn<Context>) -> u32 {
+    OSAL_E_NOERROR
+}
+
+pub fn bt_io_open(unicorn: &mut Unicorn<Context>) -> u32 {
+    device_open(
+        unicorn,
+        BT_ASIP_DEVICE_PATH,
+        OpenFileFlags::READ | OpenFileFlags::WRITE,
+    )
+}
+
+pub fn bt_io_close(unicorn: &mut Unicorn<Context>) -> u32 {
+    device_close(unicorn)
+}
+
+pub fn bt_io_read(unicorn: &mut Unicorn<Context>) -> u32 {
+    let (fd, buffer, size, ret_size_addr) = transfer_args(unicorn);
+
+    // blocks until the module sends a packet (like `s32DrvBtAsipRcvData()`)
+    wait_for_fd(unicorn, fd, false);
+    let mut content = vec![0u8; size as usize];
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let res = file_system.lock().unwrap().read(fd, &mut content);
+    match res {
+        Ok(len) => {
+            unicorn
+                .mem_write_guest(buffer as u64, &content[0..len as usize])
+                .unwrap();
+            if ret_size_addr != 0 {
+                unicorn
+                    .mem_write_guest(ret_size_addr as u64, &pack_u32(len as u32))
+                    .unwrap();
+            }
+            OSAL_E_NOERROR
+        }
+        Err(_) => OSAL_E_BADFILEDESCRIPTOR,
+    }
+}
+
+pub fn bt_io_write(unicorn: &mut Unicorn<Context>) -> u32 {
+    let (fd, buffer, size, ret_size_addr) = transfer_args(unicorn);
+
+    // the buffer contains ASIP packets
+    let mut content = vec![0u8; size as usize];
+    unicorn.mem_read(buffer as u64, &mut content).unwrap();
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let res = file_system.lock().unwrap().write(fd, &content);
+    match res {
+        Ok(len) => {
+            if ret_size_addr != 0 {
+                unicorn
+                    .mem_write_guest(ret_size_addr as u64, &pack_u32(len as u32))
+                    .unwrap();
+            }
+            OSAL_E_NOERROR
+        }
+        Err(_) => OSAL_E_BADFILEDESCRIPTOR,
+    }
+}
+
+pub fn bt_io_control(unicorn: &mut Unicorn<Context>) -> u32 {
+    device_control(unicorn)
+}
@@ -0,0 +1,83 @@
+use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
+use crate::emulator::utils::{pack_u32, read_string, unpack_u32};
+use crate::file_system::OpenFileFlags;
+use unicorn_engine::{RegisterARM, Unicorn};
+
+// error codes of the OSAL drivers (`OSAL_E_*`)
+pub const OSAL_E_NOERROR: u32 = 0x00;
+pub const OSAL_E_INVALIDVALUE: u32 = 0x02;
+pub const OSAL_E_DOESNOTEXIST: u32 = 0x08;
+pub const OSAL_E_BADFILEDESCRIPTOR: u32 = 0x0c;
+
+/// Returns OSAL error code of the negative error number returned by the device model.
+pub fn to_osal_error(res: i32) -> u32 {
+    match res {
+        0 => OSAL_E_NOERROR,
+        -9 => OSAL_E_BADFILEDESCRIPTOR,
+        _ => OSAL_E_INVALIDVALUE,
+    }
+}
+
+/// Opens the device model (the file descriptor is used as the OSAL driver handle).
+pub fn device_open(unicorn: &mut Unicorn<Context>, device_path: &str, flags: OpenFileFlags) -> u32 {
+    // <DRIVER>_s32IOOpen(s32ID, szName, enAccess, pu32FD, u16AppID)
+    let name = read_string(unicorn, unicorn.reg_read(RegisterARM::R1).unwrap() as u32);
+    let fd_addr = unicorn.reg_read(RegisterARM::R3).unwrap();
+    log::trace!("name: {}", name);
+
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let res = file_system.lock().unwrap().open(device_path, flags);
+    match res {
+        Ok(fd) => {
+            unicorn
+                .mem_write_guest(fd_addr, &pack_u32(fd as u32))
+                .unwrap();
+            OSAL_E_NOERROR
+        }
+        Err(_) => OSAL_E_DOESNOTEXIST,
+    }
+}
+
+pub fn device_close(unicorn: &mut Unicorn<Context>) -> u32 {
+    // <DRIVER>_s32IOClose(s32ID, u32FD)
+    let fd = unicorn.reg_read(RegisterARM::R1).unwrap() as i32;
+
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let res = file_system.lock().unwrap().close(fd);
+    match res {
+        Ok(_) => OSAL_E_NOERROR,
+        Err(_) => OSAL_E_BADFILEDESCRIPTOR,
+    }
+}
+
+pub fn device_control(unicorn: &mut Unicorn<Context>) -> u32 {
+    // <DRIVER>_s32IOControl(s32ID, u32FD, s32Fun, s32Arg)
+    let fd = unicorn.reg_read(RegisterARM::R1).unwrap() as i32;
+    let function = unicorn.reg_read(RegisterARM::R2).unwrap() as u32;
+    let arg = unicorn.reg_read(RegisterARM::R3).unwrap() as u32;
+    log::trace!("fd: {:#x}, function: {:#x}, arg: {:#x}", fd, function, arg);
+
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let res = file_system
+        .lock()
+        .unwrap()
+        .ioctl(unicorn, fd, function, arg);
+    to_osal_error(res)
+}
+
+/// Returns arguments of the read and write functions (buffer, size and address of the result size).
+pub fn transfer_args(unicorn: &Unicorn<Context>) -> (i32, u32, u32, u32) {
+    // <DRIVER>_s32IOxx(s32ID, u32FD, ps8Buffer, u32Size, pu32RetSize)
+    let fd = unicorn.reg_read(RegisterARM::R1).unwrap() as i32;
+    let buffer = unicorn.reg_read(RegisterARM::R2).unwrap() as u32;
+    let size = unicorn.reg_read(RegisterARM::R3).unwrap() as u32;
+    let mut ret_size_addr = [0u8; 4];
+    unicorn
+        .mem_read(
+            unicorn.reg_read(RegisterARM::SP).unwrap(),
+            &mut ret_size_addr,
+        )
+        .unwrap();
+    (fd, buffer, size, unpack_u32(&ret_size_addr))
+}
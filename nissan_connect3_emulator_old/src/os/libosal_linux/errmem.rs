@@ -0,0 +1,89 @@
+use crate::devices::{ERRMEM_ENTRY_SIZE, ERRMEM_IOCTL_WRITE_ENTRY};
+use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
+use crate::emulator::utils::pack_u32;
+use crate::file_system::OpenFileFlags;
+use crate::os::add_code_hook;
+use crate::os::libosal_linux::driver::{
+    device_close, device_control, device_open, to_osal_error, transfer_args,
+    OSAL_E_BADFILEDESCRIPTOR, OSAL_E_INVALIDVALUE, OSAL_E_NOERROR,
+};
+use unicorn_engine::{RegisterARM, Unicorn};
+
+// device model of the driver (see `devices::ErrMemDevice`)
+pub const ERRMEM_DEVICE_PATH: &str = "/dev/errmem";
+
+pub fn hook_errmem_code(unicorn: &mut Unicorn<Context>, base_address: u32) {
+    // original base address: 0x484d8000
+    add_code_hook!(unicorn, "LIBOSAL", base_address + 0x57DDC, errmem_io_open);
+    add_code_hook!(unicorn, "LIBOSAL", base_address + 0x57DB8, errmem_io_close);
+    add_code_hook!(unicorn, "LIBOSAL", base_address + 0x57D84, errmem_io_write);
+    add_code_hook!(unicorn, "LIBOSAL", base_address + 0x57D98, errmem_io_read);
+    add_code_hook!(
+        unicorn,
+        "LIBOSAL",
+        base_address + 0x57D64,
+        errmem_io_control
+    );
+}
+
+pub fn errmem_io_open(unicorn: &mut Unicorn<Context>) -> u32 {
+    device_open(
+        unicorn,
+        ERRMEM_DEVICE_PATH,
+        OpenFileFlags::READ | OpenFileFlags::WRITE,
+    )
+}
+
+pub fn errmem_io_close(unicorn: &mut Unicorn<Context>) -> u32 {
+    device_close(unicorn)
+}
+
+pub fn errmem_io_write(unicorn: &mut Unicorn<Context>) -> u32 {
+    let (fd, buffer, size, ret_size_addr) = transfer_args(unicorn);
+    // the buffer is `trErrmemEntry`
+    if (size as usize) < ERRMEM_ENTRY_SIZE {
+        return OSAL_E_INVALIDVALUE;
+    }
+
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let res = file_system
+        .lock()
+        .unwrap()
+        .ioctl(unicorn, fd, ERRMEM_IOCTL_WRITE_ENTRY, buffer);
+    if res != 0 {
+        return to_osal_error(res);
+    }
+    if ret_size_addr != 0 {
+        unicorn
+            .mem_write_guest(ret_size_addr as u64, &pack_u32(ERRMEM_ENTRY_SIZE as u32))
+            .unwrap();
+    }
+    OSAL_E_NOERROR
+}
+
+pub fn errmem_io_read(unicorn: &mut Unicorn<Context>) -> u32 {
+    let (fd, buffer, size, ret_size_addr) = transfer_args(unicorn);
+
+    let mut content = vec![0u8; size as usize];
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let res = file_system.lock().unwrap().read(fd, &mut content);
+    match res {
+        Ok(len) => {
+            unicorn
+                .mem_write_guest(buffer as u64, &content[0..len as usize])
+                .unwrap();
+            if ret_size_addr != 0 {
+                unicorn
+                    .mem_write_guest(ret_size_addr as u64, &pack_u32(len as u32))
+                    .unwrap();
+            }
+            OSAL_E_NOERROR
+        }
+        Err(_) => OSAL_E_BADFILEDESCRIPTOR,
+    }
+}
+
+pub fn errmem_io_control(unicorn: &mut Unicorn<Context>) -> u32 {
+    device_control(unicorn)
+}
@@ -0,0 +1,152 @@
+use crate::devices::{
+    FFD_IOCTRL_READ_ENTRY, FFD_IOCTRL_RELOAD, FFD_IOCTRL_SAVENOW, FFD_IOCTRL_WRITE_ENTRY,
+};
+use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
+use crate::emulator::utils::{pack_u32, unpack_u32};
+use crate::file_system::OpenFileFlags;
+use crate::os::add_code_hook;
+use crate::os::libosal_linux::driver::{
+    device_close, device_control, device_open, to_osal_error, OSAL_E_NOERROR,
+};
+use unicorn_engine::{RegisterARM, Unicorn};
+
+const OSAL_OK: u32 = 0;
+const OSAL_ERROR: u32 = -1i32 as u32;
+
+// device model of the driver (see `devices::FfdDevice`)
+const FFD_DEVICE_PATH: &str = "/dev/ffd";
+
+pub fn hook_ffd_code(unicorn: &mut Unicorn<Context>, base_address: u32) {
+    // original base address: 0x484d8000
+    add_code_hook!(
+        unicorn,
+        "LIBOSAL",
+        base_address + 0x54DC8,
+        ffd_io_device_init
+    );
+    add_code_hook!(
+        unicorn,
+        "LIBOSAL",
+        base_address + 0x54C5C,
+        ffd_io_device_remove
+    );
+    add_code_hook!(unicorn, "LIBOSAL", base_address + 0x54A40, ffd_io_open);
+    add_code_hook!(unicorn, "LIBOSAL", base_address + 0x548F4, ffd_io_close);
+    add_code_hook!(unicorn, "LIBOSAL", base_address + 0x54674, ffd_io_read);
+    add_code_hook!(unicorn, "LIBOSAL", base_address + 0x54470, ffd_io_write);
+    add_code_hook!(unicorn, "LIBOSAL", base_address + 0x54260, ffd_io_control);
+    add_code_hook!(
+        unicorn,
+        "LIBOSAL",
+        base_address + 0x552B0,
+        s32_ffd_reload_data_from_file
+    );
+    add_code_hook!(
+        unicorn,
+        "LIBOSAL",
+        base_address + 0x55118,
+        s32_ffd_save_data_to_file
+    );
+    add_code_hook!(
+        unicorn,
+        "LIBOSAL",
+        base_address + 0x5575C,
+        s32_ffd_check_valid_read_data
+    );
+}
+
+/// Sends the request to the device model without opened descriptor.
+fn device_request(unicorn: &mut Unicorn<Context>, request: u32) -> i32 {
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let mut file_system = file_system.lock().unwrap();
+    match file_system.open(FFD_DEVICE_PATH, OpenFileFlags::READ | OpenFileFlags::WRITE) {
+        Ok(fd) => {
+            let res = file_system.ioctl(unicorn, fd, request, 0);
+            file_system.close(fd).unwrap();
+            res
+        }
+        Err(_) => -2i32, // -ENOENT
+    }
+}
+
+/// Reads or writes the data set `OSAL_trFFDDeviceInfo` in the buffer.
+fn transfer_entry(unicorn: &mut Unicorn<Context>, request: u32) -> u32 {
+    // DEV_FFD_s32IOxx(s32ID, u32FD, ps8Buffer, u32Size, pu32RetSize)
+    let fd = unicorn.reg_read(RegisterARM::R1).unwrap() as i32;
+    let buffer = unicorn.reg_read(RegisterARM::R2).unwrap() as u32;
+    let mut ret_size_addr = [0u8; 4];
+    unicorn
+        .mem_read(
+            unicorn.reg_read(RegisterARM::SP).unwrap(),
+            &mut ret_size_addr,
+        )
+        .unwrap();
+    let ret_size_addr = unpack_u32(&ret_size_addr);
+
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let res = file_system
+        .lock()
+        .unwrap()
+        .ioctl(unicorn, fd, request, buffer);
+    if res < 0 {
+        return to_osal_error(res);
+    }
+    if ret_size_addr != 0 {
+        unicorn
+            .mem_write_guest(ret_size_addr as u64, &pack_u32(res as u32))
+            .unwrap();
+    }
+    OSAL_E_NOERROR
+}
+
+pub fn ffd_io_device_init(_unicorn: &mut Unicorn<Context>) -> u32 {
+    OSAL_E_NOERROR
+}
+
+pub fn ffd_io_device_remove(_unicorn: &mut Unicorn<Context>) -> u32 {
+    OSAL_E_NOERROR
+}
+
+pub fn ffd_io_open(unicorn: &mut Unicorn<Context>) -> u32 {
+    device_open(
+        unicorn,
+        FFD_DEVICE_PATH,
+        OpenFileFlags::READ | OpenFileFlags::WRITE,
+    )
+}
+
+pub fn ffd_io_close(unicorn: &mut Unicorn<Context>) -> u32 {
+    device_close(unicorn)
+}
+
+pub fn ffd_io_read(unicorn: &mut Unicorn<Context>) -> u32 {
+    transfer_entry(unicorn, FFD_IOCTRL_READ_ENTRY)
+}
+
+pub fn ffd_io_write(unicorn: &mut Unicorn<Context>) -> u32 {
+    transfer_entry(unicorn, FFD_IOCTRL_WRITE_ENTRY)
+}
+
+pub fn ffd_io_control(unicorn: &mut Unicorn<Context>) -> u32 {
+    device_control(unicorn)
+}
+
+pub fn s32_ffd_reload_data_from_file(unicorn: &mut Unicorn<Context>) -> u32 {
+    match device_request(unicorn, FFD_IOCTRL_RELOAD) {
+        0 => OSAL_OK,
+        _ => OSAL_ERROR,
+    }
+}
+
+pub fn s32_ffd_save_data_to_file(unicorn: &mut Unicorn<Context>) -> u32 {
+    match device_request(unicorn, FFD_IOCTRL_SAVENOW) {
+        0 => OSAL_OK,
+        _ => OSAL_ERROR,
+    }
+}
+
+pub fn s32_ffd_check_valid_read_data(_unicorn: &mut Unicorn<Context>) -> u32 {
+    // checksums of the data sets are checked by the device model when they are loaded
+    OSAL_OK
+}
@@ -1,8 +1,22 @@
+use crate::devices::{REGISTRY_IOCTL_CREATE_KEY, REGISTRY_IOCTL_OPEN_KEY};
 use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
 use crate::emulator::utils::read_string;
+use crate::file_system::{FileType, OpenFileFlags};
 use crate::os::add_code_hook;
+use crate::os::syscalls::wait_for_fd;
 use unicorn_engine::{RegisterARM, Unicorn};
 
+const OSAL_OK: u32 = 0;
+const OSAL_ERROR: u32 = -1i32 as u32;
+
+// descriptors of the devices opened from `/dev` (file descriptor + base),
+// other descriptors are served by the library
+const DEVICE_HANDLE_BASE: u32 = 0x10000;
+
+// keys of the registry are opened as `/dev/registry/LOCAL_MACHINE/...`
+const REGISTRY_DEVICE_PATH: &str = "/dev/registry";
+
 pub fn hook_io_code(unicorn: &mut Unicorn<Context>, base_address: u32) {
     // original base address: 0x484d8000
     add_code_hook!(unicorn, "LIBOSAL", base_address + 0x1994C, io_open);
@@ -14,12 +28,88 @@ pub fn hook_io_code(unicorn: &mut Unicorn<Context>, base_address: u32) {
         base_address + 0x31744,
         s32_check_for_iosc_queue
     );
+    add_device_hook(unicorn, base_address + 0x18B70, "s32_io_read", s32_io_read);
+    add_device_hook(
+        unicorn,
+        base_address + 0x18950,
+        "s32_io_write",
+        s32_io_write,
+    );
+    add_device_hook(
+        unicorn,
+        base_address + 0x18F90,
+        "s32_io_close",
+        s32_io_close,
+    );
+}
+
+/// Hooks the function of the descriptor in R0, only the device descriptors are handled.
+fn add_device_hook(
+    unicorn: &mut Unicorn<Context>,
+    address: u32,
+    name: &'static str,
+    func: fn(&mut Unicorn<Context>, i32) -> u32,
+) {
+    unicorn
+        .add_code_hook(address as u64, address as u64, move |uc, _, _| {
+            let Some(fd) = device_fd(uc.reg_read(RegisterARM::R0).unwrap() as u32) else {
+                return;
+            };
+            log::trace!(
+                "{:#x}: [{}] [LIBOSAL HOOK] {}() [IN]",
+                uc.reg_read(RegisterARM::PC).unwrap(),
+                uc.get_data().inner.thread_id,
+                name
+            );
+            let res = func(uc, fd);
+            log::trace!(
+                "{:#x}: [{}] [LIBOSAL HOOK] {}() => {}",
+                uc.reg_read(RegisterARM::PC).unwrap(),
+                uc.get_data().inner.thread_id,
+                name,
+                res
+            );
+            uc.reg_write(RegisterARM::R0, res as u64).unwrap();
+            uc.reg_write(RegisterARM::PC, uc.reg_read(RegisterARM::LR).unwrap())
+                .unwrap();
+        })
+        .unwrap();
+}
+
+/// Returns file descriptor of the OSAL device descriptor.
+fn device_fd(handle: u32) -> Option<i32> {
+    if handle >= DEVICE_HANDLE_BASE && handle != OSAL_ERROR {
+        Some((handle - DEVICE_HANDLE_BASE) as i32)
+    } else {
+        None
+    }
 }
 
 pub fn io_open(unicorn: &mut Unicorn<Context>) -> u32 {
     let name = read_string(unicorn, unicorn.reg_read(RegisterARM::R0).unwrap() as u32);
     let param = unicorn.reg_read(RegisterARM::R1).unwrap();
     log::trace!("name: {}, param: {:#x}", name, param);
+
+    if is_registry_path(&name) {
+        return registry_open(unicorn, REGISTRY_IOCTL_OPEN_KEY);
+    }
+
+    // devices with the model in the dev file system (like `/dev/gnss`)
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let mut file_system = file_system.lock().unwrap();
+    let is_device = name.starts_with("/dev/")
+        && matches!(
+            file_system.get_file_info_from_filepath(&name),
+            Some(file_info) if file_info.file_details.file_type == FileType::CharacterDevice
+        );
+    if is_device {
+        // the access mode is not checked by the device models
+        return match file_system.open(&name, OpenFileFlags::READ | OpenFileFlags::WRITE) {
+            Ok(fd) => fd as u32 + DEVICE_HANDLE_BASE,
+            Err(_) => OSAL_ERROR,
+        };
+    }
+
     5u32
 }
 
@@ -27,16 +117,99 @@ pub fn io_create(unicorn: &mut Unicorn<Context>) -> u32 {
     let name = read_string(unicorn, unicorn.reg_read(RegisterARM::R0).unwrap() as u32);
     let param = unicorn.reg_read(RegisterARM::R1).unwrap();
     log::trace!("name: {}, param: {:#x}", name, param);
+
+    if is_registry_path(&name) {
+        return registry_open(unicorn, REGISTRY_IOCTL_CREATE_KEY);
+    }
+
     0u32
 }
 
+fn is_registry_path(name: &str) -> bool {
+    name.strip_prefix(REGISTRY_DEVICE_PATH)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Opens the registry key of the path in R0 (the request opens or creates it).
+fn registry_open(unicorn: &mut Unicorn<Context>, request: u32) -> u32 {
+    let name_addr = unicorn.reg_read(RegisterARM::R0).unwrap() as u32;
+
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let mut file_system = file_system.lock().unwrap();
+    let Ok(fd) = file_system.open(
+        REGISTRY_DEVICE_PATH,
+        OpenFileFlags::READ | OpenFileFlags::WRITE,
+    ) else {
+        return OSAL_ERROR;
+    };
+    if file_system.ioctl(unicorn, fd, request, name_addr) < 0 {
+        file_system.close(fd).unwrap();
+        return OSAL_ERROR;
+    }
+    fd as u32 + DEVICE_HANDLE_BASE
+}
+
 pub fn s32_io_control(unicorn: &mut Unicorn<Context>) -> u32 {
     let fd = unicorn.reg_read(RegisterARM::R0).unwrap();
     let param = unicorn.reg_read(RegisterARM::R1).unwrap();
     log::trace!("fd: {:#x}, param: {:#x}", fd, param);
+
+    if let Some(fd) = device_fd(fd as u32) {
+        let arg = unicorn.reg_read(RegisterARM::R2).unwrap() as u32;
+        let file_system = unicorn.get_data().inner.file_system.clone();
+        let res = file_system
+            .lock()
+            .unwrap()
+            .ioctl(unicorn, fd, param as u32, arg);
+        return if res < 0 { OSAL_ERROR } else { res as u32 };
+    }
+
     0u32
 }
 
+pub fn s32_io_read(unicorn: &mut Unicorn<Context>, fd: i32) -> u32 {
+    let buffer = unicorn.reg_read(RegisterARM::R1).unwrap();
+    let size = unicorn.reg_read(RegisterARM::R2).unwrap() as usize;
+
+    // OSAL reads are blocking
+    wait_for_fd(unicorn, fd, false);
+    let mut content = vec![0u8; size];
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let res = file_system.lock().unwrap().read(fd, &mut content);
+    match res {
+        Ok(len) => {
+            unicorn
+                .mem_write_guest(buffer, &content[0..len as usize])
+                .unwrap();
+            len as u32
+        }
+        Err(_) => OSAL_ERROR,
+    }
+}
+
+pub fn s32_io_write(unicorn: &mut Unicorn<Context>, fd: i32) -> u32 {
+    let buffer = unicorn.reg_read(RegisterARM::R1).unwrap();
+    let size = unicorn.reg_read(RegisterARM::R2).unwrap() as usize;
+
+    let mut content = vec![0u8; size];
+    unicorn.mem_read(buffer, &mut content).unwrap();
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let res = file_system.lock().unwrap().write(fd, &content);
+    match res {
+        Ok(len) => len as u32,
+        Err(_) => OSAL_ERROR,
+    }
+}
+
+pub fn s32_io_close(unicorn: &mut Unicorn<Context>, fd: i32) -> u32 {
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let res = file_system.lock().unwrap().close(fd);
+    match res {
+        Ok(_) => OSAL_OK,
+        Err(_) => OSAL_ERROR,
+    }
+}
+
 pub fn s32_check_for_iosc_queue(unicorn: &mut Unicorn<Context>) -> u32 {
     let name = read_string(unicorn, unicorn.reg_read(RegisterARM::R0).unwrap() as u32);
     log::trace!("queue_name: {}", name);
@@ -1,4 +1,5 @@
 use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
 use crate::emulator::utils::{pack_u32, read_string};
 use crate::os::add_code_hook;
 use unicorn_engine::{RegisterARM, Unicorn};
@@ -23,12 +24,6 @@ pub fn hook_message_code(unicorn: &mut Unicorn<Context>, base_address: u32) {
         base_address + 0x33A98,
         u32_open_msg_queue
     );
-    /*add_code_hook!(
-        unicorn,
-        "LIBOSAL",
-        base_address + 0x37028,
-        message_queue_open
-    );*/
 }
 
 // vInitMessagePool
@@ -47,14 +42,7 @@ pub fn s32_message_pool_create(unicorn: &mut Unicorn<Context>) -> u32 {
 pub fn u32_open_msg_queue(unicorn: &mut Unicorn<Context>) -> u32 {
     let queue_name = read_string(unicorn, unicorn.reg_read(RegisterARM::R0).unwrap() as u32);
     let arg2 = unicorn.reg_read(RegisterARM::R1).unwrap();
-    unicorn.mem_write(arg2, &pack_u32(1)).unwrap();
+    unicorn.mem_write_guest(arg2, &pack_u32(1)).unwrap();
     log::warn!("queue_name: {}, arg2: {:#x}", queue_name, arg2);
     1u32
 }
-
-/// OSAL_s32MessageQueueOpen
-pub fn message_queue_open(unicorn: &mut Unicorn<Context>) -> u32 {
-    let queue_name = read_string(unicorn, unicorn.reg_read(RegisterARM::R0).unwrap() as u32);
-    log::warn!("queue_name: {}", queue_name);
-    0u32
-}
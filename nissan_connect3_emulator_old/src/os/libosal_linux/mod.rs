@@ -1,9 +1,18 @@
+mod acoustic;
+mod bt;
+mod driver;
+mod errmem;
+mod ffd;
 mod init;
 mod io;
 mod message;
 mod trace;
 
 use crate::emulator::context::Context;
+use crate::os::libosal_linux::acoustic::hook_acoustic_code;
+use crate::os::libosal_linux::bt::hook_bt_code;
+use crate::os::libosal_linux::errmem::hook_errmem_code;
+use crate::os::libosal_linux::ffd::hook_ffd_code;
 use crate::os::libosal_linux::init::hook_core_code;
 use crate::os::libosal_linux::io::hook_io_code;
 use crate::os::libosal_linux::message::hook_message_code;
@@ -16,14 +25,18 @@ use std::sync::atomic::Ordering;
 use unicorn_engine::{RegisterARM, Unicorn};
 
 pub fn libosal_add_code_hooks(unicorn: &mut Unicorn<Context>, base_address: u32) {
+    hook_acoustic_code(unicorn, base_address);
+    hook_bt_code(unicorn, base_address);
     hook_core_code(unicorn, base_address);
+    hook_errmem_code(unicorn, base_address);
+    hook_ffd_code(unicorn, base_address);
     hook_io_code(unicorn, base_address);
     hook_message_code(unicorn, base_address);
     hook_trace_code(unicorn, base_address);
 
     let mut method_entries = HashMap::new();
     insert_libosal_method_entries(&mut method_entries);
-    for (mut address, method_name) in method_entries {
+    for (address, method_name) in method_entries {
         //address = address - 0x484d8000 + base_address;
         unicorn
             .add_code_hook(address as u64, address as u64, move |uc, addr, _| {
@@ -34,11 +47,13 @@ pub fn libosal_add_code_hooks(unicorn: &mut Unicorn<Context>, base_address: u32)
 }
 
 fn handle_hook(uc: &mut Unicorn<Context>, addr: u64, method_name: &str) {
-    let mut tracing = uc
+    // registers and the disassembly are logged for every OSAL call on the trace level
+    let tracing = uc
         .get_data()
         .inner
         .instruction_tracing
-        .load(Ordering::Relaxed);
+        .load(Ordering::Relaxed)
+        || log::log_enabled!(log::Level::Trace);
 
     let addr = addr as u32;
 
@@ -48,8 +63,6 @@ fn handle_hook(uc: &mut Unicorn<Context>, addr: u64, method_name: &str) {
         uc.get_data().inner.thread_id,
         method_name
     );
-    tracing = true;
-
     /*if method_name == "vInitTrace" {
         // skip method that normally crashes
         uc.reg_write(RegisterARM::PC, uc.reg_read(RegisterARM::LR).unwrap())
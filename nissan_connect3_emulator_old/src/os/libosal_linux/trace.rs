@@ -1,6 +1,9 @@
+use crate::devices::ERRMEM_IOCTL_WRITE_TEXT;
 use crate::emulator::context::Context;
 use crate::emulator::utils::read_string;
+use crate::file_system::OpenFileFlags;
 use crate::os::add_code_hook;
+use crate::os::libosal_linux::errmem::ERRMEM_DEVICE_PATH;
 use unicorn_engine::{RegisterARM, Unicorn};
 
 pub fn hook_trace_code(unicorn: &mut Unicorn<Context>, base_address: u32) {
@@ -51,10 +54,23 @@ pub fn v_trace_mq_info(unicorn: &mut Unicorn<Context>) -> u32 {
 pub fn v_write_to_err_mem(unicorn: &mut Unicorn<Context>) -> u32 {
     let arg1 = unicorn.reg_read(RegisterARM::R0).unwrap() as u32;
     let arg2 = unicorn.reg_read(RegisterARM::R1).unwrap() as u32;
-    log::warn!("{:#x} {:#x}", arg1, arg2);
-    if arg2 != 0 {
-        let text = read_string(unicorn, arg2);
-        log::warn!("{}", text);
+    log::trace!("{:#x} {:#x}", arg1, arg2);
+    if arg2 == 0 {
+        return 0u32;
+    }
+
+    // the text is stored by the device model
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let mut file_system = file_system.lock().unwrap();
+    match file_system.open(ERRMEM_DEVICE_PATH, OpenFileFlags::WRITE) {
+        Ok(fd) => {
+            file_system.ioctl(unicorn, fd, ERRMEM_IOCTL_WRITE_TEXT, arg2);
+            file_system.close(fd).unwrap();
+        }
+        Err(_) => {
+            let text = read_string(unicorn, arg2);
+            log::warn!("{}", text);
+        }
     }
     0u32
 }
@@ -3,8 +3,7 @@ mod trace;
 use crate::emulator::context::Context;
 use crate::os::libtrace::trace::*;
 use std::collections::HashMap;
-use std::sync::atomic::Ordering;
-use unicorn_engine::{RegisterARM, Unicorn};
+use unicorn_engine::Unicorn;
 
 pub fn libtrace_add_code_hooks(unicorn: &mut Unicorn<Context>, base_address: u32) {
     hook_trace_code(unicorn, base_address);
@@ -12,7 +11,7 @@ pub fn libtrace_add_code_hooks(unicorn: &mut Unicorn<Context>, base_address: u32
     let mut method_entries = HashMap::new();
     insert_libtrace_method_entries(&mut method_entries);
     for (mut address, method_name) in method_entries {
-        address = base_address + address;
+        address += base_address;
         unicorn
             .add_code_hook(address as u64, address as u64, move |uc, addr, _| {
                 handle_hook(uc, addr, method_name)
@@ -0,0 +1,300 @@
+use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
+use crate::emulator::utils::{pack_u32, pack_u64, unpack_u32};
+use crate::file_system::{AnonFile, Epoll, EpollInterest, OpenFileFlags};
+use crate::os::syscalls::poll::wait_for_file_system;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use unicorn_engine::{RegisterARM, Unicorn};
+
+const EPOLL_CTL_ADD: u32 = 1;
+const EPOLL_CTL_DEL: u32 = 2;
+const EPOLL_CTL_MOD: u32 = 3;
+
+const EPOLLONESHOT: u32 = 1 << 30;
+// edge-triggered notifications are not supported (readiness is not tracked between calls)
+const EPOLLET: u32 = 1 << 31;
+
+// struct epoll_event { uint32_t events; epoll_data_t data; } (not packed on ARM)
+const EPOLL_EVENT_SIZE: u32 = 16;
+
+pub fn epoll_create(unicorn: &mut Unicorn<Context>, size: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] epoll_create(size = {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        size,
+    );
+
+    let res = if size as i32 <= 0 {
+        -22i32 as u32 // EINVAL
+    } else {
+        epoll_create_internal(unicorn)
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] epoll_create => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn epoll_create1(unicorn: &mut Unicorn<Context>, flags: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] epoll_create1(flags = {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        flags,
+    );
+
+    let res = epoll_create_internal(unicorn);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] epoll_create1 => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn epoll_ctl(unicorn: &mut Unicorn<Context>, epfd: u32, op: u32, fd: u32, event: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] epoll_ctl(epfd = {:#x}, op: {:#x}, fd: {:#x}, event: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        epfd,
+        op,
+        fd,
+        event,
+    );
+
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let mut file_system = file_system.lock().unwrap();
+
+    let interest = match file_system.file_of(fd as i32) {
+        Some(file) if event != 0 => {
+            let mut buf = vec![0u8; EPOLL_EVENT_SIZE as usize];
+            unicorn.mem_read(event as u64, &mut buf).unwrap();
+            Some(EpollInterest {
+                events: unpack_u32(&buf[0..4]),
+                data: u64::from_le_bytes(buf[8..16].try_into().unwrap()),
+                file,
+            })
+        }
+        _ => None,
+    };
+
+    let res = if !file_system.is_open(fd as i32) {
+        -9i32 as u32 // EBADF
+    } else if fd == epfd || interest.as_ref().is_some_and(|i| i.events & EPOLLET != 0) {
+        -22i32 as u32 // EINVAL
+    } else if op == EPOLL_CTL_ADD
+        && file_system.with_anon_file(epfd as i32, |file| matches!(file, AnonFile::Epoll(_)))
+            == Some(true)
+        && file_system.is_epoll_loop(epfd as i32, fd as i32)
+    {
+        -40i32 as u32 // ELOOP
+    } else {
+        file_system
+            .with_anon_file_mut(epfd as i32, |file| match file {
+                AnonFile::Epoll(epoll) => match (op, interest) {
+                    (EPOLL_CTL_ADD, Some(interest)) => {
+                        if let std::collections::btree_map::Entry::Vacant(e) =
+                            epoll.interests.entry(fd as i32)
+                        {
+                            e.insert(interest);
+                            0u32
+                        } else {
+                            -17i32 as u32 // EEXIST
+                        }
+                    }
+                    (EPOLL_CTL_MOD, Some(interest)) => {
+                        if let Some(entry) = epoll.interests.get_mut(&(fd as i32)) {
+                            *entry = interest;
+                            0u32
+                        } else {
+                            -2i32 as u32 // ENOENT
+                        }
+                    }
+                    (EPOLL_CTL_DEL, _) => {
+                        if epoll.interests.remove(&(fd as i32)).is_some() {
+                            0u32
+                        } else {
+                            -2i32 as u32 // ENOENT
+                        }
+                    }
+                    _ => -22i32 as u32, // EINVAL
+                },
+                _ => -22i32 as u32, // EINVAL
+            })
+            .unwrap_or(-9i32 as u32) // EBADF
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] epoll_ctl => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn epoll_wait(
+    unicorn: &mut Unicorn<Context>,
+    epfd: u32,
+    events: u32,
+    max_events: u32,
+    timeout: u32,
+) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] epoll_wait(epfd = {:#x}, events: {:#x}, max_events: {:#x}, timeout: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        epfd,
+        events,
+        max_events,
+        timeout,
+    );
+
+    let res = epoll_wait_internal(unicorn, epfd, events, max_events, timeout);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] epoll_wait => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn epoll_pwait(
+    unicorn: &mut Unicorn<Context>,
+    epfd: u32,
+    events: u32,
+    max_events: u32,
+    timeout: u32,
+    sigmask: u32,
+) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] epoll_pwait(epfd = {:#x}, events: {:#x}, max_events: {:#x}, timeout: {:#x}, sigmask: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        epfd,
+        events,
+        max_events,
+        timeout,
+        sigmask,
+    );
+
+    let res = epoll_wait_internal(unicorn, epfd, events, max_events, timeout);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] epoll_pwait => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+fn epoll_create_internal(unicorn: &mut Unicorn<Context>) -> u32 {
+    let epoll = Epoll {
+        interests: BTreeMap::new(),
+    };
+
+    unicorn
+        .get_data()
+        .inner
+        .file_system
+        .lock()
+        .unwrap()
+        .open_anon(AnonFile::Epoll(epoll), OpenFileFlags::READ) as u32
+}
+
+fn epoll_wait_internal(
+    unicorn: &mut Unicorn<Context>,
+    epfd: u32,
+    events: u32,
+    max_events: u32,
+    timeout: u32,
+) -> u32 {
+    if max_events as i32 <= 0 {
+        return -22i32 as u32; // EINVAL
+    }
+
+    let is_epoll = unicorn
+        .get_data()
+        .inner
+        .file_system
+        .lock()
+        .unwrap()
+        .with_anon_file(epfd as i32, |file| matches!(file, AnonFile::Epoll(_)));
+    match is_epoll {
+        None => return -9i32 as u32,         // EBADF
+        Some(false) => return -22i32 as u32, // EINVAL
+        Some(true) => {}
+    }
+
+    // negative value means infinite timeout
+    let timeout = if (timeout as i32) < 0 {
+        None
+    } else {
+        Some(Duration::from_millis(timeout as u64))
+    };
+
+    let ready_events = wait_for_file_system(unicorn, timeout, |file_system| {
+        let interests = file_system
+            .with_anon_file(epfd as i32, |file| match file {
+                AnonFile::Epoll(epoll) => epoll.interests.clone(),
+                _ => BTreeMap::new(),
+            })
+            .unwrap_or_default();
+
+        let ready_events = file_system.epoll_ready_events(interests, max_events as usize);
+        if ready_events.is_empty() {
+            None
+        } else {
+            Some(ready_events)
+        }
+    })
+    .unwrap_or_default();
+
+    // disable descriptors registered with EPOLLONESHOT
+    if !ready_events.is_empty() {
+        unicorn
+            .get_data()
+            .inner
+            .file_system
+            .lock()
+            .unwrap()
+            .with_anon_file_mut(epfd as i32, |file| {
+                if let AnonFile::Epoll(epoll) = file {
+                    for (fd, _, _) in &ready_events {
+                        if let Some(interest) = epoll.interests.get_mut(fd) {
+                            if interest.events & EPOLLONESHOT != 0 {
+                                interest.events = EPOLLONESHOT;
+                            }
+                        }
+                    }
+                }
+            });
+    }
+
+    let mut buf = Vec::new();
+    for (_, events, data) in &ready_events {
+        buf.extend_from_slice(&pack_u32(*events));
+        buf.extend_from_slice(&pack_u32(0)); // padding
+        buf.extend_from_slice(&pack_u64(*data));
+    }
+    unicorn.mem_write_guest(events as u64, &buf).unwrap();
+
+    ready_events.len() as u32
+}
@@ -0,0 +1,68 @@
+use crate::emulator::context::Context;
+use crate::file_system::{AnonFile, EventFd, OpenFileFlags};
+use unicorn_engine::{RegisterARM, Unicorn};
+
+const EFD_SEMAPHORE: u32 = 0x1;
+const EFD_NONBLOCK: u32 = 0x800;
+
+pub fn eventfd(unicorn: &mut Unicorn<Context>, initval: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] eventfd(initval = {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        initval,
+    );
+
+    let res = eventfd_internal(unicorn, initval, 0);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] eventfd => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn eventfd2(unicorn: &mut Unicorn<Context>, initval: u32, flags: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] eventfd2(initval = {:#x}, flags: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        initval,
+        flags,
+    );
+
+    let res = eventfd_internal(unicorn, initval, flags);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] eventfd2 => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+fn eventfd_internal(unicorn: &mut Unicorn<Context>, initval: u32, flags: u32) -> u32 {
+    let event_fd = EventFd {
+        counter: initval as u64,
+        is_semaphore: flags & EFD_SEMAPHORE != 0,
+    };
+
+    let open_file_flags = if flags & EFD_NONBLOCK != 0 {
+        OpenFileFlags::READ | OpenFileFlags::WRITE | OpenFileFlags::NONBLOCK
+    } else {
+        OpenFileFlags::READ | OpenFileFlags::WRITE
+    };
+
+    unicorn
+        .get_data()
+        .inner
+        .file_system
+        .lock()
+        .unwrap()
+        .open_anon(AnonFile::EventFd(event_fd), open_file_flags) as u32
+}
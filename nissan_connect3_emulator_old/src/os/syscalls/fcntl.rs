@@ -1,6 +1,8 @@
 use crate::emulator::context::Context;
 use crate::emulator::utils::read_string;
 use crate::file_system::OpenFileFlags;
+use crate::os::syscalls::stat::get_umask;
+use crate::os::syscalls::SysCallError;
 use std::path::PathBuf;
 use unicorn_engine::{RegisterARM, Unicorn};
 
@@ -52,7 +54,6 @@ pub fn openat(
     let path_name = read_string(unicorn, path_name);
     let path_name_new = get_path_relative_to_dir(unicorn, dirfd, &path_name);
 
-    // TODO: handle symbolic links
     let fd = open_internal(unicorn, &path_name_new, flags, mode);
 
     log::trace!(
@@ -116,6 +117,18 @@ pub fn get_path_relative_to_dir(
     path_name
 }
 
+/// Translates `/proc/thread-self` to the directory of the calling thread
+/// (proc-fs knows the process of `/proc/self`, but not the thread).
+pub fn resolve_proc_thread_self(unicorn: &Unicorn<Context>, path_name: &str) -> String {
+    let inner = &unicorn.get_data().inner;
+    if let Some(rest) = path_name.strip_prefix("/proc/thread-self") {
+        if rest.is_empty() || rest.starts_with('/') {
+            return format!("/proc/{}/task/{}{}", inner.pid, inner.thread_id, rest);
+        }
+    }
+    path_name.to_string()
+}
+
 pub fn fcntl64(unicorn: &mut Unicorn<Context>, fd: u32, cmd: u32, arg1: u32) -> u32 {
     log::trace!(
         "{:#x}: [{}] [SYSCALL] fcntl64(fd = {:#x}, cmd = {:#x}, arg1: {:#x}) [IN]",
@@ -127,6 +140,18 @@ pub fn fcntl64(unicorn: &mut Unicorn<Context>, fd: u32, cmd: u32, arg1: u32) ->
     );
 
     let res = match cmd {
+        0 | 1030 => {
+            // F_DUPFD, F_DUPFD_CLOEXEC
+            let flags = if cmd == 1030 { 0x1 } else { 0 };
+            match unicorn.get_data().inner.file_system.lock().unwrap().dup(
+                fd as i32,
+                arg1 as i32,
+                flags,
+            ) {
+                Ok(fd) => fd as u32,
+                Err(_) => -9i32 as u32, // -EBADF
+            }
+        }
         1 => {
             // F_GETFD
             if let Some(fileinfo) = unicorn
@@ -144,13 +169,14 @@ pub fn fcntl64(unicorn: &mut Unicorn<Context>, fd: u32, cmd: u32, arg1: u32) ->
         }
         2 => {
             // F_SETFD
-            if let Ok(_) = unicorn
+            if unicorn
                 .get_data()
                 .inner
                 .file_system
                 .lock()
                 .unwrap()
                 .set_file_status_flags(fd as i32, arg1)
+                .is_ok()
             {
                 0u32
             } else {
@@ -159,26 +185,38 @@ pub fn fcntl64(unicorn: &mut Unicorn<Context>, fd: u32, cmd: u32, arg1: u32) ->
         }
         3 => {
             // F_GETFL
-            if let Some(fileinfo) = unicorn
-                .get_data()
-                .inner
-                .file_system
-                .lock()
-                .unwrap()
-                .get_file_info(fd as i32)
-            {
-                if fileinfo.file_details.is_readonly {
+            let file_system = unicorn.get_data().inner.file_system.clone();
+            let mut file_system = file_system.lock().unwrap();
+            if let Some(fileinfo) = file_system.get_file_info(fd as i32) {
+                let access_mode = if fileinfo.file_details.is_readonly {
                     0u32
                 } else {
                     2u32 // O_RDWR
+                };
+                if file_system.is_nonblocking(fd as i32) {
+                    access_mode | 0x800 // O_NONBLOCK
+                } else {
+                    access_mode
                 }
             } else {
                 -1i32 as u32
             }
         }
         4 => {
-            // F_SETFL
-            0u32
+            // F_SETFL (only O_NONBLOCK can be changed)
+            if unicorn
+                .get_data()
+                .inner
+                .file_system
+                .lock()
+                .unwrap()
+                .set_nonblocking(fd as i32, arg1 & 0x800 != 0)
+                .is_ok()
+            {
+                0u32
+            } else {
+                -1i32 as u32
+            }
         }
         _ => panic!("unsupported command"),
     };
@@ -193,32 +231,40 @@ pub fn fcntl64(unicorn: &mut Unicorn<Context>, fd: u32, cmd: u32, arg1: u32) ->
     res
 }
 
-fn open_internal(unicorn: &mut Unicorn<Context>, path_name: &str, flags: u32, _mode: u32) -> u32 {
+fn open_internal(unicorn: &mut Unicorn<Context>, path_name: &str, flags: u32, mode: u32) -> u32 {
     let open_file_flags = convert_open_file_flags(flags);
+    let path_name = resolve_proc_thread_self(unicorn, path_name);
+    let mode = mode & !get_umask(unicorn);
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let mut file_system = file_system.lock().unwrap();
 
-    if let Ok(fd) = unicorn
-        .get_data()
-        .inner
-        .file_system
-        .lock()
-        .unwrap()
-        .open(&path_name, open_file_flags)
+    // O_NOFOLLOW fails on the symbolic link in the last component (checked before opening,
+    // so inotify watchers see no access)
+    if open_file_flags.contains(OpenFileFlags::NO_FOLLOW)
+        && file_system.read_link(&path_name).is_ok()
     {
-        fd as u32
-    } else {
-        -1i32 as u32
+        return -40i32 as u32; // -ELOOP
+    }
+
+    match file_system.open_checked(&path_name, open_file_flags, mode & 0o7777) {
+        Ok(fd) => {
+            if flags & 0x80000 != 0 {
+                // O_CLOEXEC
+                file_system.set_file_status_flags(fd, 0x1).unwrap();
+            }
+            fd as u32
+        }
+        Err(err) => err.to_syscall_error(),
     }
 }
 
 fn convert_open_file_flags(flags: u32) -> OpenFileFlags {
     let mut open_file_flags = OpenFileFlags::NONE;
 
-    if flags & 0x2 == 0 {
-        open_file_flags |= OpenFileFlags::READ;
-    } else if flags & 0x2 == 1 {
-        open_file_flags |= OpenFileFlags::WRITE;
-    } else if flags & 0x2 == 2 {
-        open_file_flags |= OpenFileFlags::READ | OpenFileFlags::WRITE;
+    match flags & 0x3 {
+        0 => open_file_flags |= OpenFileFlags::READ,
+        1 => open_file_flags |= OpenFileFlags::WRITE,
+        _ => open_file_flags |= OpenFileFlags::READ | OpenFileFlags::WRITE,
     }
 
     if flags & 0x40 != 0 {
@@ -237,11 +283,15 @@ fn convert_open_file_flags(flags: u32) -> OpenFileFlags {
         open_file_flags |= OpenFileFlags::APPEND;
     }
 
+    if flags & 0x800 != 0 {
+        open_file_flags |= OpenFileFlags::NONBLOCK;
+    }
+
     if flags & 0x4000 != 0 {
         open_file_flags |= OpenFileFlags::DIRECTORY;
     }
 
-    if flags & 0x20000 != 0 {
+    if flags & 0x8000 != 0 {
         open_file_flags |= OpenFileFlags::NO_FOLLOW;
     }
 
@@ -60,35 +60,35 @@ pub fn futex(
             let val_read = unpack_u32(&buf);
 
             if val_read != val {
-                -11i32 as u32; // EAGAIN
-            }
-
-            // wait
-            log::trace!(
-                "{:#x}: [{}] [SYSCALL] futex - wait",
-                unicorn.reg_read(RegisterARM::PC).unwrap(),
-                unicorn.get_data().inner.thread_id,
-            );
-
-            let (sender, receiver) = channel();
-            {
-                let data = unicorn.get_data();
-                let sys_call_state = &mut data.inner.sys_calls_state.lock().unwrap();
-                sys_call_state
-                    .futex_waiters
-                    .entry(uaddr)
-                    .or_insert(Vec::new())
-                    .push(sender);
-            }
-            receiver.recv().unwrap();
+                -11i32 as u32 // -EAGAIN
+            } else {
+                // wait
+                log::trace!(
+                    "{:#x}: [{}] [SYSCALL] futex - wait",
+                    unicorn.reg_read(RegisterARM::PC).unwrap(),
+                    unicorn.get_data().inner.thread_id,
+                );
+
+                let (sender, receiver) = channel();
+                {
+                    let data = unicorn.get_data();
+                    let sys_call_state = &mut data.inner.sys_calls_state.lock().unwrap();
+                    sys_call_state
+                        .futex_waiters
+                        .entry(uaddr)
+                        .or_default()
+                        .push(sender);
+                }
+                receiver.recv().unwrap();
 
-            log::trace!(
-                "{:#x}: [{}] [SYSCALL] futex - woken up",
-                unicorn.reg_read(RegisterARM::PC).unwrap(),
-                unicorn.get_data().inner.thread_id,
-            );
+                log::trace!(
+                    "{:#x}: [{}] [SYSCALL] futex - woken up",
+                    unicorn.reg_read(RegisterARM::PC).unwrap(),
+                    unicorn.get_data().inner.thread_id,
+                );
 
-            0u32
+                0u32
+            }
         }
         0x01 => {
             // FUTEX_WAKE - wake at most `val` waiters
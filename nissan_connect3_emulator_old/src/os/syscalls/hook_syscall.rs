@@ -1,8 +1,8 @@
 use crate::emulator::context::Context;
 use crate::emulator::utils::read_string;
 use crate::os::syscalls::{
-    fcntl, futex, ioctl, linux, mman, prctl, resource, sched, signal, socket, stat, time, uio,
-    unistd, utsname,
+    epoll, eventfd, fcntl, futex, inotify, ioctl, linux, mman, poll, prctl, resource, sched,
+    signal, signalfd, socket, stat, time, timerfd, uio, unistd, utsname, wait,
 };
 use std::time::Duration;
 use unicorn_engine::{RegisterARM, Unicorn};
@@ -17,6 +17,7 @@ pub fn hook_syscall(unicorn: &mut Unicorn<Context>, int_no: u32) {
     // - https://github.com/qilingframework/qiling/tree/master/qiling/os/posix/syscall
     let res = match unicorn.get_syscall_number() {
         1 => unistd::exit(unicorn, unicorn.get_u32_arg(0)),
+        2 => unistd::fork(unicorn),
         3 => unistd::read(
             unicorn,
             unicorn.get_u32_arg(0),
@@ -38,6 +39,19 @@ pub fn hook_syscall(unicorn: &mut Unicorn<Context>, int_no: u32) {
         6 => unistd::close(unicorn, unicorn.get_u32_arg(0)),
         9 => unistd::link(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
         10 => unistd::unlink(unicorn, unicorn.get_u32_arg(0)),
+        11 => unistd::execve(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+        ),
+        14 => stat::mknod(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+        ),
+        15 => stat::chmod(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
         19 => unistd::lseek(
             unicorn,
             unicorn.get_u32_arg(0),
@@ -46,7 +60,12 @@ pub fn hook_syscall(unicorn: &mut Unicorn<Context>, int_no: u32) {
         ),
         20 => unistd::get_pid(unicorn),
         33 => unistd::access(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
+        37 => signal::kill(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
+        38 => unistd::rename(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
         39 => stat::mkdir(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
+        40 => unistd::rmdir(unicorn, unicorn.get_u32_arg(0)),
+        41 => unistd::dup(unicorn, unicorn.get_u32_arg(0)),
+        42 => unistd::pipe(unicorn, unicorn.get_u32_arg(0)),
         45 => unistd::brk(unicorn, unicorn.get_u32_arg(0)),
         54 => ioctl::ioctl(
             unicorn,
@@ -55,8 +74,16 @@ pub fn hook_syscall(unicorn: &mut Unicorn<Context>, int_no: u32) {
             unicorn.get_u32_arg(2),
         ),
         60 => stat::umask(unicorn, unicorn.get_u32_arg(0)),
+        63 => unistd::dup2(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
+        64 => unistd::get_ppid(unicorn),
         78 => time::gettimeofday(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
         83 => unistd::symlink(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
+        85 => unistd::readlink(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+        ),
         88 => unistd::reboot(unicorn, unicorn.get_u32_arg(0)),
         90 => mman::mmap(
             unicorn,
@@ -68,7 +95,9 @@ pub fn hook_syscall(unicorn: &mut Unicorn<Context>, int_no: u32) {
             unicorn.get_u32_arg(5),
         ),
         91 => mman::munmap(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
+        92 => unistd::truncate(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
         93 => unistd::ftruncate(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
+        94 => stat::fchmod(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
         97 => resource::set_priority(
             unicorn,
             unicorn.get_u32_arg(0),
@@ -76,6 +105,13 @@ pub fn hook_syscall(unicorn: &mut Unicorn<Context>, int_no: u32) {
             unicorn.get_u32_arg(2),
         ),
         99 => stat::statfs(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
+        114 => wait::wait4(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+            unicorn.get_u32_arg(3),
+        ),
         120 => sched::clone(
             unicorn,
             unicorn.get_u32_arg(0),
@@ -99,6 +135,12 @@ pub fn hook_syscall(unicorn: &mut Unicorn<Context>, int_no: u32) {
             unicorn.get_u32_arg(3),
             unicorn.get_u32_arg(4),
         ),
+        144 => mman::msync(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+        ),
         146 => uio::writev(
             unicorn,
             unicorn.get_u32_arg(0),
@@ -114,6 +156,12 @@ pub fn hook_syscall(unicorn: &mut Unicorn<Context>, int_no: u32) {
         159 => sched::sched_get_priority_max(unicorn, unicorn.get_u32_arg(0)),
         160 => sched::sched_get_priority_min(unicorn, unicorn.get_u32_arg(0)),
         162 => time::nanosleep(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
+        168 => poll::poll(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+        ),
         172 => prctl::prctl(
             unicorn,
             unicorn.get_u32_arg(0),
@@ -143,6 +191,7 @@ pub fn hook_syscall(unicorn: &mut Unicorn<Context>, int_no: u32) {
             unicorn.get_u32_arg(3),
         ),
         186 => signal::sigaltstack(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
+        190 => unistd::vfork(unicorn),
         191 => resource::ugetrlimit(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
         192 => mman::mmap2(
             unicorn,
@@ -156,6 +205,24 @@ pub fn hook_syscall(unicorn: &mut Unicorn<Context>, int_no: u32) {
         195 => stat::stat64(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
         196 => stat::lstat64(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
         197 => stat::fstat64(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
+        198 => unistd::lchown32(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+        ),
+        207 => unistd::fchown32(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+        ),
+        212 => unistd::chown32(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+        ),
         217 => unistd::getdents64(
             unicorn,
             unicorn.get_u32_arg(0),
@@ -175,6 +242,7 @@ pub fn hook_syscall(unicorn: &mut Unicorn<Context>, int_no: u32) {
             unicorn.get_u32_arg(2),
         ),
         224 => unistd::get_tid(unicorn),
+        238 => signal::tkill(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
         240 => futex::futex(
             unicorn,
             unicorn.get_u32_arg(0),
@@ -185,14 +253,41 @@ pub fn hook_syscall(unicorn: &mut Unicorn<Context>, int_no: u32) {
             unicorn.get_u32_arg(5),
         ),
         248 => unistd::exit_group(unicorn, unicorn.get_u32_arg(0)),
+        250 => epoll::epoll_create(unicorn, unicorn.get_u32_arg(0)),
+        251 => epoll::epoll_ctl(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+            unicorn.get_u32_arg(3),
+        ),
+        252 => epoll::epoll_wait(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+            unicorn.get_u32_arg(3),
+        ),
         256 => unistd::set_tid_address(unicorn, unicorn.get_u32_arg(0)),
         263 => time::clock_gettime(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
+        268 => signal::tgkill(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+        ),
         281 => socket::socket(
             unicorn,
             unicorn.get_u32_arg(0),
             unicorn.get_u32_arg(1),
             unicorn.get_u32_arg(2),
         ),
+        282 => socket::bind(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+        ),
         283 => socket::connect(
             unicorn,
             unicorn.get_u32_arg(0),
@@ -206,6 +301,53 @@ pub fn hook_syscall(unicorn: &mut Unicorn<Context>, int_no: u32) {
             unicorn.get_u32_arg(2),
             unicorn.get_u32_arg(3),
         ),
+        290 => socket::sendto(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+            unicorn.get_u32_arg(3),
+            unicorn.get_u32_arg(4),
+            unicorn.get_u32_arg(5),
+        ),
+        291 => socket::recv(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+            unicorn.get_u32_arg(3),
+        ),
+        292 => socket::recvfrom(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+            unicorn.get_u32_arg(3),
+            unicorn.get_u32_arg(4),
+            unicorn.get_u32_arg(5),
+        ),
+        294 => socket::setsockopt(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+            unicorn.get_u32_arg(3),
+            unicorn.get_u32_arg(4),
+        ),
+        306 => stat::fchmodat(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+        ),
+        316 => inotify::inotify_init(unicorn),
+        317 => inotify::inotify_add_watch(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+        ),
+        318 => inotify::inotify_rm_watch(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
         322 => fcntl::openat(
             unicorn,
             unicorn.get_u32_arg(0),
@@ -213,6 +355,27 @@ pub fn hook_syscall(unicorn: &mut Unicorn<Context>, int_no: u32) {
             unicorn.get_u32_arg(2),
             unicorn.get_u32_arg(3),
         ),
+        323 => stat::mkdirat(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+        ),
+        324 => stat::mknodat(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+            unicorn.get_u32_arg(3),
+        ),
+        325 => unistd::fchownat(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+            unicorn.get_u32_arg(3),
+            unicorn.get_u32_arg(4),
+        ),
         327 => stat::fstatat64(
             unicorn,
             unicorn.get_u32_arg(0),
@@ -220,7 +383,96 @@ pub fn hook_syscall(unicorn: &mut Unicorn<Context>, int_no: u32) {
             unicorn.get_u32_arg(2),
             unicorn.get_u32_arg(3),
         ),
+        328 => unistd::unlinkat(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+        ),
+        329 => unistd::renameat(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+            unicorn.get_u32_arg(3),
+        ),
+        331 => unistd::symlinkat(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+        ),
+        332 => unistd::readlinkat(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+            unicorn.get_u32_arg(3),
+        ),
+        334 => unistd::faccessat(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+        ),
+        336 => poll::ppoll(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+            unicorn.get_u32_arg(3),
+            unicorn.get_u32_arg(4),
+        ),
         338 => futex::set_robust_list(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
+        346 => epoll::epoll_pwait(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+            unicorn.get_u32_arg(3),
+            unicorn.get_u32_arg(4),
+        ),
+        349 => signalfd::signalfd(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+        ),
+        350 => timerfd::timerfd_create(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
+        351 => eventfd::eventfd(unicorn, unicorn.get_u32_arg(0)),
+        353 => timerfd::timerfd_settime(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+            unicorn.get_u32_arg(3),
+        ),
+        354 => timerfd::timerfd_gettime(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
+        355 => signalfd::signalfd4(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+            unicorn.get_u32_arg(3),
+        ),
+        356 => eventfd::eventfd2(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
+        357 => epoll::epoll_create1(unicorn, unicorn.get_u32_arg(0)),
+        358 => unistd::dup3(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+        ),
+        359 => unistd::pipe2(unicorn, unicorn.get_u32_arg(0), unicorn.get_u32_arg(1)),
+        360 => inotify::inotify_init1(unicorn, unicorn.get_u32_arg(0)),
+        382 => unistd::renameat2(
+            unicorn,
+            unicorn.get_u32_arg(0),
+            unicorn.get_u32_arg(1),
+            unicorn.get_u32_arg(2),
+            unicorn.get_u32_arg(3),
+            unicorn.get_u32_arg(4),
+        ),
         983045 => linux::set_tls(unicorn, unicorn.get_u32_arg(0)),
         x => {
             if x == 274 {
@@ -0,0 +1,165 @@
+use crate::emulator::context::Context;
+use crate::emulator::users::{egid, euid};
+use crate::emulator::utils::read_string;
+use crate::file_system::{
+    AccessMode, AnonFile, FileType, Inotify, OpenFileFlags, IN_ALL_EVENTS, IN_DONT_FOLLOW,
+    IN_ONLYDIR,
+};
+use crate::os::syscalls::SysCallError;
+use unicorn_engine::{RegisterARM, Unicorn};
+
+const IN_NONBLOCK: u32 = 0x800;
+
+pub fn inotify_init(unicorn: &mut Unicorn<Context>) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] inotify_init() [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+    );
+
+    let res = inotify_init_internal(unicorn, 0);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] inotify_init => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn inotify_init1(unicorn: &mut Unicorn<Context>, flags: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] inotify_init1(flags: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        flags,
+    );
+
+    let res = inotify_init_internal(unicorn, flags);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] inotify_init1 => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn inotify_add_watch(unicorn: &mut Unicorn<Context>, fd: u32, path: u32, mask: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] inotify_add_watch(fd: {:#x}, path: {:#x}, mask: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        fd,
+        path,
+        mask,
+    );
+
+    let path_name = read_string(unicorn, path);
+    log::trace!("path = {}", path_name);
+
+    let res = inotify_add_watch_internal(unicorn, fd, &path_name, mask);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] inotify_add_watch => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn inotify_rm_watch(unicorn: &mut Unicorn<Context>, fd: u32, wd: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] inotify_rm_watch(fd: {:#x}, wd: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        fd,
+        wd,
+    );
+
+    let res = match unicorn
+        .get_data()
+        .inner
+        .file_system
+        .lock()
+        .unwrap()
+        .with_anon_file_mut(fd as i32, |file| match file {
+            AnonFile::Inotify(inotify) => inotify.remove_watch(wd as i32).is_ok(),
+            _ => false,
+        }) {
+        Some(true) => 0,
+        Some(false) => -22i32 as u32, // -EINVAL
+        None => -9i32 as u32,         // -EBADF
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] inotify_rm_watch => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+fn inotify_init_internal(unicorn: &mut Unicorn<Context>, flags: u32) -> u32 {
+    let open_file_flags = if flags & IN_NONBLOCK != 0 {
+        OpenFileFlags::READ | OpenFileFlags::NONBLOCK
+    } else {
+        OpenFileFlags::READ
+    };
+
+    unicorn
+        .get_data()
+        .inner
+        .file_system
+        .lock()
+        .unwrap()
+        .open_anon(AnonFile::Inotify(Inotify::new()), open_file_flags) as u32
+}
+
+fn inotify_add_watch_internal(
+    unicorn: &mut Unicorn<Context>,
+    fd: u32,
+    path_name: &str,
+    mask: u32,
+) -> u32 {
+    if mask & IN_ALL_EVENTS == 0 {
+        return -22i32 as u32; // -EINVAL
+    }
+
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let mut file_system = file_system.lock().unwrap();
+
+    // watches are matched by the resolved paths
+    let path_name = match file_system.resolve_path(path_name, mask & IN_DONT_FOLLOW == 0) {
+        Ok(path_name) => path_name,
+        Err(err) => return err.to_syscall_error(),
+    };
+    let file_details = match file_system.get_file_info_from_filepath(&path_name) {
+        Some(file_info) => file_info.file_details,
+        None => return -2i32 as u32, // -ENOENT
+    };
+    if !file_details.is_accessible(euid(), egid(), AccessMode::READ) {
+        return -13i32 as u32; // -EACCES
+    }
+    if mask & IN_ONLYDIR != 0 && file_details.file_type != FileType::Directory {
+        return -20i32 as u32; // -ENOTDIR
+    }
+
+    match file_system.with_anon_file_mut(fd as i32, |file| match file {
+        AnonFile::Inotify(inotify) => Some(inotify.add_watch(&path_name, mask)),
+        _ => None,
+    }) {
+        Some(Some(Ok(wd))) => wd as u32,
+        Some(Some(Err(_))) => -17i32 as u32, // -EEXIST
+        Some(None) => -22i32 as u32,         // -EINVAL
+        None => -9i32 as u32,                // -EBADF
+    }
+}
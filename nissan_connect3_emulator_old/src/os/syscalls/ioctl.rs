@@ -1,7 +1,7 @@
 use crate::emulator::context::Context;
 use unicorn_engine::{RegisterARM, Unicorn};
 
-pub fn ioctl(mut unicorn: &mut Unicorn<Context>, fd: u32, request: u32, addr: u32) -> u32 {
+pub fn ioctl(unicorn: &mut Unicorn<Context>, fd: u32, request: u32, addr: u32) -> u32 {
     log::trace!(
         "{:#x}: [{}] [SYSCALL] ioctl(fd = {:#x}, request: {:#x}, addr: {:#x}) [IN]",
         unicorn.reg_read(RegisterARM::PC).unwrap(),
@@ -15,7 +15,7 @@ pub fn ioctl(mut unicorn: &mut Unicorn<Context>, fd: u32, request: u32, addr: u3
     let res = file_system
         .lock()
         .unwrap()
-        .ioctl(&mut unicorn, fd as i32, request, addr) as u32;
+        .ioctl(unicorn, fd as i32, request, addr) as u32;
 
     log::trace!(
         "{:#x}: [{}] [SYSCALL] ioctl => {:#x}",
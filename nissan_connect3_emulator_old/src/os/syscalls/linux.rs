@@ -1,5 +1,6 @@
 use crate::emulator::context::Context;
 use crate::emulator::memory_map::GET_TLS_ADDR;
+use crate::emulator::mmu::GuestMemory;
 use crate::emulator::utils::pack_u32;
 use unicorn_engine::{RegisterARM, Unicorn};
 
@@ -16,7 +17,7 @@ pub fn set_tls(unicorn: &mut Unicorn<Context>, address: u32) -> u32 {
         .unwrap();
 
     unicorn
-        .mem_write(GET_TLS_ADDR as u64 + 16, &pack_u32(address))
+        .mem_write_guest(GET_TLS_ADDR as u64 + 16, &pack_u32(address))
         .unwrap();
 
     let res = 0;
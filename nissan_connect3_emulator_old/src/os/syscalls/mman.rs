@@ -1,6 +1,9 @@
 use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
 use crate::emulator::utils::{mem_align_down, mem_align_up};
+use crate::file_system::SharedMemory;
 use std::io::SeekFrom;
+use std::sync::Arc;
 use unicorn_engine::unicorn_const::Permission;
 use unicorn_engine::{RegisterARM, Unicorn};
 
@@ -44,7 +47,11 @@ pub fn mmap2(
         unicorn.get_data().inner.thread_id,
         addr, length, prot, flags, fd, pgoffset);
 
-    let res = mmapx(unicorn, addr, length, prot, flags, fd, pgoffset * 0x1000);
+    let res = match pgoffset.checked_mul(0x1000) {
+        Some(off_t) => mmapx(unicorn, addr, length, prot, flags, fd, off_t),
+        // offsets above 4 GiB are not supported
+        None => -22i32 as u32, // EINVAL
+    };
 
     log::trace!(
         "{:#x} [{}] [SYSCALL] mmap2 => {:#x}",
@@ -65,6 +72,9 @@ pub fn munmap(unicorn: &mut Unicorn<Context>, addr: u32, length: u32) -> u32 {
         length,
     );
 
+    // changes made through shared file mappings are stored before the memory is released
+    write_back_shared_mappings(unicorn, addr, mem_align_up(length, None));
+
     let unicorn_context = unicorn.get_data();
     let mmu = &mut unicorn_context.inner.mmu.lock().unwrap();
     mmu.unmap(unicorn, addr, mem_align_up(length, None));
@@ -79,6 +89,34 @@ pub fn munmap(unicorn: &mut Unicorn<Context>, addr: u32, length: u32) -> u32 {
     res
 }
 
+pub fn msync(unicorn: &mut Unicorn<Context>, addr: u32, length: u32, flags: u32) -> u32 {
+    log::trace!(
+        "{:#x} [{}] [SYSCALL] msync(addr = {:#x}, len = {:#x}, flags = {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        addr,
+        length,
+        flags,
+    );
+
+    let res = if addr != mem_align_down(addr, None) {
+        -22i32 as u32 // EINVAL
+    } else {
+        // MS_ASYNC and MS_SYNC are handled the same way, MS_INVALIDATE is not needed
+        // as all mappings of the file share the same memory
+        write_back_shared_mappings(unicorn, addr, mem_align_up(length, None));
+        0u32
+    };
+
+    log::trace!(
+        "{:#x} [{}] [SYSCALL] msync => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+    res
+}
+
 pub fn mprotect(unicorn: &mut Unicorn<Context>, addr: u32, len: u32, prot: u32) -> u32 {
     log::trace!(
         "{:#x} [{}] [SYSCALL] mprotect(addr = {:#x}, len = {:#x}, prot = {:#x}) [IN]",
@@ -118,8 +156,8 @@ pub fn mincore(unicorn: &mut Unicorn<Context>, addr: u32, length: u32, vec: u32)
         vec,
     );
 
-    let bytes = vec![1u8; ((length + 0x1000 - 1) / 0x1000) as usize];
-    unicorn.mem_write(vec as u64, &bytes).unwrap();
+    let bytes = vec![1u8; length.div_ceil(0x1000) as usize];
+    unicorn.mem_write_guest(vec as u64, &bytes).unwrap();
 
     log::trace!(
         "{:#x} [{}] [SYSCALL] mincore => {:#x}",
@@ -131,7 +169,7 @@ pub fn mincore(unicorn: &mut Unicorn<Context>, addr: u32, length: u32, vec: u32)
 }
 
 fn mmapx(
-    mut unicorn: &mut Unicorn<Context>,
+    unicorn: &mut Unicorn<Context>,
     addr: u32,
     mut length: u32,
     prot: u32,
@@ -156,6 +194,11 @@ fn mmapx(
 
     length = mem_align_up(length, None);
 
+    // MAP_SHARED - changes are visible to other mappings and written back to the file
+    if flags & 0x01u32 != 0 {
+        return mmap_shared(unicorn, addr, length, perms, flags, fd, off_t);
+    }
+
     // load file
     let mut buf = Vec::new();
     let mut filepath = String::new();
@@ -203,8 +246,8 @@ fn mmapx(
     };
 
     // write file
-    if buf.len() > 0 {
-        unicorn.mem_write(addr as u64, &buf).unwrap();
+    if !buf.is_empty() {
+        unicorn.mem_write_guest(addr as u64, &buf).unwrap();
 
         if perms.contains(Permission::EXEC) {
             unicorn_context
@@ -212,13 +255,104 @@ fn mmapx(
                 .mmu
                 .lock()
                 .unwrap()
-                .update_library_hooks_for_all_threads(&unicorn);
+                .update_library_hooks_for_all_threads(unicorn);
         }
     }
 
     addr
 }
 
+fn mmap_shared(
+    unicorn: &mut Unicorn<Context>,
+    addr: u32,
+    length: u32,
+    perms: Permission,
+    flags: u32,
+    fd: u32,
+    off_t: u32,
+) -> u32 {
+    if off_t != mem_align_down(off_t, None) {
+        return -22i32 as u32; // EINVAL
+    }
+    let Some(mapping_end) = off_t.checked_add(length) else {
+        return -22i32 as u32; // EINVAL
+    };
+
+    let (memory, filepath) = if fd == 0xFFFFFFFFu32 {
+        // anonymous shared memory is not backed by any file
+        (Arc::new(SharedMemory::new(length as usize)), String::new())
+    } else {
+        let file_system = unicorn.get_data().inner.file_system.clone();
+        let mut file_system = file_system.lock().unwrap();
+
+        let filepath = match file_system.get_file_info(fd as i32) {
+            Some(fileinfo) => fileinfo.file_path,
+            None => return -9i32 as u32, // EBADF
+        };
+
+        // changes could not be written back
+        if perms.contains(Permission::WRITE) && file_system.is_read_only(fd as i32) {
+            return -13i32 as u32; // EACCES
+        }
+
+        match file_system.get_shared_memory(fd as i32, mapping_end as usize) {
+            Some(memory) => (memory, filepath),
+            None => return -19i32 as u32, // ENODEV
+        }
+    };
+    let offset = if filepath.is_empty() { 0 } else { off_t };
+
+    let unicorn_context = unicorn.get_data();
+    let mut mmu = unicorn_context.inner.mmu.lock().unwrap();
+    let addr = if flags & 0x10 != 0 || addr != 0 {
+        // MAP_FIXED - don't interpret addr as a hint
+        addr
+    } else {
+        mmu.heap_reserve(length)
+    };
+
+    mmu.map_shared(unicorn, addr, length, perms, &filepath, memory, offset);
+
+    if perms.contains(Permission::EXEC) {
+        mmu.update_library_hooks_for_all_threads(unicorn);
+    }
+
+    addr
+}
+
+/// Writes the content of dirty writable `MAP_SHARED` file mappings within the range
+/// back to the files (read-only and unchanged mappings are skipped).
+fn write_back_shared_mappings(unicorn: &mut Unicorn<Context>, addr: u32, length: u32) {
+    let mmu = unicorn.get_data().inner.mmu.clone();
+    let shared_mappings = mmu
+        .lock()
+        .unwrap()
+        .take_dirty_shared_mappings(unicorn, addr, length);
+
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let mut file_system = file_system.lock().unwrap();
+    for shared_mapping in shared_mappings {
+        if shared_mapping.file_path.is_empty() {
+            continue;
+        }
+
+        if file_system
+            .write_back_shared_memory(
+                &shared_mapping.file_path,
+                &shared_mapping.memory,
+                shared_mapping.offset as u64,
+                shared_mapping.length as u64,
+            )
+            .is_err()
+        {
+            log::warn!(
+                "unable to write back shared mapping of {}",
+                shared_mapping.file_path
+            );
+        }
+    }
+}
+
 fn prot_to_permission(prot: u32) -> Permission {
     let mut perms = Permission::NONE;
     if prot & 1 != 0 {
@@ -3,21 +3,30 @@ use crate::file_system::OpenFileError;
 pub mod hook_syscall;
 pub mod sys_calls_state;
 
+mod epoll;
+mod eventfd;
 mod fcntl;
 mod futex;
+mod inotify;
 mod ioctl;
 mod linux;
 mod mman;
+mod poll;
 mod prctl;
 mod resource;
 mod sched;
 mod signal;
+mod signalfd;
 mod socket;
 mod stat;
 mod time;
+mod timerfd;
 mod uio;
 mod unistd;
 mod utsname;
+mod wait;
+
+pub use poll::wait_for_fd;
 
 trait SysCallError {
     fn to_syscall_error(self) -> u32;
@@ -30,6 +39,16 @@ impl SysCallError for OpenFileError {
             OpenFileError::NoSuchFileOrDirectory => -2i32 as u32, // -ENOENT
             OpenFileError::FileExists => -17i32 as u32,          // -EEXIST
             OpenFileError::NoPermission => -1i32 as u32,         // -EPERM
+            OpenFileError::AccessDenied => -13i32 as u32,        // -EACCES
+            OpenFileError::TooManySymbolicLinks => -40i32 as u32, // -ELOOP
+            OpenFileError::NotSymbolicLink => -22i32 as u32,     // -EINVAL
+            OpenFileError::NotDirectory => -20i32 as u32,        // -ENOTDIR
+            OpenFileError::IsDirectory => -21i32 as u32,         // -EISDIR
+            OpenFileError::DirectoryNotEmpty => -39i32 as u32,   // -ENOTEMPTY
+            OpenFileError::ReadOnlyFileSystem => -30i32 as u32,  // -EROFS
+            OpenFileError::CrossDeviceLink => -18i32 as u32,     // -EXDEV
+            OpenFileError::InvalidArgument => -22i32 as u32,     // -EINVAL
+            OpenFileError::NoSuchDevice => -6i32 as u32,         // -ENXIO
         }
     }
 }
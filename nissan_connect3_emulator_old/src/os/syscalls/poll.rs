@@ -0,0 +1,208 @@
+use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
+use crate::emulator::utils::{pack_u16, unpack_u32};
+use crate::file_system::{MountFileSystem, PollEvents};
+use std::time::{Duration, Instant};
+use unicorn_engine::{RegisterARM, Unicorn};
+
+// how often the file descriptors are checked when nothing notifies about changes
+// (timers, stdin and host resources)
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+const POLLNVAL: u16 = 0x20;
+
+/// Blocks the calling thread until `check` returns `Some` or `timeout` elapses
+/// (`None` timeout means waiting forever).
+pub fn wait_for_file_system<R>(
+    unicorn: &Unicorn<Context>,
+    timeout: Option<Duration>,
+    mut check: impl FnMut(&mut MountFileSystem) -> Option<R>,
+) -> Option<R> {
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let notifier = file_system.lock().unwrap().notifier();
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+    loop {
+        let generation = notifier.generation();
+
+        if let Some(res) = check(&mut file_system.lock().unwrap()) {
+            return Some(res);
+        }
+
+        let mut wait_time = POLL_INTERVAL;
+        if let Some(deadline) = deadline {
+            let now = Instant::now();
+            if now >= deadline {
+                return None;
+            }
+            wait_time = wait_time.min(deadline - now);
+        }
+
+        notifier.wait(generation, wait_time);
+    }
+}
+
+/// Waits until `fd` is ready for reading (or writing when `for_write` is set).
+/// Returns `false` if the file is in non-blocking mode and it is not ready.
+pub fn wait_for_fd(unicorn: &Unicorn<Context>, fd: i32, for_write: bool) -> bool {
+    let is_nonblocking = unicorn
+        .get_data()
+        .inner
+        .file_system
+        .lock()
+        .unwrap()
+        .is_nonblocking(fd);
+
+    let expected_events = if for_write {
+        PollEvents::OUT | PollEvents::ERR | PollEvents::HUP
+    } else {
+        PollEvents::IN | PollEvents::ERR | PollEvents::HUP
+    };
+
+    wait_for_file_system(
+        unicorn,
+        if is_nonblocking {
+            Some(Duration::ZERO)
+        } else {
+            None
+        },
+        |file_system| {
+            // the file could be closed by other thread
+            if !file_system.is_open(fd) || file_system.poll(fd).intersects(expected_events) {
+                Some(())
+            } else {
+                None
+            }
+        },
+    )
+    .is_some()
+}
+
+/// Reads `struct timespec` (32-bit) from guest memory.
+pub fn read_timespec(unicorn: &Unicorn<Context>, addr: u32) -> Duration {
+    let mut buf = vec![0u8; 8];
+    unicorn.mem_read(addr as u64, &mut buf).unwrap();
+    Duration::new(unpack_u32(&buf[0..4]) as u64, unpack_u32(&buf[4..8]))
+}
+
+pub fn poll(unicorn: &mut Unicorn<Context>, fds: u32, nfds: u32, timeout: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] poll(fds = {:#x}, nfds: {:#x}, timeout: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        fds,
+        nfds,
+        timeout,
+    );
+
+    // negative value means infinite timeout
+    let timeout = if (timeout as i32) < 0 {
+        None
+    } else {
+        Some(Duration::from_millis(timeout as u64))
+    };
+
+    let res = poll_internal(unicorn, fds, nfds, timeout);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] poll => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn ppoll(
+    unicorn: &mut Unicorn<Context>,
+    fds: u32,
+    nfds: u32,
+    timeout_ts: u32,
+    sigmask: u32,
+    sigset_size: u32,
+) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] ppoll(fds = {:#x}, nfds: {:#x}, timeout_ts: {:#x}, sigmask: {:#x}, sigset_size: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        fds,
+        nfds,
+        timeout_ts,
+        sigmask,
+        sigset_size,
+    );
+
+    let timeout = if timeout_ts == 0 {
+        None
+    } else {
+        Some(read_timespec(unicorn, timeout_ts))
+    };
+
+    let res = poll_internal(unicorn, fds, nfds, timeout);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] ppoll => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+fn poll_internal(
+    unicorn: &mut Unicorn<Context>,
+    fds: u32,
+    nfds: u32,
+    timeout: Option<Duration>,
+) -> u32 {
+    // struct pollfd { int fd; short events; short revents; }
+    let Some(poll_fds_size) = nfds.checked_mul(8) else {
+        return -22i32 as u32; // EINVAL
+    };
+    let mut poll_fds = vec![0u8; poll_fds_size as usize];
+    unicorn.mem_read(fds as u64, &mut poll_fds).unwrap();
+
+    let requests: Vec<(i32, u16)> = (0..nfds as usize)
+        .map(|index| {
+            let fd = unpack_u32(&poll_fds[index * 8..index * 8 + 4]) as i32;
+            let events = unpack_u32(&poll_fds[index * 8 + 4..index * 8 + 8]) as u16;
+            (fd, events)
+        })
+        .collect();
+
+    let check = |file_system: &mut MountFileSystem| {
+        let revents: Vec<u16> = requests
+            .iter()
+            .map(|(fd, events)| {
+                if *fd < 0 {
+                    0
+                } else if !file_system.is_open(*fd) {
+                    POLLNVAL
+                } else {
+                    // POLLERR and POLLHUP are always reported
+                    (file_system.poll(*fd).bits() as u16)
+                        & (events | PollEvents::ERR.bits() as u16 | PollEvents::HUP.bits() as u16)
+                }
+            })
+            .collect();
+
+        if revents.iter().any(|revents| *revents != 0) {
+            Some(revents)
+        } else {
+            None
+        }
+    };
+
+    let revents =
+        wait_for_file_system(unicorn, timeout, check).unwrap_or(vec![0u16; nfds as usize]);
+
+    for (index, revents) in revents.iter().enumerate() {
+        unicorn
+            .mem_write_guest(fds as u64 + index as u64 * 8 + 6, &pack_u16(*revents))
+            .unwrap();
+    }
+
+    revents.iter().filter(|revents| **revents != 0).count() as u32
+}
@@ -1,4 +1,5 @@
 use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
 use crate::emulator::utils::read_string;
 use unicorn_engine::{RegisterARM, Unicorn};
 
@@ -24,10 +25,31 @@ pub fn prctl(
     // TODO: implement
     let res = 0;
 
+    let inner = unicorn.get_data().inner.clone();
     if option == 15 {
         // PR_SET_NAME = set process name
         let process_name = read_string(unicorn, arg2);
         log::trace!("Process name: {}", process_name);
+
+        // TASK_COMM_LEN
+        let process_name = process_name.chars().take(15).collect();
+        if let Some(process) = inner.process_table.lock().unwrap().get_mut(inner.pid) {
+            process.thread_names.insert(inner.thread_id, process_name);
+        }
+    } else if option == 16 {
+        // PR_GET_NAME = get process name
+        let process_name = inner
+            .process_table
+            .lock()
+            .unwrap()
+            .get(inner.pid)
+            .map(|process| process.get_thread_name(inner.thread_id))
+            .unwrap_or_default();
+
+        let mut buf = vec![0u8; 16];
+        let len = process_name.len().min(15);
+        buf[..len].copy_from_slice(&process_name.as_bytes()[..len]);
+        unicorn.mem_write_guest(arg2 as u64, &buf).unwrap();
     }
 
     log::trace!(
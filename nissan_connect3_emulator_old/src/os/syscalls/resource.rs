@@ -1,5 +1,6 @@
 use crate::emulator::context::Context;
 use crate::emulator::memory_map::STACK_SIZE;
+use crate::emulator::mmu::GuestMemory;
 use crate::emulator::utils::pack_i64;
 use unicorn_engine::{RegisterARM, Unicorn};
 
@@ -39,10 +40,10 @@ pub fn ugetrlimit(unicorn: &mut Unicorn<Context>, resource: u32, r_limit: u32) -
         3 => {
             // RLIMIT_STACK
             unicorn
-                .mem_write(r_limit as u64, &pack_i64(STACK_SIZE as i64))
+                .mem_write_guest(r_limit as u64, &pack_i64(STACK_SIZE as i64))
                 .unwrap();
             unicorn
-                .mem_write((r_limit + 8) as u64, &pack_i64(-1i64))
+                .mem_write_guest((r_limit + 8) as u64, &pack_i64(-1i64))
                 .unwrap();
             0
         }
@@ -1,6 +1,8 @@
 use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
 use crate::emulator::thread::Thread;
 use crate::emulator::utils::pack_u32;
+use crate::os::syscalls::unistd::fork_internal;
 use std::sync::atomic::Ordering;
 use unicorn_engine::{RegisterARM, Unicorn};
 
@@ -106,6 +108,33 @@ pub fn clone(
         child_tid_ptr,
     );
 
+    if flags & 0x00000100 == 0 {
+        // no CLONE_VM - new process is created (that is how glibc implements `fork()`)
+        let is_vfork = flags & 0x00004000 != 0; // CLONE_VFORK
+        let child_tid_ptr = if flags & 0x01000000 != 0 {
+            // CLONE_CHILD_SETTID
+            Some(child_tid_ptr)
+        } else {
+            None
+        };
+        let res = fork_internal(unicorn, is_vfork, child_tid_ptr);
+        if flags & 0x00100000 != 0 {
+            // CLONE_PARENT_SETTID
+            unicorn
+                .mem_write_guest(parent_tid_ptr as u64, &pack_u32(res))
+                .unwrap();
+        }
+
+        log::trace!(
+            "{:#x}: [{}] [SYSCALL] clone => {:#x}",
+            unicorn.reg_read(RegisterARM::PC).unwrap(),
+            unicorn.get_data().inner.thread_id,
+            res
+        );
+
+        return res;
+    }
+
     let child_tid = unicorn
         .get_data()
         .inner
@@ -123,14 +152,14 @@ pub fn clone(
         // CLONE_PARENT_SETTID
         // Store child thread ID at location parent_tid_ptr in parent and child memory
         unicorn
-            .mem_write(parent_tid_ptr as u64, &pack_u32(child_tid))
+            .mem_write_guest(parent_tid_ptr as u64, &pack_u32(child_tid))
             .unwrap();
     }
     if flags & 0x01000000 != 0 {
         // CLONE_CHILD_SETTID
         // Store child thread ID at location child_tidptr in child memory
         unicorn
-            .mem_write(child_tid_ptr as u64, &pack_u32(child_tid))
+            .mem_write_guest(child_tid_ptr as u64, &pack_u32(child_tid))
             .unwrap();
     }
 
@@ -139,7 +168,7 @@ pub fn clone(
         threads.lock().unwrap().push(new_thread);
     }
 
-    let res = child_tid as u32;
+    let res = child_tid;
     log::trace!(
         "{:#x}: [{}] [SYSCALL] clone => {:#x}",
         unicorn.reg_read(RegisterARM::PC).unwrap(),
@@ -1,7 +1,15 @@
 use crate::emulator::context::Context;
-use crate::emulator::utils::unpack_u32;
+use crate::emulator::mmu::GuestMemory;
+use crate::emulator::process::kill_process;
+use crate::emulator::signals::{SigInfo, SIGKILL, SI_TKILL, SI_USER};
+use crate::emulator::utils::{pack_i32, pack_u32, unpack_u32};
+use crate::os::syscalls::poll::{read_timespec, wait_for_file_system};
+use crate::os::syscalls::signalfd::read_sigset;
 use unicorn_engine::{RegisterARM, Unicorn};
 
+// size of `siginfo_t`
+const SIGINFO_SIZE: usize = 128;
+
 pub fn rt_sigaction(
     unicorn: &mut Unicorn<Context>,
     signum: u32,
@@ -112,8 +120,35 @@ pub fn rt_sigtimedwait(
         sig_set_size,
     );
 
-    // TODO: implement
-    let res = -11i32 as u32; // EAGAIN
+    let mask = read_sigset(unicorn, set, sig_set_size);
+    let timeout = if timeout == 0 {
+        None
+    } else {
+        Some(read_timespec(unicorn, timeout))
+    };
+
+    let pending_signals = unicorn.get_data().inner.pending_signals.clone();
+    let sig_info = wait_for_file_system(unicorn, timeout, |_| {
+        pending_signals.lock().unwrap().pop(mask)
+    });
+
+    let res = if let Some(sig_info) = sig_info {
+        if info != 0 {
+            // siginfo_t { si_signo; si_errno; si_code; si_pid; si_uid; si_status; ... }
+            let mut buf = Vec::new();
+            buf.extend_from_slice(&pack_u32(sig_info.signo));
+            buf.extend_from_slice(&pack_i32(0));
+            buf.extend_from_slice(&pack_i32(sig_info.code));
+            buf.extend_from_slice(&pack_u32(sig_info.pid));
+            buf.extend_from_slice(&pack_u32(sig_info.uid));
+            buf.extend_from_slice(&pack_i32(sig_info.status));
+            buf.resize(SIGINFO_SIZE, 0u8);
+            unicorn.mem_write_guest(info as u64, &buf).unwrap();
+        }
+        sig_info.signo
+    } else {
+        -11i32 as u32 // EAGAIN
+    };
 
     log::trace!(
         "{:#x}: [{}] [SYSCALL] rt_sigtimedwait => {:#x}",
@@ -124,3 +159,131 @@ pub fn rt_sigtimedwait(
 
     res
 }
+
+pub fn kill(unicorn: &mut Unicorn<Context>, pid: u32, sig: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] kill(pid: {:#x}, sig: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        pid,
+        sig,
+    );
+
+    // there are no process groups, so the signal is sent only to the calling process
+    let res = match pid as i32 {
+        -1 | 0 => send_signal(unicorn, unicorn.get_data().inner.pid, sig, SI_USER),
+        pid if pid > 0 => send_signal(unicorn, pid as u32, sig, SI_USER),
+        _ => -3i32 as u32, // ESRCH
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] kill => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn tkill(unicorn: &mut Unicorn<Context>, tid: u32, sig: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] tkill(tid: {:#x}, sig: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        tid,
+        sig,
+    );
+
+    let res = if tid as i32 <= 0 {
+        -22i32 as u32 // EINVAL
+    } else {
+        // signals are not directed to threads, the whole process receives it
+        send_signal(unicorn, unicorn.get_data().inner.pid, sig, SI_TKILL)
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] tkill => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn tgkill(unicorn: &mut Unicorn<Context>, tgid: u32, tid: u32, sig: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] tgkill(tgid: {:#x}, tid: {:#x}, sig: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        tgid,
+        tid,
+        sig,
+    );
+
+    let res = if tgid as i32 <= 0 || tid as i32 <= 0 {
+        -22i32 as u32 // EINVAL
+    } else {
+        send_signal(unicorn, tgid, sig, SI_TKILL)
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] tgkill => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+fn send_signal(unicorn: &mut Unicorn<Context>, pid: u32, sig: u32, code: i32) -> u32 {
+    if sig > 64 {
+        return -22i32 as u32; // EINVAL
+    }
+
+    let pending_signals = match unicorn
+        .get_data()
+        .inner
+        .process_table
+        .lock()
+        .unwrap()
+        .get(pid)
+    {
+        Some(process) => process.pending_signals.clone(),
+        None => return -3i32 as u32, // ESRCH
+    };
+
+    // signal 0 only checks whether the target exists
+    if sig == 0 {
+        return 0;
+    }
+
+    // the process cannot handle it, it is terminated immediately
+    if sig == SIGKILL {
+        kill_process(unicorn, pid);
+        return 0;
+    }
+
+    pending_signals.lock().unwrap().push(SigInfo {
+        signo: sig,
+        code,
+        pid: unicorn.get_data().inner.pid,
+        uid: 0,
+        tid: unicorn.get_data().inner.thread_id,
+        status: 0,
+    });
+
+    // wake up threads waiting in signalfd, poll or rt_sigtimedwait
+    unicorn
+        .get_data()
+        .inner
+        .file_system
+        .lock()
+        .unwrap()
+        .notifier()
+        .notify();
+
+    0
+}
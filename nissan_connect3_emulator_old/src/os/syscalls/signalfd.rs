@@ -0,0 +1,115 @@
+use crate::emulator::context::Context;
+use crate::file_system::{AnonFile, OpenFileFlags, SignalFd};
+use unicorn_engine::{RegisterARM, Unicorn};
+
+const SFD_NONBLOCK: u32 = 0x800;
+
+pub fn signalfd(unicorn: &mut Unicorn<Context>, fd: u32, mask: u32, size_mask: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] signalfd(fd = {:#x}, mask: {:#x}, size_mask: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        fd,
+        mask,
+        size_mask,
+    );
+
+    let res = signalfd_internal(unicorn, fd, mask, size_mask, 0);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] signalfd => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn signalfd4(
+    unicorn: &mut Unicorn<Context>,
+    fd: u32,
+    mask: u32,
+    size_mask: u32,
+    flags: u32,
+) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] signalfd4(fd = {:#x}, mask: {:#x}, size_mask: {:#x}, flags: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        fd,
+        mask,
+        size_mask,
+        flags,
+    );
+
+    let res = signalfd_internal(unicorn, fd, mask, size_mask, flags);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] signalfd4 => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+/// Reads `sigset_t` from guest memory (bit `signo - 1` is set for every signal in the set).
+pub fn read_sigset(unicorn: &Unicorn<Context>, addr: u32, size: u32) -> u64 {
+    let mut buf = [0u8; 8];
+    let size = size.min(8) as usize;
+    unicorn.mem_read(addr as u64, &mut buf[0..size]).unwrap();
+    u64::from_le_bytes(buf)
+}
+
+fn signalfd_internal(
+    unicorn: &mut Unicorn<Context>,
+    fd: u32,
+    mask: u32,
+    size_mask: u32,
+    flags: u32,
+) -> u32 {
+    if size_mask != 8 {
+        return -22i32 as u32; // EINVAL
+    }
+
+    // SIGKILL and SIGSTOP cannot be received via signalfd and are silently ignored
+    let mask = read_sigset(unicorn, mask, size_mask) & !(1u64 << (9 - 1)) & !(1u64 << (19 - 1));
+
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let mut file_system = file_system.lock().unwrap();
+
+    if fd as i32 == -1 {
+        // create new file descriptor
+        let signal_fd = SignalFd {
+            mask,
+            pending_signals: unicorn.get_data().inner.pending_signals.clone(),
+        };
+
+        let open_file_flags = if flags & SFD_NONBLOCK != 0 {
+            OpenFileFlags::READ | OpenFileFlags::NONBLOCK
+        } else {
+            OpenFileFlags::READ
+        };
+
+        file_system.open_anon(AnonFile::SignalFd(signal_fd), open_file_flags) as u32
+    } else {
+        // replace the mask of existing file descriptor
+        let is_signal_fd = file_system
+            .with_anon_file_mut(fd as i32, |file| match file {
+                AnonFile::SignalFd(signal_fd) => {
+                    signal_fd.mask = mask;
+                    true
+                }
+                _ => false,
+            })
+            .unwrap_or(false);
+
+        if is_signal_fd {
+            fd
+        } else {
+            -22i32 as u32 // EINVAL
+        }
+    }
+}
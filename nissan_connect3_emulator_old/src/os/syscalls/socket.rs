@@ -1,6 +1,18 @@
 use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
+use crate::emulator::utils::{pack_u32, unpack_u32};
+use crate::file_system::{
+    AnonFile, IncSocket, OpenFileFlags, AF_BOSCH_INC_ADR, AF_BOSCH_INC_AUTOSAR, AF_BOSCH_INC_LINUX,
+    SOCK_DGRAM, SOCK_STREAM,
+};
+use crate::os::syscalls::poll::wait_for_fd;
 use unicorn_engine::{RegisterARM, Unicorn};
 
+const SOCK_TYPE_MASK: u32 = 0xf;
+const SOCK_NONBLOCK: u32 = 0x800;
+
+const MSG_DONTWAIT: u32 = 0x40;
+
 pub fn socket(unicorn: &mut Unicorn<Context>, domain: u32, socket_type: u32, protocol: u32) -> u32 {
     log::trace!(
         "{:#x}: [{}] [SYSCALL] socket(domain = {:#x}, socket_type: {:#x}, protocol: {:#x}) [IN]",
@@ -11,8 +23,29 @@ pub fn socket(unicorn: &mut Unicorn<Context>, domain: u32, socket_type: u32, pro
         protocol,
     );
 
-    // TODO: implement
-    let res = 0;
+    let res = if matches!(
+        domain,
+        AF_BOSCH_INC_ADR | AF_BOSCH_INC_LINUX | AF_BOSCH_INC_AUTOSAR
+    ) {
+        let file_system = unicorn.get_data().inner.file_system.clone();
+        let mut file_system = file_system.lock().unwrap();
+        match (file_system.inc_mcu(), socket_type & SOCK_TYPE_MASK) {
+            (None, _) => -97i32 as u32, // -EAFNOSUPPORT
+            (Some(mcu), SOCK_STREAM | SOCK_DGRAM) => {
+                let open_file_flags = if socket_type & SOCK_NONBLOCK != 0 {
+                    OpenFileFlags::READ | OpenFileFlags::WRITE | OpenFileFlags::NONBLOCK
+                } else {
+                    OpenFileFlags::READ | OpenFileFlags::WRITE
+                };
+                let socket = IncSocket::new(domain, socket_type & SOCK_TYPE_MASK, mcu);
+                file_system.open_anon(AnonFile::IncSocket(socket), open_file_flags) as u32
+            }
+            (Some(_), _) => -94i32 as u32, // -ESOCKTNOSUPPORT
+        }
+    } else {
+        // TODO: implement
+        0
+    };
 
     log::trace!(
         "{:#x}: [{}] [SYSCALL] socket => {:#x}",
@@ -24,6 +57,36 @@ pub fn socket(unicorn: &mut Unicorn<Context>, domain: u32, socket_type: u32, pro
     res
 }
 
+pub fn bind(unicorn: &mut Unicorn<Context>, socket_fd: u32, addr: u32, addr_len: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] bind(socket_fd = {:#x}, addr: {:#x}, addr_len: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        socket_fd,
+        addr,
+        addr_len,
+    );
+
+    let port = read_sockaddr_port(unicorn, addr, addr_len);
+    let res = with_inc_socket(unicorn, socket_fd, |socket| match port {
+        Some(port) => {
+            socket.local_port = Some(port);
+            0
+        }
+        None => -22i32 as u32, // -EINVAL
+    })
+    .unwrap_or(-88i32 as u32); // -ENOTSOCK
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] bind => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
 pub fn connect(unicorn: &mut Unicorn<Context>, socket_fd: u32, addr: u32, addr_len: u32) -> u32 {
     log::trace!(
         "{:#x}: [{}] [SYSCALL] connect(socket_fd = {:#x}, addr: {:#x}, addr_len: {:#x}) [IN]",
@@ -34,8 +97,18 @@ pub fn connect(unicorn: &mut Unicorn<Context>, socket_fd: u32, addr: u32, addr_l
         addr_len,
     );
 
-    // TODO: implement
-    let res = 0;
+    let port = read_sockaddr_port(unicorn, addr, addr_len);
+    let res = with_inc_socket(unicorn, socket_fd, |socket| match port {
+        Some(port) => match socket.connect(port) {
+            Ok(_) => 0,
+            Err(_) => -106i32 as u32, // -EISCONN
+        },
+        None => -22i32 as u32, // -EINVAL
+    })
+    .unwrap_or(
+        // TODO: implement
+        0,
+    );
 
     log::trace!(
         "{:#x}: [{}] [SYSCALL] connect => {:#x}",
@@ -58,14 +131,19 @@ pub fn send(unicorn: &mut Unicorn<Context>, socket_fd: u32, buf: u32, len: u32,
         flags,
     );
 
-    // TODO: implement
-    let res = 0;
-
     let mut buf2 = vec![0u8; len as usize];
     unicorn.mem_read(buf as u64, &mut buf2).unwrap();
-    let str = String::from_utf8(buf2).unwrap();
 
-    log::trace!("Message: {}", str);
+    let res = match with_inc_socket(unicorn, socket_fd, |socket| socket.send(&buf2)) {
+        Some(Ok(len)) => len as u32,
+        Some(Err(_)) => -107i32 as u32, // -ENOTCONN
+        None => {
+            // TODO: implement
+            let str = String::from_utf8_lossy(&buf2);
+            log::trace!("Message: {}", str);
+            0
+        }
+    };
 
     log::trace!(
         "{:#x}: [{}] [SYSCALL] send => {:#x}",
@@ -76,3 +154,230 @@ pub fn send(unicorn: &mut Unicorn<Context>, socket_fd: u32, buf: u32, len: u32,
 
     res
 }
+
+pub fn sendto(
+    unicorn: &mut Unicorn<Context>,
+    socket_fd: u32,
+    buf: u32,
+    len: u32,
+    flags: u32,
+    dest_addr: u32,
+    addr_len: u32,
+) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] sendto(socket_fd = {:#x}, buf: {:#x}, len: {:#x}, flags: {:#x}, dest_addr: {:#x}, addr_len: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        socket_fd,
+        buf,
+        len,
+        flags,
+        dest_addr,
+        addr_len,
+    );
+
+    // INC sockets are always connected, so the destination is ignored
+    let res = send(unicorn, socket_fd, buf, len, flags);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] sendto => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn recv(unicorn: &mut Unicorn<Context>, socket_fd: u32, buf: u32, len: u32, flags: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] recv(socket_fd = {:#x}, buf: {:#x}, len: {:#x}, flags: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        socket_fd,
+        buf,
+        len,
+        flags,
+    );
+
+    let res = recv_internal(unicorn, socket_fd, buf, len, flags, None);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] recv => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn recvfrom(
+    unicorn: &mut Unicorn<Context>,
+    socket_fd: u32,
+    buf: u32,
+    len: u32,
+    flags: u32,
+    src_addr: u32,
+    addr_len: u32,
+) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] recvfrom(socket_fd = {:#x}, buf: {:#x}, len: {:#x}, flags: {:#x}, src_addr: {:#x}, addr_len: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        socket_fd,
+        buf,
+        len,
+        flags,
+        src_addr,
+        addr_len,
+    );
+
+    let src_addr = if src_addr != 0 && addr_len != 0 {
+        Some((src_addr, addr_len))
+    } else {
+        None
+    };
+    let res = recv_internal(unicorn, socket_fd, buf, len, flags, src_addr);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] recvfrom => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn setsockopt(
+    unicorn: &mut Unicorn<Context>,
+    socket_fd: u32,
+    level: u32,
+    option_name: u32,
+    option_value: u32,
+    option_len: u32,
+) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] setsockopt(socket_fd = {:#x}, level: {:#x}, option_name: {:#x}, option_value: {:#x}, option_len: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        socket_fd,
+        level,
+        option_name,
+        option_value,
+        option_len,
+    );
+
+    // options (like buffer sizes) do not change behavior of the emulated sockets
+    let res = 0;
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] setsockopt => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+fn recv_internal(
+    unicorn: &mut Unicorn<Context>,
+    socket_fd: u32,
+    buf: u32,
+    len: u32,
+    flags: u32,
+    src_addr: Option<(u32, u32)>,
+) -> u32 {
+    let Some(remote_port) = with_inc_socket(unicorn, socket_fd, |socket| socket.remote_port) else {
+        return -88i32 as u32; // -ENOTSOCK
+    };
+    let Some(remote_port) = remote_port else {
+        return -107i32 as u32; // -ENOTCONN
+    };
+
+    if flags & MSG_DONTWAIT == 0 && !wait_for_fd(unicorn, socket_fd as i32, false) {
+        return -11i32 as u32; // -EAGAIN
+    }
+
+    let mut buf2 = vec![0u8; len as usize];
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let res = file_system
+        .lock()
+        .unwrap()
+        .read(socket_fd as i32, &mut buf2);
+    match res {
+        Ok(len) => {
+            unicorn
+                .mem_write_guest(buf as u64, &buf2[0..len as usize])
+                .unwrap();
+            if let Some((src_addr, addr_len)) = src_addr {
+                write_sockaddr(unicorn, socket_fd, src_addr, addr_len, remote_port);
+            }
+            len as u32
+        }
+        Err(_) => -11i32 as u32, // -EAGAIN
+    }
+}
+
+/// Calls `f` with the INC socket (returns `None` if `socket_fd` is not an INC socket).
+fn with_inc_socket<R>(
+    unicorn: &mut Unicorn<Context>,
+    socket_fd: u32,
+    f: impl FnOnce(&mut IncSocket) -> R,
+) -> Option<R> {
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let res = file_system
+        .lock()
+        .unwrap()
+        .with_anon_file_mut(socket_fd as i32, |file| match file {
+            AnonFile::IncSocket(socket) => Some(f(socket)),
+            _ => None,
+        });
+    res.flatten()
+}
+
+/// Returns port of `struct sockaddr_in` (INC sockets use it with their own family).
+fn read_sockaddr_port(unicorn: &Unicorn<Context>, addr: u32, addr_len: u32) -> Option<u16> {
+    if addr == 0 || addr_len < 8 {
+        return None;
+    }
+    let mut buf = [0u8; 8];
+    unicorn.mem_read(addr as u64, &mut buf).unwrap();
+    log::trace!(
+        "family: {}, address: {}.{}.{}.{}, port: {}",
+        u16::from_le_bytes([buf[0], buf[1]]),
+        buf[4],
+        buf[5],
+        buf[6],
+        buf[7],
+        u16::from_be_bytes([buf[2], buf[3]])
+    );
+    Some(u16::from_be_bytes([buf[2], buf[3]]))
+}
+
+/// Writes `struct sockaddr_in` of the MCU and its size to `addr_len`.
+fn write_sockaddr(
+    unicorn: &mut Unicorn<Context>,
+    socket_fd: u32,
+    addr: u32,
+    addr_len: u32,
+    port: u16,
+) {
+    let domain = with_inc_socket(unicorn, socket_fd, |socket| socket.domain).unwrap_or(0);
+    let mut sockaddr = Vec::new();
+    sockaddr.extend_from_slice(&(domain as u16).to_le_bytes());
+    sockaddr.extend_from_slice(&port.to_be_bytes());
+    sockaddr.resize(16, 0u8);
+
+    let mut buf = [0u8; 4];
+    unicorn.mem_read(addr_len as u64, &mut buf).unwrap();
+    let len = (unpack_u32(&buf) as usize).min(sockaddr.len());
+    unicorn
+        .mem_write_guest(addr as u64, &sockaddr[0..len])
+        .unwrap();
+    unicorn
+        .mem_write_guest(addr_len as u64, &pack_u32(sockaddr.len() as u32))
+        .unwrap();
+}
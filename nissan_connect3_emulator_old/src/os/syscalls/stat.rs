@@ -1,8 +1,8 @@
 use crate::emulator::context::Context;
-use crate::emulator::users::{GID, UID};
+use crate::emulator::mmu::GuestMemory;
 use crate::emulator::utils::{pack_i32, pack_u32, pack_u64, read_string};
-use crate::file_system::{FileSystemType, FileType, OpenFileFlags};
-use crate::os::syscalls::fcntl::get_path_relative_to_dir;
+use crate::file_system::{FileSystemType, FileTimes, FileType, OpenFileFlags};
+use crate::os::syscalls::fcntl::{get_path_relative_to_dir, resolve_proc_thread_self};
 use crate::os::syscalls::SysCallError;
 use std::time::SystemTime;
 use unicorn_engine::{RegisterARM, Unicorn};
@@ -20,6 +20,7 @@ pub fn stat64(unicorn: &mut Unicorn<Context>, path: u32, stat_buf: u32) -> u32 {
 
     log::trace!("path = {}", pathstr);
 
+    let pathstr = resolve_proc_thread_self(unicorn, &pathstr);
     let file_system = unicorn.get_data().inner.file_system.clone();
     let open_res = file_system
         .lock()
@@ -64,12 +65,16 @@ pub fn fstatat64(
     log::trace!("path = {}", path_name);
 
     let path_name_new = get_path_relative_to_dir(unicorn, dir_fd, &path_name);
+    let path_name_new = resolve_proc_thread_self(unicorn, &path_name_new);
     let file_system = unicorn.get_data().inner.file_system.clone();
 
-    let open_res = file_system
-        .lock()
-        .unwrap()
-        .open(&path_name_new, OpenFileFlags::READ);
+    let open_flags = if flags & 0x100 != 0 {
+        // AT_SYMLINK_NOFOLLOW
+        OpenFileFlags::READ | OpenFileFlags::NO_FOLLOW
+    } else {
+        OpenFileFlags::READ
+    };
+    let open_res = file_system.lock().unwrap().open(&path_name_new, open_flags);
     let res = if let Ok(fd) = open_res {
         let res = fstat64_internal(unicorn, fd as u32, stat_buf);
         file_system.lock().unwrap().close(fd).unwrap();
@@ -96,11 +101,11 @@ pub fn lstat64(unicorn: &mut Unicorn<Context>, path: u32, stat_buf: u32) -> u32
         stat_buf,
     );
 
-    // TODO: handle symbolic links
     let pathstr = read_string(unicorn, path);
 
     log::trace!("path = {}", pathstr);
 
+    let pathstr = resolve_proc_thread_self(unicorn, &pathstr);
     let file_system = unicorn.get_data().inner.file_system.clone();
 
     let open_res = file_system
@@ -177,6 +182,8 @@ pub fn statfs(unicorn: &mut Unicorn<Context>, path: u32, buf: u32) -> u32 {
                 FileSystemType::Proc => 0x9fa0,
                 FileSystemType::Temp => 0x01021994,
                 FileSystemType::Stream => 0,
+                FileSystemType::Anon => 0x09041934,
+                FileSystemType::Overlay => 0x794c7630,
             },
         ));
 
@@ -222,7 +229,7 @@ pub fn statfs(unicorn: &mut Unicorn<Context>, path: u32, buf: u32) -> u32 {
     };
 
     if res == 0u32 {
-        unicorn.mem_write(buf as u64, &vec).unwrap();
+        unicorn.mem_write_guest(buf as u64, &vec).unwrap();
     }
 
     log::trace!(
@@ -247,12 +254,7 @@ pub fn mkdir(unicorn: &mut Unicorn<Context>, path: u32, mode: u32) -> u32 {
 
     log::trace!("path = {}", pathstr);
 
-    let file_system = unicorn.get_data().inner.file_system.clone();
-    let res = if let Ok(_) = file_system.lock().unwrap().mkdir(&pathstr, mode) {
-        0
-    } else {
-        -1i32 as u32
-    };
+    let res = mkdir_internal(unicorn, &pathstr, mode);
 
     log::trace!(
         "{:#x}: [{}] [SYSCALL] mkdir => {:#x}",
@@ -263,6 +265,81 @@ pub fn mkdir(unicorn: &mut Unicorn<Context>, path: u32, mode: u32) -> u32 {
     res
 }
 
+pub fn mkdirat(unicorn: &mut Unicorn<Context>, dir_fd: u32, path: u32, mode: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] mkdirat(dir_fd = {:#x}, path = {:#x}, mode = {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        dir_fd,
+        path,
+        mode,
+    );
+
+    let pathstr = read_string(unicorn, path);
+    log::trace!("path = {}", pathstr);
+
+    let pathstr = get_path_relative_to_dir(unicorn, dir_fd, &pathstr);
+    let res = mkdir_internal(unicorn, &pathstr, mode);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] mkdirat => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+    res
+}
+
+pub fn mknod(unicorn: &mut Unicorn<Context>, path: u32, mode: u32, dev: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] mknod(path = {:#x}, mode = {:#o}, dev = {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        path,
+        mode,
+        dev,
+    );
+
+    let path_name = read_string(unicorn, path);
+    log::trace!("path = {}", path_name);
+
+    let res = mknod_internal(unicorn, &path_name, mode, dev);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] mknod => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+    res
+}
+
+pub fn mknodat(unicorn: &mut Unicorn<Context>, dir_fd: u32, path: u32, mode: u32, dev: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] mknodat(dir_fd = {:#x}, path = {:#x}, mode = {:#o}, dev = {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        dir_fd,
+        path,
+        mode,
+        dev,
+    );
+
+    let path_name = read_string(unicorn, path);
+    log::trace!("path = {}", path_name);
+
+    let path_name = get_path_relative_to_dir(unicorn, dir_fd, &path_name);
+    let res = mknod_internal(unicorn, &path_name, mode, dev);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] mknodat => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+    res
+}
+
 pub fn umask(unicorn: &mut Unicorn<Context>, mask: u32) -> u32 {
     log::trace!(
         "{:#x}: [{}] [SYSCALL] umask(mask = {:#x}) [IN]",
@@ -271,8 +348,13 @@ pub fn umask(unicorn: &mut Unicorn<Context>, mask: u32) -> u32 {
         mask,
     );
 
-    // TODO:
-    let res = 0777;
+    let inner = &unicorn.get_data().inner;
+    let mut process_table = inner.process_table.lock().unwrap();
+    let res = match process_table.get_mut(inner.pid) {
+        Some(process) => std::mem::replace(&mut process.umask, mask & 0o777),
+        None => 0o022,
+    };
+    drop(process_table);
 
     log::trace!(
         "{:#x}: [{}] [SYSCALL] umask => {:#x}",
@@ -283,6 +365,151 @@ pub fn umask(unicorn: &mut Unicorn<Context>, mask: u32) -> u32 {
     res
 }
 
+pub fn chmod(unicorn: &mut Unicorn<Context>, path: u32, mode: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] chmod(path = {:#x}, mode = {:#o}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        path,
+        mode,
+    );
+
+    let path_name = read_string(unicorn, path);
+    log::trace!("path = {}", path_name);
+
+    let res = chmod_internal(unicorn, &path_name, mode);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] chmod => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+    res
+}
+
+pub fn fchmod(unicorn: &mut Unicorn<Context>, fd: u32, mode: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] fchmod(fd = {:#x}, mode = {:#o}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        fd,
+        mode,
+    );
+
+    let file_info = unicorn
+        .get_data()
+        .inner
+        .file_system
+        .lock()
+        .unwrap()
+        .get_file_info(fd as i32);
+    let res = match file_info {
+        Some(file_info) => chmod_internal(unicorn, &file_info.file_path, mode),
+        None => -9i32 as u32, // -EBADF
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] fchmod => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+    res
+}
+
+pub fn fchmodat(unicorn: &mut Unicorn<Context>, dir_fd: u32, path: u32, mode: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] fchmodat(dir_fd = {:#x}, path = {:#x}, mode = {:#o}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        dir_fd,
+        path,
+        mode,
+    );
+
+    let path_name = read_string(unicorn, path);
+    log::trace!("path = {}", path_name);
+
+    let path_name = get_path_relative_to_dir(unicorn, dir_fd, &path_name);
+    let res = chmod_internal(unicorn, &path_name, mode);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] fchmodat => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+    res
+}
+
+/// Returns file mode creation mask of the calling process.
+pub fn get_umask(unicorn: &Unicorn<Context>) -> u32 {
+    let inner = &unicorn.get_data().inner;
+    let process_table = inner.process_table.lock().unwrap();
+    process_table
+        .get(inner.pid)
+        .map(|process| process.umask)
+        .unwrap_or(0o022)
+}
+
+fn mkdir_internal(unicorn: &mut Unicorn<Context>, path_name: &str, mode: u32) -> u32 {
+    let mode = mode & !get_umask(unicorn) & 0o7777;
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let res = file_system.lock().unwrap().mkdir(path_name, mode);
+    match res {
+        Ok(_) => 0u32,
+        Err(err) => err.to_syscall_error(),
+    }
+}
+
+fn mknod_internal(unicorn: &mut Unicorn<Context>, path_name: &str, mode: u32, dev: u32) -> u32 {
+    let file_type = match mode & 0o170000 {
+        0 | 0o100000 => FileType::File,
+        0o020000 => FileType::CharacterDevice,
+        0o060000 => FileType::BlockDevice,
+        0o010000 => FileType::NamedPipe,
+        0o140000 => FileType::Socket,
+        _ => return -22i32 as u32, // -EINVAL
+    };
+    let mode = mode & !get_umask(unicorn) & 0o7777;
+
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let mut file_system = file_system.lock().unwrap();
+    let res = if file_type == FileType::File {
+        // regular file is created empty
+        file_system
+            .open_checked(
+                path_name,
+                OpenFileFlags::CREATE | OpenFileFlags::EXCLUSIVE | OpenFileFlags::NO_FOLLOW,
+                mode,
+            )
+            .map(|fd| file_system.close(fd).unwrap())
+    } else {
+        // device numbers of mknod() are encoded in the same way as in stat64
+        file_system.mknod(path_name, file_type, mode, dev as u64)
+    };
+    match res {
+        Ok(_) => 0u32,
+        Err(err) => err.to_syscall_error(),
+    }
+}
+
+fn chmod_internal(unicorn: &mut Unicorn<Context>, path_name: &str, mode: u32) -> u32 {
+    let path_name = resolve_proc_thread_self(unicorn, path_name);
+    match unicorn
+        .get_data()
+        .inner
+        .file_system
+        .lock()
+        .unwrap()
+        .set_mode(&path_name, mode)
+    {
+        Ok(_) => 0,
+        Err(err) => err.to_syscall_error(),
+    }
+}
+
 fn fstat64_internal(unicorn: &mut Unicorn<Context>, fd: u32, stat_buf: u32) -> u32 {
     let file_system = unicorn.get_data().inner.file_system.clone();
 
@@ -309,26 +536,21 @@ fn fstat64_internal(unicorn: &mut Unicorn<Context>, fd: u32, stat_buf: u32) -> u
             FileType::CharacterDevice => st_mode |= 0o0020000u32,
             FileType::NamedPipe => st_mode |= 0o0010000u32,
         }
-
-        if file_info.file_details.is_readonly {
-            st_mode |= 0o000555;
-        } else {
-            st_mode |= 0o000777;
-        }
+        st_mode |= file_info.file_details.mode;
 
         stat_data.extend_from_slice(&pack_u32(st_mode));
 
         // st_nlink
-        stat_data.extend_from_slice(&pack_u32(0));
+        stat_data.extend_from_slice(&pack_u32(file_info.file_details.links_count));
 
         // st_uid
-        stat_data.extend_from_slice(&pack_u32(UID));
+        stat_data.extend_from_slice(&pack_u32(file_info.file_details.uid));
 
         // st_gid
-        stat_data.extend_from_slice(&pack_u32(GID));
+        stat_data.extend_from_slice(&pack_u32(file_info.file_details.gid));
 
         // st_rdev
-        stat_data.extend_from_slice(&pack_u64(0));
+        stat_data.extend_from_slice(&pack_u64(file_info.file_details.device));
 
         // padding
         stat_data.extend_from_slice(&pack_u64(0));
@@ -343,34 +565,24 @@ fn fstat64_internal(unicorn: &mut Unicorn<Context>, fd: u32, stat_buf: u32) -> u
         stat_data.extend_from_slice(&pack_u32(0));
 
         // st_blocks
-        stat_data.extend_from_slice(&pack_u64((file_info.file_details.length + 511) / 512));
-
-        // st_atime
-        let time = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        stat_data.extend_from_slice(&pack_u32(time as u32));
-
-        // st_atime_ns
-        stat_data.extend_from_slice(&pack_u32(0));
-
-        // st_mtime
-        stat_data.extend_from_slice(&pack_u32(time as u32));
-
-        // st_mtime_ns
-        stat_data.extend_from_slice(&pack_u32(0));
-
-        // st_ctime
-        stat_data.extend_from_slice(&pack_u32(time as u32));
-
-        // st_ctime_ns
-        stat_data.extend_from_slice(&pack_u32(0));
+        stat_data.extend_from_slice(&pack_u64(file_info.file_details.length.div_ceil(512)));
+
+        // st_atime, st_atime_ns, st_mtime, st_mtime_ns, st_ctime, st_ctime_ns
+        let times = file_info.file_details.times.unwrap_or_else(FileTimes::now);
+        for time in [times.accessed, times.modified, times.changed] {
+            let time = time
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default();
+            stat_data.extend_from_slice(&pack_u32(time.as_secs() as u32));
+            stat_data.extend_from_slice(&pack_u32(time.subsec_nanos()));
+        }
 
         // st_ino
         stat_data.extend_from_slice(&pack_u64(file_info.inode));
 
-        unicorn.mem_write(stat_buf as u64, &stat_data).unwrap();
+        unicorn
+            .mem_write_guest(stat_buf as u64, &stat_data)
+            .unwrap();
 
         0u32
     } else {
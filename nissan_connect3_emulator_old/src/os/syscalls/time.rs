@@ -1,4 +1,6 @@
+use crate::emulator::clock::clock_now;
 use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
 use crate::emulator::utils::{pack_u32, pack_u64, unpack_u32};
 use std::time::{Duration, SystemTime};
 use unicorn_engine::{RegisterARM, Unicorn};
@@ -12,16 +14,14 @@ pub fn clock_gettime(unicorn: &mut Unicorn<Context>, clock_id: u32, time_spec: u
         time_spec,
     );
 
-    let now = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap();
+    let now = clock_now(clock_id);
 
     unicorn
-        .mem_write(time_spec as u64, &pack_u64(now.as_secs()))
+        .mem_write_guest(time_spec as u64, &pack_u64(now.as_secs()))
         .unwrap();
 
     unicorn
-        .mem_write(
+        .mem_write_guest(
             (time_spec + 8) as u64,
             &pack_u64((now.as_nanos() % 1000000000u128) as u64),
         )
@@ -52,11 +52,11 @@ pub fn gettimeofday(unicorn: &mut Unicorn<Context>, time_val: u32, time_zone: u3
 
     if time_val != 0 {
         unicorn
-            .mem_write(time_val as u64, &pack_u32(now.as_secs() as u32))
+            .mem_write_guest(time_val as u64, &pack_u32(now.as_secs() as u32))
             .unwrap();
 
         unicorn
-            .mem_write(
+            .mem_write_guest(
                 (time_val + 4) as u64,
                 &pack_u32((now.as_nanos() % 1000000000u128) as u32),
             )
@@ -65,7 +65,7 @@ pub fn gettimeofday(unicorn: &mut Unicorn<Context>, time_val: u32, time_zone: u3
 
     if time_zone != 0 {
         let buf = vec![0u8; 8];
-        unicorn.mem_write(time_zone as u64, &buf).unwrap();
+        unicorn.mem_write_guest(time_zone as u64, &buf).unwrap();
     }
 
     log::trace!(
@@ -0,0 +1,160 @@
+use crate::emulator::clock::{CLOCK_BOOTTIME, CLOCK_MONOTONIC, CLOCK_REALTIME};
+use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
+use crate::emulator::utils::pack_u32;
+use crate::file_system::{AnonFile, OpenFileFlags, TimerFd};
+use crate::os::syscalls::poll::read_timespec;
+use std::time::Duration;
+use unicorn_engine::{RegisterARM, Unicorn};
+
+const TFD_NONBLOCK: u32 = 0x800;
+const TFD_TIMER_ABSTIME: u32 = 0x1;
+
+pub fn timerfd_create(unicorn: &mut Unicorn<Context>, clock_id: u32, flags: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] timerfd_create(clock_id = {:#x}, flags: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        clock_id,
+        flags,
+    );
+
+    let res = match clock_id {
+        CLOCK_REALTIME | CLOCK_MONOTONIC | CLOCK_BOOTTIME => {
+            let open_file_flags = if flags & TFD_NONBLOCK != 0 {
+                OpenFileFlags::READ | OpenFileFlags::NONBLOCK
+            } else {
+                OpenFileFlags::READ
+            };
+
+            unicorn
+                .get_data()
+                .inner
+                .file_system
+                .lock()
+                .unwrap()
+                .open_anon(AnonFile::TimerFd(TimerFd::new(clock_id)), open_file_flags)
+                as u32
+        }
+        _ => -22i32 as u32, // EINVAL
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] timerfd_create => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn timerfd_settime(
+    unicorn: &mut Unicorn<Context>,
+    fd: u32,
+    flags: u32,
+    new_value: u32,
+    old_value: u32,
+) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] timerfd_settime(fd = {:#x}, flags: {:#x}, new_value: {:#x}, old_value: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        fd,
+        flags,
+        new_value,
+        old_value,
+    );
+
+    // struct itimerspec { struct timespec it_interval; struct timespec it_value; }
+    let interval = read_timespec(unicorn, new_value);
+    let value = read_timespec(unicorn, new_value + 8);
+
+    let old_time = unicorn
+        .get_data()
+        .inner
+        .file_system
+        .lock()
+        .unwrap()
+        .with_anon_file_mut(fd as i32, |file| match file {
+            AnonFile::TimerFd(timer_fd) => {
+                let old_time = timer_fd.get_time();
+                timer_fd.set_time(value, interval, flags & TFD_TIMER_ABSTIME != 0);
+                Some(old_time)
+            }
+            _ => None,
+        });
+
+    let res = match old_time {
+        Some(Some((old_remaining, old_interval))) => {
+            if old_value != 0 {
+                write_itimerspec(unicorn, old_value, old_remaining, old_interval);
+            }
+            0u32
+        }
+        Some(None) => -22i32 as u32, // EINVAL
+        None => -9i32 as u32,        // EBADF
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] timerfd_settime => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn timerfd_gettime(unicorn: &mut Unicorn<Context>, fd: u32, curr_value: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] timerfd_gettime(fd = {:#x}, curr_value: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        fd,
+        curr_value,
+    );
+
+    let curr_time = unicorn
+        .get_data()
+        .inner
+        .file_system
+        .lock()
+        .unwrap()
+        .with_anon_file(fd as i32, |file| match file {
+            AnonFile::TimerFd(timer_fd) => Some(timer_fd.get_time()),
+            _ => None,
+        });
+
+    let res = match curr_time {
+        Some(Some((remaining, interval))) => {
+            write_itimerspec(unicorn, curr_value, remaining, interval);
+            0u32
+        }
+        Some(None) => -22i32 as u32, // EINVAL
+        None => -9i32 as u32,        // EBADF
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] timerfd_gettime => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+fn write_itimerspec(
+    unicorn: &mut Unicorn<Context>,
+    addr: u32,
+    value: Duration,
+    interval: Duration,
+) {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&pack_u32(interval.as_secs() as u32));
+    buf.extend_from_slice(&pack_u32(interval.subsec_nanos()));
+    buf.extend_from_slice(&pack_u32(value.as_secs() as u32));
+    buf.extend_from_slice(&pack_u32(value.subsec_nanos()));
+    unicorn.mem_write_guest(addr as u64, &buf).unwrap();
+}
@@ -29,18 +29,16 @@ pub fn writev(unicorn: &mut Unicorn<Context>, fd: u32, iov: u32, iovcnt: u32) ->
             let mut buf = vec![0u8; len as usize];
             unicorn.mem_read(addr as u64, &mut buf).unwrap();
 
-            match unicorn
+            if unicorn
                 .get_data()
                 .inner
                 .file_system
                 .lock()
                 .unwrap()
                 .write_all(fd as i32, &buf)
+                .is_ok()
             {
-                Ok(_) => {
-                    written_bytes += len;
-                }
-                Err(_) => {}
+                written_bytes += len;
             }
         }
         written_bytes
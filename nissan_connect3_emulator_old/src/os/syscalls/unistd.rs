@@ -1,13 +1,25 @@
 use crate::emulator::context::Context;
-use crate::emulator::utils::{mem_align_up, pack_u16, pack_u64, read_string};
-use crate::file_system::{FileType, MountFileSystem};
+use crate::emulator::elf_loader::check_elf;
+use crate::emulator::mmu::GuestMemory;
+use crate::emulator::process::{exec_process, exit_process, fork_process, kill_process};
+use crate::emulator::process_table::ExitReason;
+use crate::emulator::utils::{
+    mem_align_up, pack_u16, pack_u32, pack_u64, read_binary, read_string, unpack_u32,
+};
+use crate::file_system::{AccessMode, FileType, OpenFileError, OpenFileFlags, ProcessFileSystem};
+use crate::os::syscalls::fcntl::{get_path_relative_to_dir, resolve_proc_thread_self};
+use crate::os::syscalls::poll::{wait_for_fd, wait_for_file_system};
 use crate::os::syscalls::SysCallError;
 use std::io::SeekFrom;
 use std::path::Path;
-use std::sync::{Arc, Mutex};
 use unicorn_engine::unicorn_const::Permission;
 use unicorn_engine::{RegisterARM, Unicorn};
 
+const O_NONBLOCK: u32 = 0x800;
+const O_CLOEXEC: u32 = 0x80000;
+// `FD_CLOEXEC` file descriptor flag
+const FD_CLOEXEC: u32 = 0x1;
+
 pub fn brk(unicorn: &mut Unicorn<Context>, addr: u32) -> u32 {
     log::trace!(
         "{:#x}: [{}] [SYSCALL] brk(addr = {:#x}) [IN]",
@@ -61,25 +73,199 @@ pub fn access(unicorn: &mut Unicorn<Context>, path_name: u32, mode: u32) -> u32
 
     log::trace!("path_name: {}", path_name);
 
-    let exists = unicorn
+    let res = access_internal(unicorn, &path_name, mode);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] access => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+    res
+}
+
+pub fn faccessat(unicorn: &mut Unicorn<Context>, dirfd: u32, path_name: u32, mode: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] faccessat(dirfd = {:#x}, pathname = {:#x}, mode = {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        dirfd,
+        path_name,
+        mode,
+    );
+
+    let path_name = read_string(unicorn, path_name);
+
+    log::trace!("path_name: {}", path_name);
+
+    let path_name = get_path_relative_to_dir(unicorn, dirfd, &path_name);
+    let res = access_internal(unicorn, &path_name, mode);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] faccessat => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+    res
+}
+
+fn access_internal(unicorn: &mut Unicorn<Context>, path_name: &str, mode: u32) -> u32 {
+    let path_name = resolve_proc_thread_self(unicorn, path_name);
+    // F_OK (0) only checks that the file exists
+    let access = AccessMode::from_bits_truncate(mode);
+    match unicorn
+        .get_data()
+        .inner
+        .file_system
+        .lock()
+        .unwrap()
+        .check_access(&path_name, access)
+    {
+        Ok(_) => 0,
+        Err(err) => err.to_syscall_error(),
+    }
+}
+
+pub fn chown32(unicorn: &mut Unicorn<Context>, path_name: u32, owner: u32, group: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] chown32(pathname = {:#x}, owner = {}, group = {}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        path_name,
+        owner as i32,
+        group as i32,
+    );
+
+    let path_name = read_string(unicorn, path_name);
+    log::trace!("path_name: {}", path_name);
+
+    let res = chown_internal(unicorn, &path_name, owner, group, true);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] chown32 => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+    res
+}
+
+pub fn lchown32(unicorn: &mut Unicorn<Context>, path_name: u32, owner: u32, group: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] lchown32(pathname = {:#x}, owner = {}, group = {}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        path_name,
+        owner as i32,
+        group as i32,
+    );
+
+    let path_name = read_string(unicorn, path_name);
+    log::trace!("path_name: {}", path_name);
+
+    let res = chown_internal(unicorn, &path_name, owner, group, false);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] lchown32 => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+    res
+}
+
+pub fn fchown32(unicorn: &mut Unicorn<Context>, fd: u32, owner: u32, group: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] fchown32(fd = {:#x}, owner = {}, group = {}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        fd,
+        owner as i32,
+        group as i32,
+    );
+
+    let file_info = unicorn
         .get_data()
         .inner
         .file_system
         .lock()
         .unwrap()
-        .exists(&path_name);
-    let res = if exists { 0 } else { -1i32 as u32 };
+        .get_file_info(fd as i32);
+    let res = match file_info {
+        Some(file_info) => chown_internal(unicorn, &file_info.file_path, owner, group, false),
+        None => -9i32 as u32, // -EBADF
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] fchown32 => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+    res
+}
+
+pub fn fchownat(
+    unicorn: &mut Unicorn<Context>,
+    dirfd: u32,
+    path_name: u32,
+    owner: u32,
+    group: u32,
+    flags: u32,
+) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] fchownat(dirfd = {:#x}, pathname = {:#x}, owner = {}, group = {}, flags = {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        dirfd,
+        path_name,
+        owner as i32,
+        group as i32,
+        flags,
+    );
+
+    let path_name = read_string(unicorn, path_name);
+    log::trace!("path_name: {}", path_name);
+
+    let path_name = get_path_relative_to_dir(unicorn, dirfd, &path_name);
+    // AT_SYMLINK_NOFOLLOW
+    let follow_link = flags & 0x100 == 0;
+    let res = chown_internal(unicorn, &path_name, owner, group, follow_link);
 
     log::trace!(
-        "{:#x}: [{}] [SYSCALL] access => {:#x} [{}]",
+        "{:#x}: [{}] [SYSCALL] fchownat => {:#x}",
         unicorn.reg_read(RegisterARM::PC).unwrap(),
         unicorn.get_data().inner.thread_id,
-        res,
-        if exists { "FOUND" } else { "NOT FOUND" }
+        res
     );
     res
 }
 
+fn chown_internal(
+    unicorn: &mut Unicorn<Context>,
+    path_name: &str,
+    owner: u32,
+    group: u32,
+    follow_link: bool,
+) -> u32 {
+    let path_name = resolve_proc_thread_self(unicorn, path_name);
+    // -1 keeps the current value
+    let owner = if owner == u32::MAX { None } else { Some(owner) };
+    let group = if group == u32::MAX { None } else { Some(group) };
+    match unicorn
+        .get_data()
+        .inner
+        .file_system
+        .lock()
+        .unwrap()
+        .set_owner(&path_name, owner, group, follow_link)
+    {
+        Ok(_) => 0,
+        Err(err) => err.to_syscall_error(),
+    }
+}
+
 pub fn close(unicorn: &mut Unicorn<Context>, fd: u32) -> u32 {
     log::trace!(
         "{:#x}: [{}] [SYSCALL] close(fd: {:#x}) [IN]",
@@ -97,13 +283,14 @@ pub fn close(unicorn: &mut Unicorn<Context>, fd: u32) -> u32 {
         .get_dents_list
         .remove(&fd);
 
-    let res = if let Ok(_) = unicorn
+    let res = if unicorn
         .get_data()
         .inner
         .file_system
         .lock()
         .unwrap()
         .close(fd as i32)
+        .is_ok()
     {
         0u32
     } else {
@@ -120,6 +307,198 @@ pub fn close(unicorn: &mut Unicorn<Context>, fd: u32) -> u32 {
     res
 }
 
+pub fn dup(unicorn: &mut Unicorn<Context>, old_fd: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] dup(oldfd: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        old_fd,
+    );
+
+    let res = match unicorn
+        .get_data()
+        .inner
+        .file_system
+        .lock()
+        .unwrap()
+        .dup(old_fd as i32, 0, 0)
+    {
+        Ok(fd) => fd as u32,
+        Err(_) => -9i32 as u32, // -EBADF
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] dup => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn dup2(unicorn: &mut Unicorn<Context>, old_fd: u32, new_fd: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] dup2(oldfd: {:#x}, newfd: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        old_fd,
+        new_fd,
+    );
+
+    let res = if old_fd == new_fd {
+        // nothing is changed, only the descriptor is checked
+        if unicorn
+            .get_data()
+            .inner
+            .file_system
+            .lock()
+            .unwrap()
+            .is_open(old_fd as i32)
+        {
+            new_fd
+        } else {
+            -9i32 as u32 // -EBADF
+        }
+    } else {
+        dup_to_internal(unicorn, old_fd, new_fd, 0)
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] dup2 => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn dup3(unicorn: &mut Unicorn<Context>, old_fd: u32, new_fd: u32, flags: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] dup3(oldfd: {:#x}, newfd: {:#x}, flags: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        old_fd,
+        new_fd,
+        flags,
+    );
+
+    let res = if old_fd == new_fd || flags & !O_CLOEXEC != 0 {
+        -22i32 as u32 // -EINVAL
+    } else {
+        let fd_flags = if flags & O_CLOEXEC != 0 {
+            FD_CLOEXEC
+        } else {
+            0
+        };
+        dup_to_internal(unicorn, old_fd, new_fd, fd_flags)
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] dup3 => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+fn dup_to_internal(unicorn: &mut Unicorn<Context>, old_fd: u32, new_fd: u32, flags: u32) -> u32 {
+    let res = unicorn.get_data().inner.file_system.lock().unwrap().dup_to(
+        old_fd as i32,
+        new_fd as i32,
+        flags,
+    );
+    match res {
+        Ok(fd) => {
+            // `new_fd` could refer to a directory being read
+            unicorn
+                .get_data()
+                .inner
+                .sys_calls_state
+                .lock()
+                .unwrap()
+                .get_dents_list
+                .remove(&new_fd);
+            fd as u32
+        }
+        Err(_) => -9i32 as u32, // -EBADF
+    }
+}
+
+pub fn pipe(unicorn: &mut Unicorn<Context>, pipefd: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] pipe(pipefd: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        pipefd,
+    );
+
+    let res = pipe_internal(unicorn, pipefd, 0);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] pipe => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn pipe2(unicorn: &mut Unicorn<Context>, pipefd: u32, flags: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] pipe2(pipefd: {:#x}, flags: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        pipefd,
+        flags,
+    );
+
+    let res = pipe_internal(unicorn, pipefd, flags);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] pipe2 => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+fn pipe_internal(unicorn: &mut Unicorn<Context>, pipefd: u32, flags: u32) -> u32 {
+    if flags & !(O_NONBLOCK | O_CLOEXEC) != 0 {
+        return -22i32 as u32; // -EINVAL
+    }
+
+    let open_file_flags = if flags & O_NONBLOCK != 0 {
+        OpenFileFlags::NONBLOCK
+    } else {
+        OpenFileFlags::NONE
+    };
+
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let mut file_system = file_system.lock().unwrap();
+    let (read_fd, write_fd) = file_system.open_pipe(open_file_flags);
+    if flags & O_CLOEXEC != 0 {
+        file_system
+            .set_file_status_flags(read_fd, FD_CLOEXEC)
+            .unwrap();
+        file_system
+            .set_file_status_flags(write_fd, FD_CLOEXEC)
+            .unwrap();
+    }
+    drop(file_system);
+
+    let mut buf = pack_u32(read_fd as u32);
+    buf.extend_from_slice(&pack_u32(write_fd as u32));
+    unicorn.mem_write_guest(pipefd as u64, &buf).unwrap();
+    0
+}
+
 pub fn read(unicorn: &mut Unicorn<Context>, fd: u32, buf: u32, length: u32) -> u32 {
     log::trace!(
         "{:#x}: [{}] [SYSCALL] read(fd: {:#x}, buf: {:#x}, length: {:#x}) [IN]",
@@ -132,11 +511,15 @@ pub fn read(unicorn: &mut Unicorn<Context>, fd: u32, buf: u32, length: u32) -> u
 
     let mut buf2 = vec![0u8; length as usize];
     let file_system = &mut unicorn.get_data().inner.file_system.clone();
-    let res = if file_system.lock().unwrap().is_open(fd as i32) {
+    let res = if !file_system.lock().unwrap().is_open(fd as i32) {
+        -1i32 as u32
+    } else if !wait_for_fd(unicorn, fd as i32, false) {
+        -11i32 as u32 // EAGAIN
+    } else if file_system.lock().unwrap().is_open(fd as i32) {
         match file_system.lock().unwrap().read(fd as i32, &mut buf2) {
             Ok(len) => {
                 unicorn
-                    .mem_write(buf as u64, &buf2[0..len as usize])
+                    .mem_write_guest(buf as u64, &buf2[0..len as usize])
                     .unwrap();
                 len as u32
             }
@@ -171,13 +554,10 @@ pub fn write(unicorn: &mut Unicorn<Context>, fd: u32, buf: u32, length: u32) ->
     let file_system = &mut unicorn.get_data().inner.file_system.clone();
     let is_open = file_system.lock().unwrap().is_open(fd as i32);
     let res = if is_open {
+        // the buffer is only read, writing it back would copy the memory shared
+        // with the forked process
         match file_system.lock().unwrap().write(fd as i32, &buf2) {
-            Ok(len) => {
-                unicorn
-                    .mem_write(buf as u64, &buf2[0..len as usize])
-                    .unwrap();
-                len as u32
-            }
+            Ok(len) => len as u32,
             Err(_) => -1i32 as u32,
         }
     } else {
@@ -212,13 +592,13 @@ pub fn lseek(unicorn: &mut Unicorn<Context>, fd: u32, offset: u32, whence: u32)
         2 => Ok(SeekFrom::End(offset as i64)),
         _ => Err(()),
     } {
-        if let Ok(new_pos) = file_system.lock().unwrap().seek(fd as i32, pos) {
-            new_pos as u32
-        } else {
-            -1i32 as u32
+        let mut file_system = file_system.lock().unwrap();
+        match file_system.seek(fd as i32, pos) {
+            Ok(new_pos) => new_pos as u32,
+            Err(_) => file_system.seek_error(fd as i32) as u32,
         }
     } else {
-        -1i32 as u32
+        -22i32 as u32 // -EINVAL
     };
 
     log::trace!(
@@ -258,16 +638,18 @@ pub fn _llseek(
         2 => Ok(SeekFrom::End(offset as i64)),
         _ => Err(()),
     } {
-        if let Ok(new_pos) = file_system.lock().unwrap().seek(fd as i32, pos) {
-            unicorn
-                .mem_write(result as u64, &pack_u64(new_pos))
-                .unwrap();
-            0 as u32
-        } else {
-            -1i32 as u32
+        let res = file_system.lock().unwrap().seek(fd as i32, pos);
+        match res {
+            Ok(new_pos) => {
+                unicorn
+                    .mem_write_guest(result as u64, &pack_u64(new_pos))
+                    .unwrap();
+                0u32
+            }
+            Err(_) => file_system.lock().unwrap().seek_error(fd as i32) as u32,
         }
     } else {
-        -1i32 as u32
+        -22i32 as u32 // -EINVAL
     };
 
     log::trace!(
@@ -338,14 +720,14 @@ fn get_dents_internal(
     fd: u32,
     dirp: u32,
     count: u32,
-    file_system: Arc<Mutex<MountFileSystem>>,
+    file_system: ProcessFileSystem,
     dir_entries: Option<Vec<String>>,
 ) -> u32 {
     // check if the previous call returned all the results
     // (this situation is marked as an empty vector
     // and in that case return 0
     if let Some(dir_entries) = &dir_entries {
-        if dir_entries.len() == 0 {
+        if dir_entries.is_empty() {
             return 0u32;
         }
     }
@@ -359,10 +741,10 @@ fn get_dents_internal(
             let mut not_enough_space = false;
             let mut no_copied_entries = 0;
             for dir_entry in &dir_entries {
-                let full_path = Path::new(&dir_info.file_path).join(&dir_entry);
+                let full_path = Path::new(&dir_info.file_path).join(dir_entry);
                 let full_path = full_path.to_str().unwrap();
 
-                let rec_len = 20u16 + dir_entry.as_bytes().len() as u16;
+                let rec_len = 20u16 + dir_entry.len() as u16;
                 if res.len() + rec_len as usize > count as usize {
                     not_enough_space = true;
                     break;
@@ -401,10 +783,10 @@ fn get_dents_internal(
                 no_copied_entries += 1;
             }
 
-            return if not_enough_space && res.len() == 0 {
+            return if not_enough_space && res.is_empty() {
                 22u32 // EINVAL
             } else {
-                unicorn.mem_write(dirp as u64, &res).unwrap();
+                unicorn.mem_write_guest(dirp as u64, &res).unwrap();
 
                 let mut rest_entries = Vec::new();
                 rest_entries.extend_from_slice(&dir_entries[no_copied_entries..]);
@@ -472,8 +854,7 @@ pub fn get_pid(unicorn: &mut Unicorn<Context>) -> u32 {
         unicorn.get_data().inner.thread_id,
     );
 
-    // TODO: implement
-    let res = 1;
+    let res = unicorn.get_data().inner.pid;
 
     log::trace!(
         "{:#x}: [{}] [SYSCALL] get_pid => {:#x}",
@@ -485,32 +866,244 @@ pub fn get_pid(unicorn: &mut Unicorn<Context>) -> u32 {
     res
 }
 
-pub fn exit(unicorn: &mut Unicorn<Context>, status: u32) -> u32 {
+pub fn get_ppid(unicorn: &mut Unicorn<Context>) -> u32 {
     log::trace!(
-        "{:#x}: [{}] [SYSCALL] exit(status: {:#x}) [IN]",
+        "{:#x}: [{}] [SYSCALL] get_ppid() [IN]",
         unicorn.reg_read(RegisterARM::PC).unwrap(),
         unicorn.get_data().inner.thread_id,
-        status,
     );
 
-    let current_thread_id = unicorn.get_data().inner.thread_id;
-
-    if let Some(threads) = unicorn.get_data().inner.threads.upgrade() {
-        for thread in threads.lock().unwrap().iter_mut() {
-            if thread.unicorn.get_data().inner.thread_id == current_thread_id {
-                thread.exit().unwrap();
-            }
-        }
-    }
+    let inner = &unicorn.get_data().inner;
+    let res = inner
+        .process_table
+        .lock()
+        .unwrap()
+        .get(inner.pid)
+        .map_or(0, |process| process.parent_pid);
 
     log::trace!(
-        "{:#x}: [{}] [SYSCALL] exit => {:#x}",
+        "{:#x}: [{}] [SYSCALL] get_ppid => {:#x}",
         unicorn.reg_read(RegisterARM::PC).unwrap(),
         unicorn.get_data().inner.thread_id,
-        0u32
+        res
     );
 
-    0u32
+    res
+}
+
+pub fn fork(unicorn: &mut Unicorn<Context>) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] fork() [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+    );
+
+    let res = fork_internal(unicorn, false, None);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] fork => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn vfork(unicorn: &mut Unicorn<Context>) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] vfork() [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+    );
+
+    let res = fork_internal(unicorn, true, None);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] vfork => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+/// Creates the child process, used also by `clone()` without `CLONE_VM`.
+pub fn fork_internal(
+    unicorn: &mut Unicorn<Context>,
+    is_vfork: bool,
+    child_tid_ptr: Option<u32>,
+) -> u32 {
+    let child_pid = fork_process(unicorn, is_vfork, child_tid_ptr);
+
+    if is_vfork {
+        // the child does not share memory with the parent (it is copy-on-write as in `fork()`),
+        // but the parent is still suspended until the child calls `execve()` or exits
+        let process_table = unicorn.get_data().inner.process_table.clone();
+        wait_for_file_system(unicorn, None, |_| {
+            match process_table.lock().unwrap().get(child_pid) {
+                Some(process) if process.is_vfork_pending => None,
+                _ => Some(()),
+            }
+        });
+    }
+
+    child_pid
+}
+
+pub fn execve(unicorn: &mut Unicorn<Context>, filename: u32, argv: u32, envp: u32) -> u32 {
+    let path_name = read_string(unicorn, filename);
+    let program_args = read_string_array(unicorn, argv);
+    let program_envs: Vec<(String, String)> = read_string_array(unicorn, envp)
+        .iter()
+        .map(|env| match env.split_once('=') {
+            Some((key, value)) => (key.to_string(), value.to_string()),
+            None => (env.clone(), String::new()),
+        })
+        .collect();
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] execve(filename = {:#x} => {}, argv: {:#x} => {:?}, envp: {:#x} => {:?}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        filename,
+        path_name,
+        argv,
+        program_args,
+        envp,
+        program_envs,
+    );
+
+    // the calling thread of `/proc/thread-self` is not known to proc-fs
+    let path_name = resolve_proc_thread_self(unicorn, &path_name);
+    let res = match load_executable(unicorn, &path_name) {
+        Err(err) => err,
+        Ok((file_path, buf)) => {
+            match exec_process(unicorn, file_path, &buf, program_args, program_envs) {
+                Ok(()) => 0u32,
+                Err(error) => {
+                    // the old program is gone already, nothing to return to
+                    log::error!("execve() failed: {}", error);
+                    let pid = unicorn.get_data().inner.pid;
+                    kill_process(unicorn, pid);
+                    0u32
+                }
+            }
+        }
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] execve => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+/// Reads the program for `execve()` and checks that it can be executed
+/// (before the calling program is replaced).
+///
+/// Returns the path of the program with resolved links and its content.
+fn load_executable(unicorn: &Unicorn<Context>, path_name: &str) -> Result<(String, Vec<u8>), u32> {
+    let file_path = {
+        let file_system = unicorn.get_data().inner.file_system.clone();
+        let mut file_system = file_system.lock().unwrap();
+        let file_path = file_system
+            .resolve_path(path_name, true)
+            .map_err(|err| err.to_syscall_error())?;
+        file_system
+            .check_access(&file_path, AccessMode::EXECUTE)
+            .map_err(|err| err.to_syscall_error())?;
+
+        // only regular files can be executed
+        let file_type = file_system
+            .get_file_info_from_filepath(&file_path)
+            .map(|file_info| file_info.file_details.file_type);
+        if !matches!(file_type, Some(FileType::File)) {
+            return Err(OpenFileError::AccessDenied.to_syscall_error());
+        }
+        file_path
+    };
+
+    let buf = read_binary(unicorn, &file_path).map_err(|err| err.to_syscall_error())?;
+    let interp_path = match check_elf(&buf) {
+        Ok(interp_path) => interp_path,
+        Err(error) => {
+            log::debug!("execve() of {}: {}", file_path, error);
+            return Err(-8i32 as u32); // -ENOEXEC
+        }
+    };
+
+    // missing interpreter is reported as missing program
+    if let Some(interp_path) = interp_path {
+        let file_system = unicorn.get_data().inner.file_system.clone();
+        let mut file_system = file_system.lock().unwrap();
+        if let Err(err) = file_system.check_access(&interp_path, AccessMode::EXECUTE) {
+            return Err(err.to_syscall_error());
+        }
+    }
+
+    Ok((file_path, buf))
+}
+
+/// Reads NULL-terminated array of string pointers (`argv` or `envp`).
+fn read_string_array(unicorn: &Unicorn<Context>, mut addr: u32) -> Vec<String> {
+    let mut strings = Vec::new();
+    if addr == 0 {
+        return strings;
+    }
+
+    let mut buf = [0u8; 4];
+    loop {
+        unicorn.mem_read(addr as u64, &mut buf).unwrap();
+        let string_addr = unpack_u32(&buf);
+        if string_addr == 0 {
+            break;
+        }
+        strings.push(read_string(unicorn, string_addr));
+        addr += 4;
+    }
+    strings
+}
+
+pub fn exit(unicorn: &mut Unicorn<Context>, status: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] exit(status: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        status,
+    );
+
+    let current_thread_id = unicorn.get_data().inner.thread_id;
+
+    if let Some(threads) = unicorn.get_data().inner.threads.upgrade() {
+        let is_last_thread = {
+            let mut threads = threads.lock().unwrap();
+            for thread in threads.iter_mut() {
+                if thread.unicorn.get_data().inner.thread_id == current_thread_id {
+                    thread.exit().unwrap();
+                }
+            }
+            threads.iter().all(|thread| thread.is_exited())
+        };
+
+        if is_last_thread {
+            let pid = unicorn.get_data().inner.pid;
+            exit_process(unicorn, pid, ExitReason::Exited(status as i32));
+        }
+    }
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] exit => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        0u32
+    );
+
+    0u32
 }
 
 pub fn exit_group(unicorn: &mut Unicorn<Context>, status: u32) -> u32 {
@@ -527,6 +1120,9 @@ pub fn exit_group(unicorn: &mut Unicorn<Context>, status: u32) -> u32 {
         }
     }
 
+    let pid = unicorn.get_data().inner.pid;
+    exit_process(unicorn, pid, ExitReason::Exited(status as i32));
+
     log::trace!(
         "{:#x}: [{}] [SYSCALL] exit_group => {:#x}",
         unicorn.reg_read(RegisterARM::PC).unwrap(),
@@ -631,6 +1227,212 @@ pub fn unlink(unicorn: &mut Unicorn<Context>, path: u32) -> u32 {
     res
 }
 
+pub fn unlinkat(unicorn: &mut Unicorn<Context>, dir_fd: u32, path: u32, flags: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] unlinkat(dir_fd: {:#x}, path: {:#x}, flags: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        dir_fd,
+        path,
+        flags,
+    );
+
+    let path = read_string(unicorn, path);
+
+    log::trace!("path: {}", path);
+
+    let path = get_path_relative_to_dir(unicorn, dir_fd, &path);
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let res = if flags & 0x200 != 0 {
+        // AT_REMOVEDIR
+        file_system.lock().unwrap().rmdir(&path)
+    } else {
+        file_system.lock().unwrap().unlink(&path)
+    };
+    let res = match res {
+        Ok(_) => 0u32,
+        Err(err) => err.to_syscall_error(),
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] unlinkat => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn rmdir(unicorn: &mut Unicorn<Context>, path: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] rmdir(path: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        path,
+    );
+
+    let path = read_string(unicorn, path);
+
+    log::trace!("path: {}", path);
+
+    let res = match unicorn
+        .get_data()
+        .inner
+        .file_system
+        .lock()
+        .unwrap()
+        .rmdir(&path)
+    {
+        Ok(_) => 0u32,
+        Err(err) => err.to_syscall_error(),
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] rmdir => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn rename(unicorn: &mut Unicorn<Context>, old_path: u32, new_path: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] rename(old_path: {:#x}, new_path: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        old_path,
+        new_path,
+    );
+
+    let old_path = read_string(unicorn, old_path);
+    let new_path = read_string(unicorn, new_path);
+
+    log::trace!("old_path: {}, new_path: {}", old_path, new_path);
+
+    let res = match unicorn
+        .get_data()
+        .inner
+        .file_system
+        .lock()
+        .unwrap()
+        .rename(&old_path, &new_path)
+    {
+        Ok(_) => 0u32,
+        Err(err) => err.to_syscall_error(),
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] rename => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn renameat(
+    unicorn: &mut Unicorn<Context>,
+    old_dir_fd: u32,
+    old_path: u32,
+    new_dir_fd: u32,
+    new_path: u32,
+) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] renameat(old_dir_fd: {:#x}, old_path: {:#x}, new_dir_fd: {:#x}, new_path: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        old_dir_fd,
+        old_path,
+        new_dir_fd,
+        new_path,
+    );
+
+    let old_path = read_string(unicorn, old_path);
+    let new_path = read_string(unicorn, new_path);
+
+    log::trace!("old_path: {}, new_path: {}", old_path, new_path);
+
+    let old_path = get_path_relative_to_dir(unicorn, old_dir_fd, &old_path);
+    let new_path = get_path_relative_to_dir(unicorn, new_dir_fd, &new_path);
+    let res = renameat_internal(unicorn, &old_path, &new_path, 0);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] renameat => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn renameat2(
+    unicorn: &mut Unicorn<Context>,
+    old_dir_fd: u32,
+    old_path: u32,
+    new_dir_fd: u32,
+    new_path: u32,
+    flags: u32,
+) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] renameat2(old_dir_fd: {:#x}, old_path: {:#x}, new_dir_fd: {:#x}, new_path: {:#x}, flags: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        old_dir_fd,
+        old_path,
+        new_dir_fd,
+        new_path,
+        flags,
+    );
+
+    let old_path = read_string(unicorn, old_path);
+    let new_path = read_string(unicorn, new_path);
+
+    log::trace!("old_path: {}, new_path: {}", old_path, new_path);
+
+    let old_path = get_path_relative_to_dir(unicorn, old_dir_fd, &old_path);
+    let new_path = get_path_relative_to_dir(unicorn, new_dir_fd, &new_path);
+    let res = renameat_internal(unicorn, &old_path, &new_path, flags);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] renameat2 => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+fn renameat_internal(
+    unicorn: &mut Unicorn<Context>,
+    old_path: &str,
+    new_path: &str,
+    flags: u32,
+) -> u32 {
+    const RENAME_NOREPLACE: u32 = 1;
+
+    if flags & !RENAME_NOREPLACE != 0 {
+        // RENAME_EXCHANGE and RENAME_WHITEOUT are not supported
+        return -22i32 as u32; // -EINVAL
+    }
+
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let mut file_system = file_system.lock().unwrap();
+    if flags & RENAME_NOREPLACE != 0 && file_system.get_file_info_from_filepath(new_path).is_some()
+    {
+        return -17i32 as u32; // -EEXIST
+    }
+    match file_system.rename(old_path, new_path) {
+        Ok(_) => 0u32,
+        Err(err) => err.to_syscall_error(),
+    }
+}
+
 pub fn symlink(unicorn: &mut Unicorn<Context>, old_path: u32, new_path: u32) -> u32 {
     log::trace!(
         "{:#x}: [{}] [SYSCALL] symlink(old_path: {:#x}, new_path: {:#x}) [IN]",
@@ -651,7 +1453,7 @@ pub fn symlink(unicorn: &mut Unicorn<Context>, old_path: u32, new_path: u32) ->
         .file_system
         .lock()
         .unwrap()
-        .link(&old_path, &new_path)
+        .symlink(&old_path, &new_path)
     {
         Ok(_) => 0u32,
         Err(err) => err.to_syscall_error(),
@@ -667,6 +1469,178 @@ pub fn symlink(unicorn: &mut Unicorn<Context>, old_path: u32, new_path: u32) ->
     res
 }
 
+pub fn readlink(unicorn: &mut Unicorn<Context>, path_name: u32, buf: u32, buf_size: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] readlink(path_name: {:#x}, buf: {:#x}, buf_size: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        path_name,
+        buf,
+        buf_size,
+    );
+
+    let path_name = read_string(unicorn, path_name);
+
+    log::trace!("path_name: {}", path_name);
+
+    let res = readlink_internal(unicorn, &path_name, buf, buf_size);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] readlink => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn readlinkat(
+    unicorn: &mut Unicorn<Context>,
+    dir_fd: u32,
+    path_name: u32,
+    buf: u32,
+    buf_size: u32,
+) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] readlinkat(dir_fd: {:#x}, path_name: {:#x}, buf: {:#x}, buf_size: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        dir_fd,
+        path_name,
+        buf,
+        buf_size,
+    );
+
+    let path_name = read_string(unicorn, path_name);
+
+    log::trace!("path_name: {}", path_name);
+
+    let path_name = get_path_relative_to_dir(unicorn, dir_fd, &path_name);
+    let res = readlink_internal(unicorn, &path_name, buf, buf_size);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] readlinkat => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+fn readlink_internal(
+    unicorn: &mut Unicorn<Context>,
+    path_name: &str,
+    buf: u32,
+    buf_size: u32,
+) -> u32 {
+    let inner = &unicorn.get_data().inner;
+    let target = match path_name {
+        // link to the calling thread is not known to proc-fs
+        "/proc/thread-self" => Ok(format!("{}/task/{}", inner.pid, inner.thread_id)),
+        _ => {
+            let path_name = resolve_proc_thread_self(unicorn, path_name);
+            inner.file_system.lock().unwrap().read_link(&path_name)
+        }
+    };
+
+    match target {
+        Ok(target) => {
+            log::trace!("target: {}", target);
+
+            // the content is not null-terminated and is silently truncated
+            let length = target.len().min(buf_size as usize);
+            unicorn
+                .mem_write_guest(buf as u64, &target.as_bytes()[..length])
+                .unwrap();
+            length as u32
+        }
+        Err(err) => err.to_syscall_error(),
+    }
+}
+
+pub fn symlinkat(
+    unicorn: &mut Unicorn<Context>,
+    target: u32,
+    new_dir_fd: u32,
+    link_path: u32,
+) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] symlinkat(target: {:#x}, new_dir_fd: {:#x}, link_path: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        target,
+        new_dir_fd,
+        link_path,
+    );
+
+    let target = read_string(unicorn, target);
+    let link_path = read_string(unicorn, link_path);
+
+    log::trace!("target: {}, link_path: {}", target, link_path);
+
+    let link_path = get_path_relative_to_dir(unicorn, new_dir_fd, &link_path);
+    let res = match unicorn
+        .get_data()
+        .inner
+        .file_system
+        .lock()
+        .unwrap()
+        .symlink(&target, &link_path)
+    {
+        Ok(_) => 0u32,
+        Err(err) => err.to_syscall_error(),
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] symlinkat => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
+pub fn truncate(unicorn: &mut Unicorn<Context>, path: u32, length: u32) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] truncate(path: {:#x}, length: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        path,
+        length,
+    );
+
+    let path = read_string(unicorn, path);
+
+    log::trace!("path: {}", path);
+
+    let file_system = unicorn.get_data().inner.file_system.clone();
+    let mut file_system = file_system.lock().unwrap();
+    let res = match file_system.open_checked(&path, OpenFileFlags::WRITE, 0) {
+        Ok(fd) => {
+            let res = match file_system.ftruncate(fd, length) {
+                Ok(_) => 0u32,
+                Err(_) => -22i32 as u32, // -EINVAL
+            };
+            file_system.close(fd).unwrap();
+            res
+        }
+        Err(err) => err.to_syscall_error(),
+    };
+    drop(file_system);
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] truncate => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}
+
 pub fn ftruncate(unicorn: &mut Unicorn<Context>, fd: u32, length: u32) -> u32 {
     log::trace!(
         "{:#x}: [{}] [SYSCALL] ftruncate(fd: {:#x}, length: {:#x}) [IN]",
@@ -1,4 +1,5 @@
 use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
 use std::io::Write;
 use unicorn_engine::{RegisterARM, Unicorn};
 
@@ -14,14 +15,14 @@ pub fn uname(unicorn: &mut Unicorn<Context>, buf: u32) -> u32 {
         const UTS_LEN: usize = 65;
 
         let mut data = [0u8; UTS_LEN * 6];
-        write!(&mut data[UTS_LEN * 0..], "Linux").unwrap(); // sysname
-        write!(&mut data[UTS_LEN * 1..], "Linux-Marek").unwrap(); // nodename
+        write!(&mut data[0..], "Linux").unwrap(); // sysname
+        write!(&mut data[UTS_LEN..], "Linux-Marek").unwrap(); // nodename
         write!(&mut data[UTS_LEN * 2..], "2.6.32").unwrap(); // release
         write!(&mut data[UTS_LEN * 3..], "#1-Linux").unwrap(); // version
         write!(&mut data[UTS_LEN * 4..], "armv6l").unwrap(); // machine
         write!(&mut data[UTS_LEN * 5..], "(none)").unwrap(); // domainname
 
-        unicorn.mem_write(buf as u64, &data).unwrap();
+        unicorn.mem_write_guest(buf as u64, &data).unwrap();
         0
     };
 
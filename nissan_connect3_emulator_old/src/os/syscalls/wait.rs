@@ -0,0 +1,86 @@
+use crate::emulator::context::Context;
+use crate::emulator::mmu::GuestMemory;
+use crate::emulator::utils::pack_i32;
+use crate::os::syscalls::poll::wait_for_file_system;
+use std::time::Duration;
+use unicorn_engine::{RegisterARM, Unicorn};
+
+const WNOHANG: u32 = 0x1;
+
+// size of `struct rusage`
+const RUSAGE_SIZE: usize = 72;
+
+pub fn wait4(
+    unicorn: &mut Unicorn<Context>,
+    pid: u32,
+    status: u32,
+    options: u32,
+    rusage: u32,
+) -> u32 {
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] wait4(pid = {:#x}, status: {:#x}, options: {:#x}, rusage: {:#x}) [IN]",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        pid,
+        status,
+        options,
+        rusage,
+    );
+
+    let parent_pid = unicorn.get_data().inner.pid;
+    let process_table = unicorn.get_data().inner.process_table.clone();
+
+    // there are no process groups, so 0 and < -1 also mean any child
+    let child_pid = if pid as i32 > 0 { Some(pid) } else { None };
+
+    let has_children = {
+        let process_table = process_table.lock().unwrap();
+        match child_pid {
+            Some(child_pid) => process_table
+                .get(child_pid)
+                .is_some_and(|process| process.parent_pid == parent_pid),
+            None => process_table.has_children(parent_pid),
+        }
+    };
+
+    let res = if !has_children {
+        -10i32 as u32 // ECHILD
+    } else {
+        let timeout = if options & WNOHANG != 0 {
+            Some(Duration::ZERO)
+        } else {
+            None
+        };
+
+        let reaped = wait_for_file_system(unicorn, timeout, |_| {
+            process_table.lock().unwrap().reap(parent_pid, child_pid)
+        });
+
+        match reaped {
+            Some((child_pid, child_status)) => {
+                if status != 0 {
+                    unicorn
+                        .mem_write_guest(status as u64, &pack_i32(child_status))
+                        .unwrap();
+                }
+                if rusage != 0 {
+                    unicorn
+                        .mem_write_guest(rusage as u64, &[0u8; RUSAGE_SIZE])
+                        .unwrap();
+                }
+                child_pid
+            }
+            // WNOHANG and no child has exited yet
+            None => 0,
+        }
+    };
+
+    log::trace!(
+        "{:#x}: [{}] [SYSCALL] wait4 => {:#x}",
+        unicorn.reg_read(RegisterARM::PC).unwrap(),
+        unicorn.get_data().inner.thread_id,
+        res
+    );
+
+    res
+}